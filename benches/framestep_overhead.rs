@@ -0,0 +1,70 @@
+//! Per-frame overhead of fixed framesteps
+//!
+//! Run with `cargo bench --bench framestep_overhead`. Pass a name to only run
+//! the matching benchmarks, like `cargo bench --bench framestep_overhead -- catchup`.
+//!
+//! All fixed framesteps have a single `NoopStage`, so this measures the cost of the
+//! fixed framestep machinery itself: accumulating, syncing with the `FixedFramesteps`
+//! resource, and dispatching ticks. Each iteration is one frame: one run of every
+//! fixed framestep stage.
+
+use bevy_ecs::prelude::*;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use iyes_loopless::fixedframestep::{FixedFramestepStage, FixedFramesteps, Frames, NoopStage, MAX_FRAMESTEP};
+
+/// The numbers of fixed framesteps to measure with
+const FRAMESTEP_COUNTS: [usize; 3] = [1, 10, 100];
+
+/// Create a world with `n` fixed framesteps, and run them once to initialize everything
+fn setup(n: usize, step: Frames, speed: u32) -> (World, Vec<FixedFramestepStage>) {
+    let mut world = World::new();
+    let mut stages: Vec<FixedFramestepStage> = (0..n)
+        .map(|i| {
+            let label: &'static str = Box::leak(format!("bench_{}", i).into_boxed_str());
+            NoopStage::framestep(step, label)
+                .with_max_steps_per_frame(u32::MAX)
+        })
+        .collect();
+    for stage in stages.iter_mut() {
+        stage.run(&mut world);
+    }
+    let mut framesteps = world.resource_mut::<FixedFramesteps>();
+    for (_, info) in framesteps.iter_mut() {
+        info.set_speed(speed);
+    }
+    (world, stages)
+}
+
+/// Measure the frames of the setup, for each number of fixed framesteps
+fn bench_frames(c: &mut Criterion, name: &str, step: Frames, speed: u32) {
+    let mut group = c.benchmark_group(name);
+    for n in FRAMESTEP_COUNTS {
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            let (mut world, mut stages) = setup(n, step, speed);
+            b.iter(|| {
+                for stage in stages.iter_mut() {
+                    stage.run(black_box(&mut world));
+                }
+            });
+        });
+    }
+    group.finish();
+}
+
+/// A tick on every frame
+fn tick_every_frame(c: &mut Criterion) {
+    bench_frames(c, "tick_every_frame", Frames(1), 1);
+}
+
+/// The step is never reached: only the cost of accumulating
+fn no_tick(c: &mut Criterion) {
+    bench_frames(c, "no_tick", MAX_FRAMESTEP, 1);
+}
+
+/// 64 catch-up ticks on every frame
+fn catchup(c: &mut Criterion) {
+    bench_frames(c, "catchup_64", Frames(1), 64);
+}
+
+criterion_group!(benches, tick_every_frame, no_tick, catchup);
+criterion_main!(benches);
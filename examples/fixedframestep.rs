@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+use rand::prelude::*;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins)
+
+        // add fixed framestep stage to the default location (before Update)
+        .add_fixed_framestep(
+            // run every 15 frames
+            15,
+            // give it a label
+            "my_fixed_update",
+        )
+
+        // add an additional child "sub-stage" under the fixed framestep;
+        // this will let us apply Commands within one fixed framestep run
+        .add_fixed_framestep_child_stage("my_fixed_update")
+
+        // add a system to our fixed framestep (first sub-stage)
+        .add_fixed_framestep_system("my_fixed_update", 0, debug_fixed_framestep)
+
+        // to showcase use of Commands, we will spawn entities in one sub-stage (0) ...
+        .add_fixed_framestep_system("my_fixed_update", 0, spawn_entities)
+        // ... and mutate their transform in another (1)
+        .add_fixed_framestep_system("my_fixed_update", 1, reposition_entities)
+
+        .add_startup_system(setup_camera)
+        .add_system(debug_new_count)
+        .add_system(kbd_control_framestep)
+        .add_system(clear_entities);
+
+    // count frames using Bevy's `FrameCount`, instead of counting runs of the stage
+    app.get_fixed_framestep_stage_mut("my_fixed_update")
+        .set_accumulation_source(AccumulationSource::FrameCount);
+
+    app.run();
+}
+
+#[derive(Component)]
+struct MySprite;
+
+/// Spawn a MySprite entity
+fn spawn_entities(mut commands: Commands) {
+    let mut rng = thread_rng();
+
+    commands.spawn((SpriteBundle {
+        sprite: Sprite {
+            color: Color::rgba(rng.gen(), rng.gen(), rng.gen(), 0.5),
+            custom_size: Some(Vec2::new(64., 64.)),
+            ..Default::default()
+        },
+        // the `reposition_entities` system will take care of X and Y ;)
+        transform: Transform::from_xyz(0.0, 0.0, rng.gen_range(0.0..100.0)),
+        ..Default::default()
+    }, MySprite));
+}
+
+/// Move each sprite to a random X,Y position
+fn reposition_entities(mut q: Query<&mut Transform, With<MySprite>>) {
+    let mut rng = thread_rng();
+
+    for mut transform in q.iter_mut() {
+        transform.translation.x = rng.gen_range(-420.0..420.0);
+        transform.translation.y = rng.gen_range(-420.0..420.0);
+    }
+}
+
+/// Every fixed framestep, print info about the framestep parameters
+/// (shows how to get it from FixedFramesteps)
+fn debug_fixed_framestep(framesteps: Res<FixedFramesteps>) {
+    // unwrap: this system will run inside of the fixed framestep
+    let info = framesteps.get_current().unwrap();
+    println!("Fixed framestep length: {} frames ({:.2} Hz).", info.framestep(), info.rate());
+    println!("Actually running at {:.2} Hz.", info.measured_rate());
+    println!("Overstepped by {} frames ({:.2}%).", info.remaining(), info.overstep() * 100.0);
+}
+
+/// Every frame, print if new MySprites have been spawned
+fn debug_new_count(q: Query<(), Added<MySprite>>) {
+    let new = q.iter().count();
+    if new > 0 {
+        println!("{:?} new sprites spawned this frame", new);
+        println!();
+    }
+}
+
+/// Keypresses for speeding up / slowing down / pausing the fixed framestep
+/// (by mutating the FixedFramestepInfo from FixedFramesteps)
+fn kbd_control_framestep(
+    kbd: Res<Input<KeyCode>>,
+    mut framesteps: ResMut<FixedFramesteps>,
+) {
+    // this system runs outside of the fixed framestep, so we need
+    // to get the fixed framestep info by label
+    let info = framesteps.get_mut("my_fixed_update").unwrap();
+
+    // `try_set_step` refuses invalid values (like 0 frames)
+    if kbd.any_just_pressed([KeyCode::Minus, KeyCode::Underline]) {
+        info.try_set_step(info.step - Frames(1)).ok();
+    }
+    if kbd.any_just_pressed([KeyCode::Plus, KeyCode::Equals]) {
+        info.try_set_step(info.step + Frames(1)).ok();
+    }
+    if kbd.just_pressed(KeyCode::Space) {
+        info.toggle_pause();
+    }
+}
+
+/// Clear entities with keypress
+fn clear_entities(
+    mut commands: Commands,
+    kbd: Res<Input<KeyCode>>,
+    q: Query<Entity, With<MySprite>>
+) {
+    if kbd.any_just_pressed([KeyCode::Delete, KeyCode::Back]) {
+        for e in q.iter() {
+            commands.entity(e).despawn();
+        }
+    }
+}
+
+fn setup_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle::default());
+}
@@ -0,0 +1,58 @@
+//! Derive macros for `iyes_loopless`
+//!
+//! Use them through the re-exports in `iyes_loopless`, not from this crate directly.
+
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields};
+
+/// Derive `FramestepLabel` for a fieldless enum or a unit struct
+///
+/// Each enum variant becomes a separate fixed framestep, named `"Type::Variant"`.
+/// A unit struct is named `"Type"`.
+#[proc_macro_derive(FramestepLabel)]
+pub fn derive_framestep_label(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let body = match &input.data {
+        Data::Enum(data) => {
+            let mut arms = Vec::new();
+            for variant in &data.variants {
+                if !matches!(variant.fields, Fields::Unit) {
+                    return syn::Error::new_spanned(
+                        variant,
+                        "FramestepLabel can only be derived for enums without fields",
+                    ).to_compile_error().into();
+                }
+                let v = &variant.ident;
+                let name = format!("{}::{}", ident, v);
+                arms.push(quote! { Self::#v => #name, });
+            }
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Struct(data) if matches!(data.fields, Fields::Unit) => {
+            let name = ident.to_string();
+            quote! { #name }
+        }
+        _ => {
+            return syn::Error::new_spanned(
+                &input,
+                "FramestepLabel can only be derived for fieldless enums and unit structs",
+            ).to_compile_error().into();
+        }
+    };
+
+    quote! {
+        impl #impl_generics ::iyes_loopless::fixedframestep::FramestepLabel for #ident #ty_generics #where_clause {
+            fn framestep_name(&self) -> &'static str {
+                #body
+            }
+        }
+    }.into()
+}
@@ -0,0 +1,4601 @@
+//! Fixed Framestep implementation as a Bevy Stage
+//!
+//! This is the frame-counting sibling of the fixed timestep.
+//! Instead of accumulating the time delta, it counts frame updates, and runs its
+//! child stages every N frames. It runs in a dedicated stage, separate from your
+//! regular update systems, and can be combined with states, run conditions, etc.
+//!
+//! It is possible to add multiple "sub-stages" within a fixed framestep, allowing
+//! you to apply `Commands` within a single framestep run. For example, if you want
+//! to spawn entities and then do something with them, on the same tick.
+//!
+//! It is also possible to have multiple independent fixed framesteps, should you need to.
+//!
+//! (see `examples/fixedframestep.rs` to learn how to use it)
+//!
+//! Every frame, the [`FixedFramestepStage`] will accumulate the elapsed frames. When
+//! it reaches the set framestep value, it will run all the child stages. It
+//! will repeat the sequence of child stages multiple times if needed, if
+//! more than one framestep has accumulated.
+//!
+//! By default, every run of the stage counts as one frame. If the stage might not
+//! run on every frame (for example, if it is nested under something with its own
+//! run criteria), you can use [`AccumulationSource::FrameCount`] to derive the
+//! elapsed frames from Bevy's `FrameCount` resource instead. The fixed framestep can also
+//! follow a clock instead of counting frames: [`AccumulationSource::RealTime`] for
+//! the real time (for UI and audio), or [`AccumulationSource::GameTime`] for the scaled
+//! game time of Bevy's `Time` resource. Each fixed framestep has its own source.
+//!
+//! You can use the [`FixedFramesteps`] resource to access information about a
+//! fixed framestep and to control its parameters, like the framestep length.
+//!
+//! The bookkeeping that is mostly useful while developing is behind the
+//! `"framestep-bookkeeping"` cargo feature, which is enabled by default: the events of the
+//! fixed framesteps (like [`FixedFramestepSkipped`] and [`TickOverBudget`]), the
+//! [history](FixedFramestepStage::set_history_len), the
+//! [measured rate](FixedFramestepInfo::measured_rate), and the
+//! [determinism linter](FixedFramestepStage::set_determinism_lint). For the leanest shipping
+//! builds, disable the default features and leave it out: the events are then never sent,
+//! nor registered, the history stays empty, the rate is not measured, and the linter does
+//! not run. The API stays the same, so this can be done for release builds only. The Bevy
+//! diagnostics are opt-in already (the `"framestep-diagnostics"` cargo feature): leave them
+//! out, too. Internally, the fixed framesteps are looked up by [`FramestepId`], so the
+//! string names are only hashed where you pass them in. The names themselves are not
+//! stripped from shipping builds: the Bevy stage labels need them, and they are stored in
+//! savegames, replays, reports, and debug dumps.
+
+use std::any::TypeId;
+
+use std::borrow::Cow;
+use std::collections::VecDeque;
+use std::ops::{Index, IndexMut};
+use std::sync::Arc;
+
+use bevy_core::FrameCount;
+use bevy_time::Time;
+use bevy_utils::Duration;
+use bevy_utils::HashMap;
+use bevy_utils::hashbrown::hash_map;
+use bevy_utils::Instant;
+use bevy_utils::tracing::warn;
+use bevy_tasks::TaskPool;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::{ParallelExecutor, ShouldRun, SingleThreadedExecutor, StageLabelId};
+use bevy_ecs::event::Event;
+use bevy_ecs::system::BoxedSystem;
+
+#[cfg(feature = "framestep-animation")]
+pub mod animation;
+#[cfg(all(feature = "framestep-save", feature = "app"))]
+pub mod autosave;
+#[cfg(feature = "framestep-big-brain")]
+pub mod bigbrain;
+pub mod budget;
+pub mod cleanup;
+pub mod commands;
+pub mod config;
+pub mod cooldown;
+pub mod debugdump;
+#[cfg(feature = "app")]
+pub mod determinism;
+#[cfg(feature = "framestep-diagnostics")]
+pub mod diagnostics;
+pub mod dynamic;
+pub mod events;
+pub mod executor;
+pub mod groups;
+#[cfg(feature = "app")]
+pub mod harness;
+pub mod instances;
+#[cfg(feature = "framestep-interest")]
+pub mod interest;
+#[cfg(feature = "app")]
+pub mod journal;
+pub mod lint;
+pub mod local;
+#[cfg(feature = "app")]
+pub mod lockstep;
+pub mod lod;
+pub mod metronome;
+pub mod migration;
+#[cfg(feature = "framestep-net")]
+pub mod net;
+#[cfg(feature = "app")]
+pub mod overrides;
+pub mod params;
+pub mod periodic;
+pub mod placement;
+#[cfg(feature = "app")]
+pub mod prediction;
+pub mod presim;
+pub mod regions;
+pub mod relocate;
+#[cfg(feature = "app")]
+pub mod replay;
+pub mod report;
+#[cfg(feature = "framestep-save")]
+pub mod save;
+pub mod scheduler;
+pub mod simtime;
+pub mod snapshots;
+pub mod slots;
+pub mod split;
+#[cfg(feature = "states")]
+pub mod states;
+#[cfg(feature = "app")]
+pub mod tasks;
+#[cfg(feature = "framestep-scripting")]
+pub mod scripting;
+pub mod tick;
+#[cfg(feature = "app")]
+pub mod timers;
+pub mod timescale;
+pub mod tween;
+pub mod units;
+pub mod watchdog;
+#[cfg(feature = "framestep-debugger")]
+pub mod debugger;
+#[cfg(feature = "framestep-tas")]
+pub mod tas;
+
+/// The "name" of a fixed framestep. Used to manipulate it.
+pub type FramestepName = &'static str;
+
+/// Types that can be used to name a fixed framestep
+///
+/// Implemented for the plain [`FramestepName`] strings. To avoid scattering string constants
+/// around a large codebase, you can `#[derive(FramestepLabel)]` on your own fieldless enums
+/// (each variant names a different fixed framestep) or unit structs.
+pub trait FramestepLabel {
+    /// The name used internally to identify the fixed framestep
+    fn framestep_name(&self) -> FramestepName;
+
+    /// The [`FramestepId`] of the fixed framestep
+    fn framestep_id(&self) -> FramestepId {
+        FramestepId::new(self.framestep_name())
+    }
+}
+
+impl FramestepLabel for FramestepName {
+    fn framestep_name(&self) -> FramestepName {
+        self
+    }
+}
+
+/// The name of a fixed framestep, with its hash computed once
+///
+/// The [`FixedFramesteps`] resource is keyed by these, so looking up a fixed framestep by
+/// its id does not hash the name again. The stages keep their own id, and so can you, in a
+/// constant: `const PHYSICS: FramestepId = FramestepId::new("physics");`. An id can be used
+/// anywhere a [`FramestepLabel`] is expected.
+#[derive(Clone, Copy)]
+pub struct FramestepId {
+    name: FramestepName,
+    hash: u64,
+}
+
+impl FramestepId {
+    /// The id of the fixed framestep with the given name
+    pub const fn new(name: FramestepName) -> Self {
+        // FNV-1a
+        let bytes = name.as_bytes();
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut i = 0;
+        while i < bytes.len() {
+            hash ^= bytes[i] as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+            i += 1;
+        }
+        Self { name, hash }
+    }
+
+    /// The name of the fixed framestep
+    pub fn name(&self) -> FramestepName {
+        self.name
+    }
+}
+
+impl PartialEq for FramestepId {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.name == other.name
+    }
+}
+
+impl Eq for FramestepId {}
+
+impl std::hash::Hash for FramestepId {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash);
+    }
+}
+
+impl std::fmt::Debug for FramestepId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.name.fmt(f)
+    }
+}
+
+impl std::fmt::Display for FramestepId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.name.fmt(f)
+    }
+}
+
+impl FramestepLabel for FramestepId {
+    fn framestep_name(&self) -> FramestepName {
+        self.name
+    }
+
+    fn framestep_id(&self) -> FramestepId {
+        *self
+    }
+}
+
+pub use iyes_loopless_macros::FramestepLabel;
+
+pub use units::{Frames, Ticks};
+
+/// Integer type used to count frames (the number inside [`Frames`])
+pub type FrameCounter = u64;
+
+/// Resource type that allows you to get info about and to manipulate fixed framestep state
+///
+/// If you want to access parameters of your fixed framestep(s), such as the framestep length,
+/// accumulator, and paused state, you can get them from this resource. They are contained
+/// in a [`FixedFramestepInfo`] struct, which you can get using the various methods on this type.
+///
+/// If you mutate the framestep length or paused state, they will be taken into account
+/// from the next run of that fixed framestep.
+///
+/// From within a fixed framestep system, you can also mutate the accumulator. For networking
+/// or other use cases that need to stretch time, prefer [`FixedFramestepInfo::stretch`].
+#[derive(Default)]
+#[derive(Resource)]
+pub struct FixedFramesteps {
+    info: HashMap<FramestepId, FixedFramestepInfo>,
+    // the fixed framesteps whose ticks are running, innermost last
+    running: Vec<FramestepId>,
+    generations: u64,
+}
+
+impl FixedFramesteps {
+    /// Returns a reference to the framestep info for a given framestep by name.
+    pub fn get(&self, label: impl FramestepLabel) -> Option<&FixedFramestepInfo> {
+        self.info.get(&label.framestep_id())
+    }
+
+    /// Returns a reference to the framestep info for the currently running stage.
+    ///
+    /// Returns [`Some`] only if called inside a fixed framestep stage. Inside nested fixed
+    /// framesteps, this is the innermost one.
+    pub fn get_current(&self) -> Option<&FixedFramestepInfo> {
+        self.running.last().and_then(|id| self.info.get(id))
+    }
+
+    /// The name of the fixed framestep whose tick is running (the innermost one, if nested)
+    pub fn current_label(&self) -> Option<FramestepName> {
+        self.running.last().map(FramestepId::name)
+    }
+
+    /// The ids of all the fixed framesteps whose ticks are running, outermost first
+    ///
+    /// Empty outside of the fixed framesteps. Has more than one id inside nested fixed
+    /// framesteps, and may have the same id more than once, if a tick runs its own fixed
+    /// framestep stage again (say, using [`FixedFramestepStage::run_once`]).
+    pub fn running(&self) -> &[FramestepId] {
+        &self.running
+    }
+
+    /// Panicking version of [`get_current`]
+    ///
+    /// For systems that may also run outside of a fixed framestep, prefer the
+    /// [`Framesteps`](params::Framesteps) system parameter, which does not panic.
+    pub fn current(&self) -> &FixedFramestepInfo {
+        self.get_current()
+            .expect("FixedFramesteps::current can only be used when running inside a fixed framestep.")
+    }
+
+    /// Returns a reference to the framestep info, assuming you only have one.
+    pub fn get_single(&self) -> Option<&FixedFramestepInfo> {
+        self.single_checked().ok()
+    }
+
+    /// Returns a reference to the framestep info, assuming you only have one.
+    ///
+    /// Unlike [`get_single`], tells you why it failed.
+    pub fn single_checked(&self) -> Result<&FixedFramestepInfo, SingleError> {
+        match self.info.len() {
+            0 => Err(SingleError::NoFramesteps),
+            1 => Ok(self.info.values().next().unwrap()),
+            n => Err(SingleError::MultipleFramesteps(n)),
+        }
+    }
+
+    /// Panicking version of [`get_single`]
+    pub fn single(&self) -> &FixedFramestepInfo {
+        match self.single_checked() {
+            Ok(info) => info,
+            Err(e) => panic!("Expected exactly one fixed framestep: {}", e),
+        }
+    }
+
+    /// Returns a mut reference to the framestep info for a given framestep by name.
+    pub fn get_mut(&mut self, label: impl FramestepLabel) -> Option<&mut FixedFramestepInfo> {
+        self.info.get_mut(&label.framestep_id())
+    }
+
+    /// Returns a mut reference to the framestep info for the currently running stage.
+    ///
+    /// Returns [`Some`] only if called inside a fixed framestep stage.
+    pub fn get_current_mut(&mut self) -> Option<&mut FixedFramestepInfo> {
+        let id = *self.running.last()?;
+        self.info.get_mut(&id)
+    }
+
+    /// Panicking version of [`get_current_mut`]
+    ///
+    /// For systems that may also run outside of a fixed framestep, prefer the
+    /// [`CurrentFramestepMut`](params::CurrentFramestepMut) system parameter, which does not panic.
+    pub fn current_mut(&mut self) -> &mut FixedFramestepInfo {
+        self.get_current_mut()
+            .expect("FixedFramesteps::current can only be used when running inside a fixed framestep.")
+    }
+
+    /// Returns a mut reference to the framestep info, assuming you only have one.
+    pub fn get_single_mut(&mut self) -> Option<&mut FixedFramestepInfo> {
+        self.single_checked_mut().ok()
+    }
+
+    /// Returns a mut reference to the framestep info, assuming you only have one.
+    ///
+    /// Unlike [`get_single_mut`], tells you why it failed.
+    pub fn single_checked_mut(&mut self) -> Result<&mut FixedFramestepInfo, SingleError> {
+        match self.info.len() {
+            0 => Err(SingleError::NoFramesteps),
+            1 => Ok(self.info.values_mut().next().unwrap()),
+            n => Err(SingleError::MultipleFramesteps(n)),
+        }
+    }
+
+    /// Panicking version of [`get_single_mut`]
+    pub fn single_mut(&mut self) -> &mut FixedFramestepInfo {
+        match self.single_checked_mut() {
+            Ok(info) => info,
+            Err(e) => panic!("Expected exactly one fixed framestep: {}", e),
+        }
+    }
+
+    /// Rewind the tick counter of a fixed framestep to an earlier tick, e.g. after a rollback
+    ///
+    /// The tick counter and the history are rewound immediately. The next time the fixed
+    /// framestep runs, it also drops any [`TickCommands`](commands::TickCommands) that were
+    /// waiting for future ticks, and sends a [`TicksRewound`] event. Nested fixed framesteps
+    /// have their own tick counters, and need to be rewound separately.
+    ///
+    /// Returns `false` (and does nothing) if the fixed framestep does not exist, or if
+    /// `to_tick` is later than its current tick.
+    pub fn rewind(&mut self, label: impl FramestepLabel, to_tick: u64) -> bool {
+        self.info.get_mut(&label.framestep_id())
+            .map(|info| info.rewind(to_tick))
+            .unwrap_or(false)
+    }
+
+    /// Returns the recent history of a fixed framestep by name, oldest first.
+    ///
+    /// The history is empty unless enabled on the stage, using
+    /// [`FixedFramestepStage::set_history_len`].
+    pub fn history(&self, label: impl FramestepLabel) -> Option<&VecDeque<FixedFramestepRecord>> {
+        self.info.get(&label.framestep_id()).map(|info| &info.history)
+    }
+
+    /// Iterate over the ids and infos of all fixed framesteps, in arbitrary order
+    pub fn iter(&self) -> hash_map::Iter<'_, FramestepId, FixedFramestepInfo> {
+        self.info.iter()
+    }
+
+    /// Iterate over the ids and mutable infos of all fixed framesteps, in arbitrary order
+    pub fn iter_mut(&mut self) -> hash_map::IterMut<'_, FramestepId, FixedFramestepInfo> {
+        self.info.iter_mut()
+    }
+
+    /// Take a snapshot of the stepping state of all fixed framesteps
+    ///
+    /// The snapshot has the tick counters, accumulators, step, pause reasons, speed, history,
+    /// queued ticks, and the [rng](FixedFramestepInfo::rng) of every fixed framestep: all
+    /// the tick bookkeeping, so that a rollback system can revert it together with the
+    /// components, using [`restore`](Self::restore). Queued [`TickCommands`](commands::TickCommands)
+    /// are not in the snapshot.
+    ///
+    /// Should be called between ticks: outside of the fixed framesteps, or from inside
+    /// another fixed framestep that runs before them.
+    pub fn snapshot(&self) -> FramestepsSnapshot {
+        FramestepsSnapshot {
+            framesteps: self.info.iter()
+                .map(|(id, info)| FramestepSnapshot {
+                    label: id.name(),
+                    step: info.step,
+                    accumulator: info.accumulator,
+                    pause_reasons: info.pause_reasons.clone(),
+                    tick: info.tick,
+                    queued_ticks: info.queued_ticks,
+                    history: info.history.clone(),
+                    pending_stretch: info.pending_stretch,
+                    speed: info.speed,
+                    step_index: info.step_index,
+                    steps_this_frame: info.steps_this_frame,
+                    last_frame_count: info.last_frame_count,
+                    time_carry: info.time_carry,
+                    scale_carry: info.scale_carry,
+                    pending_rewind: info.pending_rewind,
+                    rng: info.rng.clone(),
+                    warmup_remaining: info.warmup_remaining,
+                })
+                .collect(),
+        }
+    }
+
+    /// Revert the stepping state of the fixed framesteps to a [`snapshot`](Self::snapshot)
+    ///
+    /// Fixed framesteps that are not in the snapshot are left as they are, and those in
+    /// the snapshot that no longer exist are ignored. The values of [`TickLocal`](local::TickLocal)
+    /// system params are reset, like on a [rewind](Self::rewind). Like `snapshot`, should
+    /// be called between ticks.
+    pub fn restore(&mut self, snapshot: &FramestepsSnapshot) {
+        for saved in snapshot.framesteps.iter() {
+            let info = match self.info.get_mut(&FramestepId::new(saved.label)) {
+                Some(info) => info,
+                None => continue,
+            };
+            info.step = saved.step;
+            info.accumulator = saved.accumulator;
+            info.pause_reasons = saved.pause_reasons.clone();
+            info.tick = saved.tick;
+            info.queued_ticks = saved.queued_ticks;
+            info.history = saved.history.clone();
+            info.pending_stretch = saved.pending_stretch;
+            info.speed = saved.speed;
+            info.step_index = saved.step_index;
+            info.steps_this_frame = saved.steps_this_frame;
+            info.last_frame_count = saved.last_frame_count;
+            info.time_carry = saved.time_carry;
+            info.scale_carry = saved.scale_carry;
+            info.pending_rewind = saved.pending_rewind;
+            info.rng = saved.rng.clone();
+            info.warmup_remaining = saved.warmup_remaining;
+            self.generations += 1;
+            info.generation = self.generations;
+        }
+    }
+}
+
+/// The stepping state of all fixed framesteps, from [`FixedFramesteps::snapshot`]
+#[derive(Debug, Clone)]
+pub struct FramestepsSnapshot {
+    framesteps: Vec<FramestepSnapshot>,
+}
+
+impl FramestepsSnapshot {
+    /// The tick counter of the given fixed framestep, when the snapshot was taken
+    pub fn tick(&self, label: impl FramestepLabel) -> Option<u64> {
+        let label = label.framestep_name();
+        self.framesteps.iter()
+            .find(|saved| saved.label == label)
+            .map(|saved| saved.tick)
+    }
+
+    /// The number of fixed framesteps in the snapshot
+    pub fn len(&self) -> usize {
+        self.framesteps.len()
+    }
+
+    /// Is the snapshot empty (there were no fixed framesteps)?
+    pub fn is_empty(&self) -> bool {
+        self.framesteps.is_empty()
+    }
+}
+
+#[derive(Debug, Clone)]
+struct FramestepSnapshot {
+    label: FramestepName,
+    step: Frames,
+    accumulator: Frames,
+    pause_reasons: Vec<(PauseReason, u32)>,
+    tick: u64,
+    queued_ticks: u32,
+    history: VecDeque<FixedFramestepRecord>,
+    pending_stretch: i64,
+    speed: u32,
+    step_index: u32,
+    steps_this_frame: u32,
+    last_frame_count: Option<u32>,
+    time_carry: Duration,
+    scale_carry: f64,
+    pending_rewind: Option<u64>,
+    rng: TickRng,
+    warmup_remaining: u64,
+}
+
+impl<'a> IntoIterator for &'a FixedFramesteps {
+    type Item = (&'a FramestepId, &'a FixedFramestepInfo);
+    type IntoIter = hash_map::Iter<'a, FramestepId, FixedFramestepInfo>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut FixedFramesteps {
+    type Item = (&'a FramestepId, &'a mut FixedFramestepInfo);
+    type IntoIter = hash_map::IterMut<'a, FramestepId, FixedFramestepInfo>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// Access the info of a fixed framestep by name, like `framesteps["physics"]`
+///
+/// Panics if there is no fixed framestep with that name.
+impl<L: FramestepLabel> Index<L> for FixedFramesteps {
+    type Output = FixedFramestepInfo;
+
+    fn index(&self, label: L) -> &FixedFramestepInfo {
+        let id = label.framestep_id();
+        self.info.get(&id)
+            .unwrap_or_else(|| panic!("Fixed framestep {:?} not found.", id))
+    }
+}
+
+impl<L: FramestepLabel> IndexMut<L> for FixedFramesteps {
+    fn index_mut(&mut self, label: L) -> &mut FixedFramestepInfo {
+        let id = label.framestep_id();
+        self.info.get_mut(&id)
+            .unwrap_or_else(|| panic!("Fixed framestep {:?} not found.", id))
+    }
+}
+
+/// Error from [`FixedFramesteps::single_checked`], like Bevy's `QuerySingleError`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SingleError {
+    /// There are no fixed framesteps
+    NoFramesteps,
+    /// There are several fixed framesteps (how many)
+    MultipleFramesteps(usize),
+}
+
+impl std::fmt::Display for SingleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SingleError::NoFramesteps => write!(f, "there are no fixed framesteps"),
+            SingleError::MultipleFramesteps(n) => write!(f, "there are {} fixed framesteps", n),
+        }
+    }
+}
+
+impl std::error::Error for SingleError {}
+
+/// The lowest rate of the elastic sub-stages (see [`FixedFramestepStage::set_elastic_substage`])
+const MAX_ELASTIC_THROTTLE: u32 = 4;
+
+/// The largest allowed number of frames in a fixed framestep tick
+pub const MAX_FRAMESTEP: Frames = Frames(u32::MAX as FrameCounter);
+
+/// Error for an invalid number of frames in a fixed framestep tick
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramestepError {
+    /// The step was `0` frames; the fixed framestep would never advance
+    Zero,
+    /// The step was larger than [`MAX_FRAMESTEP`]
+    TooLarge(Frames),
+}
+
+impl std::fmt::Display for FramestepError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FramestepError::Zero => write!(f, "fixed framestep cannot be 0 frames"),
+            FramestepError::TooLarge(step) => write!(
+                f, "fixed framestep of {} frames is larger than the maximum ({})",
+                step, MAX_FRAMESTEP,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FramestepError {}
+
+fn validate_framestep(step: Frames) -> Result<Frames, FramestepError> {
+    match step {
+        Frames::ZERO => Err(FramestepError::Zero),
+        step if step > MAX_FRAMESTEP => Err(FramestepError::TooLarge(step)),
+        step => Ok(step),
+    }
+}
+
+/// The number of frames in each tick, for the given rate in Hz, rounded to the nearest frame
+fn framestep_for_hz(hz: f64, frame_duration: Duration) -> Result<Frames, FramestepError> {
+    let frames = (1.0 / (hz * frame_duration.as_secs_f64())).round();
+    // also catches NaN, from a zero (or negative) rate or frame duration
+    if frames.is_nan() || frames < 1.0 {
+        return Err(FramestepError::Zero);
+    }
+    // the cast saturates, so this is still too large
+    validate_framestep(Frames(frames as FrameCounter))
+}
+
+/// Provides access to the parameters of a fixed framestep
+///
+/// You can get this using the [`FixedFramesteps`] resource.
+pub struct FixedFramestepInfo {
+    /// Number of frames in each fixed framestep tick
+    ///
+    /// Changing this never panics. Prefer [`try_set_step`](Self::try_set_step), which
+    /// tells you if the value is invalid. If you write an invalid value here directly,
+    /// the fixed framestep ignores it (with a warning) and restores the previous value.
+    pub step: Frames,
+    /// Accumulated frames since the last fixed framestep run
+    pub accumulator: Frames,
+    // held pause reasons, with the number of times each one was pushed
+    pause_reasons: Vec<(PauseReason, u32)>,
+    auto_paused: bool,
+    tick: u64,
+    queued_ticks: u32,
+    history: VecDeque<FixedFramestepRecord>,
+    tick_times: VecDeque<Instant>,
+    rate_window: Duration,
+    frame_duration: Duration,
+    measure_start: Option<Instant>,
+    pending_stretch: i64,
+    speed: u32,
+    step_index: u32,
+    steps_this_frame: u32,
+    source: AccumulationSource,
+    last_frame_count: Option<u32>,
+    time_carry: Duration,
+    scale_carry: f64,
+    global_scale: bool,
+    pending_rewind: Option<u64>,
+    strict_determinism: bool,
+    // the step, pause state, and speed that the stage last saw
+    synced: (Frames, bool, u32),
+    label: FramestepName,
+    pause_mode: PauseMode,
+    suppressed: Option<Suppression>,
+    seed: u64,
+    rng: TickRng,
+    pending_reset: bool,
+    resets: u64,
+    tick_budget: Option<Duration>,
+    fill_budget: Option<Duration>,
+    warmup_ticks: u64,
+    warmup_remaining: u64,
+    last_tick_duration: Option<Duration>,
+    over_budget_ticks: u64,
+    deferred_ticks: u32,
+    total_deferred_ticks: u64,
+    elastic_throttle: u32,
+    // unique to this run of the fixed framestep: changes on every reset and rewind, for `TickLocal`
+    generation: u64,
+}
+
+impl FixedFramestepInfo {
+    /// The name of the fixed framestep
+    pub fn label(&self) -> FramestepName {
+        self.label
+    }
+    /// The number of frames in each framestep
+    pub fn framestep(&self) -> Frames {
+        self.step
+    }
+    /// Change the number of frames in each framestep, if it is valid
+    ///
+    /// On error, the step is left unchanged.
+    pub fn try_set_step(&mut self, step: impl Into<Frames>) -> Result<(), FramestepError> {
+        self.step = validate_framestep(step.into())?;
+        Ok(())
+    }
+    /// The number of the current tick
+    ///
+    /// Incremented every time before the child stages run, so the first tick is `1`.
+    /// Outside of the fixed framestep, this is the number of the last tick that ran.
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+    /// The nominal duration of a frame (see [`FixedFramestepStage::set_frame_duration`])
+    pub fn frame_duration(&self) -> Duration {
+        self.frame_duration
+    }
+    /// The nominal duration of a tick: the [frame duration](Self::frame_duration) times the step
+    pub fn step_duration(&self) -> Duration {
+        self.frame_duration.saturating_mul(self.step.min(MAX_FRAMESTEP).0 as u32)
+    }
+    /// The nominal number of steps per second (Hz), based on the [frame duration](Self::frame_duration)
+    pub fn rate(&self) -> f64 {
+        self.rate_at(self.frame_duration)
+    }
+    /// The number of steps per second (Hz), if every frame takes `frame_time`
+    ///
+    /// Useful if the frame rate is not the nominal one (say, with the current frame time).
+    pub fn rate_at(&self, frame_time: Duration) -> f64 {
+        1.0 / (self.step.0 as f64 * frame_time.as_secs_f64())
+    }
+    /// Change the step to the number of frames closest to the given rate (Hz), if it is valid
+    ///
+    /// Based on the [frame duration](Self::frame_duration). On error (if the rate is higher
+    /// than one step per frame, or too low), the step is left unchanged.
+    pub fn set_rate_hz(&mut self, hz: f64) -> Result<(), FramestepError> {
+        self.step = framestep_for_hz(hz, self.frame_duration)?;
+        Ok(())
+    }
+    /// The actual number of ticks per second (Hz), measured over the recent past
+    ///
+    /// Unlike [`rate`](Self::rate), this is measured in real time: it counts the ticks that ran
+    /// during the last [rate window](FixedFramestepStage::set_rate_window), so you can check
+    /// that your simulation keeps up with the intended rate under load.
+    /// Returns `0.0` before the fixed framestep has run for the first time, and always
+    /// without the `"framestep-bookkeeping"` cargo feature.
+    pub fn measured_rate(&self) -> f64 {
+        let start = match self.measure_start {
+            Some(start) => start,
+            None => return 0.0,
+        };
+        let now = Instant::now();
+        // do not count the time before the first run
+        let window = self.rate_window.min(now - start);
+        if window.is_zero() {
+            return 0.0;
+        }
+        let n_ticks = self.tick_times.iter()
+            .filter(|time| now - **time <= window)
+            .count();
+        n_ticks as f64 / window.as_secs_f64()
+    }
+    /// The number of frames left over from the last framestep
+    pub fn remaining(&self) -> Frames {
+        self.accumulator
+    }
+    /// How much has the main game update "overstepped" the fixed framestep?
+    /// (how many more (fractional) framesteps are left over in the accumulator)
+    pub fn overstep(&self) -> f64 {
+        self.accumulator.0 as f64 / self.step.0 as f64
+    }
+
+    /// Pause the fixed framestep, for the [`PauseReason::User`] reason
+    ///
+    /// Sugar for [`push_pause(PauseReason::User)`](Self::push_pause): every call must be
+    /// matched by an [`unpause`](Self::unpause).
+    pub fn pause(&mut self) {
+        self.push_pause(PauseReason::User);
+    }
+
+    /// Un-pause (resume) the fixed framestep, undoing one [`pause`](Self::pause)
+    ///
+    /// Sugar for [`pop_pause(PauseReason::User)`](Self::pop_pause). The fixed framestep
+    /// stays paused while other reasons are held.
+    pub fn unpause(&mut self) {
+        self.pop_pause(PauseReason::User);
+    }
+
+    /// Toggle the [`PauseReason::User`] reason: clear it if it is held, or push it
+    pub fn toggle_pause(&mut self) {
+        if self.is_paused_by(PauseReason::User) {
+            self.clear_pause(PauseReason::User);
+        } else {
+            self.push_pause(PauseReason::User);
+        }
+    }
+
+    /// Pause the fixed framestep for the given reason, until it is [popped](Self::pop_pause)
+    ///
+    /// The fixed framestep stays paused as long as any reasons are held, so several systems
+    /// (say, the menu and the cutscene player) can pause it without clobbering each other.
+    /// Every push of a reason must be matched by a pop. The reasons are part of
+    /// [snapshots](FixedFramesteps::snapshot) and savegames, and all but
+    /// [`PauseReason::User`] survive a [reset](Self::reset).
+    pub fn push_pause(&mut self, reason: PauseReason) {
+        match self.pause_reasons.iter_mut().find(|(held, _)| *held == reason) {
+            Some((_, count)) => *count += 1,
+            None => self.pause_reasons.push((reason, 1)),
+        }
+    }
+
+    /// Undo one [`push_pause`](Self::push_pause) of the given reason
+    ///
+    /// Returns `false` if the reason was not held.
+    pub fn pop_pause(&mut self, reason: PauseReason) -> bool {
+        let i = match self.pause_reasons.iter().position(|(held, _)| *held == reason) {
+            Some(i) => i,
+            None => return false,
+        };
+        self.pause_reasons[i].1 -= 1;
+        if self.pause_reasons[i].1 == 0 {
+            self.pause_reasons.remove(i);
+        }
+        true
+    }
+
+    /// Stop holding the given reason, no matter how many times it was pushed
+    pub fn clear_pause(&mut self, reason: PauseReason) {
+        self.pause_reasons.retain(|(held, _)| *held != reason);
+    }
+
+    /// Is the fixed framestep paused for the given reason?
+    pub fn is_paused_by(&self, reason: PauseReason) -> bool {
+        self.pause_count(reason) > 0
+    }
+
+    /// How many times the given reason is held (pushes that were not popped yet)
+    pub fn pause_count(&self, reason: PauseReason) -> u32 {
+        self.pause_reasons.iter()
+            .find(|(held, _)| *held == reason)
+            .map_or(0, |(_, count)| *count)
+    }
+
+    /// The pause reasons that are held, in the order they were first pushed
+    pub fn pause_reasons(&self) -> impl Iterator<Item = &PauseReason> + '_ {
+        self.pause_reasons.iter().map(|(reason, _)| reason)
+    }
+
+    /// Is the fixed framestep paused by one of its [pause conditions](FixedFramestepStage::add_pause_condition)?
+    ///
+    /// Updated every time the fixed framestep runs.
+    pub fn is_auto_paused(&self) -> bool {
+        self.auto_paused
+    }
+
+    /// Is the fixed framestep paused, for any [reason](Self::push_pause), or by a
+    /// [pause condition](Self::is_auto_paused)?
+    ///
+    /// The fixed framestep may also be prevented from running for other reasons; see
+    /// [`suppressed`](Self::suppressed).
+    pub fn is_paused(&self) -> bool {
+        !self.pause_reasons.is_empty() || self.auto_paused
+    }
+
+    /// Why the ticks are not running, other than being [paused](Self::is_paused)
+    ///
+    /// [`None`] if nothing besides the pause state is holding the fixed framestep back.
+    /// Updated every time the fixed framestep runs.
+    pub fn suppressed(&self) -> Option<Suppression> {
+        self.suppressed
+    }
+
+    /// Is the fixed framestep running its ticks? (neither paused nor suppressed)
+    pub fn is_running(&self) -> bool {
+        !self.is_paused() && self.suppressed.is_none()
+    }
+
+    /// Does the fixed framestep follow the [`GlobalTickScale`](timescale::GlobalTickScale)?
+    ///
+    /// See [`FixedFramestepStage::set_global_scale`].
+    pub fn uses_global_scale(&self) -> bool {
+        self.global_scale
+    }
+
+    /// What happens to the elapsed frames while paused
+    ///
+    /// See [`PauseMode`] and [`FixedFramestepStage::set_pause_mode`].
+    pub fn pause_mode(&self) -> PauseMode {
+        self.pause_mode
+    }
+
+    /// The index of the current tick among the ticks that run on this frame
+    ///
+    /// `0` for the first tick on each frame, `1` for the second catch-up tick, and so on.
+    pub fn step_index(&self) -> u32 {
+        self.step_index
+    }
+
+    /// The number of ticks that are due on this frame
+    ///
+    /// Calculated before the first tick of the frame runs. Fewer ticks may actually run,
+    /// if a tick pauses the fixed framestep, or changes the step or accumulator.
+    pub fn steps_this_frame(&self) -> u32 {
+        self.steps_this_frame
+    }
+
+    /// Iterate over only this tick's share of the items, when several ticks run on the same frame
+    ///
+    /// See [`SlicedQueryIter`].
+    pub fn sliced<I: Iterator>(&self, iter: I) -> SlicedQueryIter<I> {
+        SlicedQueryIter::new(iter, self)
+    }
+
+    /// The speed multiplier (see [`set_speed`](Self::set_speed))
+    pub fn speed(&self) -> u32 {
+        self.speed
+    }
+
+    /// Run the fixed framestep `multiplier` times faster
+    ///
+    /// Every elapsed frame is counted `multiplier` times, and the
+    /// [max steps per frame](FixedFramestepStage::set_max_steps_per_frame) are multiplied too.
+    /// Useful for fast-forwarding through long simulations. The default is `1` (normal speed);
+    /// `0` is treated as `1`. To stop the fixed framestep, pause it instead.
+    pub fn set_speed(&mut self, multiplier: u32) {
+        self.speed = multiplier.max(1);
+    }
+
+    /// Add (or, if negative, remove) frames to the accumulator
+    ///
+    /// Use this instead of writing the accumulator directly, for networking or other use cases
+    /// that need to stretch time. It works from anywhere, not only from within the fixed framestep.
+    /// The change is applied by the fixed framestep stage (immediately after the current sub-stage,
+    /// if called from within the fixed framestep, otherwise on its next run), and the resulting
+    /// accumulator is clamped to the [stretch bounds](FixedFramestepStage::set_stretch_bounds).
+    /// If the clamp is hit, a [`FixedFramestepStretchClamped`] event is sent.
+    pub fn stretch(&mut self, frames: i32) {
+        self.pending_stretch += frames as i64;
+    }
+
+    /// Run `n` extra ticks on the next run of the fixed framestep
+    ///
+    /// The ticks will run even if the fixed framestep is paused, and do not
+    /// affect the accumulator. Useful for stepping through ticks when debugging.
+    pub fn queue_ticks(&mut self, n: impl Into<Ticks>) {
+        let n = u32::try_from(n.into().0).unwrap_or(u32::MAX);
+        self.queued_ticks = self.queued_ticks.saturating_add(n);
+    }
+
+    /// Run exactly one tick on the next run of the fixed framestep, even if it is paused
+    ///
+    /// Does not affect the accumulator. Unlike [`queue_ticks(1)`](Self::queue_ticks), calling
+    /// this several times before the fixed framestep runs still only runs one tick.
+    pub fn step_while_paused(&mut self) {
+        self.queued_ticks = self.queued_ticks.max(1);
+    }
+
+    /// Reset the fixed framestep to its initial parameters
+    ///
+    /// Applied at the start of the next run of the fixed framestep (on the next frame, if
+    /// called from inside a tick): the tick counter and accumulator go back to `0`, the step,
+    /// pause state, and speed to those the stage was created with, and the history, queued
+    /// ticks, queued [`TickCommands`](commands::TickCommands), and tasks scheduled with the
+    /// [`TickScheduler`](scheduler::TickScheduler) are dropped. The values of
+    /// [`TickLocal`](local::TickLocal) system params are reset too. Nested fixed framesteps
+    /// are not affected.
+    pub fn reset(&mut self) {
+        self.pending_reset = true;
+    }
+
+    /// How many times the fixed framestep was [reset](Self::reset)
+    pub fn resets(&self) -> u64 {
+        self.resets
+    }
+
+    /// Start the warm-up ticks (see [`FixedFramestepStage::set_warmup_ticks`])
+    ///
+    /// Called automatically after a [reset](Self::reset). Call it yourself when the world
+    /// was rebuilt some other way, like from the enter system of a state. The warm-up ticks
+    /// are [queued](Self::queue_ticks), to run on the next run of the fixed framestep. If a
+    /// warm-up is already running, it starts over. Does nothing if there are no warm-up ticks.
+    pub fn start_warmup(&mut self) {
+        if self.warmup_ticks == 0 {
+            return;
+        }
+        // the ticks left of a warm-up that is running are queued already
+        self.queue_ticks(self.warmup_ticks - self.warmup_remaining.min(self.warmup_ticks));
+        self.warmup_remaining = self.warmup_ticks;
+    }
+
+    /// Is the fixed framestep warming up? (see [`FixedFramestepStage::set_warmup_ticks`])
+    ///
+    /// `true` during the warm-up ticks, and between the start of the warm-up and the first
+    /// of them. Gameplay and rendering systems can use it to treat the world as not live yet.
+    pub fn is_warming_up(&self) -> bool {
+        self.warmup_remaining > 0
+    }
+
+    /// The number of warm-up ticks left, including the one that is running
+    pub fn warmup_remaining(&self) -> Ticks {
+        Ticks(self.warmup_remaining)
+    }
+
+    /// The number of warm-up ticks run after every reset
+    pub fn warmup_ticks(&self) -> Ticks {
+        Ticks(self.warmup_ticks)
+    }
+
+    /// The number of extra ticks that will run on the next run of the fixed framestep
+    pub fn queued_ticks(&self) -> Ticks {
+        Ticks(self.queued_ticks as u64)
+    }
+
+    /// The determinism seed of the fixed framestep (see [`FixedFramestepStage::set_seed`])
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// The CPU time budget for each tick (see [`FixedFramestepStage::set_tick_budget`])
+    pub fn tick_budget(&self) -> Option<Duration> {
+        self.tick_budget
+    }
+
+    /// The time budget filled with ticks on every frame (see [`FixedFramestepStage::set_fill_budget`])
+    pub fn fill_budget(&self) -> Option<Duration> {
+        self.fill_budget
+    }
+
+    /// How long the last tick took to run (wall-clock time)
+    ///
+    /// Includes applying the due [`TickCommands`](commands::TickCommands). [`None`] if
+    /// there was no tick yet.
+    pub fn last_tick_duration(&self) -> Option<Duration> {
+        self.last_tick_duration
+    }
+
+    /// How many ticks took longer than the [tick budget](Self::tick_budget), so far
+    pub fn over_budget_ticks(&self) -> Ticks {
+        Ticks(self.over_budget_ticks)
+    }
+
+    /// How many due ticks were deferred to a later frame on this frame, to stay within the [`FrameBudget`](budget::FrameBudget)
+    pub fn deferred_ticks(&self) -> Ticks {
+        Ticks(self.deferred_ticks as u64)
+    }
+
+    /// How many due ticks were deferred to a later frame, so far (see [`deferred_ticks`](Self::deferred_ticks))
+    ///
+    /// A tick that is deferred on several frames in a row is counted on every one of them.
+    pub fn total_deferred_ticks(&self) -> Ticks {
+        Ticks(self.total_deferred_ticks)
+    }
+
+    /// The elastic sub-stages run on every n-th tick; this is n (`1`, `2`, or `4`)
+    ///
+    /// See [`FixedFramestepStage::set_elastic_substage`].
+    pub fn elastic_throttle(&self) -> u32 {
+        self.elastic_throttle
+    }
+
+    /// A seed for the current tick, derived from the [`seed`](Self::seed) and the tick number
+    ///
+    /// Use it to seed your own random number generators, if you do not want to use [`rng`](Self::rng).
+    pub fn tick_seed(&self) -> u64 {
+        tick_seed(self.seed, self.tick)
+    }
+
+    /// The random number generator of the current tick
+    ///
+    /// Reseeded with the [`tick_seed`](Self::tick_seed) at the start of every tick, so the
+    /// numbers only depend on the seed, the tick number, and the order in which your systems
+    /// draw them (which is only fixed with [strict determinism](FixedFramestepStage::set_strict_determinism),
+    /// or explicit system ordering).
+    pub fn rng(&mut self) -> &mut TickRng {
+        &mut self.rng
+    }
+
+    /// Is strict determinism enabled? (see [`FixedFramestepStage::set_strict_determinism`])
+    pub fn strict_determinism(&self) -> bool {
+        self.strict_determinism
+    }
+
+    /// Collect query items with an `Entity` into a stable order, if strict determinism is enabled
+    ///
+    /// With strict determinism, the items are sorted by `Entity`. Otherwise, they are left
+    /// in the order of the iterator. Use it like `info.stable_order(query.iter())`, in systems
+    /// where the processing order affects the result.
+    pub fn stable_order<T>(&self, iter: impl IntoIterator<Item = (Entity, T)>) -> std::vec::IntoIter<(Entity, T)> {
+        let mut items: Vec<_> = iter.into_iter().collect();
+        if self.strict_determinism {
+            items.sort_by_key(|(entity, _)| *entity);
+        }
+        items.into_iter()
+    }
+
+    /// Set the tick counter back to an earlier tick (see [`FixedFramesteps::rewind`])
+    ///
+    /// Returns `false` (and does nothing) if `to_tick` is later than the current tick.
+    pub fn rewind(&mut self, to_tick: u64) -> bool {
+        if to_tick > self.tick {
+            return false;
+        }
+        self.pending_rewind.get_or_insert(self.tick);
+        self.tick = to_tick;
+        while self.history.back().map(|record| record.tick > to_tick).unwrap_or(false) {
+            self.history.pop_back();
+        }
+        true
+    }
+}
+
+/// Iterator adapter for splitting work across the catch-up ticks that run on the same frame
+///
+/// When the fixed framestep needs to run several ticks on one frame, it can be useful
+/// to spread expensive, non-critical work (like cosmetic or AI updates) across them,
+/// instead of doing all of it on every tick: with `N` ticks on the frame, each of them
+/// processes every `N`-th item. When there is only one tick on the frame, all items are
+/// processed.
+///
+/// The split depends on the order of the items (for queries, that is deterministic for
+/// the same world) and on how many ticks run on each frame (which is not). Do not use it
+/// for gameplay logic that must produce the same results regardless of frame rate.
+///
+/// Create using [`FixedFramestepInfo::sliced`].
+pub struct SlicedQueryIter<I> {
+    inner: I,
+    i: u32,
+    slice: u32,
+    slices: u32,
+}
+
+impl<I: Iterator> SlicedQueryIter<I> {
+    /// Wrap an iterator, to yield only the share of the current tick
+    pub fn new(inner: I, info: &FixedFramestepInfo) -> Self {
+        let slices = info.steps_this_frame.max(1);
+        Self {
+            inner,
+            i: 0,
+            slice: info.step_index % slices,
+            slices,
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for SlicedQueryIter<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        loop {
+            let item = self.inner.next()?;
+            let i = self.i;
+            self.i = (self.i + 1) % self.slices;
+            if i == self.slice {
+                return Some(item);
+            }
+        }
+    }
+}
+
+/// A record of one run of a [`FixedFramestepStage`] (see [`FixedFramesteps::history`])
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FixedFramestepRecord {
+    /// The tick number at the end of the run (see [`FixedFramestepInfo::tick`])
+    pub tick: u64,
+    /// The accumulator at the end of the run
+    pub accumulator: Frames,
+    /// How many ticks were run
+    pub steps: u32,
+    /// How long it took to run all the ticks
+    pub duration: Duration,
+}
+
+/// Event sent when ticks of a fixed framestep are skipped, because a required resource is missing
+///
+/// See [`FixedFramestepStage::add_required_resource`]. Only sent if the event type has been
+/// registered; the `App` extension methods for adding fixed framesteps do that for you.
+#[derive(Debug, Clone)]
+pub struct FixedFramestepSkipped {
+    /// The name of the fixed framestep
+    pub label: FramestepName,
+    /// The type name of the missing resource
+    pub missing_resource: &'static str,
+    /// How many ticks were skipped
+    pub ticks: u32,
+}
+
+/// Event sent when a [`FixedFramestepInfo::stretch`] was limited by the stretch bounds
+///
+/// See [`FixedFramestepStage::set_stretch_bounds`]. Only sent if the event type has been
+/// registered; the `App` extension methods for adding fixed framesteps do that for you.
+#[derive(Debug, Clone)]
+pub struct FixedFramestepStretchClamped {
+    /// The name of the fixed framestep
+    pub label: FramestepName,
+    /// The total number of frames requested using `stretch`
+    pub requested: i64,
+    /// The value of the accumulator after clamping
+    pub accumulator: Frames,
+}
+
+/// Event sent when the tick counter of a fixed framestep was rewound
+///
+/// See [`FixedFramesteps::rewind`]. Sent when the fixed framestep runs, after the rewind
+/// was requested. Use it to discard your own tick-stamped state (like buffered
+/// [`Ticked`](events::Ticked) events) for the ticks after `to`. Only sent if the event type has
+/// been registered; the `App` extension methods for adding fixed framesteps do that for you.
+#[derive(Debug, Clone)]
+pub struct TicksRewound {
+    /// The name of the fixed framestep
+    pub label: FramestepName,
+    /// The tick number before the rewind
+    pub from: u64,
+    /// The tick number after the rewind
+    pub to: u64,
+}
+
+/// Event sent when the last warm-up tick of a fixed framestep has run
+///
+/// See [`FixedFramestepStage::set_warmup_ticks`]. Sent right after the tick. Only sent if
+/// the event type has been registered; the `App` extension methods for adding fixed
+/// framesteps do that for you.
+#[derive(Debug, Clone)]
+pub struct WarmupFinished {
+    /// The name of the fixed framestep
+    pub label: FramestepName,
+    /// The number of the last warm-up tick
+    pub tick: u64,
+}
+
+/// Event sent when a tick of a fixed framestep took longer than its budget
+///
+/// See [`FixedFramestepStage::set_tick_budget`]. Sent right after the tick. Only sent if
+/// the event type has been registered; the `App` extension methods for adding fixed
+/// framesteps do that for you.
+#[derive(Debug, Clone)]
+pub struct TickOverBudget {
+    /// The name of the fixed framestep
+    pub label: FramestepName,
+    /// The number of the tick
+    pub tick: u64,
+    /// How long the tick took to run
+    pub duration: Duration,
+    /// The budget that it exceeded
+    pub budget: Duration,
+}
+
+/// What happens to the elapsed frames while a fixed framestep is paused
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PauseMode {
+    /// The frames are not counted: unpausing continues where it left off
+    #[default]
+    Freeze,
+    /// The frames keep accumulating, up to `max_frames`: unpausing runs the missed ticks
+    ///
+    /// Useful for simulating in the background, like in idle/management games. The missed
+    /// ticks are still limited by [`FixedFramestepStage::set_max_steps_per_frame`] on each
+    /// frame, so raise that if you want all of them to run on the first frame after unpausing.
+    Accumulate {
+        /// The most frames that can accumulate while paused
+        max_frames: Frames,
+    },
+}
+
+/// Small deterministic random number generator (SplitMix64)
+///
+/// Every fixed framestep has one, reseeded at the start of every tick; see
+/// [`FixedFramestepInfo::rng`]. Fast and reproducible on every platform, but not
+/// suitable for cryptography.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TickRng {
+    state: u64,
+}
+
+impl TickRng {
+    /// Create a generator with the given seed
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Generate a random `u64`
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        mix64(self.state)
+    }
+
+    /// Generate a random `f64` in the range `0.0..1.0`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Generate a random integer in the range `0..n`
+    ///
+    /// Panics if `n` is `0`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "TickRng::below called with 0");
+        ((self.next_u64() as u128 * n as u128) >> 64) as u64
+    }
+}
+
+/// The SplitMix64 output function
+fn mix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn tick_seed(seed: u64, tick: u64) -> u64 {
+    mix64(seed ^ mix64(tick))
+}
+
+/// Why a fixed framestep is paused, for pausing from several places independently
+///
+/// See [`FixedFramestepInfo::push_pause`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PauseReason {
+    /// Paused by [`pause`](FixedFramestepInfo::pause), or by starting [`paused`](FixedFramestepStage::paused)
+    User,
+    /// An in-game menu is open
+    Menu,
+    /// A cutscene is playing
+    CutScene,
+    /// The window lost focus
+    LostFocus,
+    /// Any other reason, with a name
+    Custom(Cow<'static, str>),
+}
+
+impl PauseReason {
+    /// A [`Custom`](Self::Custom) reason with a static name, usable in `const`s
+    pub const fn custom(name: &'static str) -> Self {
+        PauseReason::Custom(Cow::Borrowed(name))
+    }
+}
+
+impl std::fmt::Display for PauseReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PauseReason::User => write!(f, "user"),
+            PauseReason::Menu => write!(f, "menu"),
+            PauseReason::CutScene => write!(f, "cutscene"),
+            PauseReason::LostFocus => write!(f, "lost focus"),
+            PauseReason::Custom(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Why a fixed framestep is not running its ticks, even though it is not paused
+///
+/// See [`FixedFramestepInfo::suppressed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Suppression {
+    /// A required resource is missing (see [`FixedFramestepStage::add_required_resource`])
+    MissingResource(&'static str),
+    /// The next tick is waiting for something, like the inputs of the other players
+    /// (see the `lockstep` module); the due ticks run once it arrives
+    Stalled,
+    /// The due ticks did not fit in the [`FrameBudget`](budget::FrameBudget), next to the
+    /// fixed framesteps with a higher priority; they run on a later frame
+    Deferred,
+}
+
+impl std::fmt::Display for Suppression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Suppression::MissingResource(name) => write!(f, "missing resource {}", name),
+            Suppression::Stalled => write!(f, "stalled"),
+            Suppression::Deferred => write!(f, "deferred"),
+        }
+    }
+}
+
+/// Where a fixed framestep stage was added to the schedule
+///
+/// Known for fixed framesteps added using the `App`/`Schedule` extension methods.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SchedulePosition {
+    /// Before the stage with the given label
+    Before(&'static str),
+    /// After the stage with the given label
+    After(&'static str),
+    /// Nested inside the fixed framestep with the given name
+    Nested(FramestepName),
+    /// The stage was added to the schedule directly
+    #[default]
+    Unknown,
+}
+
+/// Where to add a fixed framestep stage to the schedule, for `add_fixed_framestep_at`
+///
+/// Stages are referred to by the string of their label (`StageLabel::as_str`), such as
+/// `"CoreStage::Update"`, so a position is plain data that can be stored in config files,
+/// or saved and used to register the fixed framestep again (it is serializable with the
+/// `serde` cargo feature). If several stages have the same label string, the first one in
+/// the schedule is used.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FramestepPosition {
+    /// The default position: before `CoreStage::Update`
+    #[default]
+    Default,
+    /// Before the stage with the given label
+    Before(Cow<'static, str>),
+    /// After the stage with the given label
+    After(Cow<'static, str>),
+    /// At the given index among the stages of the schedule (at the end, if out of range)
+    AtIndex(usize),
+}
+
+impl FramestepPosition {
+    /// Before the given stage
+    pub fn before(stage: impl StageLabel) -> Self {
+        FramestepPosition::Before(Cow::Borrowed(stage.as_str()))
+    }
+
+    /// After the given stage
+    pub fn after(stage: impl StageLabel) -> Self {
+        FramestepPosition::After(Cow::Borrowed(stage.as_str()))
+    }
+}
+
+/// The static layout of a fixed framestep: where it is in the schedule, and what it is made of
+///
+/// Stored in the [`FramestepLayouts`] resource when the fixed framestep first runs in a
+/// world, and again only if its sub-stages change. Unlike the [`FixedFramestepInfo`], it is
+/// not updated on every tick.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FramestepLayout {
+    label: FramestepName,
+    position: SchedulePosition,
+    registered_position: Option<FramestepPosition>,
+    substage_count: usize,
+}
+
+impl FramestepLayout {
+    /// The name of the fixed framestep
+    pub fn label(&self) -> FramestepName {
+        self.label
+    }
+    /// Where the fixed framestep stage is in the schedule
+    pub fn schedule_position(&self) -> SchedulePosition {
+        self.position
+    }
+    /// The position the fixed framestep was registered with, if it was added using `add_fixed_framestep_at`
+    ///
+    /// Unlike [`schedule_position`](Self::schedule_position), this is what was asked for (like
+    /// [`FramestepPosition::Default`]), so it can be saved and used to register the fixed
+    /// framestep again.
+    pub fn registered_position(&self) -> Option<&FramestepPosition> {
+        self.registered_position.as_ref()
+    }
+    /// The number of child sub-stages of the fixed framestep
+    pub fn substage_count(&self) -> usize {
+        self.substage_count
+    }
+}
+
+/// Resource with the [`FramestepLayout`] of every fixed framestep that has run
+#[derive(Debug, Default)]
+#[derive(Resource)]
+pub struct FramestepLayouts {
+    layouts: HashMap<FramestepName, FramestepLayout>,
+}
+
+impl FramestepLayouts {
+    /// The layout of the given fixed framestep, if it has run
+    pub fn get(&self, label: impl FramestepLabel) -> Option<&FramestepLayout> {
+        self.layouts.get(label.framestep_name())
+    }
+
+    /// Iterate over the layouts of all the fixed framesteps that have run
+    pub fn iter(&self) -> impl Iterator<Item = &FramestepLayout> {
+        self.layouts.values()
+    }
+
+    /// Does the world have a layout for the given fixed framestep?
+    fn contains(world: &World, label: FramestepName) -> bool {
+        world.get_resource::<FramestepLayouts>()
+            .map(|layouts| layouts.layouts.contains_key(label))
+            .unwrap_or(false)
+    }
+
+    /// Forget the layout of a fixed framestep that was removed
+    pub(crate) fn remove(&mut self, label: FramestepName) {
+        self.layouts.remove(label);
+    }
+}
+
+/// Add a fixed framestep stage to the schedule, at the given position
+///
+/// Panics if a stage that the position refers to is not in the schedule.
+fn insert_framestep(schedule: &mut Schedule, position: FramestepPosition, mut stage: FixedFramestepStage) {
+    let target = resolve_position(schedule, &position);
+    stage.registered_position = Some(position);
+    let name = stage.label;
+    let label = FixedFramestepStageLabel(name);
+    match target {
+        Insertion::Before(target) => {
+            stage.position = SchedulePosition::Before(target.as_str());
+            schedule.add_stage_before(target, label, stage);
+        }
+        Insertion::After(target) => {
+            stage.position = SchedulePosition::After(target.as_str());
+            schedule.add_stage_after(target, label, stage);
+        }
+        Insertion::End => {
+            schedule.add_stage(label, stage);
+        }
+    }
+    add_anchors(schedule, name);
+}
+
+/// A [`FramestepPosition`], resolved against the stages of a schedule
+enum Insertion {
+    Before(StageLabelId),
+    After(StageLabelId),
+    End,
+}
+
+/// Resolve a [`FramestepPosition`] against the stages of a schedule
+///
+/// Panics if a stage that the position refers to is not in the schedule.
+fn resolve_position(schedule: &Schedule, position: &FramestepPosition) -> Insertion {
+    let find = |name: &str| -> StageLabelId {
+        schedule.iter_stages()
+            .map(|(label, _)| label)
+            .find(|label| label.as_str() == name)
+            .unwrap_or_else(|| panic!("Stage {:?} not found in the schedule.", name))
+    };
+    match position {
+        FramestepPosition::Default => Insertion::Before(find("CoreStage::Update")),
+        FramestepPosition::Before(name) => Insertion::Before(find(name)),
+        FramestepPosition::After(name) => Insertion::After(find(name)),
+        FramestepPosition::AtIndex(i) => {
+            let labels: Vec<_> = schedule.iter_stages().map(|(label, _)| label).collect();
+            match (labels.get(*i), labels.last()) {
+                (Some(label), _) => Insertion::Before(*label),
+                (None, Some(last)) => Insertion::After(*last),
+                (None, None) => Insertion::End,
+            }
+        }
+    }
+}
+
+/// A parameter of a fixed framestep that can be changed through [`FixedFramesteps`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FramestepConfigField {
+    /// The step ([`FixedFramestepInfo::step`])
+    Step,
+    /// The pause state ([`FixedFramestepInfo::is_paused`])
+    Paused,
+    /// The speed multiplier ([`FixedFramestepInfo::set_speed`])
+    Speed,
+}
+
+/// Event sent when the step, pause state, or speed of a fixed framestep was changed
+///
+/// Sent by the fixed framestep, when it picks up the change from the [`FixedFramesteps`]
+/// resource (on its next run, or after the current sub-stage if changed from inside a tick).
+/// Invalid values that are ignored do not count as changes. Only sent if the event type has
+/// been registered; the `App` extension methods for adding fixed framesteps do that for you.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FramestepConfigChanged {
+    /// The name of the fixed framestep
+    pub label: FramestepName,
+    /// Which parameter was changed
+    pub field: FramestepConfigField,
+}
+
+/// Where a [`FixedFramestepStage`] gets its elapsed frame count from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccumulationSource {
+    /// Every run of the stage counts as exactly one frame
+    ///
+    /// If the stage does not run on some frames (say, because of an outer
+    /// run criteria), those frames are not counted.
+    #[default]
+    StageRuns,
+    /// Diff the value of Bevy's `FrameCount` resource since the last run of the stage
+    ///
+    /// Frames on which the stage was skipped are still accounted for. Requires
+    /// the `FrameCount` resource (added by Bevy's `CorePlugin`); the stage
+    /// does nothing if it is missing. The first run counts as one frame.
+    FrameCount,
+    /// Count the real time, from the unscaled delta of Bevy's `Time` resource
+    ///
+    /// Every [frame duration](FixedFramestepStage::set_frame_duration) of time that passed
+    /// counts as one frame (the rest is carried over), no matter how many frames Bevy
+    /// actually ran. Ignores the pause and relative speed of the `Time`, so it keeps going
+    /// while the game time is paused: useful for UI and audio ticks. Requires the `Time`
+    /// resource (added by Bevy's `TimePlugin`); the stage does nothing if it is missing.
+    RealTime,
+    /// Count the game time, from the scaled delta of Bevy's `Time` resource
+    ///
+    /// Like [`RealTime`](Self::RealTime), but follows the pause and relative speed of the
+    /// `Time`, so slowing down or pausing the game time does the same to the fixed framestep.
+    GameTime,
+}
+
+/// The default length of the window for [`FixedFramestepInfo::measured_rate`]
+pub const DEFAULT_RATE_WINDOW: Duration = Duration::from_secs(1);
+
+/// The default nominal duration of a frame (1/60 s), for [`FixedFramestepInfo::rate`]
+pub const DEFAULT_FRAME_DURATION: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// The default maximum number of ticks a [`FixedFramestepStage`] runs in a single frame
+pub const DEFAULT_MAX_STEPS_PER_FRAME: u32 = 64;
+
+/// Type name and presence check of a required resource
+type RequiredResource = (&'static str, fn(&World) -> bool);
+
+/// Check if the next tick of a fixed framestep can run (see `FixedFramestepStage::add_tick_gate`)
+type TickGate = fn(&mut World, FramestepName, u64) -> bool;
+
+/// Callback for the start or end of every tick (see [`FixedFramestepStage::on_tick_begin`])
+type TickCallback = Box<dyn FnMut(&mut World, u64) + Send + Sync>;
+
+/// Rescale the state of the world for a new step (see [`FixedFramestepStage::add_rate_migration`])
+type BoxedRateMigration = Box<dyn migration::RateMigration>;
+
+/// Check if a fixed framestep should be paused (see [`FixedFramestepStage::add_pause_condition`])
+type PauseCondition = Box<dyn Fn(&World) -> bool + Send + Sync>;
+
+/// A Stage that runs a number of child stages with a fixed framestep
+///
+/// You can set the framestep length. Every frame update, the elapsed frames
+/// will be accumulated, and the child stages will run when it reaches
+/// the framestep threshold. If multiple framesteps have been accumulated,
+/// the child stages will be run multiple times.
+///
+/// You can add multiple child stages, allowing you to use `Commands` in
+/// your fixed framestep systems, and have their effects applied.
+///
+/// All the state that changes while stepping (accumulator, tick counter, etc.) is kept
+/// in the [`FixedFramesteps`] resource of the `World`, so the same schedule can drive
+/// several worlds independently (for example, in tests, or one per server room). A world
+/// that has not been run before starts from the initial parameters of the stage.
+/// Note that Bevy's own `SystemStage` can only ever run on one world, so this
+/// only helps if the child stages also support running on several worlds.
+///
+/// A good place to add the `FixedFramestepStage` is usually before
+/// `CoreStage::Update`.
+pub struct FixedFramestepStage {
+    step: Frames,
+    accumulator: Frames,
+    label: FramestepName,
+    id: FramestepId,
+    stages: Vec<Box<dyn Stage>>,
+    source: AccumulationSource,
+    last_frame_count: Option<u32>,
+    time_carry: Duration,
+    scale_carry: f64,
+    global_scale: bool,
+    tick: u64,
+    history_len: usize,
+    required_resources: Vec<RequiredResource>,
+    warned_missing: bool,
+    max_steps: u32,
+    rate_window: Duration,
+    frame_duration: Duration,
+    measure_start: Option<Instant>,
+    stretch_bounds: (Frames, Frames),
+    speed: u32,
+    step_index: u32,
+    steps_this_frame: u32,
+    initial_step: Frames,
+    initial_paused: bool,
+    // are any pause reasons held? (see `FixedFramestepInfo::push_pause`)
+    pause_held: bool,
+    pause_conditions: Vec<PauseCondition>,
+    auto_paused: bool,
+    strict_determinism: bool,
+    task_pool: Option<Arc<TaskPool>>,
+    determinism_lint: bool,
+    linted: bool,
+    tick_begin_hooks: Vec<fn(&mut World)>,
+    framestep_resource: Option<(split::SyncFn, split::SyncFn)>,
+    tick_gates: Vec<TickGate>,
+    tick_begin_callbacks: Vec<TickCallback>,
+    tick_end_callbacks: Vec<TickCallback>,
+    rate_migrations: Vec<BoxedRateMigration>,
+    pending_configs: Vec<config::ConfigInit>,
+    position: SchedulePosition,
+    registered_position: Option<FramestepPosition>,
+    // the sub-stages changed since the `FramestepLayout` was stored
+    layout_changed: bool,
+    // left behind in the schedule by `move_fixed_framestep`: does nothing, and is never found by name
+    moved: bool,
+    pause_mode: PauseMode,
+    suppressed: Option<Suppression>,
+    seed: u64,
+    tick_budget: Option<Duration>,
+    fill_budget: Option<Duration>,
+    warmup_ticks: u64,
+    watchdog: Option<watchdog::TickWatchdog>,
+    elastic_substages: Vec<usize>,
+    region_substages: Vec<usize>,
+    regions_per_tick: Option<usize>,
+    state_transitions: Vec<Box<dyn Stage>>,
+}
+
+impl FixedFramestepStage {
+    /// Helper to create a `FixedFramestepStage` with a single child stage
+    pub fn from_stage<S: Stage>(framestep: impl Into<Frames>, label: impl FramestepLabel, stage: S) -> Self {
+        Self::new(framestep, label).with_stage(stage)
+    }
+
+    /// Create a new empty `FixedFramestepStage` with no child stages
+    ///
+    /// Panics if `framestep` is invalid (see [`try_new`](Self::try_new)).
+    pub fn new(framestep: impl Into<Frames>, label: impl FramestepLabel) -> Self {
+        let label = label.framestep_name();
+        match Self::try_new(framestep, label) {
+            Ok(stage) => stage,
+            Err(e) => panic!("Invalid fixed framestep {:?}: {}", label, e),
+        }
+    }
+
+    /// Create a new empty `FixedFramestepStage` with no child stages
+    ///
+    /// Fails if `framestep` is `0` or larger than [`MAX_FRAMESTEP`].
+    pub fn try_new(framestep: impl Into<Frames>, label: impl FramestepLabel) -> Result<Self, FramestepError> {
+        let framestep = validate_framestep(framestep.into())?;
+        Ok(Self {
+            step: framestep,
+            accumulator: Frames::ZERO,
+            label: label.framestep_name(),
+            id: label.framestep_id(),
+            stages: Vec::new(),
+            source: AccumulationSource::default(),
+            last_frame_count: None,
+            time_carry: Duration::ZERO,
+            scale_carry: 0.0,
+            global_scale: false,
+            tick: 0,
+            history_len: 0,
+            required_resources: Vec::new(),
+            warned_missing: false,
+            max_steps: DEFAULT_MAX_STEPS_PER_FRAME,
+            rate_window: DEFAULT_RATE_WINDOW,
+            frame_duration: DEFAULT_FRAME_DURATION,
+            measure_start: None,
+            stretch_bounds: (Frames::ZERO, Frames::MAX),
+            speed: 1,
+            step_index: 0,
+            steps_this_frame: 0,
+            initial_step: framestep,
+            initial_paused: false,
+            pause_held: false,
+            pause_conditions: Vec::new(),
+            auto_paused: false,
+            strict_determinism: false,
+            task_pool: None,
+            determinism_lint: false,
+            linted: false,
+            tick_begin_hooks: Vec::new(),
+            framestep_resource: None,
+            tick_gates: Vec::new(),
+            tick_begin_callbacks: Vec::new(),
+            tick_end_callbacks: Vec::new(),
+            rate_migrations: Vec::new(),
+            pending_configs: Vec::new(),
+            position: SchedulePosition::Unknown,
+            registered_position: None,
+            layout_changed: true,
+            moved: false,
+            pause_mode: PauseMode::Freeze,
+            suppressed: None,
+            seed: 0,
+            tick_budget: None,
+            fill_budget: None,
+            warmup_ticks: 0,
+            watchdog: None,
+            elastic_substages: Vec::new(),
+            region_substages: Vec::new(),
+            regions_per_tick: None,
+            state_transitions: Vec::new(),
+        })
+    }
+
+    /// Builder method for starting in a paused state
+    ///
+    /// It starts with the [`PauseReason::User`] reason held, as if [`pause`](FixedFramestepInfo::pause)
+    /// was called once; it is held again after every [reset](FixedFramestepInfo::reset).
+    pub fn paused(mut self) -> Self {
+        self.pause_held = true;
+        self.initial_paused = true;
+        self
+    }
+
+    /// Add a child stage
+    ///
+    /// Every tick, each child stage is run exactly once, in order. Bevy's `SystemStage`
+    /// and `Schedule` evaluate their own run criteria during that run, including looping
+    /// ones (`ShouldRun::YesAndCheckAgain`). If you have a custom stage type that does not
+    /// handle run criteria, or want to loop several stages together, wrap it in a
+    /// [`RunCriteriaStage`].
+    ///
+    /// The `Commands` of each child stage are applied before the next one runs; see the
+    /// [ordering guarantees](commands#ordering-guarantees).
+    ///
+    /// If there is a [cleanup sub-stage](cleanup), the new stage is inserted before it.
+    pub fn add_stage<S: Stage>(&mut self, stage: S) {
+        let mut stage: Box<dyn Stage> = Box::new(stage);
+        if self.strict_determinism {
+            make_strict(stage.as_mut());
+        } else if self.task_pool.is_some() {
+            use_task_pool(stage.as_mut(), self.task_pool.as_ref());
+        }
+        if self.cleanup_substage().is_some() {
+            self.stages.insert(self.stages.len() - 1, stage);
+        } else {
+            self.stages.push(stage);
+        }
+        self.layout_changed = true;
+    }
+
+    /// Builder method for adding a child stage
+    pub fn with_stage<S: Stage>(mut self, stage: S) -> Self {
+        self.add_stage(stage);
+        self
+    }
+
+    /// Where this stage was added to the schedule (see [`FramestepLayout::schedule_position`])
+    pub fn schedule_position(&self) -> SchedulePosition {
+        self.position
+    }
+
+    /// The position this stage was registered with (see [`FramestepLayout::registered_position`])
+    pub fn registered_position(&self) -> Option<&FramestepPosition> {
+        self.registered_position.as_ref()
+    }
+
+    /// The name of this fixed framestep
+    pub fn label(&self) -> FramestepName {
+        self.label
+    }
+
+    /// The number of frames in each framestep (see [`FixedFramestepInfo::framestep`])
+    ///
+    /// This is the value of the stage itself, as of its last run; change it using the
+    /// [`FixedFramesteps`] resource.
+    pub fn framestep(&self) -> Frames {
+        self.step
+    }
+
+    /// The nominal duration of a frame (see [`set_frame_duration`](Self::set_frame_duration))
+    pub fn frame_duration(&self) -> Duration {
+        self.frame_duration
+    }
+
+    /// How the elapsed frames are counted (see [`set_accumulation_source`](Self::set_accumulation_source))
+    pub fn accumulation_source(&self) -> AccumulationSource {
+        self.source
+    }
+
+    /// The child sub-stages, in the order they run in
+    pub fn substages(&self) -> impl Iterator<Item = &dyn Stage> {
+        self.stages.iter().map(|stage| &**stage)
+    }
+
+    /// Run exactly one tick right now, regardless of the accumulator
+    ///
+    /// Runs all the child stages once, like a normal tick: the tick counter is incremented,
+    /// due [`TickCommands`](commands::TickCommands) are applied, and the [`FixedFramesteps`]
+    /// resource is updated. The accumulator, the elapsed frame count, and the pause state
+    /// are left alone, so the next normal run of the stage is not affected. Useful for tests,
+    /// editors, and external drivers that want to step the simulation themselves.
+    pub fn run_once(&mut self, world: &mut World) {
+        self.load_world_state(world);
+
+        if self.measure_start.is_none() {
+            self.measure_start = Some(Instant::now());
+        }
+
+        self.steps_this_frame = 1;
+        self.step_index = 0;
+        self.run_tick(world);
+
+        self.store_fixedframestepinfo(world);
+    }
+
+    /// Set where the elapsed frames are counted from (see [`AccumulationSource`])
+    pub fn set_accumulation_source(&mut self, source: AccumulationSource) {
+        self.source = source;
+        self.last_frame_count = None;
+        self.time_carry = Duration::ZERO;
+    }
+
+    /// Builder-style method for [`set_accumulation_source`]
+    pub fn with_accumulation_source(mut self, source: AccumulationSource) -> Self {
+        self.set_accumulation_source(source);
+        self
+    }
+
+    /// Opt into (or out of) the [`GlobalTickScale`](timescale::GlobalTickScale)
+    ///
+    /// See the [`timescale`] module. Fixed framesteps do not follow the scale by default.
+    pub fn set_global_scale(&mut self, enabled: bool) {
+        self.global_scale = enabled;
+        self.scale_carry = 0.0;
+    }
+
+    /// Builder-style method for [`set_global_scale`](Self::set_global_scale)
+    pub fn with_global_scale(mut self, enabled: bool) -> Self {
+        self.set_global_scale(enabled);
+        self
+    }
+
+    /// Keep a history of the last `len` runs of this stage (`0` to disable)
+    ///
+    /// Every frame when the fixed framestep is not paused, a [`FixedFramestepRecord`]
+    /// is added to the history, which you can get using [`FixedFramesteps::history`].
+    /// Useful for debug tools that want to graph the tick stability. Without the
+    /// `"framestep-bookkeeping"` cargo feature, the history is always empty.
+    pub fn set_history_len(&mut self, len: usize) {
+        self.history_len = len;
+    }
+
+    /// Builder-style method for [`set_history_len`]
+    pub fn with_history(mut self, len: usize) -> Self {
+        self.set_history_len(len);
+        self
+    }
+
+    /// Set the length of the time window for measuring the actual tick rate
+    ///
+    /// See [`FixedFramestepInfo::measured_rate`]. The default is [`DEFAULT_RATE_WINDOW`].
+    /// A longer window gives a smoother value, but reacts slower to changes.
+    pub fn set_rate_window(&mut self, window: Duration) {
+        self.rate_window = window;
+    }
+
+    /// Builder-style method for [`set_rate_window`]
+    pub fn with_rate_window(mut self, window: Duration) -> Self {
+        self.set_rate_window(window);
+        self
+    }
+
+    /// Set the nominal duration of a frame (the default is [`DEFAULT_FRAME_DURATION`], 1/60 s)
+    ///
+    /// Used to convert between steps and rates in Hz (see [`FixedFramestepInfo::rate`]). It
+    /// does not change the step: call [`set_rate_hz`](Self::set_rate_hz) afterwards, to
+    /// keep the same rate. With the [`AccumulationSource::RealTime`] and
+    /// [`AccumulationSource::GameTime`] sources, this is also how much time counts as one frame.
+    pub fn set_frame_duration(&mut self, duration: Duration) {
+        self.frame_duration = duration;
+    }
+
+    /// Builder-style method for [`set_frame_duration`]
+    pub fn with_frame_duration(mut self, duration: Duration) -> Self {
+        self.set_frame_duration(duration);
+        self
+    }
+
+    /// Set the step to the number of frames closest to the given rate (Hz), if it is valid
+    ///
+    /// Based on the [frame duration](Self::set_frame_duration). On error, the step is left unchanged.
+    pub fn set_rate_hz(&mut self, hz: f64) -> Result<(), FramestepError> {
+        let step = framestep_for_hz(hz, self.frame_duration)?;
+        self.step = step;
+        self.initial_step = step;
+        Ok(())
+    }
+
+    /// Create a fixed framestep stage running at (close to) the given rate, in Hz
+    ///
+    /// Like [`try_new`](Self::try_new), with the step computed from the rate and the
+    /// [default frame duration](DEFAULT_FRAME_DURATION). For another frame duration, use
+    /// [`with_frame_duration`](Self::with_frame_duration) and [`set_rate_hz`](Self::set_rate_hz).
+    pub fn try_from_hz(hz: f64, label: impl FramestepLabel) -> Result<Self, FramestepError> {
+        Self::try_new(framestep_for_hz(hz, DEFAULT_FRAME_DURATION)?, label)
+    }
+
+    /// Create a fixed framestep for UI or audio logic, ticking `hz` times per second of real time
+    ///
+    /// It follows the [real time](AccumulationSource::RealTime), so it keeps ticking while
+    /// the game time is paused or slowed down: the frame duration is `1 / hz` seconds, with
+    /// a step of one frame. It has one single-threaded sub-stage, since such logic is usually
+    /// small, and often needs to run in order. Pausing the fixed framestep itself still pauses it.
+    ///
+    /// Panics if `hz` is not a positive, finite number.
+    pub fn realtime(hz: f64, label: impl FramestepLabel) -> Self {
+        let label = label.framestep_name();
+        if !(hz > 0.0 && hz.is_finite()) {
+            panic!("Invalid rate for the real-time fixed framestep {:?}: {} Hz", label, hz);
+        }
+        Self::from_stage(1, label, SystemStage::single_threaded())
+            .with_accumulation_source(AccumulationSource::RealTime)
+            .with_frame_duration(Duration::from_secs_f64(1.0 / hz))
+    }
+
+    /// Create a fixed framestep for amortized work, that runs as many ticks as fit in `budget` on every frame
+    ///
+    /// See [`set_fill_budget`](Self::set_fill_budget). It has one sub-stage, like a normal
+    /// fixed framestep, and no limit on the number of ticks per frame, besides the budget.
+    pub fn budgeted(budget: Duration, label: impl FramestepLabel) -> Self {
+        let label = label.framestep_name();
+        Self::from_stage(1, label, new_substage())
+            .with_fill_budget(budget)
+            .with_max_steps_per_frame(u32::MAX)
+    }
+
+    /// Set the range of values that the accumulator may be stretched to
+    ///
+    /// See [`FixedFramestepInfo::stretch`]. By default, the accumulator can be stretched
+    /// to any value from `0` upwards.
+    pub fn set_stretch_bounds(&mut self, min: impl Into<Frames>, max: impl Into<Frames>) {
+        let (min, max) = (min.into(), max.into());
+        assert!(min <= max);
+        self.stretch_bounds = (min, max);
+    }
+
+    /// Builder-style method for [`set_stretch_bounds`]
+    pub fn with_stretch_bounds(mut self, min: impl Into<Frames>, max: impl Into<Frames>) -> Self {
+        self.set_stretch_bounds(min, max);
+        self
+    }
+
+    /// Set the maximum number of ticks to run in a single frame
+    ///
+    /// If more ticks than that are due (for example, if the accumulator was set to a
+    /// huge value, or after a long stall with [`AccumulationSource::FrameCount`]), the
+    /// excess ones are dropped, with a warning. Ticks queued using
+    /// [`FixedFramestepInfo::queue_ticks`] do not count towards the limit.
+    ///
+    /// The default is [`DEFAULT_MAX_STEPS_PER_FRAME`].
+    pub fn set_max_steps_per_frame(&mut self, max_steps: u32) {
+        assert!(max_steps > 0);
+        self.max_steps = max_steps;
+    }
+
+    /// Builder-style method for [`set_max_steps_per_frame`]
+    pub fn with_max_steps_per_frame(mut self, max_steps: u32) -> Self {
+        self.set_max_steps_per_frame(max_steps);
+        self
+    }
+
+    /// Set a CPU time budget for each tick, like 4 ms (or [`None`] for no budget, the default)
+    ///
+    /// The duration of every tick is measured anyway (see [`FixedFramestepInfo::last_tick_duration`]).
+    /// When a tick takes longer than the budget, the fixed framestep sends a [`TickOverBudget`]
+    /// event, and counts it in [`FixedFramestepInfo::over_budget_ticks`].
+    pub fn set_tick_budget(&mut self, budget: Option<Duration>) {
+        self.tick_budget = budget;
+    }
+
+    /// Builder-style method for [`set_tick_budget`]
+    pub fn with_tick_budget(mut self, budget: Duration) -> Self {
+        self.set_tick_budget(Some(budget));
+        self
+    }
+
+    /// Run as many ticks as fit in the given time on every frame (or [`None`] for normal ticks, the default)
+    ///
+    /// For amortized work, like chunk generation or navmesh baking, that wants "as much as
+    /// fits per frame" instead of a fixed rate: the fixed framestep ignores the step and the
+    /// accumulator, and keeps running ticks until the time since the first tick of the frame
+    /// exceeds the budget. It always runs at least one tick per frame (unless paused), and
+    /// never more than the [max steps per frame](Self::set_max_steps_per_frame). Pause the
+    /// fixed framestep (say, from the last tick) when the work is done.
+    ///
+    /// The [shared frame budget](budget::FrameBudget), the tick gates, and the queued ticks
+    /// work as usual. [`FixedFramestepInfo::steps_this_frame`] is not known in advance, so it
+    /// counts only one tick (besides the queued ones).
+    pub fn set_fill_budget(&mut self, budget: Option<Duration>) {
+        self.fill_budget = budget;
+    }
+
+    /// Builder-style method for [`set_fill_budget`](Self::set_fill_budget)
+    pub fn with_fill_budget(mut self, budget: Duration) -> Self {
+        self.set_fill_budget(Some(budget));
+        self
+    }
+
+    /// Run `n` warm-up ticks after every reset (`0` for none, the default)
+    ///
+    /// Lets the simulation settle (say, physics bodies coming to rest after a level load)
+    /// before gameplay and rendering treat the world as live. The warm-up ticks are
+    /// [queued](FixedFramestepInfo::queue_ticks), so they all run on the next run of the
+    /// fixed framestep, even if it is paused, and are not limited by the max steps per frame.
+    /// While they run, [`FixedFramestepInfo::is_warming_up`] is `true`, and after the last
+    /// one, a [`WarmupFinished`] event is sent.
+    ///
+    /// To warm up when entering a state, call [`FixedFramestepInfo::start_warmup`] from its
+    /// enter system.
+    pub fn set_warmup_ticks(&mut self, n: u64) {
+        self.warmup_ticks = n;
+    }
+
+    /// Builder-style method for [`set_warmup_ticks`](Self::set_warmup_ticks)
+    pub fn with_warmup_ticks(mut self, n: u64) -> Self {
+        self.set_warmup_ticks(n);
+        self
+    }
+
+    /// Watch the ticks with the given [`TickWatchdog`](watchdog::TickWatchdog) (or [`None`] to stop)
+    ///
+    /// A tick that runs for longer than the watchdog's timeout is logged while it is still
+    /// running, and a [`TickStalled`](watchdog::TickStalled) event is sent once it ends.
+    pub fn set_watchdog(&mut self, watchdog: Option<watchdog::TickWatchdog>) {
+        self.watchdog = watchdog;
+    }
+
+    /// Builder-style method for [`set_watchdog`]
+    pub fn with_watchdog(mut self, watchdog: watchdog::TickWatchdog) -> Self {
+        self.set_watchdog(Some(watchdog));
+        self
+    }
+
+    /// Mark the i-th child stage as *elastic*: it may run at a lower rate while over budget
+    ///
+    /// While the ticks take longer than the [tick budget](Self::set_tick_budget), the elastic
+    /// sub-stages are throttled to run only on every 2nd tick, and then on every 4th tick (when
+    /// the tick number is a multiple of that). Once the ticks that run them take at most 3/4 of
+    /// the budget again, they go back to every 2nd tick, and then to every tick. The current
+    /// rate is in [`FixedFramestepInfo::elastic_throttle`].
+    ///
+    /// Useful for things that tolerate a lower cadence, like AI or audio analysis. As the
+    /// throttling depends on how long the ticks take, it is not deterministic: elastic
+    /// sub-stages should not change simulation state that must replay exactly.
+    pub fn set_elastic_substage(&mut self, substage_i: usize, elastic: bool) {
+        self.elastic_substages.retain(|i| *i != substage_i);
+        if elastic {
+            self.elastic_substages.push(substage_i);
+        }
+    }
+
+    /// Builder-style method for [`set_elastic_substage`]
+    pub fn with_elastic_substage(mut self, substage_i: usize) -> Self {
+        self.set_elastic_substage(substage_i, true);
+        self
+    }
+
+    /// Mark the i-th child stage as *per-region*: it only processes the regions that are due
+    ///
+    /// Before the first per-region sub-stage of every tick, the regions of this fixed
+    /// framestep (the entities with a [`RegionTickState`](regions::RegionTickState) for it)
+    /// take turns being due. See the [`regions`] module.
+    pub fn set_region_substage(&mut self, substage_i: usize, per_region: bool) {
+        self.region_substages.retain(|i| *i != substage_i);
+        if per_region {
+            self.region_substages.push(substage_i);
+        }
+    }
+
+    /// Builder-style method for [`set_region_substage`]
+    pub fn with_region_substage(mut self, substage_i: usize) -> Self {
+        self.set_region_substage(substage_i, true);
+        self
+    }
+
+    /// Set the maximum number of regions processed on each tick (`None` for all, the default)
+    ///
+    /// See [`set_region_substage`](Self::set_region_substage).
+    pub fn set_regions_per_tick(&mut self, regions: Option<usize>) {
+        self.regions_per_tick = regions;
+    }
+
+    /// Builder-style method for [`set_regions_per_tick`](Self::set_regions_per_tick)
+    pub fn with_regions_per_tick(mut self, regions: usize) -> Self {
+        self.set_regions_per_tick(Some(regions));
+        self
+    }
+
+    /// Set what happens to the elapsed frames while paused (see [`PauseMode`])
+    pub fn set_pause_mode(&mut self, mode: PauseMode) {
+        self.pause_mode = mode;
+    }
+
+    /// Builder-style method for [`set_pause_mode`]
+    pub fn with_pause_mode(mut self, mode: PauseMode) -> Self {
+        self.set_pause_mode(mode);
+        self
+    }
+
+    /// Set the determinism seed (the default is `0`)
+    ///
+    /// The random number generator of every tick is seeded from it (see [`FixedFramestepInfo::rng`]).
+    /// Set a fixed seed in tests, to reproduce a bug report exactly.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// Builder-style method for [`set_seed`]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.set_seed(seed);
+        self
+    }
+
+    /// Enable or disable strict determinism mode
+    ///
+    /// A heavy hammer for lockstep simulations: all `SystemStage` sub-stages (including
+    /// ones added later, and those of nested fixed framesteps) are switched to the
+    /// single-threaded executor, so their systems always run in the same order. Systems
+    /// can sort their query results with [`FixedFramestepInfo::stable_order`].
+    ///
+    /// Custom sub-stages are left as they are. Disabling it again does not restore the
+    /// parallel executor.
+    pub fn set_strict_determinism(&mut self, strict: bool) {
+        self.strict_determinism = strict;
+        if strict {
+            for stage in self.stages.iter_mut() {
+                make_strict(stage.as_mut());
+            }
+        }
+    }
+
+    /// Builder-style method for enabling [`set_strict_determinism`]
+    pub fn with_strict_determinism(mut self) -> Self {
+        self.set_strict_determinism(true);
+        self
+    }
+
+    /// Run the systems of the sub-stages on the given task pool, instead of the global compute pool
+    ///
+    /// All `SystemStage` sub-stages (including ones added later, and those of nested fixed
+    /// framesteps) are switched to the [`TaskPoolExecutor`](executor::TaskPoolExecutor).
+    /// Use a pool with fewer threads than the compute pool, to keep huge ticks from
+    /// starving the other work of the frame. `None` switches them back to Bevy's parallel
+    /// executor. Ignored with [strict determinism](Self::set_strict_determinism), which
+    /// runs everything on one thread.
+    pub fn set_task_pool(&mut self, pool: Option<Arc<TaskPool>>) {
+        self.task_pool = pool;
+        if !self.strict_determinism {
+            for stage in self.stages.iter_mut() {
+                use_task_pool(stage.as_mut(), self.task_pool.as_ref());
+            }
+        }
+    }
+
+    /// Builder-style method for [`set_task_pool`]
+    pub fn with_task_pool(mut self, pool: Arc<TaskPool>) -> Self {
+        self.set_task_pool(Some(pool));
+        self
+    }
+
+    /// The task pool that the sub-stages run on, if not the global compute pool
+    pub fn task_pool(&self) -> Option<&Arc<TaskPool>> {
+        self.task_pool.as_ref()
+    }
+
+    /// Enable or disable the determinism linter (see the [`lint`] module)
+    ///
+    /// Only has an effect in debug builds, with the `"framestep-bookkeeping"` cargo feature.
+    pub fn set_determinism_lint(&mut self, enabled: bool) {
+        self.determinism_lint = enabled;
+        self.linted = false;
+    }
+
+    /// Builder-style method for enabling [`set_determinism_lint`]
+    pub fn with_determinism_lint(mut self) -> Self {
+        self.set_determinism_lint(true);
+        self
+    }
+
+    /// Require a resource to be present for ticks to run
+    ///
+    /// If any of the required resources are missing, the ticks that are due are skipped
+    /// instead of run: a warning is logged (once, until the resources are available again)
+    /// and a [`FixedFramestepSkipped`] event is sent. Useful during app teardown, or if
+    /// the fixed framestep is added before the plugin that provides its resources.
+    pub fn add_required_resource<R: Resource>(&mut self) {
+        self.required_resources.push((std::any::type_name::<R>(), World::contains_resource::<R>));
+    }
+
+    /// Builder-style method for [`add_required_resource`]
+    pub fn requires_resource<R: Resource>(mut self) -> Self {
+        self.add_required_resource::<R>();
+        self
+    }
+
+    /// Update the `Events<E>` resource at the start of every tick, instead of every frame
+    ///
+    /// See the [`events`] module. The `Events<E>` must not also be updated by Bevy (do not
+    /// use `add_event` for the type), nor by another fixed framestep.
+    pub fn add_tick_event<E: Event>(&mut self) {
+        self.add_tick_begin_hook(events::update_tick_events::<E>);
+    }
+
+    /// Builder-style method for [`add_tick_event`](Self::add_tick_event)
+    pub fn with_tick_event<E: Event>(mut self) -> Self {
+        self.add_tick_event::<E>();
+        self
+    }
+
+    /// Clear the [`TickScopedEvents<E>`](events::TickScopedEvents) resource at the end of every tick
+    ///
+    /// See the [`events`] module. The resource is not created for you.
+    pub fn add_tick_scoped_event<E: Event>(&mut self) {
+        self.on_tick_end(events::clear_tick_scoped_events::<E>);
+    }
+
+    /// Builder-style method for [`add_tick_scoped_event`](Self::add_tick_scoped_event)
+    pub fn with_tick_scoped_event<E: Event>(mut self) -> Self {
+        self.add_tick_scoped_event::<E>();
+        self
+    }
+
+    /// Keep the [`TickOf<F>`](tick::TickOf) resource updated with the number of the current tick
+    ///
+    /// See the [`tick`] module. The resource is created on the first tick, if it does not
+    /// exist. `F` should name this fixed framestep.
+    pub fn add_tick_resource<F: Send + Sync + 'static>(&mut self) {
+        self.on_tick_begin(tick::sync_tick_of::<F>);
+    }
+
+    /// Builder-style method for [`add_tick_resource`](Self::add_tick_resource)
+    pub fn with_tick_resource<F: Send + Sync + 'static>(mut self) -> Self {
+        self.add_tick_resource::<F>();
+        self
+    }
+
+    /// Keep the [`FramestepOf<F>`](split::FramestepOf) resource in sync with this fixed framestep
+    ///
+    /// See the [`split`] module. The resource is created on the next run, if it does not
+    /// exist. `F` should name this fixed framestep. A fixed framestep can only have one such
+    /// resource; calling this again replaces it.
+    pub fn add_framestep_resource<F: Send + Sync + 'static>(&mut self) {
+        self.framestep_resource = Some((split::pull_framestep_of::<F>, split::push_framestep_of::<F>));
+    }
+
+    /// Builder-style method for [`add_framestep_resource`](Self::add_framestep_resource)
+    pub fn with_framestep_resource<F: Send + Sync + 'static>(mut self) -> Self {
+        self.add_framestep_resource::<F>();
+        self
+    }
+
+    /// Add a callback to be called at the start of every tick, before the first child stage
+    ///
+    /// Called with the world and the number of the tick. Useful for cross-cutting concerns
+    /// (say, opening a profiling scope), without adding a whole sub-stage. Like the systems
+    /// of the tick, the callback may change the [`FixedFramesteps`] resource; it runs
+    /// after the due [`TickCommands`](commands::TickCommands) have been applied.
+    pub fn on_tick_begin(&mut self, callback: impl FnMut(&mut World, u64) + Send + Sync + 'static) {
+        self.tick_begin_callbacks.push(Box::new(callback));
+    }
+
+    /// Builder-style method for [`on_tick_begin`](Self::on_tick_begin)
+    pub fn with_on_tick_begin(mut self, callback: impl FnMut(&mut World, u64) + Send + Sync + 'static) -> Self {
+        self.on_tick_begin(callback);
+        self
+    }
+
+    /// Add a callback to be called at the end of every tick, after the last child stage
+    ///
+    /// Called with the world and the number of the tick, after the state transitions of the
+    /// tick (see the `states` module). Useful for cross-cutting concerns (say, triggering an
+    /// autosave, or flushing network messages), without adding a whole sub-stage.
+    pub fn on_tick_end(&mut self, callback: impl FnMut(&mut World, u64) + Send + Sync + 'static) {
+        self.tick_end_callbacks.push(Box::new(callback));
+    }
+
+    /// Builder-style method for [`on_tick_end`](Self::on_tick_end)
+    pub fn with_on_tick_end(mut self, callback: impl FnMut(&mut World, u64) + Send + Sync + 'static) -> Self {
+        self.on_tick_end(callback);
+        self
+    }
+
+    /// Add a callback to rescale rate-dependent state, when the step is changed
+    ///
+    /// See the [`migration`] module. The migrations are called in the order they were
+    /// added, before the [`RateChanged`](migration::RateChanged) event is sent.
+    pub fn add_rate_migration(&mut self, migration: impl migration::RateMigration) {
+        self.rate_migrations.push(Box::new(migration));
+    }
+
+    /// Builder-style method for [`add_rate_migration`](Self::add_rate_migration)
+    pub fn with_rate_migration(mut self, migration: impl migration::RateMigration) -> Self {
+        self.add_rate_migration(migration);
+        self
+    }
+
+    /// Give this fixed framestep a config value of type `T`, for its tick systems
+    ///
+    /// See the [`config`] module. The config is inserted into the
+    /// [`FramestepConfigs<T>`](config::FramestepConfigs) resource on the next run of the
+    /// stage, unless the world already has a config of this type for this fixed framestep.
+    pub fn add_config<T: Send + Sync + 'static>(&mut self, config: T) {
+        self.pending_configs.push(config::config_init(config));
+    }
+
+    /// Builder-style method for [`add_config`](Self::add_config)
+    pub fn with_config<T: Send + Sync + 'static>(mut self, config: T) -> Self {
+        self.add_config(config);
+        self
+    }
+
+    /// Pause the fixed framestep while the given condition holds
+    ///
+    /// Checked on every run of the stage, before the elapsed frames are accumulated (so
+    /// the [`PauseMode`] applies), and not between the ticks on the same frame. This is a
+    /// pause of its own, independent of [`FixedFramestepInfo::pause`] and the
+    /// [pause reasons](FixedFramestepInfo::push_pause); see
+    /// [`FixedFramestepInfo::is_auto_paused`].
+    pub fn add_pause_condition(&mut self, condition: impl Fn(&World) -> bool + Send + Sync + 'static) {
+        self.pause_conditions.push(Box::new(condition));
+    }
+
+    /// Builder-style method for [`add_pause_condition`](Self::add_pause_condition)
+    pub fn with_pause_when(mut self, condition: impl Fn(&World) -> bool + Send + Sync + 'static) -> Self {
+        self.add_pause_condition(condition);
+        self
+    }
+
+    /// Pause the fixed framestep while the resource `R` has the given value
+    ///
+    /// Like [`add_pause_condition`](Self::add_pause_condition). Not paused while the
+    /// resource is missing.
+    pub fn add_pause_when_resource_equals<R: Resource + PartialEq>(&mut self, value: R) {
+        self.add_pause_condition(move |world| world.get_resource::<R>() == Some(&value));
+    }
+
+    /// Builder-style method for [`add_pause_when_resource_equals`](Self::add_pause_when_resource_equals)
+    pub fn pause_when_resource_equals<R: Resource + PartialEq>(mut self, value: R) -> Self {
+        self.add_pause_when_resource_equals(value);
+        self
+    }
+
+    /// Returns the type name of the first required resource that is missing, if any
+    fn missing_resource(&self, world: &World) -> Option<&'static str> {
+        self.required_resources.iter()
+            .find(|(_, is_present)| !is_present(world))
+            .map(|(name, _)| *name)
+    }
+
+    /// How many frames have passed since the last run, according to our [`AccumulationSource`]
+    ///
+    /// Returns [`None`] if the frame count is not available.
+    fn elapsed_frames(&mut self, world: &World) -> Option<Frames> {
+        match self.source {
+            AccumulationSource::StageRuns => Some(Frames(1)),
+            AccumulationSource::FrameCount => {
+                let frame_count = world.get_resource::<FrameCount>()?.0;
+                let elapsed = match self.last_frame_count {
+                    // FrameCount wraps around on overflow
+                    Some(last) => Frames(frame_count.wrapping_sub(last) as FrameCounter),
+                    None => Frames(1),
+                };
+                self.last_frame_count = Some(frame_count);
+                Some(elapsed)
+            }
+            AccumulationSource::RealTime => {
+                let delta = world.get_resource::<Time>()?.raw_delta();
+                self.time_to_frames(delta)
+            }
+            AccumulationSource::GameTime => {
+                let delta = world.get_resource::<Time>()?.delta();
+                self.time_to_frames(delta)
+            }
+        }
+    }
+
+    /// Multiply the elapsed frames by the [`GlobalTickScale`](timescale::GlobalTickScale), if we opted into it
+    ///
+    /// The fraction of a frame left over is carried over to the next run. Huge scales
+    /// saturate at the largest number of frames.
+    fn scale_frames(&mut self, world: &World, elapsed: Frames) -> Frames {
+        if !self.global_scale {
+            return elapsed;
+        }
+        let scale = match world.get_resource::<timescale::GlobalTickScale>() {
+            Some(scale) => scale.get(),
+            None => return elapsed,
+        };
+        let scaled = (elapsed.0 as f64 * scale + self.scale_carry).min(FrameCounter::MAX as f64);
+        let whole = scaled.floor();
+        self.scale_carry = scaled - whole;
+        Frames(whole as FrameCounter)
+    }
+
+    /// Convert elapsed time to whole frames of the [frame duration](Self::set_frame_duration)
+    ///
+    /// The time left over is carried over to the next run. Returns [`None`] if the
+    /// frame duration is zero.
+    fn time_to_frames(&mut self, delta: Duration) -> Option<Frames> {
+        let frame = self.frame_duration.as_nanos();
+        if frame == 0 {
+            return None;
+        }
+        let elapsed = (self.time_carry + delta).as_nanos();
+        self.time_carry = Duration::from_nanos((elapsed % frame) as u64);
+        Some(Frames((elapsed / frame) as FrameCounter))
+    }
+
+    /// Run all the child stages once, as one tick
+    fn run_tick(&mut self, world: &mut World) {
+        self.tick += 1;
+
+        let mut framesteps = self.store_fixedframestepinfo(world);
+        framesteps.running.push(self.id);
+
+        let info = framesteps.info.get_mut(&self.id).unwrap();
+        info.rng = TickRng::new(info.tick_seed());
+        let throttle = info.elastic_throttle as u64;
+        let skip_elastic = throttle > 1 && self.tick % throttle != 0;
+
+        // remember when the tick ran, for the measured tick rate
+        let now = Instant::now();
+        #[cfg(feature = "framestep-bookkeeping")]
+        {
+            info.tick_times.push_back(now);
+            while let Some(time) = info.tick_times.front() {
+                if now - *time <= self.rate_window {
+                    break;
+                }
+                info.tick_times.pop_front();
+            }
+        }
+        if let Some(watchdog) = &self.watchdog {
+            watchdog.begin_tick(self.label, self.tick, now);
+        }
+
+        commands::apply_due_commands(world, self.label, self.tick);
+        scheduler::run_due_tasks(world, self.label, self.tick);
+
+        for hook in self.tick_begin_hooks.iter() {
+            hook(world);
+        }
+        if let Some((_, push)) = self.framestep_resource {
+            push(world, self.label);
+        }
+        if !self.tick_begin_callbacks.is_empty() {
+            for callback in self.tick_begin_callbacks.iter_mut() {
+                callback(world, self.tick);
+            }
+            self.load_fixedframestepinfo(world, true);
+        }
+
+        let mut regions_scheduled = false;
+        let mut stages = std::mem::take(&mut self.stages);
+        for (i, stage) in stages.iter_mut().enumerate() {
+            if skip_elastic && self.elastic_substages.contains(&i) {
+                continue;
+            }
+            if !regions_scheduled && self.region_substages.contains(&i) {
+                regions::schedule_regions(world, self.label, self.tick, self.regions_per_tick);
+                regions_scheduled = true;
+            }
+
+            if let Some(watchdog) = &self.watchdog {
+                watchdog.enter_substage(self.label, Some(i));
+            }
+
+            // run user systems
+            stage.run(world);
+
+            // if the user modified fixed framestep info, we need to copy it back
+            self.load_fixedframestepinfo(world, true);
+        }
+        self.stages = stages;
+        if let Some(watchdog) = &self.watchdog {
+            watchdog.enter_substage(self.label, None);
+        }
+
+        // on tick boundaries only, see the `states` module
+        let mut transitions = std::mem::take(&mut self.state_transitions);
+        for stage in transitions.iter_mut() {
+            stage.run(world);
+            self.load_fixedframestepinfo(world, true);
+        }
+        self.state_transitions = transitions;
+
+        if !self.tick_end_callbacks.is_empty() {
+            for callback in self.tick_end_callbacks.iter_mut() {
+                callback(world, self.tick);
+            }
+            self.load_fixedframestepinfo(world, true);
+        }
+
+        self.measure_tick(world, now.elapsed(), !skip_elastic);
+        if let Some(stalled) = self.watchdog.as_ref().and_then(|watchdog| watchdog.end_tick(self.label)) {
+            send_event(world, || stalled);
+        }
+
+        // the systems are only initialized once they have run
+        if cfg!(all(debug_assertions, feature = "framestep-bookkeeping")) && self.determinism_lint && !self.linted {
+            self.linted = true;
+            self.lint_determinism(world);
+        }
+
+        // only our own entry: anything nested inside has removed its own already
+        let mut framesteps = world.resource_mut::<FixedFramesteps>();
+        if let Some(i) = framesteps.running.iter().rposition(|id| *id == self.id) {
+            framesteps.running.remove(i);
+        }
+
+        let mut warmed_up = false;
+        if let Some(info) = framesteps.info.get_mut(&self.id) {
+            if info.warmup_remaining > 0 {
+                info.warmup_remaining -= 1;
+                warmed_up = info.warmup_remaining == 0;
+            }
+        }
+        if warmed_up {
+            send_event(world, || WarmupFinished {
+                label: self.label,
+                tick: self.tick,
+            });
+        }
+    }
+
+    /// Record the duration of a tick, and check it against the budget
+    ///
+    /// Also throttles the elastic sub-stages, or lets them go back to a higher rate.
+    fn measure_tick(&mut self, world: &mut World, duration: Duration, ran_elastic: bool) {
+        let mut framesteps = world.resource_mut::<FixedFramesteps>();
+        let info = framesteps.info.get_mut(&self.id).unwrap();
+        info.last_tick_duration = Some(duration);
+        let budget = match self.tick_budget {
+            Some(budget) => budget,
+            None => return,
+        };
+        if duration <= budget {
+            // the ticks that skip the elastic sub-stages say nothing about the headroom
+            if ran_elastic && duration <= budget * 3 / 4 {
+                info.elastic_throttle = (info.elastic_throttle / 2).max(1);
+            }
+            return;
+        }
+        info.over_budget_ticks += 1;
+        if !self.elastic_substages.is_empty() {
+            info.elastic_throttle = (info.elastic_throttle * 2).min(MAX_ELASTIC_THROTTLE);
+        }
+        send_event(world, || TickOverBudget {
+            label: self.label,
+            tick: self.tick,
+            duration,
+            budget,
+        });
+    }
+
+    /// Set up a world that this stage has not run in yet, or whose layout is out of date
+    ///
+    /// Adds the resources that the stage needs, and stores its [`FramestepLayout`].
+    fn set_up_world(&mut self, world: &mut World) {
+        world.init_resource::<commands::TickCommandQueue>();
+        world.init_resource::<scheduler::TickScheduler>();
+        let layout = FramestepLayout {
+            label: self.label,
+            position: self.position,
+            registered_position: self.registered_position.clone(),
+            substage_count: self.stages.len(),
+        };
+        world.get_resource_or_insert_with(FramestepLayouts::default).layouts.insert(self.label, layout);
+        self.layout_changed = false;
+    }
+
+    /// Add a function to be called at the start of every tick, before the child stages
+    ///
+    /// Runs after the due [`TickCommands`](commands::TickCommands) have been applied.
+    fn add_tick_begin_hook(&mut self, hook: fn(&mut World)) {
+        self.tick_begin_hooks.push(hook);
+    }
+
+    /// Add a function that can hold back the next tick, until it returns `true`
+    ///
+    /// Called with the name of the fixed framestep and the number of the next tick, before
+    /// every tick that is due. While it returns `false`, the fixed framestep stalls: the
+    /// ticks stay due (in the accumulator), and run on a later frame. Ticks queued using
+    /// [`FixedFramestepInfo::queue_ticks`] are not held back.
+    #[cfg(feature = "app")]
+    fn add_tick_gate(&mut self, gate: TickGate) {
+        self.tick_gates.push(gate);
+    }
+
+    /// Can the next tick run? (see [`add_tick_gate`](Self::add_tick_gate))
+    fn tick_allowed(&self, world: &mut World) -> bool {
+        self.tick_gates.iter().all(|gate| gate(world, self.label, self.tick + 1))
+    }
+
+    /// Warn about any systems that read non-deterministic resources
+    fn lint_determinism(&self, world: &World) {
+        for (i, stage) in self.stages.iter().enumerate() {
+            let stage = stage.downcast_ref::<SystemStage>()
+                .or_else(|| stage.downcast_ref::<groups::SystemGroupStage>().map(groups::SystemGroupStage::stage))
+                .or_else(|| stage.downcast_ref::<cleanup::CleanupStage>().map(cleanup::CleanupStage::stage));
+            if let Some(stage) = stage {
+                for (system, resource) in lint::nondeterministic_reads(world, stage) {
+                    warn!(
+                        "Fixed framestep {:?}: system {:?} in sub-stage {} reads {}, which is not deterministic.",
+                        self.label, system, i, resource,
+                    );
+                }
+            }
+        }
+    }
+
+    /// Update our parameters from the info resource, in case the user has modified them
+    ///
+    /// An invalid step length is rejected: we keep our previous value and restore it
+    /// in the info resource.
+    ///
+    /// Any pending [`stretch`](FixedFramestepInfo::stretch) is applied here.
+    fn load_fixedframestepinfo(&mut self, world: &mut World, sync_accumulator: bool) {
+        if let Some((pull, _)) = self.framestep_resource {
+            pull(world, self.label);
+        }
+        let (step, accumulator, pause_held, speed, stretch, rewind, synced) = match world.get_resource_mut::<FixedFramesteps>() {
+            Some(mut framesteps) => match framesteps.info.get_mut(&self.id) {
+                Some(info) => (
+                    info.step, info.accumulator, !info.pause_reasons.is_empty(), info.speed.max(1),
+                    std::mem::take(&mut info.pending_stretch),
+                    info.pending_rewind.take().map(|from| (from, info.tick)),
+                    info.synced,
+                ),
+                None => return,
+            },
+            None => return,
+        };
+        match validate_framestep(step) {
+            Ok(step) => self.step = step,
+            Err(e) => {
+                warn!(
+                    "Invalid step for fixed framestep {:?}: {}; keeping the previous value ({}).",
+                    self.label, e, self.step,
+                );
+                let mut framesteps = world.resource_mut::<FixedFramesteps>();
+                framesteps.info.get_mut(&self.id).unwrap().step = self.step;
+            }
+        }
+        self.pause_held = pause_held;
+        self.speed = speed;
+        self.send_config_changes(world, synced);
+        if sync_accumulator {
+            self.accumulator = accumulator;
+        }
+        if stretch != 0 {
+            self.apply_stretch(world, stretch);
+        }
+        if let Some((from, to)) = rewind {
+            self.apply_rewind(world, from, to);
+        }
+    }
+
+    /// Send events for the parameters that are different from the `synced` ones
+    fn send_config_changes(&mut self, world: &mut World, synced: (Frames, bool, u32)) {
+        let current = (self.step, self.is_paused(), self.speed);
+        if current == synced {
+            return;
+        }
+        world.resource_mut::<FixedFramesteps>()
+            .info.get_mut(&self.id).unwrap()
+            .synced = current;
+        if current.0 != synced.0 {
+            self.migrate_rate(world, synced.0);
+        }
+        let changes = [
+            (current.0 != synced.0, FramestepConfigField::Step),
+            (current.1 != synced.1, FramestepConfigField::Paused),
+            (current.2 != synced.2, FramestepConfigField::Speed),
+        ];
+        for (changed, field) in changes {
+            if changed {
+                send_event(world, || FramestepConfigChanged { label: self.label, field });
+            }
+        }
+    }
+
+    /// Call the rate migrations and send the [`RateChanged`](migration::RateChanged) event, after the step was changed from `old_step`
+    fn migrate_rate(&mut self, world: &mut World, old_step: Frames) {
+        let rate = |step: Frames| 1.0 / (step.0 as f64 * self.frame_duration.as_secs_f64());
+        let change = migration::RateChanged {
+            label: self.label,
+            old_step,
+            new_step: self.step,
+            old_rate: rate(old_step),
+            new_rate: rate(self.step),
+        };
+        for migration in self.rate_migrations.iter_mut() {
+            migration.migrate(world, &change);
+        }
+        send_event(world, || change);
+    }
+
+    /// Apply a rewind requested using [`FixedFramesteps::rewind`]
+    fn apply_rewind(&mut self, world: &mut World, from: u64, to: u64) {
+        self.tick = to;
+        let mut framesteps = world.resource_mut::<FixedFramesteps>();
+        framesteps.generations += 1;
+        let generation = framesteps.generations;
+        framesteps.info.get_mut(&self.id).unwrap().generation = generation;
+        // the commands for future ticks were queued by ticks that are now undone
+        if let Some(mut queue) = world.get_resource_mut::<commands::TickCommandQueue>() {
+            queue.clear(self.label);
+        }
+        send_event(world, || TicksRewound {
+            label: self.label,
+            from,
+            to,
+        });
+    }
+
+    fn apply_stretch(&mut self, world: &mut World, stretch: i64) {
+        let (min, max) = self.stretch_bounds;
+        let requested = self.accumulator.0 as i128 + stretch as i128;
+        let clamped = requested.clamp(min.0 as i128, max.0 as i128);
+        self.accumulator = Frames(clamped as FrameCounter);
+        world.resource_mut::<FixedFramesteps>()
+            .info.get_mut(&self.id).unwrap()
+            .accumulator = self.accumulator;
+        if clamped != requested {
+            send_event(world, || FixedFramestepStretchClamped {
+                label: self.label,
+                requested: stretch,
+                accumulator: self.accumulator,
+            });
+        }
+    }
+
+    /// Called when we hit the max steps per frame: drop any whole steps left in the accumulator
+    fn drop_excess_steps(&mut self, max_steps: u32) {
+        let excess = self.accumulator / self.step;
+        warn!(
+            "Fixed framestep {:?} ran the maximum of {} steps this frame, dropping {} more.",
+            self.label, max_steps, excess,
+        );
+        self.accumulator %= self.step;
+    }
+
+    /// Collect the names of this and all nested fixed framesteps (recursively)
+    fn collect_labels(&self, labels: &mut Vec<FramestepName>) {
+        labels.push(self.label);
+        for stage in self.stages.iter().filter_map(|stage| stage.downcast_ref::<FixedFramestepStage>()) {
+            stage.collect_labels(labels);
+        }
+    }
+
+    /// Find this or a nested fixed framestep (in the child stages, recursively) by name
+    fn find_nested(&self, label: FramestepName) -> Option<&FixedFramestepStage> {
+        if self.moved {
+            return None;
+        }
+        if self.label == label {
+            return Some(self);
+        }
+        self.stages.iter()
+            .filter_map(|stage| stage.downcast_ref::<FixedFramestepStage>())
+            .find_map(|stage| stage.find_nested(label))
+    }
+
+    /// Find this or a nested fixed framestep (in the child stages, recursively) by name
+    fn find_nested_mut(&mut self, label: FramestepName) -> Option<&mut FixedFramestepStage> {
+        if self.moved {
+            return None;
+        }
+        if self.label == label {
+            return Some(self);
+        }
+        self.stages.iter_mut()
+            .filter_map(|stage| stage.downcast_mut::<FixedFramestepStage>())
+            .find_map(|stage| stage.find_nested_mut(label))
+    }
+
+    /// Load all the stepping state of the given world
+    ///
+    /// If this fixed framestep has never run in this world, reset to the initial parameters.
+    fn load_world_state(&mut self, world: &mut World) {
+        let info = world.get_resource::<FixedFramesteps>()
+            .and_then(|framesteps| framesteps.info.get(&self.id));
+        if let Some(info) = info {
+            if info.pending_reset {
+                self.apply_reset(world);
+                return;
+            }
+        }
+        match info {
+            Some(info) => {
+                self.tick = info.tick;
+                self.measure_start = info.measure_start;
+                self.scale_carry = info.scale_carry;
+                // if the source was changed, the last frame count is meaningless
+                if info.source == self.source {
+                    self.last_frame_count = info.last_frame_count;
+                    self.time_carry = info.time_carry;
+                } else {
+                    self.last_frame_count = None;
+                    self.time_carry = Duration::ZERO;
+                }
+                self.load_fixedframestepinfo(world, true);
+            }
+            None => self.reset_state(),
+        }
+    }
+
+    /// Paused for any reason, or by a pause condition?
+    fn is_paused(&self) -> bool {
+        self.pause_held || self.auto_paused
+    }
+
+    /// The pause reasons held by a new run of the fixed framestep
+    fn initial_pause_reasons(&self) -> Vec<(PauseReason, u32)> {
+        match self.initial_paused {
+            true => vec![(PauseReason::User, 1)],
+            false => Vec::new(),
+        }
+    }
+
+    /// Go back to the initial parameters
+    fn reset_state(&mut self) {
+        self.step = self.initial_step;
+        self.accumulator = Frames::ZERO;
+        self.pause_held = self.initial_paused;
+        self.last_frame_count = None;
+        self.time_carry = Duration::ZERO;
+        self.scale_carry = 0.0;
+        self.tick = 0;
+        self.warned_missing = false;
+        self.measure_start = None;
+        self.speed = 1;
+        self.step_index = 0;
+        self.steps_this_frame = 0;
+        self.suppressed = None;
+    }
+
+    /// Apply a reset requested using [`FixedFramestepInfo::reset`]
+    fn apply_reset(&mut self, world: &mut World) {
+        let old = world.resource_mut::<FixedFramesteps>().info.remove(&self.id).unwrap();
+        if let Some(mut queue) = world.get_resource_mut::<commands::TickCommandQueue>() {
+            queue.clear(self.label);
+        }
+        if let Some(mut scheduler) = world.get_resource_mut::<scheduler::TickScheduler>() {
+            scheduler.clear(self.label);
+        }
+        self.reset_state();
+        // the other reasons are independent of the simulation, like an open menu
+        let mut pause_reasons = self.initial_pause_reasons();
+        pause_reasons.extend(old.pause_reasons.into_iter().filter(|(reason, _)| *reason != PauseReason::User));
+        self.pause_held = !pause_reasons.is_empty();
+        let mut framesteps = self.store_fixedframestepinfo(world);
+        let info = framesteps.info.get_mut(&self.id).unwrap();
+        info.resets = old.resets + 1;
+        info.pause_reasons = pause_reasons;
+        info.start_warmup();
+    }
+
+    /// ensure the FixedFramesteps resource exists and contains the latest data
+    fn store_fixedframestepinfo<'w>(&self, world: &'w mut World) -> Mut<'w, FixedFramesteps> {
+        let mut framesteps = world.get_resource_or_insert_with(FixedFramesteps::default);
+        if !framesteps.info.contains_key(&self.id) {
+            framesteps.generations += 1;
+        }
+        let generation = framesteps.generations;
+        let info = framesteps.info.entry(self.id).or_insert(FixedFramestepInfo {
+            step: self.step,
+            accumulator: self.accumulator,
+            pause_reasons: self.initial_pause_reasons(),
+            auto_paused: self.auto_paused,
+            tick: self.tick,
+            queued_ticks: 0,
+            history: VecDeque::new(),
+            tick_times: VecDeque::new(),
+            rate_window: self.rate_window,
+            frame_duration: self.frame_duration,
+            measure_start: self.measure_start,
+            pending_stretch: 0,
+            speed: self.speed,
+            step_index: self.step_index,
+            steps_this_frame: self.steps_this_frame,
+            source: self.source,
+            last_frame_count: self.last_frame_count,
+            time_carry: self.time_carry,
+            scale_carry: self.scale_carry,
+            global_scale: self.global_scale,
+            pending_rewind: None,
+            strict_determinism: self.strict_determinism,
+            synced: (self.step, self.is_paused(), self.speed),
+            label: self.label,
+            pause_mode: self.pause_mode,
+            suppressed: self.suppressed,
+            seed: self.seed,
+            rng: TickRng::new(tick_seed(self.seed, self.tick)),
+            pending_reset: false,
+            resets: 0,
+            tick_budget: self.tick_budget,
+            fill_budget: self.fill_budget,
+            warmup_ticks: self.warmup_ticks,
+            warmup_remaining: 0,
+            last_tick_duration: None,
+            over_budget_ticks: 0,
+            deferred_ticks: 0,
+            total_deferred_ticks: 0,
+            elastic_throttle: 1,
+            generation,
+        });
+        info.step = self.step;
+        info.accumulator = self.accumulator;
+        info.auto_paused = self.auto_paused;
+        info.tick = self.tick;
+        info.rate_window = self.rate_window;
+        info.frame_duration = self.frame_duration;
+        info.measure_start = self.measure_start;
+        info.speed = self.speed;
+        info.step_index = self.step_index;
+        info.steps_this_frame = self.steps_this_frame;
+        info.source = self.source;
+        info.last_frame_count = self.last_frame_count;
+        info.time_carry = self.time_carry;
+        info.scale_carry = self.scale_carry;
+        info.global_scale = self.global_scale;
+        info.strict_determinism = self.strict_determinism;
+        info.synced = (self.step, self.is_paused(), self.speed);
+        info.pause_mode = self.pause_mode;
+        info.suppressed = self.suppressed;
+        info.seed = self.seed;
+        info.tick_budget = self.tick_budget;
+        info.fill_budget = self.fill_budget;
+        info.warmup_ticks = self.warmup_ticks;
+        framesteps
+    }
+}
+
+impl Stage for FixedFramestepStage {
+    fn run(&mut self, world: &mut World) {
+        if self.moved {
+            return;
+        }
+        if self.layout_changed || !FramestepLayouts::contains(world, self.label) {
+            self.set_up_world(world);
+        }
+        if !self.pending_configs.is_empty() {
+            for init in std::mem::take(&mut self.pending_configs) {
+                init(world, self.label);
+            }
+        }
+        self.run_frame(world);
+        if let Some((_, push)) = self.framestep_resource {
+            push(world, self.label);
+        }
+    }
+}
+
+impl FixedFramestepStage {
+    /// Everything the stage does on one frame: accumulate the elapsed frames, and run the due ticks
+    fn run_frame(&mut self, world: &mut World) {
+
+        self.load_world_state(world);
+
+        self.auto_paused = self.pause_conditions.iter().any(|condition| condition(world));
+
+        // if we are nested inside another fixed framestep, it is still running
+        let outer = world.get_resource::<FixedFramesteps>().and_then(FixedFramesteps::current_label);
+        if outer.is_none() {
+            budget::begin_turn(world, self.label);
+        }
+
+        if self.measure_start.is_none() {
+            self.measure_start = Some(Instant::now());
+        }
+
+        // always count the frames, even if paused, so that
+        // frames that pass while paused are not counted on unpause
+        let elapsed = match self.elapsed_frames(world) {
+            Some(elapsed) => self.scale_frames(world, elapsed * self.speed as FrameCounter),
+            None => return,
+        };
+        let max_steps = self.max_steps.saturating_mul(self.speed);
+
+        let mut queued = 0;
+        if let Some(mut framesteps) = world.get_resource_mut::<FixedFramesteps>() {
+            if let Some(info) = framesteps.info.get_mut(&self.id) {
+                queued = std::mem::take(&mut info.queued_ticks);
+                info.deferred_ticks = 0;
+            }
+        }
+
+        // check even while paused, so that the info always tells why ticks are not running
+        let missing = self.missing_resource(world);
+        self.suppressed = missing.map(Suppression::MissingResource);
+
+        if self.is_paused() {
+            if let PauseMode::Accumulate { max_frames } = self.pause_mode {
+                // never drop frames that were already accumulated
+                if self.accumulator < max_frames {
+                    self.accumulator = (self.accumulator + elapsed).min(max_frames);
+                }
+            }
+        }
+
+        if self.is_paused() && queued == 0 {
+            // remember the frame count
+            self.store_fixedframestepinfo(world);
+            return;
+        }
+
+        if let Some(missing) = missing {
+            // skip all the ticks that are due, without running them
+            let mut skipped = queued;
+            if !self.is_paused() {
+                self.accumulator += elapsed;
+                while self.accumulator >= self.step {
+                    if skipped - queued >= max_steps {
+                        self.drop_excess_steps(max_steps);
+                        break;
+                    }
+                    self.accumulator -= self.step;
+                    skipped += 1;
+                }
+            }
+            if skipped > 0 {
+                if !self.warned_missing {
+                    warn!(
+                        "Skipping ticks of fixed framestep {:?}, because required resource {} is missing.",
+                        self.label, missing,
+                    );
+                    self.warned_missing = true;
+                }
+                send_event(world, || FixedFramestepSkipped {
+                    label: self.label,
+                    missing_resource: missing,
+                    ticks: skipped,
+                });
+            }
+            self.store_fixedframestepinfo(world);
+            return;
+        }
+        self.warned_missing = false;
+
+        let start = Instant::now();
+        let mut n_steps = 0;
+
+        self.steps_this_frame = queued;
+        if self.fill_budget.is_some() {
+            self.steps_this_frame += !self.is_paused() as u32;
+        } else if !self.is_paused() {
+            let due = (self.accumulator + elapsed) / self.step;
+            self.steps_this_frame += due.0.min(max_steps as u64) as u32;
+        }
+
+        // queued ticks run regardless of the paused state and the accumulator
+        for _ in 0..queued {
+            self.step_index = n_steps;
+            self.run_tick(world);
+            n_steps += 1;
+        }
+
+        let mut deferred = 0;
+        if let Some(fill) = self.fill_budget {
+            // as many ticks as fit in the budget; the elapsed frames do not matter
+            let mut n_fill = 0;
+            while !self.is_paused() && n_fill < max_steps && (n_fill == 0 || start.elapsed() < fill) {
+                if outer.is_none() && !budget::tick_fits(world, self.label, start.elapsed()) {
+                    self.suppressed = Some(Suppression::Deferred);
+                    break;
+                }
+                if !self.tick_allowed(world) {
+                    self.suppressed = Some(Suppression::Stalled);
+                    break;
+                }
+                self.step_index = n_steps;
+                self.run_tick(world);
+                n_steps += 1;
+                n_fill += 1;
+            }
+        } else if !self.is_paused() {
+            self.accumulator += elapsed;
+
+            // if a tick pauses the fixed framestep, stop after it
+            let mut n_catchup = 0;
+            while !self.is_paused() && self.accumulator >= self.step {
+                if n_catchup >= max_steps {
+                    self.drop_excess_steps(max_steps);
+                    break;
+                }
+                // nested fixed framesteps are part of the ticks of the outer one
+                if outer.is_none() && !budget::tick_fits(world, self.label, start.elapsed()) {
+                    self.suppressed = Some(Suppression::Deferred);
+                    deferred = (self.accumulator / self.step).0.min((max_steps - n_catchup) as u64) as u32;
+                    break;
+                }
+                if !self.tick_allowed(world) {
+                    self.suppressed = Some(Suppression::Stalled);
+                    break;
+                }
+                self.accumulator -= self.step;
+                self.step_index = n_steps;
+                self.run_tick(world);
+                n_steps += 1;
+                n_catchup += 1;
+            }
+        }
+
+        let duration = start.elapsed();
+        if outer.is_none() {
+            budget::spend(world, duration);
+        }
+
+        let mut framesteps = self.store_fixedframestepinfo(world);
+        if deferred > 0 {
+            let info = framesteps.info.get_mut(&self.id).unwrap();
+            info.deferred_ticks = deferred;
+            info.total_deferred_ticks += deferred as u64;
+        }
+
+        #[cfg(feature = "framestep-bookkeeping")]
+        if self.history_len > 0 {
+            let info = framesteps.info.get_mut(&self.id).unwrap();
+            while info.history.len() >= self.history_len {
+                info.history.pop_front();
+            }
+            info.history.push_back(FixedFramestepRecord {
+                tick: self.tick,
+                accumulator: self.accumulator,
+                steps: n_steps,
+                duration,
+            });
+        }
+    }
+}
+
+/// Send one of the events of the fixed framesteps, if its type was registered
+///
+/// Compiled out without the `"framestep-bookkeeping"` cargo feature.
+fn send_event<E: Event>(world: &mut World, event: impl FnOnce() -> E) {
+    #[cfg(feature = "framestep-bookkeeping")]
+    if let Some(mut events) = world.get_resource_mut::<Events<E>>() {
+        events.send(event());
+    }
+    #[cfg(not(feature = "framestep-bookkeeping"))]
+    let _ = (world, event);
+}
+
+/// Switch a child stage of a fixed framestep to run on the given task pool (or back to the compute pool)
+fn use_task_pool(stage: &mut dyn Stage, pool: Option<&Arc<TaskPool>>) {
+    let stage = if let Some(stage) = stage.downcast_mut::<SystemStage>() {
+        stage
+    } else if let Some(group) = stage.downcast_mut::<groups::SystemGroupStage>() {
+        &mut group.stage
+    } else if let Some(cleanup) = stage.downcast_mut::<cleanup::CleanupStage>() {
+        &mut cleanup.stage
+    } else {
+        if let Some(stage) = stage.downcast_mut::<FixedFramestepStage>() {
+            stage.set_task_pool(pool.cloned());
+        }
+        return;
+    };
+    match pool {
+        Some(pool) => stage.set_executor(Box::new(executor::TaskPoolExecutor::new(pool.clone()))),
+        // leave the stages that were single-threaded to begin with alone
+        None if stage.get_executor::<executor::TaskPoolExecutor>().is_some() => {
+            stage.set_executor(Box::<ParallelExecutor>::default());
+        }
+        None => {}
+    }
+}
+
+/// Switch a child stage of a fixed framestep to strict determinism mode
+fn make_strict(stage: &mut dyn Stage) {
+    if let Some(stage) = stage.downcast_mut::<SystemStage>() {
+        stage.set_executor(Box::<SingleThreadedExecutor>::default());
+    } else if let Some(group) = stage.downcast_mut::<groups::SystemGroupStage>() {
+        group.stage.set_executor(Box::<SingleThreadedExecutor>::default());
+    } else if let Some(cleanup) = stage.downcast_mut::<cleanup::CleanupStage>() {
+        cleanup.stage.set_executor(Box::<SingleThreadedExecutor>::default());
+    } else if let Some(stage) = stage.downcast_mut::<FixedFramestepStage>() {
+        stage.set_strict_determinism(true);
+    }
+}
+
+/// A Stage that runs another stage according to a run criteria
+///
+/// Behaves like the run criteria of a Bevy `Schedule`: the wrapped stage runs
+/// once for `ShouldRun::Yes`, and again and again for as long as the run criteria
+/// returns `ShouldRun::YesAndCheckAgain`.
+///
+/// Useful as a child stage of a [`FixedFramestepStage`], to apply (looping) run criteria
+/// to a custom stage type, or to a group of stages (wrap a `Schedule`).
+pub struct RunCriteriaStage<S: Stage> {
+    stage: S,
+    run_criteria: BoxedSystem<(), ShouldRun>,
+    initialized: bool,
+}
+
+impl<S: Stage> RunCriteriaStage<S> {
+    /// Wrap a stage with a run criteria
+    pub fn new<Param>(stage: S, run_criteria: impl IntoSystem<(), ShouldRun, Param>) -> Self {
+        Self {
+            stage,
+            run_criteria: Box::new(IntoSystem::into_system(run_criteria)),
+            initialized: false,
+        }
+    }
+
+    /// Get access to the wrapped stage
+    pub fn inner(&self) -> &S {
+        &self.stage
+    }
+
+    /// Get mut access to the wrapped stage
+    pub fn inner_mut(&mut self) -> &mut S {
+        &mut self.stage
+    }
+}
+
+impl<S: Stage> Stage for RunCriteriaStage<S> {
+    fn run(&mut self, world: &mut World) {
+        if !self.initialized {
+            self.run_criteria.initialize(world);
+            self.initialized = true;
+        }
+        loop {
+            let should_run = self.run_criteria.run((), world);
+            self.run_criteria.apply_buffers(world);
+            match should_run {
+                ShouldRun::No => return,
+                ShouldRun::Yes => {
+                    self.stage.run(world);
+                    return;
+                }
+                ShouldRun::YesAndCheckAgain => {
+                    self.stage.run(world);
+                }
+                ShouldRun::NoAndCheckAgain => {
+                    panic!("`NoAndCheckAgain` would loop infinitely in this situation.")
+                }
+            }
+        }
+    }
+}
+
+/// A Stage that does nothing
+///
+/// Use it as the child stage of a [`FixedFramestepStage`], to measure the overhead
+/// of the fixed framestep itself (like in the benchmarks of this crate), or as a placeholder.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopStage;
+
+impl NoopStage {
+    /// Create a fixed framestep with a single `NoopStage` child stage
+    pub fn framestep(framestep: impl Into<Frames>, label: impl FramestepLabel) -> FixedFramestepStage {
+        FixedFramestepStage::from_stage(framestep, label, NoopStage)
+    }
+}
+
+impl Stage for NoopStage {
+    fn run(&mut self, _world: &mut World) {}
+}
+
+/// Type used as a Bevy Stage Label for fixed framestep stages
+#[derive(Debug, Clone)]
+pub struct FixedFramestepStageLabel(pub FramestepName);
+
+impl StageLabel for FixedFramestepStageLabel {
+    fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+/// Stage labels for ordering the rest of the schedule relative to a fixed framestep
+///
+/// The `App`/`Schedule` extension methods that add a top-level fixed framestep (see [`app`]
+/// and [`schedule`]) also add two empty parallel `SystemStage`s around it: one labeled
+/// `Begin` with the name of the fixed framestep, right before the fixed framestep stage, and
+/// one labeled `End`, right after it. They run once per frame, whether any ticks run or
+/// not. Add systems to them to run them right before or after the ticks of the frame, or
+/// add your own stages relative to them, instead of relying on where the fixed framestep
+/// was inserted:
+///
+/// ```ignore
+/// app.add_fixed_framestep(2, "sim")
+///     .add_system_to_stage(FixedFramestepAnchorLabel::Begin("sim"), gather_input)
+///     .add_stage_after(FixedFramestepAnchorLabel::End("sim"), "interpolate", SystemStage::parallel());
+/// ```
+///
+/// All the sub-stages of the fixed framestep run inside its stage, on every tick, so stages
+/// elsewhere in the schedule can only be ordered relative to the fixed framestep as a whole.
+/// To run systems between two sub-stages, add a sub-stage for them, with
+/// `add_fixed_framestep_child_stage`. Fixed framesteps that are nested, dynamic, or
+/// added to the schedule directly do not have anchors, and [moved](relocate) ones leave
+/// them behind, like their [`FixedFramestepStageLabel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FixedFramestepAnchorLabel {
+    /// The stage right before the fixed framestep stage
+    Begin(FramestepName),
+    /// The stage right after the fixed framestep stage
+    End(FramestepName),
+}
+
+struct BeginAnchorMarker;
+struct EndAnchorMarker;
+
+impl StageLabel for FixedFramestepAnchorLabel {
+    fn as_str(&self) -> &'static str {
+        match *self {
+            FixedFramestepAnchorLabel::Begin(name) => name,
+            FixedFramestepAnchorLabel::End(name) => name,
+        }
+    }
+
+    // Bevy labels are compared by type and string, so use a different type for each
+    // variant (and not that of `FixedFramestepStageLabel`, which has the same string)
+    fn type_id(&self) -> TypeId {
+        match self {
+            FixedFramestepAnchorLabel::Begin(_) => TypeId::of::<BeginAnchorMarker>(),
+            FixedFramestepAnchorLabel::End(_) => TypeId::of::<EndAnchorMarker>(),
+        }
+    }
+}
+
+/// Add the `Begin`/`End` anchor stages around the top-level fixed framestep stage with the given name
+fn add_anchors(schedule: &mut Schedule, label: FramestepName) {
+    schedule.add_stage_before(FixedFramestepStageLabel(label), FixedFramestepAnchorLabel::Begin(label), SystemStage::parallel());
+    schedule.add_stage_after(FixedFramestepStageLabel(label), FixedFramestepAnchorLabel::End(label), SystemStage::parallel());
+}
+
+/// Create a fixed framestep stage with one sub-stage, for the extension methods
+fn new_framestep(framestep: Frames, label: FramestepName, position: SchedulePosition) -> FixedFramestepStage {
+    let mut stage = FixedFramestepStage::from_stage(framestep, label, new_substage());
+    stage.position = position;
+    stage
+}
+
+/// Create a parallel `SystemStage`, for a sub-stage of a fixed framestep
+fn new_substage() -> SystemStage {
+    SystemStage::parallel()
+}
+
+/// Find a fixed framestep by name, including ones nested inside other fixed framesteps
+fn find_framestep(schedule: &Schedule, label: FramestepName) -> Option<&FixedFramestepStage> {
+    if let Some(stage) = schedule.get_stage::<FixedFramestepStage>(FixedFramestepStageLabel(label)) {
+        if !stage.moved {
+            return Some(stage);
+        }
+    }
+    schedule.iter_stages()
+        .find_map(|(_, stage)| find_in_stage(stage, label))
+}
+
+/// Find a fixed framestep by name, including ones nested inside other fixed framesteps
+fn find_framestep_mut(schedule: &mut Schedule, label: FramestepName) -> Option<&mut FixedFramestepStage> {
+    // find the top-level stage that contains it, first
+    let (outer, instanced, dynamic, slot) = schedule.iter_stages()
+        .find(|(_, stage)| find_in_stage(*stage, label).is_some())
+        .map(|(outer, stage)| (
+            outer.as_label(),
+            stage.is::<instances::InstancedFramestepStage>(),
+            stage.is::<dynamic::DynamicFramestepsStage>(),
+            stage.is::<relocate::FramestepSlotStage>(),
+        ))?;
+    if slot {
+        return schedule.get_stage_mut::<relocate::FramestepSlotStage>(outer)?
+            .stage.as_mut()?
+            .find_nested_mut(label);
+    }
+    if dynamic {
+        return schedule.get_stage_mut::<dynamic::DynamicFramestepsStage>(outer)?
+            .stages.iter_mut()
+            .find_map(|stage| stage.find_nested_mut(label));
+    }
+    let stage = if instanced {
+        &mut schedule.get_stage_mut::<instances::InstancedFramestepStage>(outer)?.template
+    } else {
+        schedule.get_stage_mut::<FixedFramestepStage>(outer)?
+    };
+    stage.find_nested_mut(label)
+}
+
+/// Find a fixed framestep by name in a top-level stage, including in dynamic and nested ones
+fn find_in_stage(stage: &dyn Stage, label: FramestepName) -> Option<&FixedFramestepStage> {
+    if let Some(stage) = stage.downcast_ref::<dynamic::DynamicFramestepsStage>() {
+        return stage.stages.iter().find_map(|stage| stage.find_nested(label));
+    }
+    as_framestep(stage)?.find_nested(label)
+}
+
+/// The fixed framestep of a top-level stage, if it is one (or the template of an instanced
+/// one, or the one in a slot)
+///
+/// [`None`] for the empty stages left behind by `move_fixed_framestep`.
+fn as_framestep(stage: &dyn Stage) -> Option<&FixedFramestepStage> {
+    stage.downcast_ref::<FixedFramestepStage>()
+        .or_else(|| stage.downcast_ref::<instances::InstancedFramestepStage>().map(|stage| &stage.template))
+        .or_else(|| stage.downcast_ref::<relocate::FramestepSlotStage>().and_then(relocate::FramestepSlotStage::framestep))
+        .filter(|stage| !stage.moved)
+}
+
+/// Extensions to `bevy_app`
+#[cfg(feature = "app")]
+pub mod app {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use bevy_ecs::prelude::*;
+    use bevy_tasks::TaskPool;
+    use bevy_ecs::event::Event;
+    use bevy_ecs::schedule::IntoSystemDescriptor;
+    use bevy_app::{App, CoreStage};
+
+    use super::{FixedFramestepStage, FixedFramesteps, FixedFramestepStageLabel, FramestepLabel, Frames};
+    use super::{SchedulePosition, FramestepPosition};
+    use super::{new_framestep, new_substage, add_anchors, find_framestep, find_framestep_mut, insert_framestep};
+    use super::commands::{CommandBarrier, TickCommandQueue};
+    use super::scheduler::TickScheduler;
+    use super::dynamic::{DynamicFramestepsStage, DynamicFramestepsStageLabel};
+    use super::instances::{FramestepInstances, InstancedFramestepStage};
+    use super::relocate::move_framestep;
+    use super::simtime::{SimulationClock, SimulationTime, advance_simulation_time};
+    use super::events::TickScopedEvents;
+    use super::migration::RateMigration;
+    #[cfg(feature = "states")]
+    use bevy_ecs::schedule::StateData;
+    #[cfg(feature = "states")]
+    use crate::state::StateTransitionStage;
+
+    /// Add the tick queues, and register the events sent by the fixed framesteps (none, without the `"framestep-bookkeeping"` cargo feature)
+    fn add_framestep_events(app: &mut App) {
+        app.init_resource::<TickCommandQueue>();
+        app.init_resource::<TickScheduler>();
+        #[cfg(feature = "framestep-bookkeeping")]
+        {
+            use super::{FixedFramestepSkipped, FixedFramestepStretchClamped, TicksRewound, FramestepConfigChanged, TickOverBudget, WarmupFinished};
+            use super::migration::RateChanged;
+            use super::watchdog::TickStalled;
+
+            app.add_event::<FixedFramestepSkipped>();
+            app.add_event::<FixedFramestepStretchClamped>();
+            app.add_event::<TicksRewound>();
+            app.add_event::<FramestepConfigChanged>();
+            app.add_event::<TickOverBudget>();
+            app.add_event::<TickStalled>();
+            app.add_event::<RateChanged>();
+            app.add_event::<WarmupFinished>();
+        }
+        #[cfg(not(feature = "framestep-bookkeeping"))]
+        let _ = app;
+    }
+
+    /// Extension trait with the methods to add to Bevy's `App`
+    pub trait AppLooplessFixedFramestepExt {
+        /// Create a new fixed framestep stage and add it to the schedule in the default position
+        ///
+        /// You need to provide a name string, which you can use later to do things with the framestep.
+        ///
+        /// The [`FixedFramestepStage`] is created with one child sub-stage: a Bevy parallel `SystemStage`.
+        ///
+        /// The new stage is inserted into the default position: before `CoreStage::Update`.
+        fn add_fixed_framestep(&mut self, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App;
+        /// Create a new fixed framestep stage and add it to the schedule at the given position
+        ///
+        /// Like [`add_fixed_framestep`], but you control where to add the fixed framestep stage.
+        /// The position is kept in the [`FramestepLayout`](super::FramestepLayout::registered_position).
+        /// Panics if a stage that the position refers to is not in the schedule.
+        ///
+        /// To pick another position later (say, after profiling), use [`move_fixed_framestep`].
+        fn add_fixed_framestep_at(&mut self, position: FramestepPosition, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App;
+        /// Create a new fixed framestep stage and add it to the schedule before a given stage
+        ///
+        /// Shorthand for [`add_fixed_framestep_at`] with [`FramestepPosition::before`].
+        fn add_fixed_framestep_before_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App;
+        /// Create a new fixed framestep stage and add it to the schedule after a given stage
+        ///
+        /// Shorthand for [`add_fixed_framestep_at`] with [`FramestepPosition::after`].
+        fn add_fixed_framestep_after_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App;
+        /// Create a new fixed framestep for UI or audio logic, ticking `hz` times per second of real time
+        ///
+        /// See [`FixedFramestepStage::realtime`]. Add systems to it like to any other fixed
+        /// framestep, in the sub-stage `0`. The new stage is inserted into the default
+        /// position: before `CoreStage::Update`.
+        fn add_realtime_fixed_framestep(&mut self, hz: f64, label: impl FramestepLabel) -> &mut App;
+        /// Create a new fixed framestep for amortized work, running as many ticks as fit in `budget` on every frame
+        ///
+        /// See [`FixedFramestepStage::budgeted`]. Add systems to it like to any other fixed
+        /// framestep, in the sub-stage `0`. The new stage is inserted into the default
+        /// position: before `CoreStage::Update`.
+        fn add_budgeted_fixed_framestep(&mut self, budget: Duration, label: impl FramestepLabel) -> &mut App;
+        /// Move a fixed framestep stage to another position in the schedule
+        ///
+        /// The stage keeps all its sub-stages and systems, and all its state (tick counter,
+        /// accumulator, ...), so it just carries on from its new position. Only top-level
+        /// fixed framesteps can be moved (not nested, instanced, or dynamic ones). Panics if
+        /// the fixed framestep is not found, or if a stage that the position refers to is not
+        /// in the schedule.
+        ///
+        /// The stage with the `FixedFramestepStageLabel` stays at the old position, empty;
+        /// see the [`relocate`](super::relocate) module for how the stage is moved.
+        fn move_fixed_framestep(&mut self, framestep_name: impl FramestepLabel, position: FramestepPosition) -> &mut App;
+        /// Add a child sub-stage to a fixed framestep stage
+        ///
+        /// It will be added at the end, after any sub-stages that already exist.
+        ///
+        /// The new stage will be a Bevy parallel `SystemStage`.
+        fn add_fixed_framestep_child_stage(&mut self, framestep_name: impl FramestepLabel) -> &mut App;
+        /// Add a custom child sub-stage to a fixed framestep stage
+        ///
+        /// It will be added at the end, after any sub-stages that already exist.
+        ///
+        /// You can provide any stage type you like.
+        fn add_fixed_framestep_custom_child_stage(&mut self, framestep_name: impl FramestepLabel, stage: impl Stage) -> &mut App;
+        /// Add a [`CommandBarrier`](super::commands::CommandBarrier) pseudo sub-stage to a fixed framestep stage
+        ///
+        /// It will be added at the end, after any sub-stages that already exist. It takes up
+        /// a sub-stage index, like any other sub-stage.
+        fn add_fixed_framestep_command_barrier(&mut self, framestep_name: impl FramestepLabel) -> &mut App;
+        /// Create a new fixed framestep nested inside another one, as its last child sub-stage
+        ///
+        /// The nested fixed framestep counts the ticks of the outer one, instead of frames:
+        /// it runs every `framestep` ticks of `outer`. Useful for running expensive logic
+        /// (like AI) on a coarser tick than the rest of the simulation. Like any other fixed
+        /// framestep, it can be used by name with all the other methods.
+        fn add_fixed_framestep_nested(&mut self, outer: impl FramestepLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App;
+        /// Create a new instanced fixed framestep stage and add it to the schedule in the default position
+        ///
+        /// Like [`add_fixed_framestep`], but the fixed framestep is a template: it only runs
+        /// for each of its instances, spawned at runtime using the [`FramestepInstances`](super::instances::FramestepInstances)
+        /// resource. Add sub-stages and systems to it by name, like to any other fixed framestep.
+        ///
+        /// The new stage is inserted into the default position: before `CoreStage::Update`.
+        fn add_instanced_fixed_framestep(&mut self, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App;
+        /// Create a new instanced fixed framestep stage and add it to the schedule before a given stage
+        ///
+        /// Like [`add_instanced_fixed_framestep`], but you control where to add the stage.
+        fn add_instanced_fixed_framestep_before_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App;
+        /// Create a new instanced fixed framestep stage and add it to the schedule after a given stage
+        ///
+        /// Like [`add_instanced_fixed_framestep`], but you control where to add the stage.
+        fn add_instanced_fixed_framestep_after_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App;
+        /// Add the stage that hosts the fixed framesteps created at runtime, in the default position
+        ///
+        /// See the [`dynamic`](super::dynamic) module. Fixed framesteps can then be created from
+        /// systems, using [`DynamicFramestepsExt`](super::dynamic::DynamicFramestepsExt).
+        ///
+        /// The new stage is inserted into the default position: before `CoreStage::Update`.
+        fn add_dynamic_fixed_framesteps(&mut self) -> &mut App;
+        /// Add the stage that hosts the fixed framesteps created at runtime, before a given stage
+        ///
+        /// Like [`add_dynamic_fixed_framesteps`], but you control where to add the stage.
+        fn add_dynamic_fixed_framesteps_before_stage(&mut self, stage: impl StageLabel) -> &mut App;
+        /// Add the stage that hosts the fixed framesteps created at runtime, after a given stage
+        ///
+        /// Like [`add_dynamic_fixed_framesteps`], but you control where to add the stage.
+        fn add_dynamic_fixed_framesteps_after_stage(&mut self, stage: impl StageLabel) -> &mut App;
+        /// Add a system to run under a fixed framestep
+        ///
+        /// To specify where to add the system, provide the name string of the fixed framestep, and the
+        /// numeric index of the sub-stage (`0` if you have not added any additional sub-stages).
+        fn add_fixed_framestep_system<Params>(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, system: impl IntoSystemDescriptor<Params>) -> &mut App;
+        /// Add many systems to run under a fixed framestep
+        ///
+        /// To specify where to add the systems, provide the name string of the fixed framestep, and the
+        /// numeric index of the sub-stage (`0` if you have not added any additional sub-stages).
+        fn add_fixed_framestep_system_set(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, system_set: SystemSet) -> &mut App;
+        /// Add a named system group to a fixed framestep stage, as a new child sub-stage at the end
+        ///
+        /// See the [`groups`](super::groups) module. Panics if the group exists already.
+        fn add_fixed_framestep_group(&mut self, framestep_name: impl FramestepLabel, group: &'static str, system_set: SystemSet) -> &mut App;
+        /// Replace all the systems of a named system group of a fixed framestep
+        ///
+        /// Panics if the group does not exist.
+        fn replace_fixed_framestep_group(&mut self, framestep_name: impl FramestepLabel, group: &str, system_set: SystemSet) -> &mut App;
+        /// Add a system to a fixed framestep, in a slot with the given label, so that it can be removed or replaced later
+        ///
+        /// The slot runs as an exclusive system, at the start of the sub-stage. See the
+        /// [`slots`](super::slots) module. Panics if the slot exists already.
+        fn add_fixed_framestep_replaceable_system<Params>(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, label: impl SystemLabel + Clone, system: impl IntoSystem<(), (), Params>) -> &mut App;
+        /// Remove the system in the slot with the given label (see `add_fixed_framestep_replaceable_system`)
+        ///
+        /// Only works for systems added using `add_fixed_framestep_replaceable_system`: Bevy
+        /// cannot remove systems from a `SystemStage`, so other systems with the same label
+        /// are not affected. Fails with [`SlotNotFound`](super::slots::SlotNotFound) if there
+        /// is no such slot.
+        fn remove_fixed_framestep_system(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, label: impl SystemLabel) -> Result<&mut App, super::slots::SlotNotFound>;
+        /// Put another system in the slot with the given label (see `add_fixed_framestep_replaceable_system`)
+        ///
+        /// Like `remove_fixed_framestep_system`, only works for systems added using
+        /// `add_fixed_framestep_replaceable_system`. The new system runs exclusively, in the
+        /// slot, at the start of the sub-stage. Fails with [`SlotNotFound`](super::slots::SlotNotFound)
+        /// if there is no such slot; the system is not added then.
+        fn replace_fixed_framestep_system<Params>(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, label: impl SystemLabel, system: impl IntoSystem<(), (), Params>) -> Result<&mut App, super::slots::SlotNotFound>;
+        /// Check that no [`TickOnly`](super::placement::TickOnly) systems are in per-frame stages, and no [`FrameOnly`](super::placement::FrameOnly) systems are in fixed framesteps
+        ///
+        /// Call it after all the systems have been added. Panics with a list of the misplaced
+        /// systems, if there are any. See the [`placement`](super::placement) module.
+        fn validate_fixed_framestep_systems(&mut self) -> &mut App;
+        /// Log a human-readable report of all the fixed framesteps in the app
+        ///
+        /// For each fixed framestep: its position in the schedule, its rate (in frames and
+        /// Hz), and its sub-stages with their number of systems. See the [`report`](super::report) module.
+        fn print_fixed_framestep_report(&mut self) -> &mut App;
+        /// Add a system to the cleanup sub-stage of a fixed framestep, which runs last on every tick
+        ///
+        /// See the [`cleanup`](super::cleanup) module. Adds the cleanup sub-stage, if the fixed
+        /// framestep does not have one yet.
+        fn add_fixed_framestep_cleanup_system<Params>(&mut self, framestep_name: impl FramestepLabel, system: impl IntoSystemDescriptor<Params>) -> &mut App;
+        /// Give a fixed framestep a virtual clock, advanced on every tick
+        ///
+        /// See the [`simtime`](super::simtime) module. The clock can be read from the
+        /// [`SimulationTime`](super::simtime::SimulationTime) resource. Replaces any clock
+        /// that the fixed framestep already has.
+        fn add_fixed_framestep_simulation_time(&mut self, framestep_name: impl FramestepLabel, clock: SimulationClock) -> &mut App;
+        /// Let a fixed framestep drive the transitions of a state type, at the end of every tick
+        ///
+        /// See the [`states`](super::states) module. Instead of `add_loopless_state`.
+        #[cfg(feature = "states")]
+        fn add_fixed_framestep_state<T: StateData>(&mut self, framestep_name: impl FramestepLabel, init: T) -> &mut App;
+        /// Add an enter system for a state driven by a fixed framestep (see `add_fixed_framestep_state`)
+        #[cfg(feature = "states")]
+        fn add_fixed_framestep_enter_system<T: StateData, Params>(&mut self, framestep_name: impl FramestepLabel, state: T, system: impl IntoSystemDescriptor<Params>) -> &mut App;
+        /// Add an exit system for a state driven by a fixed framestep (see `add_fixed_framestep_state`)
+        #[cfg(feature = "states")]
+        fn add_fixed_framestep_exit_system<T: StateData, Params>(&mut self, framestep_name: impl FramestepLabel, state: T, system: impl IntoSystemDescriptor<Params>) -> &mut App;
+        /// Get access to the [`FixedFramestepStage`] for the fixed framestep with a given name string
+        fn get_fixed_framestep_stage(&self, framestep_name: impl FramestepLabel) -> &FixedFramestepStage;
+        /// Get mut access to the [`FixedFramestepStage`] for the fixed framestep with a given name string
+        fn get_fixed_framestep_stage_mut(&mut self, framestep_name: impl FramestepLabel) -> &mut FixedFramestepStage;
+        /// Override the determinism seed of the fixed framestep with the given name string
+        ///
+        /// Takes effect from the next tick, even if the app has already run. Useful in
+        /// tests, or for applying a seed given on the command line.
+        fn set_fixed_framestep_seed(&mut self, framestep_name: impl FramestepLabel, seed: u64) -> &mut App;
+        /// Run the systems of the fixed framestep with the given name string on the given task pool
+        ///
+        /// See [`FixedFramestepStage::set_task_pool`]. Call it after adding all the sub-stages,
+        /// or before: the ones added later use the task pool too.
+        fn set_fixed_framestep_task_pool(&mut self, framestep_name: impl FramestepLabel, pool: Arc<TaskPool>) -> &mut App;
+        /// Pause the fixed framestep with the given name string while the given condition holds
+        ///
+        /// See [`FixedFramestepStage::add_pause_condition`].
+        fn pause_fixed_framestep_when(&mut self, framestep_name: impl FramestepLabel, condition: impl Fn(&World) -> bool + Send + Sync + 'static) -> &mut App;
+        /// Call a function at the start of every tick of the fixed framestep with the given name string
+        ///
+        /// See [`FixedFramestepStage::on_tick_begin`].
+        fn on_fixed_framestep_tick_begin(&mut self, framestep_name: impl FramestepLabel, callback: impl FnMut(&mut World, u64) + Send + Sync + 'static) -> &mut App;
+        /// Call a function at the end of every tick of the fixed framestep with the given name string
+        ///
+        /// See [`FixedFramestepStage::on_tick_end`].
+        fn on_fixed_framestep_tick_end(&mut self, framestep_name: impl FramestepLabel, callback: impl FnMut(&mut World, u64) + Send + Sync + 'static) -> &mut App;
+        /// Rescale rate-dependent state when the step of the fixed framestep with the given name string is changed
+        ///
+        /// See [`FixedFramestepStage::add_rate_migration`] and the [`migration`](super::migration) module.
+        fn add_fixed_framestep_rate_migration(&mut self, framestep_name: impl FramestepLabel, migration: impl RateMigration) -> &mut App;
+        /// Set the config of type `T` of the given fixed framestep, for its tick systems
+        ///
+        /// Read it with the [`TickConfig<T>`](super::config::TickConfig) system parameter.
+        /// Replaces any config of the same type the fixed framestep had. See the
+        /// [`config`](super::config) module.
+        fn add_fixed_framestep_config<T: Send + Sync + 'static>(&mut self, framestep_name: impl FramestepLabel, config: T) -> &mut App;
+        /// Register an event type whose events are updated on every tick of the given fixed framestep
+        ///
+        /// Use it instead of `add_event`, for events that are sent and read by the systems of
+        /// the fixed framestep: they are kept for two ticks, instead of two frames, so readers
+        /// see each event once, no matter how many frames pass between the ticks. See the
+        /// [`events`](super::events) module.
+        fn register_tick_event<E: Event>(&mut self, framestep_name: impl FramestepLabel) -> &mut App;
+        /// Add the [`TickScopedEvents<E>`](super::events::TickScopedEvents) resource, cleared at the end of every tick of the given fixed framestep
+        ///
+        /// The events sent to it during a tick can be read by all the systems that run later
+        /// in the same tick, and by none on later ticks. See the [`events`](super::events) module.
+        fn register_tick_scoped_event<E: Event>(&mut self, framestep_name: impl FramestepLabel) -> &mut App;
+        /// Add the [`TickOf<F>`](super::tick::TickOf) resource, with the number of the current tick of the fixed framestep named by `F`
+        ///
+        /// Cheaper to read than the [`FixedFramesteps`] resource, and only changed when a new
+        /// tick starts. See the [`tick`](super::tick) module.
+        fn register_tick_resource<F: FramestepLabel + Default + Send + Sync + 'static>(&mut self) -> &mut App;
+        /// Add the [`FramestepOf<F>`](super::split::FramestepOf) resource, with the parameters of the fixed framestep named by `F`
+        ///
+        /// Systems that access the resources of different fixed framesteps do not conflict,
+        /// unlike with the [`FixedFramesteps`] resource. See the [`split`](super::split) module.
+        fn register_framestep_resource<F: FramestepLabel + Default + Send + Sync + 'static>(&mut self) -> &mut App;
+        /// Get access to the i-th child sub-stage of the fixed framestep with the given name string
+        fn get_fixed_framestep_child_substage<S: Stage>(&self, framestep_name: impl FramestepLabel, substage_i: usize) -> &S;
+        /// Get mut access to the i-th child sub-stage of the fixed framestep with the given name string
+        fn get_fixed_framestep_child_substage_mut<S: Stage>(&mut self, framestep_name: impl FramestepLabel, substage_i: usize) -> &mut S;
+    }
+
+    impl AppLooplessFixedFramestepExt for App {
+        fn add_fixed_framestep(&mut self, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App {
+            self.add_fixed_framestep_at(FramestepPosition::Default, framestep, label)
+        }
+
+        fn add_fixed_framestep_at(&mut self, position: FramestepPosition, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App {
+            let label = label.framestep_name();
+            let ffstage = new_framestep(framestep.into(), label, SchedulePosition::Unknown);
+            insert_framestep(&mut self.schedule, position, ffstage);
+            find_framestep(&self.schedule, label).unwrap()
+                .store_fixedframestepinfo(&mut self.world);
+            add_framestep_events(self);
+            self
+        }
+
+        fn add_fixed_framestep_before_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App {
+            self.add_fixed_framestep_at(FramestepPosition::before(stage), framestep, label)
+        }
+
+        fn add_realtime_fixed_framestep(&mut self, hz: f64, label: impl FramestepLabel) -> &mut App {
+            let label = label.framestep_name();
+            let ffstage = FixedFramestepStage::realtime(hz, label);
+            insert_framestep(&mut self.schedule, FramestepPosition::Default, ffstage);
+            find_framestep(&self.schedule, label).unwrap()
+                .store_fixedframestepinfo(&mut self.world);
+            add_framestep_events(self);
+            self
+        }
+
+        fn add_budgeted_fixed_framestep(&mut self, budget: Duration, label: impl FramestepLabel) -> &mut App {
+            let label = label.framestep_name();
+            let ffstage = FixedFramestepStage::budgeted(budget, label);
+            insert_framestep(&mut self.schedule, FramestepPosition::Default, ffstage);
+            find_framestep(&self.schedule, label).unwrap()
+                .store_fixedframestepinfo(&mut self.world);
+            add_framestep_events(self);
+            self
+        }
+
+        fn add_fixed_framestep_after_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App {
+            self.add_fixed_framestep_at(FramestepPosition::after(stage), framestep, label)
+        }
+
+        fn move_fixed_framestep(&mut self, framestep_name: impl FramestepLabel, position: FramestepPosition) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            move_framestep(&mut self.schedule, framestep_name, position);
+            // update the layout right away, rather than on the next run
+            find_framestep_mut(&mut self.schedule, framestep_name).unwrap()
+                .set_up_world(&mut self.world);
+            self
+        }
+
+        fn add_fixed_framestep_child_stage(&mut self, framestep_name: impl FramestepLabel) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(&mut self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            stage.add_stage(new_substage());
+            self
+        }
+
+        fn add_fixed_framestep_custom_child_stage(&mut self, framestep_name: impl FramestepLabel, custom_stage: impl Stage) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(&mut self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            stage.add_stage(custom_stage);
+            self
+        }
+
+        fn add_fixed_framestep_command_barrier(&mut self, framestep_name: impl FramestepLabel) -> &mut App {
+            self.add_fixed_framestep_custom_child_stage(framestep_name, CommandBarrier)
+        }
+
+        fn add_fixed_framestep_nested(&mut self, outer: impl FramestepLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App {
+            let label = label.framestep_name();
+            let outer = outer.framestep_name();
+            let ffstage = new_framestep(framestep.into(), label, SchedulePosition::Nested(outer));
+            ffstage.store_fixedframestepinfo(&mut self.world);
+            self.add_fixed_framestep_custom_child_stage(outer, ffstage)
+        }
+
+        fn add_instanced_fixed_framestep(&mut self, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App {
+            self.add_instanced_fixed_framestep_before_stage(CoreStage::Update, framestep, label)
+        }
+
+        fn add_instanced_fixed_framestep_before_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App {
+            let label = label.framestep_name();
+            let position = SchedulePosition::Before(stage.as_str());
+            self.init_resource::<FramestepInstances>();
+            add_framestep_events(self);
+            self.add_stage_before(
+                stage,
+                FixedFramestepStageLabel(label),
+                InstancedFramestepStage::new(new_framestep(framestep.into(), label, position))
+            );
+            add_anchors(&mut self.schedule, label);
+            self
+        }
+
+        fn add_instanced_fixed_framestep_after_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut App {
+            let label = label.framestep_name();
+            let position = SchedulePosition::After(stage.as_str());
+            self.init_resource::<FramestepInstances>();
+            add_framestep_events(self);
+            self.add_stage_after(
+                stage,
+                FixedFramestepStageLabel(label),
+                InstancedFramestepStage::new(new_framestep(framestep.into(), label, position))
+            );
+            add_anchors(&mut self.schedule, label);
+            self
+        }
+
+        fn add_dynamic_fixed_framesteps(&mut self) -> &mut App {
+            self.add_dynamic_fixed_framesteps_before_stage(CoreStage::Update)
+        }
+
+        fn add_dynamic_fixed_framesteps_before_stage(&mut self, stage: impl StageLabel) -> &mut App {
+            let host = DynamicFramestepsStage {
+                stages: Vec::new(),
+                position: SchedulePosition::Before(stage.as_str()),
+            };
+            // so that systems can use it before the first fixed framestep is created
+            self.init_resource::<FixedFramesteps>();
+            add_framestep_events(self);
+            self.add_stage_before(stage, DynamicFramestepsStageLabel, host)
+        }
+
+        fn add_dynamic_fixed_framesteps_after_stage(&mut self, stage: impl StageLabel) -> &mut App {
+            let host = DynamicFramestepsStage {
+                stages: Vec::new(),
+                position: SchedulePosition::After(stage.as_str()),
+            };
+            // so that systems can use it before the first fixed framestep is created
+            self.init_resource::<FixedFramesteps>();
+            add_framestep_events(self);
+            self.add_stage_after(stage, DynamicFramestepsStageLabel, host)
+        }
+
+        fn add_fixed_framestep_system<Params>(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, system: impl IntoSystemDescriptor<Params>) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(&mut self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            let substage = stage.stages.get_mut(substage_i)
+                .expect("Fixed Framestep sub-stage not found")
+                .downcast_mut::<SystemStage>()
+                .expect("Fixed Framestep sub-stage is not a SystemStage");
+            substage.add_system(system);
+            self
+        }
+
+        fn add_fixed_framestep_system_set(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, system_set: SystemSet) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(&mut self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            let substage = stage.stages.get_mut(substage_i)
+                .expect("Fixed Framestep sub-stage not found")
+                .downcast_mut::<SystemStage>()
+                .expect("Fixed Framestep sub-stage is not a SystemStage");
+            substage.add_system_set(system_set);
+            self
+        }
+
+        fn add_fixed_framestep_group(&mut self, framestep_name: impl FramestepLabel, group: &'static str, system_set: SystemSet) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(&mut self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            stage.add_group(group, system_set);
+            self
+        }
+
+        fn replace_fixed_framestep_group(&mut self, framestep_name: impl FramestepLabel, group: &str, system_set: SystemSet) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(&mut self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            if !stage.replace_group(group, system_set) {
+                panic!("Fixed Framestep system group {:?} not found", group);
+            }
+            self
+        }
+
+        fn add_fixed_framestep_replaceable_system<Params>(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, label: impl SystemLabel + Clone, system: impl IntoSystem<(), (), Params>) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            let key = (framestep_name, substage_i, label.as_label());
+            let system: bevy_ecs::system::BoxedSystem = Box::new(IntoSystem::into_system(system));
+            let mut slots = self.world.get_resource_or_insert_with(super::slots::TickSystemSlots::default);
+            if !slots.insert(key, system) {
+                panic!("Fixed Framestep system slot {:?} already exists", key.2);
+            }
+            let stage = find_framestep_mut(&mut self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            stage.stages.get_mut(substage_i)
+                .expect("Fixed Framestep sub-stage not found")
+                .downcast_mut::<SystemStage>()
+                .expect("Fixed Framestep sub-stage is not a SystemStage")
+                .add_system(super::slots::slot_system(key.0, key.1, key.2).label(label));
+            self
+        }
+
+        fn remove_fixed_framestep_system(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, label: impl SystemLabel) -> Result<&mut App, super::slots::SlotNotFound> {
+            let framestep = framestep_name.framestep_name();
+            let label = label.as_label();
+            let removed = self.world.get_resource_mut::<super::slots::TickSystemSlots>()
+                .map(|mut slots| slots.remove(framestep, substage_i, label))
+                .unwrap_or(false);
+            if !removed {
+                return Err(super::slots::SlotNotFound { framestep, substage: substage_i, label });
+            }
+            Ok(self)
+        }
+
+        fn replace_fixed_framestep_system<Params>(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, label: impl SystemLabel, system: impl IntoSystem<(), (), Params>) -> Result<&mut App, super::slots::SlotNotFound> {
+            let framestep = framestep_name.framestep_name();
+            let label = label.as_label();
+            let replaced = self.world.get_resource_mut::<super::slots::TickSystemSlots>()
+                .map(|mut slots| slots.replace(framestep, substage_i, label, system))
+                .unwrap_or(false);
+            if !replaced {
+                return Err(super::slots::SlotNotFound { framestep, substage: substage_i, label });
+            }
+            Ok(self)
+        }
+
+        fn validate_fixed_framestep_systems(&mut self) -> &mut App {
+            let misplaced = super::placement::misplaced_systems(&self.schedule);
+            if !misplaced.is_empty() {
+                let list: Vec<_> = misplaced.iter().map(|m| format!(" - {}", m)).collect();
+                panic!("Found {} misplaced system(s):\n{}", misplaced.len(), list.join("\n"));
+            }
+            self
+        }
+
+        fn print_fixed_framestep_report(&mut self) -> &mut App {
+            bevy_utils::tracing::info!("{}", super::report::schedule_report(&self.schedule));
+            self
+        }
+
+        fn add_fixed_framestep_cleanup_system<Params>(&mut self, framestep_name: impl FramestepLabel, system: impl IntoSystemDescriptor<Params>) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(&mut self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            stage.add_cleanup_substage();
+            stage.cleanup_substage_mut().unwrap().add_system(system);
+            self
+        }
+
+        #[cfg(feature = "states")]
+        fn add_fixed_framestep_state<T: StateData>(&mut self, framestep_name: impl FramestepLabel, init: T) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            find_framestep_mut(&mut self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found")
+                .add_state_transitions(StateTransitionStage::new(init));
+            self
+        }
+
+        #[cfg(feature = "states")]
+        fn add_fixed_framestep_enter_system<T: StateData, Params>(&mut self, framestep_name: impl FramestepLabel, state: T, system: impl IntoSystemDescriptor<Params>) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            find_framestep_mut(&mut self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found")
+                .state_transitions_mut::<T>()
+                .expect("Fixed Framestep does not drive this state type")
+                .add_enter_system(state, system);
+            self
+        }
+
+        #[cfg(feature = "states")]
+        fn add_fixed_framestep_exit_system<T: StateData, Params>(&mut self, framestep_name: impl FramestepLabel, state: T, system: impl IntoSystemDescriptor<Params>) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            find_framestep_mut(&mut self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found")
+                .state_transitions_mut::<T>()
+                .expect("Fixed Framestep does not drive this state type")
+                .add_exit_system(state, system);
+            self
+        }
+
+        fn add_fixed_framestep_simulation_time(&mut self, framestep_name: impl FramestepLabel, clock: SimulationClock) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(&mut self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            let mut time = self.world.get_resource_or_insert_with(SimulationTime::default);
+            // only hook it up once
+            if time.clocks.insert(framestep_name, clock).is_none() {
+                stage.add_tick_begin_hook(advance_simulation_time);
+            }
+            self
+        }
+
+        fn get_fixed_framestep_stage(&self, framestep_name: impl FramestepLabel) -> &FixedFramestepStage {
+            let framestep_name = framestep_name.framestep_name();
+            find_framestep(&self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found")
+        }
+
+        fn get_fixed_framestep_stage_mut(&mut self, framestep_name: impl FramestepLabel) -> &mut FixedFramestepStage {
+            let framestep_name = framestep_name.framestep_name();
+            find_framestep_mut(&mut self.schedule, framestep_name)
+                .expect("Fixed Framestep Stage not found")
+        }
+
+        fn set_fixed_framestep_seed(&mut self, framestep_name: impl FramestepLabel, seed: u64) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = self.get_fixed_framestep_stage_mut(framestep_name);
+            stage.set_seed(seed);
+            if let Some(mut framesteps) = self.world.get_resource_mut::<FixedFramesteps>() {
+                if let Some(info) = framesteps.info.get_mut(&framestep_name.framestep_id()) {
+                    info.seed = seed;
+                }
+            }
+            self
+        }
+
+        fn set_fixed_framestep_task_pool(&mut self, framestep_name: impl FramestepLabel, pool: Arc<TaskPool>) -> &mut App {
+            self.get_fixed_framestep_stage_mut(framestep_name).set_task_pool(Some(pool));
+            self
+        }
+
+        fn pause_fixed_framestep_when(&mut self, framestep_name: impl FramestepLabel, condition: impl Fn(&World) -> bool + Send + Sync + 'static) -> &mut App {
+            self.get_fixed_framestep_stage_mut(framestep_name).add_pause_condition(condition);
+            self
+        }
+
+        fn on_fixed_framestep_tick_begin(&mut self, framestep_name: impl FramestepLabel, callback: impl FnMut(&mut World, u64) + Send + Sync + 'static) -> &mut App {
+            self.get_fixed_framestep_stage_mut(framestep_name).on_tick_begin(callback);
+            self
+        }
+
+        fn on_fixed_framestep_tick_end(&mut self, framestep_name: impl FramestepLabel, callback: impl FnMut(&mut World, u64) + Send + Sync + 'static) -> &mut App {
+            self.get_fixed_framestep_stage_mut(framestep_name).on_tick_end(callback);
+            self
+        }
+
+        fn add_fixed_framestep_rate_migration(&mut self, framestep_name: impl FramestepLabel, migration: impl RateMigration) -> &mut App {
+            self.get_fixed_framestep_stage_mut(framestep_name).add_rate_migration(migration);
+            self
+        }
+
+        fn add_fixed_framestep_config<T: Send + Sync + 'static>(&mut self, framestep_name: impl FramestepLabel, config: T) -> &mut App {
+            let framestep_name = framestep_name.framestep_name();
+            // make sure that the fixed framestep exists
+            self.get_fixed_framestep_stage(framestep_name);
+            self.world.get_resource_or_insert_with(super::config::FramestepConfigs::<T>::default)
+                .insert(framestep_name, config);
+            self
+        }
+
+        fn register_tick_event<E: Event>(&mut self, framestep_name: impl FramestepLabel) -> &mut App {
+            self.init_resource::<Events<E>>();
+            self.get_fixed_framestep_stage_mut(framestep_name).add_tick_event::<E>();
+            self
+        }
+
+        fn register_tick_scoped_event<E: Event>(&mut self, framestep_name: impl FramestepLabel) -> &mut App {
+            self.init_resource::<TickScopedEvents<E>>();
+            self.get_fixed_framestep_stage_mut(framestep_name).add_tick_scoped_event::<E>();
+            self
+        }
+
+        fn register_tick_resource<F: FramestepLabel + Default + Send + Sync + 'static>(&mut self) -> &mut App {
+            let tick = self.world.get_resource::<FixedFramesteps>()
+                .and_then(|framesteps| framesteps.get(F::default()))
+                .map_or(0, |info| info.tick);
+            super::tick::sync_tick_of::<F>(&mut self.world, tick);
+            self.get_fixed_framestep_stage_mut(F::default()).add_tick_resource::<F>();
+            self
+        }
+
+        fn register_framestep_resource<F: FramestepLabel + Default + Send + Sync + 'static>(&mut self) -> &mut App {
+            let label = F::default().framestep_name();
+            self.get_fixed_framestep_stage_mut(label).add_framestep_resource::<F>();
+            super::split::push_framestep_of::<F>(&mut self.world, label);
+            self
+        }
+
+        fn get_fixed_framestep_child_substage<S: Stage>(&self, framestep_name: impl FramestepLabel, substage_i: usize) -> &S {
+            let stage = self.get_fixed_framestep_stage(framestep_name);
+            stage.stages.get(substage_i)
+                .expect("Fixed Framestep sub-stage not found")
+                .downcast_ref::<S>()
+                .expect("Fixed Framestep sub-stage is not the requested type")
+        }
+
+        fn get_fixed_framestep_child_substage_mut<S: Stage>(&mut self, framestep_name: impl FramestepLabel, substage_i: usize) -> &mut S {
+            let stage = self.get_fixed_framestep_stage_mut(framestep_name);
+            stage.stages.get_mut(substage_i)
+                .expect("Fixed Framestep sub-stage not found")
+                .downcast_mut::<S>()
+                .expect("Fixed Framestep sub-stage is not the requested type")
+        }
+    }
+}
+
+/// Declare a fixed framestep, its sub-stages, and their systems, in one go
+///
+/// Adds the fixed framestep to the `App` in the default position (see
+/// [`add_fixed_framestep`](app::AppLooplessFixedFramestepExt::add_fixed_framestep)),
+/// with one sub-stage for each named list of systems, in the order they are written.
+/// The sub-stage names are only for readability; the sub-stages are still
+/// addressed by index (`0`, `1`, ...) elsewhere.
+///
+/// ```ignore
+/// fixed_framestep!(app, "sim", every 4 frames, {
+///     pre: [gather_input],
+///     main: [movement, collide.after(movement)],
+///     post: [cleanup],
+/// });
+/// ```
+///
+/// `app` can be an `App` or a `&mut App` (like in `Plugin::build`). The step can be
+/// any single token: a literal, a variable name, or an expression in parentheses.
+#[cfg(feature = "app")]
+#[macro_export]
+macro_rules! fixed_framestep {
+    ($app:expr, $label:expr, every $step:tt frames, { $($substage:ident : [$($system:expr),* $(,)?]),* $(,)? }) => {{
+        use $crate::fixedframestep::app::AppLooplessFixedFramestepExt as _;
+        let label = $crate::fixedframestep::FramestepLabel::framestep_name(&$label);
+        $app.add_fixed_framestep($step, label);
+        let mut _substage_i: usize = 0;
+        $(
+            // the first sub-stage is created along with the fixed framestep
+            if _substage_i > 0 {
+                $app.add_fixed_framestep_child_stage(label);
+            }
+            let _ = stringify!($substage);
+            $(
+                $app.add_fixed_framestep_system(label, _substage_i, $system);
+            )*
+            _substage_i += 1;
+        )*
+    }};
+}
+
+/// Extensions to Bevy Schedule
+pub mod schedule {
+    use bevy_ecs::prelude::*;
+    use bevy_ecs::schedule::IntoSystemDescriptor;
+
+    use super::{FixedFramestepStage, FixedFramestepStageLabel, FramestepLabel, Frames};
+    use super::{SchedulePosition, FramestepPosition, new_framestep, new_substage, add_anchors, find_framestep, find_framestep_mut, insert_framestep};
+    use super::commands::CommandBarrier;
+    use super::relocate::move_framestep;
+    use super::dynamic::{DynamicFramestepsStage, DynamicFramestepsStageLabel};
+    use super::instances::InstancedFramestepStage;
+    #[cfg(feature = "states")]
+    use bevy_ecs::schedule::StateData;
+    #[cfg(feature = "states")]
+    use crate::state::StateTransitionStage;
+
+    /// Extension trait with the methods to add to Bevy's `Schedule`
+    pub trait ScheduleLooplessFixedFramestepExt {
+        /// Create a new fixed framestep stage and add it to the schedule at the given position
+        ///
+        /// You need to provide a name string, which you can use later to do things with the framestep.
+        ///
+        /// The [`FixedFramestepStage`] is created with one child sub-stage: a Bevy parallel `SystemStage`.
+        /// Panics if a stage that the position refers to is not in the schedule.
+        fn add_fixed_framestep_at(&mut self, position: FramestepPosition, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut Schedule;
+        /// Create a new fixed framestep stage and add it to the schedule before a given stage
+        ///
+        /// Shorthand for [`add_fixed_framestep_at`] with [`FramestepPosition::before`].
+        fn add_fixed_framestep_before_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut Schedule;
+        /// Create a new fixed framestep stage and add it to the schedule after a given stage
+        ///
+        /// Shorthand for [`add_fixed_framestep_at`] with [`FramestepPosition::after`].
+        fn add_fixed_framestep_after_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut Schedule;
+        /// Move a fixed framestep stage to another position in the schedule
+        ///
+        /// The stage keeps all its sub-stages, systems, and state. Only top-level fixed
+        /// framesteps can be moved (see the `App` version, `move_fixed_framestep`).
+        fn move_fixed_framestep(&mut self, framestep_name: impl FramestepLabel, position: FramestepPosition) -> &mut Schedule;
+        /// Add a child sub-stage to a fixed framestep stage
+        ///
+        /// It will be added at the end, after any sub-stages that already exist.
+        ///
+        /// The new stage will be a Bevy parallel `SystemStage`.
+        fn add_fixed_framestep_child_stage(&mut self, framestep_name: impl FramestepLabel) -> &mut Schedule;
+        /// Add a custom child sub-stage to a fixed framestep stage
+        ///
+        /// It will be added at the end, after any sub-stages that already exist.
+        ///
+        /// You can provide any stage type you like.
+        fn add_fixed_framestep_custom_child_stage(&mut self, framestep_name: impl FramestepLabel, stage: impl Stage) -> &mut Schedule;
+        /// Add a [`CommandBarrier`](super::commands::CommandBarrier) pseudo sub-stage to a fixed framestep stage
+        ///
+        /// It will be added at the end, after any sub-stages that already exist. It takes up
+        /// a sub-stage index, like any other sub-stage.
+        fn add_fixed_framestep_command_barrier(&mut self, framestep_name: impl FramestepLabel) -> &mut Schedule;
+        /// Create a new fixed framestep nested inside another one, as its last child sub-stage
+        ///
+        /// The nested fixed framestep counts the ticks of the outer one, instead of frames:
+        /// it runs every `framestep` ticks of `outer`. Useful for running expensive logic
+        /// (like AI) on a coarser tick than the rest of the simulation. Like any other fixed
+        /// framestep, it can be used by name with all the other methods.
+        fn add_fixed_framestep_nested(&mut self, outer: impl FramestepLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut Schedule;
+        /// Create a new instanced fixed framestep stage and add it to the schedule before a given stage
+        ///
+        /// The fixed framestep is a template: it only runs for each of its instances, spawned at
+        /// runtime using the [`FramestepInstances`](super::instances::FramestepInstances) resource
+        /// (which you need to insert into the world).
+        fn add_instanced_fixed_framestep_before_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut Schedule;
+        /// Create a new instanced fixed framestep stage and add it to the schedule after a given stage
+        ///
+        /// Like [`add_instanced_fixed_framestep_before_stage`], but after the given stage.
+        fn add_instanced_fixed_framestep_after_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut Schedule;
+        /// Add the stage that hosts the fixed framesteps created at runtime, before a given stage
+        ///
+        /// See the [`dynamic`](super::dynamic) module.
+        fn add_dynamic_fixed_framesteps_before_stage(&mut self, stage: impl StageLabel) -> &mut Schedule;
+        /// Add the stage that hosts the fixed framesteps created at runtime, after a given stage
+        ///
+        /// Like [`add_dynamic_fixed_framesteps_before_stage`], but after the given stage.
+        fn add_dynamic_fixed_framesteps_after_stage(&mut self, stage: impl StageLabel) -> &mut Schedule;
+        /// Add a system to run under a fixed framestep
+        ///
+        /// To specify where to add the system, provide the name string of the fixed framestep, and the
+        /// numeric index of the sub-stage (`0` if you have not added any additional sub-stages).
+        fn add_fixed_framestep_system<Params>(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, system: impl IntoSystemDescriptor<Params>) -> &mut Schedule;
+        /// Add many systems to run under a fixed framestep
+        ///
+        /// To specify where to add the systems, provide the name string of the fixed framestep, and the
+        /// numeric index of the sub-stage (`0` if you have not added any additional sub-stages).
+        fn add_fixed_framestep_system_set(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, system_set: SystemSet) -> &mut Schedule;
+        /// Add a named system group to a fixed framestep stage, as a new child sub-stage at the end
+        ///
+        /// See the [`groups`](super::groups) module. Panics if the group exists already.
+        fn add_fixed_framestep_group(&mut self, framestep_name: impl FramestepLabel, group: &'static str, system_set: SystemSet) -> &mut Schedule;
+        /// Replace all the systems of a named system group of a fixed framestep
+        ///
+        /// Panics if the group does not exist.
+        fn replace_fixed_framestep_group(&mut self, framestep_name: impl FramestepLabel, group: &str, system_set: SystemSet) -> &mut Schedule;
+        /// Add a system to the cleanup sub-stage of a fixed framestep, which runs last on every tick
+        ///
+        /// See the [`cleanup`](super::cleanup) module. Adds the cleanup sub-stage, if the fixed
+        /// framestep does not have one yet.
+        fn add_fixed_framestep_cleanup_system<Params>(&mut self, framestep_name: impl FramestepLabel, system: impl IntoSystemDescriptor<Params>) -> &mut Schedule;
+        /// Let a fixed framestep drive the transitions of a state type, at the end of every tick
+        ///
+        /// See the [`states`](super::states) module. Instead of `add_loopless_state`.
+        #[cfg(feature = "states")]
+        fn add_fixed_framestep_state<T: StateData>(&mut self, framestep_name: impl FramestepLabel, init: T) -> &mut Schedule;
+        /// Add an enter system for a state driven by a fixed framestep (see `add_fixed_framestep_state`)
+        #[cfg(feature = "states")]
+        fn add_fixed_framestep_enter_system<T: StateData, Params>(&mut self, framestep_name: impl FramestepLabel, state: T, system: impl IntoSystemDescriptor<Params>) -> &mut Schedule;
+        /// Add an exit system for a state driven by a fixed framestep (see `add_fixed_framestep_state`)
+        #[cfg(feature = "states")]
+        fn add_fixed_framestep_exit_system<T: StateData, Params>(&mut self, framestep_name: impl FramestepLabel, state: T, system: impl IntoSystemDescriptor<Params>) -> &mut Schedule;
+        /// Get access to the [`FixedFramestepStage`] for the fixed framestep with a given name string
+        fn get_fixed_framestep_stage(&self, framestep_name: impl FramestepLabel) -> &FixedFramestepStage;
+        /// Get mut access to the [`FixedFramestepStage`] for the fixed framestep with a given name string
+        fn get_fixed_framestep_stage_mut(&mut self, framestep_name: impl FramestepLabel) -> &mut FixedFramestepStage;
+        /// Get access to the i-th child sub-stage of the fixed framestep with the given name string
+        fn get_fixed_framestep_child_substage<S: Stage>(&self, framestep_name: impl FramestepLabel, substage_i: usize) -> &S;
+        /// Get mut access to the i-th child sub-stage of the fixed framestep with the given name string
+        fn get_fixed_framestep_child_substage_mut<S: Stage>(&mut self, framestep_name: impl FramestepLabel, substage_i: usize) -> &mut S;
+    }
+
+    impl ScheduleLooplessFixedFramestepExt for Schedule {
+        fn add_fixed_framestep_at(&mut self, position: FramestepPosition, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut Schedule {
+            let label = label.framestep_name();
+            insert_framestep(self, position, new_framestep(framestep.into(), label, SchedulePosition::Unknown));
+            self
+        }
+
+        fn add_fixed_framestep_before_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut Schedule {
+            self.add_fixed_framestep_at(FramestepPosition::before(stage), framestep, label)
+        }
+
+        fn add_fixed_framestep_after_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut Schedule {
+            self.add_fixed_framestep_at(FramestepPosition::after(stage), framestep, label)
+        }
+
+        fn move_fixed_framestep(&mut self, framestep_name: impl FramestepLabel, position: FramestepPosition) -> &mut Schedule {
+            move_framestep(self, framestep_name.framestep_name(), position);
+            self
+        }
+
+        fn add_fixed_framestep_child_stage(&mut self, framestep_name: impl FramestepLabel) -> &mut Schedule {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(self, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            stage.add_stage(new_substage());
+            self
+        }
+
+        fn add_fixed_framestep_custom_child_stage(&mut self, framestep_name: impl FramestepLabel, custom_stage: impl Stage) -> &mut Schedule {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(self, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            stage.add_stage(custom_stage);
+            self
+        }
+
+        fn add_fixed_framestep_command_barrier(&mut self, framestep_name: impl FramestepLabel) -> &mut Schedule {
+            self.add_fixed_framestep_custom_child_stage(framestep_name, CommandBarrier)
+        }
+
+        fn add_fixed_framestep_nested(&mut self, outer: impl FramestepLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut Schedule {
+            let label = label.framestep_name();
+            let outer = outer.framestep_name();
+            self.add_fixed_framestep_custom_child_stage(
+                outer,
+                new_framestep(framestep.into(), label, SchedulePosition::Nested(outer))
+            )
+        }
+
+        fn add_instanced_fixed_framestep_before_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut Schedule {
+            let label = label.framestep_name();
+            let position = SchedulePosition::Before(stage.as_str());
+            self.add_stage_before(
+                stage,
+                FixedFramestepStageLabel(label),
+                InstancedFramestepStage::new(new_framestep(framestep.into(), label, position))
+            );
+            add_anchors(self, label);
+            self
+        }
+
+        fn add_instanced_fixed_framestep_after_stage(&mut self, stage: impl StageLabel, framestep: impl Into<Frames>, label: impl FramestepLabel) -> &mut Schedule {
+            let label = label.framestep_name();
+            let position = SchedulePosition::After(stage.as_str());
+            self.add_stage_after(
+                stage,
+                FixedFramestepStageLabel(label),
+                InstancedFramestepStage::new(new_framestep(framestep.into(), label, position))
+            );
+            add_anchors(self, label);
+            self
+        }
+
+        fn add_dynamic_fixed_framesteps_before_stage(&mut self, stage: impl StageLabel) -> &mut Schedule {
+            let host = DynamicFramestepsStage {
+                stages: Vec::new(),
+                position: SchedulePosition::Before(stage.as_str()),
+            };
+            self.add_stage_before(stage, DynamicFramestepsStageLabel, host)
+        }
+
+        fn add_dynamic_fixed_framesteps_after_stage(&mut self, stage: impl StageLabel) -> &mut Schedule {
+            let host = DynamicFramestepsStage {
+                stages: Vec::new(),
+                position: SchedulePosition::After(stage.as_str()),
+            };
+            self.add_stage_after(stage, DynamicFramestepsStageLabel, host)
+        }
+
+        fn add_fixed_framestep_system<Params>(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, system: impl IntoSystemDescriptor<Params>) -> &mut Schedule {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(self, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            let substage = stage.stages.get_mut(substage_i)
+                .expect("Fixed Framestep sub-stage not found")
+                .downcast_mut::<SystemStage>()
+                .expect("Fixed Framestep sub-stage is not a SystemStage");
+            substage.add_system(system);
+            self
+        }
+
+        fn add_fixed_framestep_system_set(&mut self, framestep_name: impl FramestepLabel, substage_i: usize, system_set: SystemSet) -> &mut Schedule {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(self, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            let substage = stage.stages.get_mut(substage_i)
+                .expect("Fixed Framestep sub-stage not found")
+                .downcast_mut::<SystemStage>()
+                .expect("Fixed Framestep sub-stage is not a SystemStage");
+            substage.add_system_set(system_set);
+            self
+        }
+
+        fn add_fixed_framestep_group(&mut self, framestep_name: impl FramestepLabel, group: &'static str, system_set: SystemSet) -> &mut Schedule {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(self, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            stage.add_group(group, system_set);
+            self
+        }
+
+        fn replace_fixed_framestep_group(&mut self, framestep_name: impl FramestepLabel, group: &str, system_set: SystemSet) -> &mut Schedule {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(self, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            if !stage.replace_group(group, system_set) {
+                panic!("Fixed Framestep system group {:?} not found", group);
+            }
+            self
+        }
+
+        fn add_fixed_framestep_cleanup_system<Params>(&mut self, framestep_name: impl FramestepLabel, system: impl IntoSystemDescriptor<Params>) -> &mut Schedule {
+            let framestep_name = framestep_name.framestep_name();
+            let stage = find_framestep_mut(self, framestep_name)
+                .expect("Fixed Framestep Stage not found");
+            stage.add_cleanup_substage();
+            stage.cleanup_substage_mut().unwrap().add_system(system);
+            self
+        }
+
+        #[cfg(feature = "states")]
+        fn add_fixed_framestep_state<T: StateData>(&mut self, framestep_name: impl FramestepLabel, init: T) -> &mut Schedule {
+            let framestep_name = framestep_name.framestep_name();
+            find_framestep_mut(self, framestep_name)
+                .expect("Fixed Framestep Stage not found")
+                .add_state_transitions(StateTransitionStage::new(init));
+            self
+        }
+
+        #[cfg(feature = "states")]
+        fn add_fixed_framestep_enter_system<T: StateData, Params>(&mut self, framestep_name: impl FramestepLabel, state: T, system: impl IntoSystemDescriptor<Params>) -> &mut Schedule {
+            let framestep_name = framestep_name.framestep_name();
+            find_framestep_mut(self, framestep_name)
+                .expect("Fixed Framestep Stage not found")
+                .state_transitions_mut::<T>()
+                .expect("Fixed Framestep does not drive this state type")
+                .add_enter_system(state, system);
+            self
+        }
+
+        #[cfg(feature = "states")]
+        fn add_fixed_framestep_exit_system<T: StateData, Params>(&mut self, framestep_name: impl FramestepLabel, state: T, system: impl IntoSystemDescriptor<Params>) -> &mut Schedule {
+            let framestep_name = framestep_name.framestep_name();
+            find_framestep_mut(self, framestep_name)
+                .expect("Fixed Framestep Stage not found")
+                .state_transitions_mut::<T>()
+                .expect("Fixed Framestep does not drive this state type")
+                .add_exit_system(state, system);
+            self
+        }
+
+        fn get_fixed_framestep_stage(&self, framestep_name: impl FramestepLabel) -> &FixedFramestepStage {
+            let framestep_name = framestep_name.framestep_name();
+            find_framestep(self, framestep_name)
+                .expect("Fixed Framestep Stage not found")
+        }
+
+        fn get_fixed_framestep_stage_mut(&mut self, framestep_name: impl FramestepLabel) -> &mut FixedFramestepStage {
+            let framestep_name = framestep_name.framestep_name();
+            find_framestep_mut(self, framestep_name)
+                .expect("Fixed Framestep Stage not found")
+        }
+
+        fn get_fixed_framestep_child_substage<S: Stage>(&self, framestep_name: impl FramestepLabel, substage_i: usize) -> &S {
+            let stage = self.get_fixed_framestep_stage(framestep_name);
+            stage.stages.get(substage_i)
+                .expect("Fixed Framestep sub-stage not found")
+                .downcast_ref::<S>()
+                .expect("Fixed Framestep sub-stage is not the requested type")
+        }
+
+        fn get_fixed_framestep_child_substage_mut<S: Stage>(&mut self, framestep_name: impl FramestepLabel, substage_i: usize) -> &mut S {
+            let stage = self.get_fixed_framestep_stage_mut(framestep_name);
+            stage.stages.get_mut(substage_i)
+                .expect("Fixed Framestep sub-stage not found")
+                .downcast_mut::<S>()
+                .expect("Fixed Framestep sub-stage is not the requested type")
+        }
+    }
+}
+
+#[cfg(all(test, feature = "app"))]
+mod tests {
+    use bevy_app::App;
+    use bevy_ecs::prelude::*;
+
+    use super::app::AppLooplessFixedFramestepExt;
+    use super::{FixedFramestepAnchorLabel, FixedFramestepStage, FixedFramesteps, FramestepId, FramestepName};
+
+    #[derive(Default, Resource)]
+    struct Order(Vec<&'static str>);
+
+    #[derive(Default, Resource)]
+    struct Running(Vec<(&'static str, Option<FramestepName>, Vec<FramestepName>)>);
+
+    /// A system that records `current_label()` and `running()`
+    fn record(name: &'static str) -> impl FnMut(ResMut<Running>, Res<FixedFramesteps>) {
+        move |mut log: ResMut<Running>, framesteps: Res<FixedFramesteps>| {
+            let running = framesteps.running().iter().map(FramestepId::name).collect();
+            log.0.push((name, framesteps.current_label(), running));
+        }
+    }
+
+    /// A child stage that runs one tick of another fixed framestep on every tick
+    struct RunOnce(FixedFramestepStage);
+
+    impl Stage for RunOnce {
+        fn run(&mut self, world: &mut World) {
+            self.0.run_once(world);
+        }
+    }
+
+    #[test]
+    fn anchor_stages_run_around_the_fixed_framestep() {
+        let mut app = App::default();
+        app.init_resource::<Order>();
+        app.add_fixed_framestep(1, "sim");
+        app.add_fixed_framestep_child_stage("sim");
+        app.add_fixed_framestep_system("sim", 0, |mut order: ResMut<Order>| order.0.push("sim 0"));
+        app.add_fixed_framestep_system("sim", 1, |mut order: ResMut<Order>| order.0.push("sim 1"));
+        app.add_system_to_stage(FixedFramestepAnchorLabel::Begin("sim"), |mut order: ResMut<Order>| order.0.push("begin"));
+        app.add_system_to_stage(FixedFramestepAnchorLabel::End("sim"), |mut order: ResMut<Order>| order.0.push("end"));
+        app.update();
+        assert_eq!(app.world.resource::<Order>().0, ["begin", "sim 0", "sim 1", "end"]);
+    }
+    #[test]
+    fn nested_framestep_restores_the_outer_one() {
+        let mut app = App::default();
+        app.init_resource::<Running>();
+        app.add_fixed_framestep(1, "outer");
+        app.add_fixed_framestep_system("outer", 0, record("outer before"));
+        app.add_fixed_framestep_nested("outer", 1, "inner");
+        app.add_fixed_framestep_system("inner", 0, record("inner"));
+        app.add_fixed_framestep_child_stage("outer");
+        app.add_fixed_framestep_system("outer", 2, record("outer after"));
+        app.add_system(record("update"));
+        app.update();
+        assert_eq!(app.world.resource::<Running>().0, [
+            ("outer before", Some("outer"), vec!["outer"]),
+            ("inner", Some("inner"), vec!["outer", "inner"]),
+            ("outer after", Some("outer"), vec!["outer"]),
+            ("update", None, vec![]),
+        ]);
+        assert!(app.world.resource::<FixedFramesteps>().running().is_empty());
+    }
+
+    #[test]
+    fn run_once_from_a_child_stage() {
+        let mut app = App::default();
+        app.init_resource::<Running>();
+        app.add_fixed_framestep(1, "outer");
+        app.add_fixed_framestep_system("outer", 0, record("outer before"));
+        let mut driven = FixedFramestepStage::new(100, "driven");
+        driven.add_stage(SystemStage::single_threaded().with_system(record("driven")));
+        app.add_fixed_framestep_custom_child_stage("outer", RunOnce(driven));
+        app.add_fixed_framestep_child_stage("outer");
+        app.add_fixed_framestep_system("outer", 2, record("outer after"));
+        app.update();
+        app.update();
+        let expected = [
+            ("outer before", Some("outer"), vec!["outer"]),
+            ("driven", Some("driven"), vec!["outer", "driven"]),
+            ("outer after", Some("outer"), vec!["outer"]),
+        ];
+        assert_eq!(app.world.resource::<Running>().0, [expected.clone(), expected].concat());
+        let framesteps = app.world.resource::<FixedFramesteps>();
+        assert!(framesteps.running().is_empty());
+        // `run_once` does not touch the accumulator, so only the forced ticks ran
+        assert_eq!(framesteps["driven"].tick(), 2);
+    }
+}
@@ -0,0 +1,92 @@
+//! Sprite sheet animations driven by fixed framestep ticks
+//!
+//! A [`TickAnimation`] component advances the `index` of the `TextureAtlasSprite` on the
+//! same entity every K ticks. Add the [`animate_sprites`] system to the fixed framestep that
+//! should drive the animations. Because the animations follow the ticks, they match the
+//! simulation rate exactly, and stop when the fixed framestep is paused.
+//!
+//! Requires the `"framestep-animation"` cargo feature.
+
+use bevy_ecs::prelude::*;
+use bevy_sprite::TextureAtlasSprite;
+
+/// Component for animating a sprite sheet (see the [module docs](self))
+#[derive(Debug, Clone)]
+#[derive(Component)]
+pub struct TickAnimation {
+    /// The index of the first frame in the texture atlas
+    pub first: usize,
+    /// The index of the last frame in the texture atlas
+    pub last: usize,
+    /// How many ticks to show each frame for
+    pub ticks_per_frame: u32,
+    /// Start over from the first frame after the last one? Otherwise, stay on the last frame.
+    pub looping: bool,
+    ticks: u32,
+}
+
+impl TickAnimation {
+    /// Create a looping animation over the frames `first..=last`, showing each for `ticks_per_frame` ticks
+    pub fn new(first: usize, last: usize, ticks_per_frame: u32) -> Self {
+        assert!(first <= last);
+        Self {
+            first,
+            last,
+            ticks_per_frame: ticks_per_frame.max(1),
+            looping: true,
+            ticks: 0,
+        }
+    }
+
+    /// Builder method for an animation that plays only once
+    pub fn once(mut self) -> Self {
+        self.looping = false;
+        self
+    }
+
+    /// Start over from the first frame
+    pub fn restart(&mut self) {
+        self.ticks = 0;
+    }
+
+    /// The number of frames in the animation
+    pub fn frame_count(&self) -> usize {
+        self.last - self.first + 1
+    }
+
+    /// The atlas index of the frame that should currently be shown
+    pub fn current_index(&self) -> usize {
+        let frame = (self.ticks / self.ticks_per_frame.max(1)) as usize;
+        if self.looping {
+            self.first + frame % self.frame_count()
+        } else {
+            self.first + frame.min(self.frame_count() - 1)
+        }
+    }
+
+    /// Has a non-looping animation reached its last frame?
+    pub fn is_finished(&self) -> bool {
+        !self.looping && self.ticks / self.ticks_per_frame.max(1) >= self.frame_count() as u32 - 1
+    }
+}
+
+/// System that advances all [`TickAnimation`]s by one tick
+///
+/// Add it to the fixed framestep that the animations should follow.
+pub fn animate_sprites(
+    mut q: Query<(&mut TickAnimation, &mut TextureAtlasSprite)>,
+) {
+    for (mut anim, mut sprite) in q.iter_mut() {
+        if !anim.is_finished() {
+            anim.ticks += 1;
+            // wrap around, so the counter does not overflow on long-running animations
+            if anim.looping && anim.ticks >= anim.ticks_per_frame.max(1) * anim.frame_count() as u32 {
+                anim.ticks = 0;
+            }
+        }
+        let index = anim.current_index();
+        if sprite.index != index {
+            sprite.index = index;
+        }
+    }
+}
@@ -0,0 +1,210 @@
+//! Saving the game automatically, on tick boundaries
+//!
+//! Add the [`AutosavePlugin`] for a fixed framestep, to save the world using the
+//! [`save`](super::save) module every so many ticks (see
+//! [`with_interval`](AutosavePlugin::with_interval)), and/or after specific ticks (see
+//! [`with_milestone`](AutosavePlugin::with_milestone)).
+//!
+//! The save happens at the end of the tick, after all of its sub-stages have run, so it
+//! holds exactly the state between that tick and the next one: loading it resumes with
+//! the next tick, even if the save happened in the middle of a batch of catch-up ticks.
+//! Use the outermost fixed framestep: the ticks of an outer fixed framestep are not at a
+//! boundary while a nested one runs.
+//!
+//! The saves rotate through a number of slots (see [`with_slots`](AutosavePlugin::with_slots)),
+//! kept in the [`Autosaves`] resource, and optionally written to files (see
+//! [`with_directory`](AutosavePlugin::with_directory)). An [`AutosaveCompleted`] event is
+//! sent after every save. Errors are logged, and the slot is not used.
+//!
+//! Requires the `"framestep-save"` cargo feature.
+
+use std::path::PathBuf;
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_utils::tracing::warn;
+
+use super::{FramestepLabel, FramestepName, find_framestep_mut};
+use super::save::save_world;
+
+/// The default number of slots of the [`AutosavePlugin`]
+pub const DEFAULT_AUTOSAVE_SLOTS: usize = 3;
+
+/// An autosave, in a slot of the [`Autosaves`] resource
+#[derive(Debug, Clone)]
+pub struct Autosave {
+    /// The tick the game was saved after
+    pub tick: u64,
+    /// The data written by [`save_world`]; restore it with [`load_world`](super::save::load_world)
+    pub data: Vec<u8>,
+}
+
+/// Resource with the autosave slots (see the [module docs](self))
+#[derive(Debug)]
+#[derive(Resource)]
+pub struct Autosaves {
+    framestep: FramestepName,
+    slots: Vec<Option<Autosave>>,
+    // the slot to write next
+    next: usize,
+    directory: Option<PathBuf>,
+}
+
+impl Autosaves {
+    /// The fixed framestep whose ticks trigger the autosaves
+    pub fn framestep(&self) -> FramestepName {
+        self.framestep
+    }
+
+    /// The number of slots
+    pub fn slots(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// The autosave in the given slot, if any
+    pub fn get(&self, slot: usize) -> Option<&Autosave> {
+        self.slots.get(slot)?.as_ref()
+    }
+
+    /// The newest autosave, if any
+    pub fn latest(&self) -> Option<&Autosave> {
+        self.slots.iter()
+            .flatten()
+            .max_by_key(|autosave| autosave.tick)
+    }
+
+    /// The file that the given slot is written to, if writing to a directory
+    pub fn path(&self, slot: usize) -> Option<PathBuf> {
+        self.directory.as_ref().map(|directory| directory.join(format!("autosave-{}.ron", slot)))
+    }
+
+    /// Empty all the slots (the files are left alone)
+    pub fn clear(&mut self) {
+        for slot in self.slots.iter_mut() {
+            *slot = None;
+        }
+        self.next = 0;
+    }
+}
+
+/// Event sent after every autosave
+///
+/// Sent by the [`AutosavePlugin`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AutosaveCompleted {
+    /// The name of the fixed framestep
+    pub label: FramestepName,
+    /// The tick the game was saved after
+    pub tick: u64,
+    /// The slot of the [`Autosaves`] resource it was saved into
+    pub slot: usize,
+    /// The file it was written to, if writing to a directory
+    pub path: Option<PathBuf>,
+}
+
+/// Plugin for saving the game automatically, on the ticks of a fixed framestep
+///
+/// See the [module docs](self). The fixed framestep must already be added to the app.
+pub struct AutosavePlugin {
+    framestep: FramestepName,
+    interval: u64,
+    milestones: Vec<u64>,
+    slots: usize,
+    directory: Option<PathBuf>,
+}
+
+impl AutosavePlugin {
+    /// Create the plugin for the given fixed framestep
+    ///
+    /// It does not save anything, until you set an interval or some milestones.
+    pub fn new(framestep: impl FramestepLabel) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            interval: 0,
+            milestones: Vec::new(),
+            slots: DEFAULT_AUTOSAVE_SLOTS,
+            directory: None,
+        }
+    }
+
+    /// Builder method for saving after every tick that is a multiple of `ticks` (`0` to disable)
+    pub fn with_interval(mut self, ticks: u64) -> Self {
+        self.interval = ticks;
+        self
+    }
+
+    /// Builder method for saving after the given tick
+    pub fn with_milestone(mut self, tick: u64) -> Self {
+        self.milestones.push(tick);
+        self
+    }
+
+    /// Builder method for setting the number of slots to rotate through
+    ///
+    /// Panics if `slots` is `0`.
+    pub fn with_slots(mut self, slots: usize) -> Self {
+        assert!(slots > 0, "Autosaves need at least one slot.");
+        self.slots = slots;
+        self
+    }
+
+    /// Builder method for also writing every autosave to a file in the given directory
+    ///
+    /// The files are named `autosave-<slot>.ron`. The directory must exist.
+    pub fn with_directory(mut self, directory: impl Into<PathBuf>) -> Self {
+        self.directory = Some(directory.into());
+        self
+    }
+}
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Autosaves {
+            framestep: self.framestep,
+            slots: vec![None; self.slots],
+            next: 0,
+            directory: self.directory.clone(),
+        });
+        app.add_event::<AutosaveCompleted>();
+        let interval = self.interval;
+        let milestones = self.milestones.clone();
+        let stage = find_framestep_mut(&mut app.schedule, self.framestep)
+            .expect("Fixed Framestep Stage not found");
+        stage.on_tick_end(move |world, tick| {
+            let due = (interval > 0 && tick % interval == 0) || milestones.contains(&tick);
+            if due {
+                autosave(world, tick);
+            }
+        });
+    }
+}
+
+fn autosave(world: &mut World, tick: u64) {
+    let data = match save_world(world) {
+        Ok(data) => data,
+        Err(e) => {
+            warn!("Autosave after tick {} failed: {}", tick, e);
+            return;
+        }
+    };
+    let mut autosaves = world.resource_mut::<Autosaves>();
+    let slot = autosaves.next;
+    let path = autosaves.path(slot);
+    if let Some(path) = &path {
+        if let Err(e) = std::fs::write(path, &data) {
+            warn!("Autosave after tick {} could not be written to {:?}: {}", tick, path, e);
+            return;
+        }
+    }
+    autosaves.slots[slot] = Some(Autosave { tick, data });
+    autosaves.next = (slot + 1) % autosaves.slots.len();
+    let label = autosaves.framestep;
+    if let Some(mut events) = world.get_resource_mut::<Events<AutosaveCompleted>>() {
+        events.send(AutosaveCompleted {
+            label,
+            tick,
+            slot,
+            path,
+        });
+    }
+}
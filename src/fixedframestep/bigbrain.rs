@@ -0,0 +1,115 @@
+//! Running [big-brain](https://docs.rs/big-brain) AI on a coarse fixed framestep
+//!
+//! Utility AI is too expensive to run on every tick of the simulation, and does not need
+//! to: deciding what to do a few times per second is plenty. The [`BigBrainFramestepPlugin`]
+//! adds a [nested fixed framestep](super::app::AppLooplessFixedFramestepExt::add_fixed_framestep_nested),
+//! which runs every few ticks of your simulation framestep, and installs the big-brain
+//! systems in its sub-stages, in the same order as big-brain's own stages:
+//!
+//! | Sub-stage             | big-brain stage | Systems                                   |
+//! |-----------------------|-----------------|-------------------------------------------|
+//! | [`SCORERS_SUBSTAGE`]  | `Scorers`       | the built-in scorers, and yours           |
+//! | [`THINKERS_SUBSTAGE`] | `Thinkers`      | `thinker_system`                          |
+//! | [`ACTIONS_SUBSTAGE`]  | `Actions`       | the built-in composite actions, and yours |
+//! | [`CLEANUP_SUBSTAGE`]  | `Cleanup`       | attaching and detaching thinkers          |
+//!
+//! Every sub-stage applies its `Commands` before the next one runs, so the scorers and
+//! actions spawned by a thinker exist on the next AI tick, like with big-brain's stages.
+//! Add your own scorer and action systems to the matching sub-stages:
+//!
+//! ```ignore
+//! use iyes_loopless::fixedframestep::bigbrain::{BigBrainFramestepPlugin, ACTIONS_SUBSTAGE, SCORERS_SUBSTAGE};
+//!
+//! app.add_fixed_framestep(1, "sim")
+//!     // think every 10 ticks of "sim"
+//!     .add_plugin(BigBrainFramestepPlugin::new("sim", "ai").with_ticks_per_think(10))
+//!     .add_fixed_framestep_system("ai", SCORERS_SUBSTAGE, thirsty_scorer_system)
+//!     .add_fixed_framestep_system("ai", ACTIONS_SUBSTAGE, drink_action_system);
+//! ```
+//!
+//! Do not add big-brain's `BigBrainPlugin` as well: it would run the same systems again,
+//! on every frame. Requires the `"framestep-big-brain"` cargo feature.
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use big_brain::{actions, scorers, thinker};
+
+use super::app::AppLooplessFixedFramestepExt;
+use super::{FramestepLabel, FramestepName};
+
+/// The sub-stage of the AI fixed framestep with the scorer systems
+pub const SCORERS_SUBSTAGE: usize = 0;
+/// The sub-stage of the AI fixed framestep with the thinker system
+pub const THINKERS_SUBSTAGE: usize = 1;
+/// The sub-stage of the AI fixed framestep with the action systems
+pub const ACTIONS_SUBSTAGE: usize = 2;
+/// The sub-stage of the AI fixed framestep with the systems that attach and clean up thinkers
+pub const CLEANUP_SUBSTAGE: usize = 3;
+
+/// Plugin for running big-brain on a fixed framestep nested in another one (see the [module docs](self))
+///
+/// The outer fixed framestep must already be added to the app.
+#[derive(Debug, Clone, Copy)]
+pub struct BigBrainFramestepPlugin {
+    outer: FramestepName,
+    label: FramestepName,
+    ticks_per_think: u64,
+}
+
+impl BigBrainFramestepPlugin {
+    /// Create the plugin, for a new fixed framestep named `label`, nested in `outer`
+    ///
+    /// The AI runs every `10` ticks of `outer`, by default.
+    pub fn new(outer: impl FramestepLabel, label: impl FramestepLabel) -> Self {
+        Self {
+            outer: outer.framestep_name(),
+            label: label.framestep_name(),
+            ticks_per_think: 10,
+        }
+    }
+
+    /// Builder method for running the AI every `ticks` ticks of the outer fixed framestep
+    pub fn with_ticks_per_think(mut self, ticks: u64) -> Self {
+        self.ticks_per_think = ticks;
+        self
+    }
+}
+
+impl Plugin for BigBrainFramestepPlugin {
+    fn build(&self, app: &mut App) {
+        let label = self.label;
+        // the nested fixed framestep comes with its first sub-stage
+        app.add_fixed_framestep_nested(self.outer, self.ticks_per_think, label)
+            .add_fixed_framestep_child_stage(label)
+            .add_fixed_framestep_child_stage(label)
+            .add_fixed_framestep_child_stage(label);
+        app.add_fixed_framestep_system_set(
+            label,
+            SCORERS_SUBSTAGE,
+            SystemSet::new()
+                .with_system(scorers::fixed_score_system)
+                .with_system(scorers::measured_scorers_system)
+                .with_system(scorers::all_or_nothing_system)
+                .with_system(scorers::sum_of_scorers_system)
+                .with_system(scorers::product_of_scorers_system)
+                .with_system(scorers::winning_scorer_system)
+                .with_system(scorers::evaluating_scorer_system),
+        );
+        app.add_fixed_framestep_system(label, THINKERS_SUBSTAGE, thinker::thinker_system);
+        app.add_fixed_framestep_system_set(
+            label,
+            ACTIONS_SUBSTAGE,
+            SystemSet::new()
+                .with_system(actions::steps_system)
+                .with_system(actions::concurrent_system),
+        );
+        app.add_fixed_framestep_system_set(
+            label,
+            CLEANUP_SUBSTAGE,
+            SystemSet::new()
+                .with_system(thinker::thinker_component_attach_system)
+                .with_system(thinker::thinker_component_detach_system)
+                .with_system(thinker::actor_gone_cleanup),
+        );
+    }
+}
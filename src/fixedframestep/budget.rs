@@ -0,0 +1,165 @@
+//! Sharing a time budget for each frame between several fixed framesteps, by priority
+//!
+//! When the ticks get slow (say, during a load spike), the fixed framesteps that run
+//! first in the schedule can use up the whole frame, even if the ones after them matter
+//! more: the AI ticks can steal the time of the physics ticks. Insert a [`FrameBudget`]
+//! resource, with the CPU time that the ticks of all the fixed framesteps may take on a
+//! frame, and the fixed framesteps in order of priority.
+//!
+//! Before every catch-up tick, a fixed framestep checks that the tick fits in what is
+//! left of the budget, after setting aside time for the fixed framesteps with a higher
+//! priority that have not run yet on this frame (as many ticks as they were due on
+//! their last frame, as long as their last tick took). If it does not fit, the tick is
+//! deferred: it stays due (in the accumulator), and runs on a later frame. The fixed
+//! framestep is then [suppressed](super::FixedFramestepInfo::suppressed) with
+//! [`Suppression::Deferred`](super::Suppression::Deferred), and counts the deferred ticks
+//! in [`deferred_ticks`](super::FixedFramestepInfo::deferred_ticks).
+//!
+//! Fixed framesteps that are not in the list have the lowest priority. So that the
+//! simulation always makes progress, a tick is never deferred before any time has been
+//! spent on the frame, unless a fixed framestep with a higher priority is still to run.
+//! Nested fixed framesteps are never deferred: their ticks count as part of the ticks of
+//! the outer fixed framestep. Ticks queued using
+//! [`queue_ticks`](super::FixedFramestepInfo::queue_ticks) always run.
+//!
+//! A new frame starts when a fixed framestep that already ran on this frame runs again,
+//! so every fixed framestep must only run once per frame.
+
+use bevy_ecs::prelude::*;
+use bevy_utils::Duration;
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName};
+
+/// Resource with the time budget for the ticks of all the fixed framesteps on each frame
+///
+/// See the [module docs](self).
+#[derive(Debug, Clone)]
+#[derive(Resource)]
+pub struct FrameBudget {
+    budget: Duration,
+    // highest priority first
+    priorities: Vec<FramestepName>,
+    spent: Duration,
+    // the fixed framesteps that have had their turn on this frame
+    done: Vec<FramestepName>,
+}
+
+impl FrameBudget {
+    /// Create a budget of the given CPU time per frame, with no priorities
+    pub fn new(budget: Duration) -> Self {
+        Self {
+            budget,
+            priorities: Vec::new(),
+            spent: Duration::ZERO,
+            done: Vec::new(),
+        }
+    }
+
+    /// Builder method for giving the next lower priority to the given fixed framestep
+    ///
+    /// Add the most important fixed framestep first.
+    pub fn with_priority(mut self, framestep: impl FramestepLabel) -> Self {
+        let label = framestep.framestep_name();
+        self.priorities.retain(|l| *l != label);
+        self.priorities.push(label);
+        self
+    }
+
+    /// Replace the priorities, with the most important fixed framestep first
+    pub fn set_priorities<L: FramestepLabel>(&mut self, framesteps: impl IntoIterator<Item = L>) {
+        self.priorities.clear();
+        for framestep in framesteps {
+            let label = framestep.framestep_name();
+            if !self.priorities.contains(&label) {
+                self.priorities.push(label);
+            }
+        }
+    }
+
+    /// The fixed framesteps, with the most important one first
+    pub fn priorities(&self) -> &[FramestepName] {
+        &self.priorities
+    }
+
+    /// The priority of the given fixed framestep: `0` is the highest, `None` is below all of them
+    pub fn priority(&self, framestep: impl FramestepLabel) -> Option<usize> {
+        let label = framestep.framestep_name();
+        self.priorities.iter().position(|l| *l == label)
+    }
+
+    /// The CPU time that the ticks may take on each frame
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Change the CPU time that the ticks may take on each frame
+    pub fn set_budget(&mut self, budget: Duration) {
+        self.budget = budget;
+    }
+
+    /// The CPU time the ticks have taken on this frame, so far
+    pub fn spent(&self) -> Duration {
+        self.spent
+    }
+
+    /// What is left of the budget on this frame
+    pub fn remaining(&self) -> Duration {
+        self.budget.saturating_sub(self.spent)
+    }
+
+    /// Does `a` have a higher priority than `b`?
+    fn outranks(&self, a: FramestepName, b: FramestepName) -> bool {
+        match (self.priority(a), self.priority(b)) {
+            (Some(a), Some(b)) => a < b,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+/// A fixed framestep (not nested in another one) starts running on a frame
+pub(super) fn begin_turn(world: &mut World, label: FramestepName) {
+    let mut budget = match world.get_resource_mut::<FrameBudget>() {
+        Some(budget) => budget,
+        None => return,
+    };
+    if budget.done.contains(&label) {
+        // it is the next frame
+        budget.done.clear();
+        budget.spent = Duration::ZERO;
+    }
+    budget.done.push(label);
+}
+
+/// Can another tick of the fixed framestep run on this frame?
+///
+/// `spent` is the time its ticks have taken on this frame, so far.
+pub(super) fn tick_fits(world: &World, label: FramestepName, spent: Duration) -> bool {
+    let budget = match world.get_resource::<FrameBudget>() {
+        Some(budget) => budget,
+        None => return true,
+    };
+    let framesteps = world.resource::<FixedFramesteps>();
+    let spent = budget.spent + spent;
+    // set aside time for the more important fixed framesteps that are still to run
+    let reserved: Duration = budget.priorities.iter()
+        .filter(|&&other| budget.outranks(other, label) && !budget.done.contains(&other))
+        .filter_map(|&other| framesteps.get(other))
+        .filter(|info| !info.is_paused())
+        .filter_map(|info| Some(info.last_tick_duration()? * info.steps_this_frame().max(1)))
+        .sum();
+    if spent.is_zero() && reserved.is_zero() {
+        return true;
+    }
+    let next = framesteps.get(label)
+        .and_then(|info| info.last_tick_duration())
+        .unwrap_or_default();
+    spent + reserved + next <= budget.budget
+}
+
+/// Count the time that the ticks of a fixed framestep (not nested in another one) took on this frame
+pub(super) fn spend(world: &mut World, duration: Duration) {
+    if let Some(mut budget) = world.get_resource_mut::<FrameBudget>() {
+        budget.spent += duration;
+    }
+}
@@ -0,0 +1,141 @@
+//! A trailing "cleanup" sub-stage, for despawns and other end-of-tick work
+//!
+//! Despawning entities in the middle of a tick is a common source of ordering bugs:
+//! systems in later sub-stages (or later in the same one) may still expect to see them.
+//! By convention, put such work in the *cleanup sub-stage* of the fixed framestep
+//! (see [`FixedFramestepStage::with_cleanup_substage`]): despawns, clearing per-tick
+//! buffers, draining events that were only meant for this tick, and the like. It always
+//! runs last in every tick, even if more sub-stages are added after it, and its
+//! `Commands` are applied before the tick ends.
+//!
+//! Mark entities with [`DespawnAtTickEnd`] to have them despawned by the cleanup
+//! sub-stage, at the end of the current tick, or with [`DespawnAfterTicks`] to have
+//! them despawned after a number of ticks of a given fixed framestep.
+
+use bevy_ecs::prelude::*;
+
+use super::{FixedFramestepStage, FixedFramesteps, FramestepLabel, FramestepName, make_strict, new_substage};
+
+/// Marker component for entities to despawn at the end of the current tick
+///
+/// Processed by the [cleanup sub-stage](self) of the fixed framesteps. If several fixed
+/// framesteps have one, the entity is despawned at the end of the first tick that ends.
+/// Fixed framesteps without a cleanup sub-stage ignore it.
+#[derive(Debug, Default, Clone, Copy)]
+#[derive(Component)]
+pub struct DespawnAtTickEnd;
+
+/// Component for entities to despawn after a number of ticks of a fixed framestep
+///
+/// At the end of every tick of `framestep`, its [cleanup sub-stage](self) decrements
+/// `remaining`, and despawns the entity when it gets to `0`. So, with `remaining: 1`,
+/// the entity is despawned at the end of the current (or next) tick, like with
+/// [`DespawnAtTickEnd`]. The fixed framestep must have a cleanup sub-stage.
+///
+/// Counting ticks (not time) makes this deterministic. All the state is in the
+/// component, so a rollback that restores the components also restores the countdown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Component)]
+pub struct DespawnAfterTicks {
+    /// The fixed framestep whose ticks are counted
+    pub framestep: FramestepName,
+    /// The number of ticks left (including the current one, if added during a tick)
+    pub remaining: u32,
+}
+
+impl DespawnAfterTicks {
+    /// Despawn after the given number of ticks of the given fixed framestep
+    pub fn new(framestep: impl FramestepLabel, ticks: u32) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            remaining: ticks,
+        }
+    }
+}
+
+/// The cleanup sub-stage of a fixed framestep (see the [module docs](self))
+pub struct CleanupStage {
+    pub(super) stage: SystemStage,
+}
+
+impl CleanupStage {
+    /// Get access to the `SystemStage` with the cleanup systems
+    pub fn stage(&self) -> &SystemStage {
+        &self.stage
+    }
+
+    /// Get mut access to the `SystemStage` with the cleanup systems
+    pub fn stage_mut(&mut self) -> &mut SystemStage {
+        &mut self.stage
+    }
+}
+
+impl Stage for CleanupStage {
+    fn run(&mut self, world: &mut World) {
+        self.stage.run(world);
+    }
+}
+
+fn despawn_at_tick_end(mut commands: Commands, query: Query<Entity, With<DespawnAtTickEnd>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn despawn_after_ticks(
+    mut commands: Commands,
+    framesteps: Res<FixedFramesteps>,
+    mut query: Query<(Entity, &mut DespawnAfterTicks)>,
+) {
+    let label = framesteps.current().label();
+    for (entity, mut countdown) in query.iter_mut() {
+        if countdown.framestep != label {
+            continue;
+        }
+        countdown.remaining = countdown.remaining.saturating_sub(1);
+        if countdown.remaining == 0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+impl FixedFramestepStage {
+    /// Add the cleanup sub-stage, which runs last on every tick (see the [`cleanup`](self) module)
+    ///
+    /// Any child stages added afterwards are inserted before it. Does nothing if the fixed
+    /// framestep already has a cleanup sub-stage.
+    pub fn add_cleanup_substage(&mut self) {
+        if self.cleanup_substage().is_some() {
+            return;
+        }
+        let stage = new_substage()
+            .with_system(despawn_at_tick_end)
+            .with_system(despawn_after_ticks);
+        let mut stage = CleanupStage { stage };
+        if self.strict_determinism {
+            make_strict(&mut stage);
+        }
+        self.stages.push(Box::new(stage));
+        self.layout_changed = true;
+    }
+
+    /// Builder method for adding the cleanup sub-stage
+    pub fn with_cleanup_substage(mut self) -> Self {
+        self.add_cleanup_substage();
+        self
+    }
+
+    /// Get access to the `SystemStage` of the cleanup sub-stage, if there is one
+    pub fn cleanup_substage(&self) -> Option<&SystemStage> {
+        self.stages.last()
+            .and_then(|stage| stage.downcast_ref::<CleanupStage>())
+            .map(CleanupStage::stage)
+    }
+
+    /// Get mut access to the `SystemStage` of the cleanup sub-stage, if there is one
+    pub fn cleanup_substage_mut(&mut self) -> Option<&mut SystemStage> {
+        self.stages.last_mut()
+            .and_then(|stage| stage.downcast_mut::<CleanupStage>())
+            .map(CleanupStage::stage_mut)
+    }
+}
@@ -0,0 +1,187 @@
+//! Commands deferred to a later fixed framestep tick
+//!
+//! Regular `Commands` are applied at the end of the sub-stage they were issued in.
+//! With [`TickCommands`], you can instead queue commands to be applied at the start
+//! of the *next* tick of a fixed framestep, or at the start of a specific tick number.
+//! Useful for deterministic simulations that need to schedule world mutations against
+//! future ticks.
+//!
+//! The queued commands are applied by the [`FixedFramestepStage`](super::FixedFramestepStage),
+//! in the order they were queued, before any of the systems of the tick run.
+//!
+//! # Ordering guarantees
+//!
+//! Within one tick of a fixed framestep:
+//!
+//! - The regular `Commands` of the systems in sub-stage N are applied before sub-stage N+1
+//!   starts, so all systems in sub-stage N+1 see their effects. For Bevy's `SystemStage`,
+//!   this happens at the end of the stage (after its parallel systems and its exclusive
+//!   `before_commands` systems, but before its exclusive `at_end` systems). Custom stage
+//!   types are responsible for applying their own systems' commands.
+//! - Changes to the [`FixedFramestepInfo`](super::FixedFramestepInfo) (pausing, stretching,
+//!   changing the step, etc.) are picked up after every sub-stage.
+//! - [`TickCommands`] are never applied in the middle of a tick; only at the start of
+//!   the tick they are due.
+//!
+//! Systems in the *same* sub-stage never see each other's `Commands`. To make an ordering
+//! point explicit, insert a [`CommandBarrier`] between two sub-stages.
+
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::{Command, SystemParam};
+use bevy_utils::HashMap;
+use bevy_utils::synccell::SyncCell;
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName};
+
+type BoxedCommand = SyncCell<Box<dyn FnOnce(&mut World) + Send>>;
+
+/// All the commands queued for one fixed framestep, by tick
+pub(super) type TickQueue = BTreeMap<u64, Vec<BoxedCommand>>;
+
+/// Resource that stores the commands queued using [`TickCommands`]
+///
+/// Inserted automatically by the fixed framestep stages. You should not
+/// need to use this directly.
+#[derive(Default)]
+#[derive(Resource)]
+pub struct TickCommandQueue {
+    queues: HashMap<FramestepName, TickQueue>,
+}
+
+impl TickCommandQueue {
+    /// Queue a command to be applied at the start of the given tick of the given fixed framestep
+    pub fn push(&mut self, label: FramestepName, tick: u64, command: impl Command) {
+        let command: Box<dyn FnOnce(&mut World) + Send> = Box::new(move |world: &mut World| command.write(world));
+        self.queues.entry(label)
+            .or_default()
+            .entry(tick)
+            .or_default()
+            .push(SyncCell::new(command));
+    }
+
+    /// The number of commands waiting for future ticks of the given fixed framestep
+    pub fn len(&self, label: FramestepName) -> usize {
+        self.queues.get(label)
+            .map(|queue| queue.values().map(Vec::len).sum())
+            .unwrap_or(0)
+    }
+
+    /// Are there no commands waiting for future ticks of the given fixed framestep?
+    pub fn is_empty(&self, label: FramestepName) -> bool {
+        self.len(label) == 0
+    }
+
+    /// Remove any commands waiting for future ticks of the given fixed framestep
+    pub fn clear(&mut self, label: FramestepName) {
+        self.queues.remove(label);
+    }
+
+    /// Take out all the commands queued for the given fixed framestep
+    pub(super) fn take_queue(&mut self, label: FramestepName) -> TickQueue {
+        self.queues.remove(label).unwrap_or_default()
+    }
+
+    /// Replace all the commands queued for the given fixed framestep
+    pub(super) fn put_queue(&mut self, label: FramestepName, queue: TickQueue) {
+        if queue.is_empty() {
+            self.queues.remove(label);
+        } else {
+            self.queues.insert(label, queue);
+        }
+    }
+
+    /// Remove all commands that are due at (or before) the given tick, in order
+    fn take_due(&mut self, label: FramestepName, tick: u64) -> Vec<BoxedCommand> {
+        let queue = match self.queues.get_mut(label) {
+            Some(queue) => queue,
+            None => return Vec::new(),
+        };
+        let later = queue.split_off(&(tick + 1));
+        let due = std::mem::replace(queue, later);
+        due.into_values().flatten().collect()
+    }
+}
+
+/// Pseudo sub-stage that marks the point where all earlier `Commands` have been applied
+///
+/// Add it as a child stage of a fixed framestep (using `add_fixed_framestep_command_barrier`,
+/// or [`FixedFramestepStage::add_stage`](super::FixedFramestepStage::add_stage)), between the
+/// sub-stage whose commands must be applied, and the sub-stage that must see their effects.
+/// See the [ordering guarantees](self#ordering-guarantees).
+///
+/// It does nothing when it runs: the guarantee comes from the sub-stage boundary itself.
+/// The barrier makes the dependency visible in the schedule, so that it is not lost if
+/// the sub-stages around it are merged later.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CommandBarrier;
+
+impl Stage for CommandBarrier {
+    fn run(&mut self, _world: &mut World) {}
+}
+
+/// Apply all queued commands that are due at the given tick of a fixed framestep
+pub(super) fn apply_due_commands(world: &mut World, label: FramestepName, tick: u64) {
+    let due = match world.get_resource_mut::<TickCommandQueue>() {
+        Some(mut queue) => queue.take_due(label, tick),
+        None => return,
+    };
+    for command in due {
+        (SyncCell::to_inner(command))(world);
+    }
+}
+
+/// System parameter for queueing commands to be applied on a later fixed framestep tick
+///
+/// The `add` and `at_tick` methods target the fixed framestep that is currently running,
+/// and panic if used outside of a fixed framestep (like [`FixedFramesteps::current`]).
+/// Use `add_to` and `at_tick_of` to target a fixed framestep by name.
+///
+/// If the requested tick has already started (or passed), the command is applied
+/// at the start of the next tick instead.
+#[derive(SystemParam)]
+pub struct TickCommands<'w, 's> {
+    framesteps: Res<'w, FixedFramesteps>,
+    queue: ResMut<'w, TickCommandQueue>,
+    #[system_param(ignore)]
+    marker: PhantomData<&'s ()>,
+}
+
+impl<'w, 's> TickCommands<'w, 's> {
+    /// Queue a command for the start of the next tick of the current fixed framestep
+    pub fn add(&mut self, command: impl Command) {
+        let label = self.current_label();
+        self.add_to(label, command);
+    }
+
+    /// Queue a command for the start of the given tick of the current fixed framestep
+    pub fn at_tick(&mut self, tick: u64, command: impl Command) {
+        let label = self.current_label();
+        self.at_tick_of(label, tick, command);
+    }
+
+    /// Queue a command for the start of the next tick of the given fixed framestep
+    pub fn add_to(&mut self, label: impl FramestepLabel, command: impl Command) {
+        let label = label.framestep_name();
+        let tick = self.next_tick(label);
+        self.queue.push(label, tick, command);
+    }
+
+    /// Queue a command for the start of the given tick of the given fixed framestep
+    pub fn at_tick_of(&mut self, label: impl FramestepLabel, tick: u64, command: impl Command) {
+        let label = label.framestep_name();
+        let tick = tick.max(self.next_tick(label));
+        self.queue.push(label, tick, command);
+    }
+
+    fn current_label(&self) -> FramestepName {
+        self.framesteps.current_label()
+            .expect("TickCommands::add/at_tick can only be used when running inside a fixed framestep.")
+    }
+
+    fn next_tick(&self, label: FramestepName) -> u64 {
+        self.framesteps.get(label).map(|info| info.tick()).unwrap_or(0) + 1
+    }
+}
@@ -0,0 +1,113 @@
+//! Typed configuration for each fixed framestep
+//!
+//! Tick tuning data (say, the gravity and the solver iterations of the physics tick)
+//! belongs with the tick itself. Give a fixed framestep a config value of your own type
+//! with [`FixedFramestepStage::with_config`](super::FixedFramestepStage::with_config) (or
+//! `add_fixed_framestep_config` on the `App`), and read it from the tick systems with the
+//! [`TickConfig`] system parameter, which finds the config of the fixed framestep that is
+//! running. Several fixed framesteps can have configs of the same type, with different
+//! values; they are all kept in the [`FramestepConfigs`] resource, where you can also
+//! change them.
+//!
+//! The configs given to the stage are inserted into the world on its first run, unless the
+//! world already has a config of that type for the fixed framestep (say, from a savegame).
+//! With the `"framestep-save"` cargo feature, register the config types with
+//! [`SaveRegistry::register_config`](super::save::SaveRegistry::register_config) to save
+//! them along with the state of the fixed framesteps.
+
+use std::ops::Deref;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+use bevy_utils::HashMap;
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName};
+
+/// Resource with the configs of type `T` of all the fixed framesteps (see the [module docs](self))
+#[derive(Resource)]
+pub struct FramestepConfigs<T> {
+    configs: HashMap<FramestepName, T>,
+}
+
+impl<T> Default for FramestepConfigs<T> {
+    fn default() -> Self {
+        Self {
+            configs: HashMap::default(),
+        }
+    }
+}
+
+impl<T> FramestepConfigs<T> {
+    /// The config of the fixed framestep with the given name
+    pub fn get(&self, label: impl FramestepLabel) -> Option<&T> {
+        self.configs.get(label.framestep_name())
+    }
+
+    /// The config of the fixed framestep with the given name, mutably
+    pub fn get_mut(&mut self, label: impl FramestepLabel) -> Option<&mut T> {
+        self.configs.get_mut(label.framestep_name())
+    }
+
+    /// Set the config of the fixed framestep with the given name, returning the old one
+    pub fn insert(&mut self, label: impl FramestepLabel, config: T) -> Option<T> {
+        self.configs.insert(label.framestep_name(), config)
+    }
+
+    /// Remove the config of the fixed framestep with the given name
+    pub fn remove(&mut self, label: impl FramestepLabel) -> Option<T> {
+        self.configs.remove(label.framestep_name())
+    }
+
+    /// Iterate over the names of the fixed framesteps and their configs, in arbitrary order
+    pub fn iter(&self) -> impl Iterator<Item = (FramestepName, &T)> {
+        self.configs.iter().map(|(label, config)| (*label, config))
+    }
+}
+
+/// System parameter for reading the config of type `T` of the fixed framestep that is running
+///
+/// Derefs to the config, and panics if there is none: outside of a fixed framestep, or if
+/// the running one has no config of this type. Use [`get`](Self::get) to handle that.
+#[derive(SystemParam)]
+pub struct TickConfig<'w, 's, T: Send + Sync + 'static> {
+    configs: Option<Res<'w, FramestepConfigs<T>>>,
+    framesteps: Option<Res<'w, FixedFramesteps>>,
+    #[system_param(ignore)]
+    _marker: std::marker::PhantomData<&'s ()>,
+}
+
+impl<'w, 's, T: Send + Sync + 'static> TickConfig<'w, 's, T> {
+    /// The config of the fixed framestep that is running, if any
+    pub fn get(&self) -> Option<&T> {
+        let label = self.framesteps.as_ref()?.current_label()?;
+        self.configs.as_ref()?.get(label)
+    }
+}
+
+impl<'w, 's, T: Send + Sync + 'static> Deref for TickConfig<'w, 's, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        match self.get() {
+            Some(config) => config,
+            None => panic!(
+                "No {} config for the running fixed framestep ({:?}).",
+                std::any::type_name::<T>(),
+                self.framesteps.as_ref().and_then(|framesteps| framesteps.current_label()),
+            ),
+        }
+    }
+}
+
+/// Inserts a config into the world, on the first run of the stage it was given to
+pub(super) type ConfigInit = Box<dyn FnOnce(&mut World, FramestepName) + Send + Sync>;
+
+/// Make the [`ConfigInit`] for the given config
+pub(super) fn config_init<T: Send + Sync + 'static>(config: T) -> ConfigInit {
+    Box::new(move |world: &mut World, label: FramestepName| {
+        let mut configs = world.get_resource_or_insert_with(FramestepConfigs::<T>::default);
+        if configs.get(label).is_none() {
+            configs.insert(label, config);
+        }
+    })
+}
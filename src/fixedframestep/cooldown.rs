@@ -0,0 +1,133 @@
+//! Cooldowns counted in fixed framestep ticks
+//!
+//! Add a [`TickCooldown`] component to your entities, and add the [`tick_cooldowns`]
+//! system to the fixed framestep that should drive them. Every tick, the system counts
+//! down all the cooldowns that are not ready yet.
+//!
+//! To find entities whose cooldown is ready, you can check [`TickCooldown::is_ready`],
+//! or use the [`Ready<TickCooldown>`](Ready) query filter. The filter relies on a
+//! marker component that the system adds/removes using `Commands`, so it only
+//! reflects the change after the end of the sub-stage the system runs in.
+
+use std::marker::PhantomData;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::component::SparseStorage;
+
+use super::Ticks;
+use super::migration::RateChanged;
+
+/// A cooldown that counts down one tick at a time (see the [module docs](self))
+#[derive(Debug, Clone, Default)]
+#[derive(Component)]
+pub struct TickCooldown {
+    /// The number of ticks that the cooldown lasts when (re)started
+    pub duration: u32,
+    remaining: u32,
+}
+
+impl TickCooldown {
+    /// Create a cooldown of `duration` ticks, starting out ready
+    pub fn new(duration: u32) -> Self {
+        Self {
+            duration,
+            remaining: 0,
+        }
+    }
+
+    /// Create a cooldown of `duration` ticks, starting out running (not ready)
+    pub fn started(duration: u32) -> Self {
+        Self {
+            duration,
+            remaining: duration,
+        }
+    }
+
+    /// (Re)start the cooldown, for its full duration
+    pub fn start(&mut self) {
+        self.remaining = self.duration;
+    }
+
+    /// (Re)start the cooldown, for a custom number of ticks
+    pub fn start_for(&mut self, ticks: u32) {
+        self.remaining = ticks;
+    }
+
+    /// Make the cooldown ready immediately
+    pub fn reset(&mut self) {
+        self.remaining = 0;
+    }
+
+    /// The number of ticks until the cooldown is ready
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+
+    /// Rescale the duration and the remaining ticks for a new step of the fixed framestep
+    ///
+    /// For use with [`migrate_components`](super::migration::migrate_components).
+    pub fn rescale(&mut self, change: &RateChanged) {
+        let rescale = |ticks: u32| change.rescale_ticks(Ticks(ticks as u64)).0.min(u32::MAX as u64) as u32;
+        self.duration = rescale(self.duration);
+        self.remaining = rescale(self.remaining);
+    }
+
+    /// Is the cooldown ready?
+    pub fn is_ready(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// If the cooldown is ready, restart it and return `true`
+    ///
+    /// Convenient for "fire if ready" gameplay logic.
+    pub fn try_use(&mut self) -> bool {
+        if self.is_ready() {
+            self.start();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Marker component for entities whose cooldown of type `C` is ready
+///
+/// Managed by [`tick_cooldowns`]; you should not need to add or remove it yourself.
+pub struct ReadyMarker<C>(PhantomData<C>);
+
+impl<C: Component> Component for ReadyMarker<C> {
+    // added and removed often
+    type Storage = SparseStorage;
+}
+
+impl<C> Default for ReadyMarker<C> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+/// Query filter for entities whose cooldown of type `C` is ready
+pub type Ready<C> = With<ReadyMarker<C>>;
+
+/// System that counts down all [`TickCooldown`]s by one tick
+///
+/// Add it to the fixed framestep that the cooldowns should follow.
+pub fn tick_cooldowns(
+    mut commands: Commands,
+    mut q: Query<(Entity, &mut TickCooldown, Option<&ReadyMarker<TickCooldown>>)>,
+) {
+    for (e, mut cooldown, marker) in q.iter_mut() {
+        if cooldown.remaining > 0 {
+            cooldown.remaining -= 1;
+        }
+        match (cooldown.is_ready(), marker.is_some()) {
+            (true, false) => {
+                commands.entity(e).insert(ReadyMarker::<TickCooldown>::default());
+            }
+            (false, true) => {
+                commands.entity(e).remove::<ReadyMarker<TickCooldown>>();
+            }
+            _ => {}
+        }
+    }
+}
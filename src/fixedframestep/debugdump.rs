@@ -0,0 +1,114 @@
+//! Graphviz (DOT) export of the structure of fixed framesteps
+//!
+//! Use [`schedule_graph`] to get a description of all the fixed framestep
+//! stages in a schedule, their sub-stages, and the systems inside them. You
+//! can render it with `dot -Tsvg`, or any other Graphviz-compatible tool.
+//! This is useful to check where your fixed framestep systems actually run.
+//!
+//! Systems are listed in the order they were added, not the order they run in.
+
+use std::fmt::Write;
+
+use bevy_ecs::prelude::*;
+
+use super::FixedFramestepStage;
+use super::dynamic::DynamicFramestepsStage;
+use super::relocate::FramestepSlotStage;
+use super::cleanup::CleanupStage;
+use super::groups::SystemGroupStage;
+use super::instances::InstancedFramestepStage;
+
+/// Produce a DOT graph with all the fixed framesteps in a schedule
+///
+/// Nested schedules and nested fixed framesteps are included too. Sub-stages that are not a `SystemStage`
+/// are shown without any systems.
+pub fn schedule_graph(schedule: &Schedule) -> String {
+    let mut dot = String::new();
+    writeln!(dot, "digraph fixed_framesteps {{").unwrap();
+    writeln!(dot, "    node [shape = box];").unwrap();
+    write_schedule(&mut dot, schedule);
+    writeln!(dot, "}}").unwrap();
+    dot
+}
+
+fn write_schedule(dot: &mut String, schedule: &Schedule) {
+    for (_, stage) in schedule.iter_stages() {
+        if let Some(ffstage) = stage.downcast_ref::<FixedFramestepStage>() {
+            // skip the empty stages left behind by `move_fixed_framestep`
+            if !ffstage.moved {
+                write_framestep(dot, ffstage, "frames");
+            }
+        } else if let Some(slot) = stage.downcast_ref::<FramestepSlotStage>() {
+            if let Some(ffstage) = slot.framestep() {
+                write_framestep(dot, ffstage, "frames");
+            }
+        } else if let Some(instanced) = stage.downcast_ref::<InstancedFramestepStage>() {
+            write_framestep(dot, instanced.template(), "frames, per instance");
+        } else if let Some(dynamic) = stage.downcast_ref::<DynamicFramestepsStage>() {
+            for ffstage in dynamic.framesteps() {
+                write_framestep(dot, ffstage, "frames");
+            }
+        } else if let Some(nested) = stage.downcast_ref::<Schedule>() {
+            write_schedule(dot, nested);
+        }
+    }
+}
+
+fn write_framestep(dot: &mut String, stage: &FixedFramestepStage, unit: &str) {
+    let label = stage.label;
+    writeln!(dot, "    subgraph \"cluster_{}\" {{", escape(label)).unwrap();
+    writeln!(dot, "        label = \"{} (every {} {})\";", escape(label), stage.step, escape(unit)).unwrap();
+
+    // chain the sub-stages, so they are laid out in the order they run in
+    let mut prev_anchor: Option<String> = None;
+    for (i, substage) in stage.stages.iter().enumerate() {
+        let anchor = format!("{}/{}", escape(label), i);
+        let group = substage.downcast_ref::<SystemGroupStage>();
+        let cleanup = substage.downcast_ref::<CleanupStage>();
+        let system_stage = substage.downcast_ref::<SystemStage>()
+            .or_else(|| group.map(SystemGroupStage::stage))
+            .or_else(|| cleanup.map(CleanupStage::stage));
+        let nested = substage.downcast_ref::<FixedFramestepStage>();
+        writeln!(dot, "        subgraph \"cluster_{}\" {{", anchor).unwrap();
+        if let Some(group) = group {
+            writeln!(dot, "            label = \"sub-stage {} (group {})\";", i, escape(group.name())).unwrap();
+        } else if cleanup.is_some() {
+            writeln!(dot, "            label = \"sub-stage {} (cleanup)\";", i).unwrap();
+        } else if system_stage.is_some() {
+            writeln!(dot, "            label = \"sub-stage {}\";", i).unwrap();
+        } else if nested.is_some() {
+            writeln!(dot, "            label = \"sub-stage {} (nested fixed framestep)\";", i).unwrap();
+        } else {
+            writeln!(dot, "            label = \"sub-stage {} (custom stage)\";", i).unwrap();
+        }
+        writeln!(dot, "            \"{}\" [label = \"\", shape = point, style = invis];", anchor).unwrap();
+
+        if let Some(substage) = system_stage {
+            let systems = substage.exclusive_at_start_systems().iter()
+                .chain(substage.parallel_systems())
+                .chain(substage.exclusive_before_commands_systems())
+                .chain(substage.exclusive_at_end_systems());
+            for (j, system) in systems.enumerate() {
+                writeln!(
+                    dot, "            \"{}/{}\" [label = \"{}\"];",
+                    anchor, j, escape(&system.name())
+                ).unwrap();
+            }
+        }
+        if let Some(nested) = nested {
+            write_framestep(dot, nested, &format!("ticks of {}", label));
+        }
+
+        writeln!(dot, "        }}").unwrap();
+        if let Some(prev) = prev_anchor {
+            writeln!(dot, "        \"{}\" -> \"{}\" [style = invis];", prev, anchor).unwrap();
+        }
+        prev_anchor = Some(anchor);
+    }
+
+    writeln!(dot, "    }}").unwrap();
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,157 @@
+//! Interactive tick stepping for debugging fixed framesteps
+//!
+//! Add the [`FramestepDebuggerPlugin`] to your app (usually only in development builds)
+//! to control a fixed framestep with hotkeys: pause it, run exactly one tick or ten
+//! ticks at a time, resume it, and fast-forward while a key is held. The current tick
+//! number is shown in the title of the primary window.
+//!
+//! Requires the `"framestep-debugger"` cargo feature.
+
+use bevy_app::{App, CoreStage, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_input::{Input, InputSystem};
+use bevy_input::keyboard::KeyCode;
+use bevy_window::Windows;
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName, PauseReason};
+
+/// The hotkeys used by the [`FramestepDebuggerPlugin`]
+#[derive(Debug, Clone)]
+pub struct FramestepDebuggerKeys {
+    /// Pause the fixed framestep
+    pub pause: KeyCode,
+    /// Un-pause (resume) the fixed framestep
+    pub resume: KeyCode,
+    /// Run exactly one tick
+    pub step: KeyCode,
+    /// Run ten ticks
+    pub step_10: KeyCode,
+    /// Hold to fast-forward (see [`FramestepDebuggerPlugin::fast_forward_speed`])
+    pub fast_forward: KeyCode,
+}
+
+impl Default for FramestepDebuggerKeys {
+    fn default() -> Self {
+        Self {
+            pause: KeyCode::F6,
+            resume: KeyCode::F5,
+            step: KeyCode::F10,
+            step_10: KeyCode::F11,
+            fast_forward: KeyCode::F8,
+        }
+    }
+}
+
+/// Plugin for controlling a fixed framestep with hotkeys (see the [module docs](self))
+///
+/// The fixed framestep must be in its default position, or anywhere after `CoreStage::PreUpdate`,
+/// for ticks to run on the same frame as the key press.
+#[derive(Debug, Clone)]
+#[derive(Resource)]
+pub struct FramestepDebuggerPlugin {
+    /// The name of the fixed framestep to control
+    pub framestep: FramestepName,
+    /// The hotkeys to use
+    pub keys: FramestepDebuggerKeys,
+    /// Show the current tick in the title of the primary window
+    pub show_in_title: bool,
+    /// The speed multiplier to use while the fast-forward key is held
+    /// (see [`FixedFramestepInfo::set_speed`](super::FixedFramestepInfo::set_speed))
+    pub fast_forward_speed: u32,
+}
+
+impl FramestepDebuggerPlugin {
+    /// Create the plugin to control the given fixed framestep, with the default hotkeys
+    pub fn new(framestep: impl FramestepLabel) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            keys: FramestepDebuggerKeys::default(),
+            show_in_title: true,
+            fast_forward_speed: 8,
+        }
+    }
+
+    /// Builder method for using different hotkeys
+    pub fn with_keys(mut self, keys: FramestepDebuggerKeys) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Builder method for a different fast-forward speed multiplier
+    pub fn with_fast_forward_speed(mut self, multiplier: u32) -> Self {
+        self.fast_forward_speed = multiplier;
+        self
+    }
+}
+
+impl Plugin for FramestepDebuggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.clone());
+        app.add_system_to_stage(CoreStage::PreUpdate, debugger_hotkeys.after(InputSystem));
+        if self.show_in_title {
+            app.add_system_to_stage(CoreStage::PostUpdate, debugger_title);
+        }
+    }
+}
+
+fn debugger_hotkeys(
+    config: Res<FramestepDebuggerPlugin>,
+    kbd: Res<Input<KeyCode>>,
+    mut framesteps: ResMut<FixedFramesteps>,
+    mut speed_before_ff: Local<Option<u32>>,
+) {
+    let info = match framesteps.get_mut(config.framestep) {
+        Some(info) => info,
+        None => return,
+    };
+    // the hotkeys do not nest: pressing pause twice still resumes with one press
+    if kbd.just_pressed(config.keys.pause) && !info.is_paused_by(PauseReason::User) {
+        info.pause();
+    }
+    if kbd.just_pressed(config.keys.resume) {
+        info.clear_pause(PauseReason::User);
+    }
+    if kbd.just_pressed(config.keys.step) {
+        info.queue_ticks(1);
+    }
+    if kbd.just_pressed(config.keys.step_10) {
+        info.queue_ticks(10);
+    }
+    if kbd.just_pressed(config.keys.fast_forward) {
+        *speed_before_ff = Some(info.speed());
+        info.set_speed(config.fast_forward_speed);
+    }
+    if kbd.just_released(config.keys.fast_forward) {
+        if let Some(speed) = speed_before_ff.take() {
+            info.set_speed(speed);
+        }
+    }
+}
+
+fn debugger_title(
+    config: Res<FramestepDebuggerPlugin>,
+    framesteps: Res<FixedFramesteps>,
+    windows: Option<ResMut<Windows>>,
+    mut base_title: Local<Option<String>>,
+) {
+    let mut windows = match windows {
+        Some(windows) => windows,
+        None => return,
+    };
+    let (window, info) = match (windows.get_primary_mut(), framesteps.get(config.framestep)) {
+        (Some(window), Some(info)) => (window, info),
+        _ => return,
+    };
+    let base_title = base_title.get_or_insert_with(|| window.title().to_owned());
+    let title = format!(
+        "{} [{}: tick {}{}{}]",
+        base_title,
+        config.framestep,
+        info.tick(),
+        if info.is_paused() { ", paused" } else if info.suppressed().is_some() { ", suppressed" } else { "" },
+        if info.speed() > 1 { format!(", {}x", info.speed()) } else { String::new() },
+    );
+    if window.title() != title {
+        window.set_title(title);
+    }
+}
@@ -0,0 +1,287 @@
+//! Checking that a simulation is deterministic, by running it twice and comparing
+//!
+//! [`DeterminismCheck`] builds your `App` twice, using the same setup function, and runs
+//! both for the same number of ticks of a fixed framestep, with the same
+//! [seed](DeterminismCheck::with_seed) and the same [scripted input](DeterminismCheck::with_script).
+//! After every tick, it hashes the components you registered (say, the positions and
+//! velocities of your physics bodies), and compares the two runs. It reports the first
+//! tick on which they diverge, and which components were different, so it can gate CI:
+//!
+//! ```ignore
+//! #[test]
+//! fn physics_is_deterministic() {
+//!     DeterminismCheck::new("physics", build_app)
+//!         .with_seed(42)
+//!         .with_script(|world, tick| world.resource_mut::<Jump>().0 = tick % 60 == 0)
+//!         .hash_component::<Health>()
+//!         .hash_component_with::<Transform>(|transform, hasher| {
+//!             transform.translation.to_array().map(f32::to_bits).hash(hasher);
+//!         })
+//!         .assert_deterministic(1000);
+//! }
+//! ```
+//!
+//! Both runs happen in the same process, and the apps are driven like the
+//! [`SimulationHarness`] does: one frame per update. Sources of nondeterminism that differ
+//! between two runs (like the iteration order of hash maps, or systems racing in parallel)
+//! are caught; anything that is the same on both runs (like the platform) is not.
+
+use std::collections::hash_map::DefaultHasher;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use bevy_app::App;
+use bevy_ecs::prelude::*;
+
+use super::harness::SimulationHarness;
+use super::{FramestepLabel, FramestepName, find_framestep_mut};
+
+/// Function that hashes all the components of one type in the world
+type HashFn = Arc<dyn Fn(&mut World) -> u64 + Send + Sync>;
+
+/// Function that sets up the input for a tick
+type ScriptFn = Arc<dyn Fn(&mut World, u64) + Send + Sync>;
+
+/// The hashes of the registered components after every tick of a run, in order
+#[derive(Default)]
+#[derive(Resource)]
+struct TickHashLog(Vec<(u64, Vec<u64>)>);
+
+/// The first tick on which the two runs of a [`DeterminismCheck`] diverged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The number of the tick
+    pub tick: u64,
+    /// The type names of the components whose hashes were different after the tick
+    ///
+    /// Empty if the runs did not even run the same ticks (say, one of them rewound).
+    pub components: Vec<&'static str>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.components.is_empty() {
+            write!(f, "The runs diverged on tick {}: they ran different ticks.", self.tick)
+        } else {
+            write!(f, "The runs diverged on tick {}, in: {}.", self.tick, self.components.join(", "))
+        }
+    }
+}
+
+impl std::error::Error for Divergence {}
+
+/// Runs an `App` twice and compares the results (see the [module docs](self))
+pub struct DeterminismCheck {
+    framestep: FramestepName,
+    setup: Box<dyn Fn() -> App>,
+    seed: Option<u64>,
+    script: Option<ScriptFn>,
+    hashers: Vec<(&'static str, HashFn)>,
+    max_updates: u64,
+}
+
+impl DeterminismCheck {
+    /// Check the given fixed framestep, in the apps built by `setup`
+    ///
+    /// `setup` is called once for each run, and must add the fixed framestep.
+    pub fn new(framestep: impl FramestepLabel, setup: impl Fn() -> App + 'static) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            setup: Box::new(setup),
+            seed: None,
+            script: None,
+            hashers: Vec::new(),
+            max_updates: 100_000,
+        }
+    }
+
+    /// Builder method for setting the [seed](super::FixedFramestepStage::set_seed) of the fixed framestep in both runs
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Builder method for scripting the input
+    ///
+    /// The script is called at the start of every tick of both runs, with the tick number,
+    /// before any systems of the tick run.
+    pub fn with_script(mut self, script: impl Fn(&mut World, u64) + Send + Sync + 'static) -> Self {
+        self.script = Some(Arc::new(script));
+        self
+    }
+
+    /// Builder method for comparing the `T` components of the two runs
+    pub fn hash_component<T: Component + Hash>(self) -> Self {
+        self.hash_component_with::<T>(|value, hasher| value.hash(hasher))
+    }
+
+    /// Builder method for comparing the `T` components of the two runs, hashed with the given function
+    ///
+    /// For component types that do not implement `Hash`, like the ones with floats in them.
+    pub fn hash_component_with<T: Component>(mut self, hash: fn(&T, &mut DefaultHasher)) -> Self {
+        let hash: HashFn = Arc::new(move |world: &mut World| hash_components(world, hash));
+        self.hashers.push((std::any::type_name::<T>(), hash));
+        self
+    }
+
+    /// Builder method for a different limit on the number of updates of each run (`100000` by default)
+    ///
+    /// A run that does not get through all the ticks within this many updates panics,
+    /// instead of hanging (say, if the fixed framestep is paused).
+    pub fn with_max_updates(mut self, updates: u64) -> Self {
+        self.max_updates = updates;
+        self
+    }
+
+    /// Run the app twice, for `ticks` ticks each, and compare them
+    ///
+    /// Returns the first tick on which they diverged, if any.
+    pub fn run(&self, ticks: u64) -> Result<(), Divergence> {
+        let first = self.run_once(ticks);
+        let second = self.run_once(ticks);
+        for ((tick, hashes), (other_tick, other_hashes)) in first.iter().zip(second.iter()) {
+            if tick != other_tick {
+                return Err(Divergence {
+                    tick: *tick.min(other_tick),
+                    components: Vec::new(),
+                });
+            }
+            let components: Vec<&'static str> = self.hashers.iter()
+                .zip(hashes.iter().zip(other_hashes))
+                .filter(|(_, (hash, other_hash))| hash != other_hash)
+                .map(|((name, _), _)| *name)
+                .collect();
+            if !components.is_empty() {
+                return Err(Divergence { tick: *tick, components });
+            }
+        }
+        Ok(())
+    }
+
+    /// Panic if the two runs diverge within `ticks` ticks (see [`run`](Self::run))
+    pub fn assert_deterministic(&self, ticks: u64) {
+        if let Err(divergence) = self.run(ticks) {
+            panic!("Fixed framestep {:?} is not deterministic. {}", self.framestep, divergence);
+        }
+    }
+
+    /// Build and run the app once, returning the hashes after each of the first `ticks` ticks
+    fn run_once(&self, ticks: u64) -> Vec<(u64, Vec<u64>)> {
+        let mut app = (self.setup)();
+        app.init_resource::<TickHashLog>();
+        let stage = find_framestep_mut(&mut app.schedule, self.framestep)
+            .expect("Fixed Framestep Stage not found");
+        if let Some(seed) = self.seed {
+            stage.set_seed(seed);
+        }
+        if let Some(script) = self.script.clone() {
+            stage.on_tick_begin(move |world, tick| script(world, tick));
+        }
+        let hashers: Vec<HashFn> = self.hashers.iter().map(|(_, hash)| hash.clone()).collect();
+        stage.on_tick_end(move |world, tick| {
+            let hashes = hashers.iter().map(|hash| hash(world)).collect();
+            world.resource_mut::<TickHashLog>().0.push((tick, hashes));
+        });
+
+        let mut harness = SimulationHarness::new(app);
+        let mut updates = 0;
+        while (harness.app().world.resource::<TickHashLog>().0.len() as u64) < ticks {
+            assert!(
+                updates < self.max_updates,
+                "Fixed framestep {:?} did not run {} ticks within {} updates.",
+                self.framestep, ticks, self.max_updates,
+            );
+            harness.update_with_elapsed(1);
+            updates += 1;
+        }
+        let mut log = std::mem::take(&mut harness.app_mut().world.resource_mut::<TickHashLog>().0);
+        log.truncate(ticks as usize);
+        log
+    }
+}
+
+/// Hash all the `T` components in the world, in the order of their entities
+fn hash_components<T: Component>(world: &mut World, hash: fn(&T, &mut DefaultHasher)) -> u64 {
+    let mut query = world.query::<(Entity, &T)>();
+    let mut components: Vec<(Entity, &T)> = query.iter(world).collect();
+    components.sort_unstable_by_key(|(entity, _)| *entity);
+    let mut hasher = DefaultHasher::new();
+    for (entity, value) in components {
+        entity.hash(&mut hasher);
+        hash(value, &mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    use bevy_app::App;
+    use bevy_ecs::prelude::*;
+
+    use super::{DeterminismCheck, Divergence};
+    use crate::fixedframestep::app::AppLooplessFixedFramestepExt;
+    use crate::fixedframestep::FixedFramesteps;
+
+    #[derive(Component, Hash)]
+    struct Position(u64);
+
+    #[derive(Component, Hash)]
+    struct Velocity(u64);
+
+    /// Accelerate by a random amount from the tick RNG, and move
+    fn step(mut framesteps: ResMut<FixedFramesteps>, mut query: Query<(&mut Position, &mut Velocity)>) {
+        let rng = framesteps.current_mut().rng();
+        for (mut position, mut velocity) in query.iter_mut() {
+            velocity.0 += rng.below(10);
+            position.0 = position.0.wrapping_add(velocity.0);
+        }
+    }
+
+    /// Jitter the positions from tick 3, using a source that is not seeded by the fixed framestep
+    fn jitter(framesteps: Res<FixedFramesteps>, mut query: Query<&mut Position>) {
+        if framesteps.current().tick() < 3 {
+            return;
+        }
+        for mut position in query.iter_mut() {
+            position.0 ^= RandomState::new().build_hasher().finish();
+        }
+    }
+
+    fn build_app() -> App {
+        let mut app = App::default();
+        app.add_fixed_framestep(1, "sim");
+        app.add_fixed_framestep_system("sim", 0, step);
+        app.world.spawn((Position(0), Velocity(1)));
+        app.world.spawn((Position(100), Velocity(0)));
+        app
+    }
+
+    fn check(setup: fn() -> App) -> DeterminismCheck {
+        DeterminismCheck::new("sim", setup)
+            .with_seed(42)
+            .hash_component::<Position>()
+            .hash_component::<Velocity>()
+    }
+
+    #[test]
+    fn deterministic_app_does_not_diverge() {
+        assert_eq!(check(build_app).run(20), Ok(()));
+    }
+
+    #[test]
+    fn unseeded_source_diverges_on_the_first_tick_that_reads_it() {
+        let setup = || {
+            let mut app = build_app();
+            app.add_fixed_framestep_system("sim", 0, jitter.after(step));
+            app
+        };
+        assert_eq!(check(setup).run(20), Err(Divergence {
+            tick: 3,
+            components: vec![std::any::type_name::<Position>()],
+        }));
+    }
+}
@@ -0,0 +1,111 @@
+//! Bevy diagnostics for the tick budgets of fixed framesteps
+//!
+//! Add the [`FramestepDiagnosticsPlugin`] to report, for each of the given fixed framesteps:
+//!
+//! - `{name}_tick`: the duration of the last tick on every frame that ran a tick, in ms
+//! - `{name}_over_budget`: how many ticks so far took longer than the
+//!   [tick budget](super::FixedFramestepStage::set_tick_budget)
+//!
+//! They can be logged with Bevy's `LogDiagnosticsPlugin`, like any other diagnostics.
+//! The `DiagnosticsPlugin` (part of Bevy's default plugins) must also be added.
+//!
+//! Requires the `"framestep-diagnostics"` cargo feature.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use bevy_app::{App, CoreStage, Plugin};
+use bevy_diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
+use bevy_ecs::prelude::*;
+use bevy_utils::HashMap;
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName};
+
+/// Plugin for reporting the tick durations of fixed framesteps as Bevy diagnostics
+///
+/// See the [module docs](self).
+#[derive(Debug, Clone)]
+#[derive(Resource)]
+pub struct FramestepDiagnosticsPlugin {
+    framesteps: Vec<FramestepName>,
+}
+
+impl FramestepDiagnosticsPlugin {
+    /// Create the plugin to report the diagnostics of the given fixed framestep
+    pub fn new(framestep: impl FramestepLabel) -> Self {
+        Self {
+            framesteps: vec![framestep.framestep_name()],
+        }
+    }
+
+    /// Builder method for reporting the diagnostics of another fixed framestep too
+    pub fn with_framestep(mut self, framestep: impl FramestepLabel) -> Self {
+        self.framesteps.push(framestep.framestep_name());
+        self
+    }
+
+    /// The id of the tick duration diagnostic of the given fixed framestep
+    pub fn tick_duration_id(framestep: impl FramestepLabel) -> DiagnosticId {
+        diagnostic_id(framestep.framestep_name(), 0)
+    }
+
+    /// The id of the over-budget ticks diagnostic of the given fixed framestep
+    pub fn over_budget_id(framestep: impl FramestepLabel) -> DiagnosticId {
+        diagnostic_id(framestep.framestep_name(), 1)
+    }
+}
+
+/// A stable id for each (fixed framestep, diagnostic) pair
+fn diagnostic_id(label: FramestepName, kind: u8) -> DiagnosticId {
+    let mut hasher = DefaultHasher::new();
+    label.hash(&mut hasher);
+    kind.hash(&mut hasher);
+    DiagnosticId::from_u128(0x6666_7364_6961_6700_0000_0000_0000_0000 | hasher.finish() as u128)
+}
+
+impl Plugin for FramestepDiagnosticsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.clone());
+        app.add_startup_system(setup_diagnostics);
+        app.add_system_to_stage(CoreStage::Last, measure_diagnostics);
+    }
+}
+
+fn setup_diagnostics(config: Res<FramestepDiagnosticsPlugin>, mut diagnostics: ResMut<Diagnostics>) {
+    for label in config.framesteps.iter() {
+        diagnostics.add(
+            Diagnostic::new(FramestepDiagnosticsPlugin::tick_duration_id(*label), format!("{}_tick", label), 20)
+                .with_suffix("ms")
+        );
+        diagnostics.add(
+            Diagnostic::new(FramestepDiagnosticsPlugin::over_budget_id(*label), format!("{}_over_budget", label), 1)
+                .with_smoothing_factor(0.0)
+        );
+    }
+}
+
+fn measure_diagnostics(
+    config: Res<FramestepDiagnosticsPlugin>,
+    framesteps: Option<Res<FixedFramesteps>>,
+    mut diagnostics: ResMut<Diagnostics>,
+    mut last_ticks: Local<HashMap<FramestepName, u64>>,
+) {
+    let framesteps = match framesteps {
+        Some(framesteps) => framesteps,
+        None => return,
+    };
+    for label in config.framesteps.iter() {
+        let info = match framesteps.get(*label) {
+            Some(info) => info,
+            None => continue,
+        };
+        diagnostics.add_measurement(FramestepDiagnosticsPlugin::over_budget_id(*label), || info.over_budget_ticks().0 as f64);
+        // only on the frames that ran a tick
+        if last_ticks.insert(*label, info.tick()) == Some(info.tick()) {
+            continue;
+        }
+        if let Some(duration) = info.last_tick_duration() {
+            diagnostics.add_measurement(FramestepDiagnosticsPlugin::tick_duration_id(*label), || duration.as_secs_f64() * 1000.0);
+        }
+    }
+}
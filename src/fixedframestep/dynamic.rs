@@ -0,0 +1,154 @@
+//! Fixed framesteps created and removed at runtime, from systems
+//!
+//! Systems cannot change the schedule, so they cannot add fixed framestep stages
+//! directly. Instead, add a [`DynamicFramestepsStage`] to the schedule once (using
+//! `add_dynamic_fixed_framesteps`), and then queue the creation of fixed framesteps with
+//! [`create_fixed_framestep`](DynamicFramestepsExt::create_fixed_framestep), from `Commands`
+//! or a `World`. Useful for gameplay or scripting code that needs its own ticks (say, for a
+//! mini-game) while it is active.
+//!
+//! The queued changes are applied at the start of the next run of the `DynamicFramestepsStage`,
+//! which is a safe point: none of its fixed framesteps are running. The new fixed framestep
+//! runs on that frame already, after the ones that were created earlier.
+//!
+//! Once created, a dynamic fixed framestep works like any other: use [`FixedFramesteps`] to
+//! control it, and the `App`/`Schedule` extension methods (like `add_fixed_framestep_system`)
+//! to modify it. Configure its sub-stages and systems before creating it, though: it is only
+//! in the schedule after the `DynamicFramestepsStage` has run.
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::Command;
+use bevy_utils::tracing::warn;
+
+use super::commands::TickCommandQueue;
+use super::{FixedFramestepStage, FixedFramesteps, FramestepId, FramestepLabel, FramestepLayouts, FramestepName, SchedulePosition};
+
+/// Type used as the Bevy Stage Label of the [`DynamicFramestepsStage`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(StageLabel)]
+pub struct DynamicFramestepsStageLabel;
+
+/// The changes queued using [`DynamicFramestepsExt`], not yet applied
+#[derive(Default)]
+#[derive(Resource)]
+struct PendingFramesteps {
+    create: Vec<FixedFramestepStage>,
+    remove: Vec<FramestepName>,
+}
+
+struct CreateFramestep(FixedFramestepStage);
+
+impl Command for CreateFramestep {
+    fn write(self, world: &mut World) {
+        world.create_fixed_framestep(self.0);
+    }
+}
+
+struct RemoveFramestep(FramestepName);
+
+impl Command for RemoveFramestep {
+    fn write(self, world: &mut World) {
+        world.remove_fixed_framestep(self.0);
+    }
+}
+
+/// Extension trait for creating and removing dynamic fixed framesteps (see the [module docs](self))
+pub trait DynamicFramestepsExt {
+    /// Queue a fixed framestep to be added to the [`DynamicFramestepsStage`]
+    ///
+    /// If a fixed framestep with the same name already exists, it is not added (with a warning).
+    fn create_fixed_framestep(&mut self, stage: FixedFramestepStage);
+    /// Queue a dynamic fixed framestep to be removed, along with all its state
+    ///
+    /// Fixed framesteps that were not created using [`create_fixed_framestep`](Self::create_fixed_framestep)
+    /// cannot be removed.
+    fn remove_fixed_framestep(&mut self, label: impl FramestepLabel);
+}
+
+impl DynamicFramestepsExt for Commands<'_, '_> {
+    fn create_fixed_framestep(&mut self, stage: FixedFramestepStage) {
+        self.add(CreateFramestep(stage));
+    }
+
+    fn remove_fixed_framestep(&mut self, label: impl FramestepLabel) {
+        self.add(RemoveFramestep(label.framestep_name()));
+    }
+}
+
+impl DynamicFramestepsExt for World {
+    fn create_fixed_framestep(&mut self, stage: FixedFramestepStage) {
+        self.get_resource_or_insert_with(PendingFramesteps::default).create.push(stage);
+    }
+
+    fn remove_fixed_framestep(&mut self, label: impl FramestepLabel) {
+        self.get_resource_or_insert_with(PendingFramesteps::default).remove.push(label.framestep_name());
+    }
+}
+
+/// Stage that hosts the fixed framesteps created at runtime
+///
+/// Runs all of them, one after the other, in the order they were created.
+/// See the [module docs](self) for how to use it.
+#[derive(Default)]
+pub struct DynamicFramestepsStage {
+    pub(super) stages: Vec<FixedFramestepStage>,
+    pub(super) position: SchedulePosition,
+}
+
+impl DynamicFramestepsStage {
+    /// Create the stage, without any fixed framesteps
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The fixed framesteps that have been created so far
+    pub fn framesteps(&self) -> &[FixedFramestepStage] {
+        &self.stages
+    }
+
+    /// Apply the queued creations and removals
+    fn apply_pending(&mut self, world: &mut World) {
+        let pending = match world.get_resource_mut::<PendingFramesteps>() {
+            Some(mut pending) => std::mem::take(&mut *pending),
+            None => return,
+        };
+        for label in pending.remove {
+            let n_stages = self.stages.len();
+            self.stages.retain(|stage| stage.label != label);
+            if self.stages.len() == n_stages {
+                warn!("Cannot remove fixed framestep {:?}: it is not a dynamic fixed framestep.", label);
+                continue;
+            }
+            if let Some(mut framesteps) = world.get_resource_mut::<FixedFramesteps>() {
+                framesteps.info.remove(&FramestepId::new(label));
+            }
+            if let Some(mut layouts) = world.get_resource_mut::<FramestepLayouts>() {
+                layouts.remove(label);
+            }
+            if let Some(mut queue) = world.get_resource_mut::<TickCommandQueue>() {
+                queue.clear(label);
+            }
+        }
+        for mut stage in pending.create {
+            let exists = world.get_resource::<FixedFramesteps>()
+                .map(|framesteps| framesteps.info.contains_key(&stage.id))
+                .unwrap_or(false);
+            if exists || self.stages.iter().any(|other| other.label == stage.label) {
+                warn!("Cannot create fixed framestep {:?}: a fixed framestep with that name already exists.", stage.label);
+                continue;
+            }
+            stage.position = self.position;
+            stage.store_fixedframestepinfo(world);
+            self.stages.push(stage);
+        }
+    }
+}
+
+impl Stage for DynamicFramestepsStage {
+    fn run(&mut self, world: &mut World) {
+        self.apply_pending(world);
+        for stage in self.stages.iter_mut() {
+            stage.run(world);
+        }
+    }
+}
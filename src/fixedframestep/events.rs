@@ -0,0 +1,140 @@
+//! Events stamped with the fixed framestep tick that produced them
+//!
+//! Send events using [`TickedEventWriter`] from your fixed framestep systems, and
+//! read them as [`Ticked<E>`] using a regular `EventReader`. Useful for replays,
+//! networking, or UI, that need to know which simulation tick an event came from.
+//!
+//! Remember to register the event type: `app.add_event::<Ticked<E>>()`.
+//!
+//! Bevy's `Events<E>` keep every event for two updates, and `add_event` updates them on
+//! every frame. Systems in a fixed framestep that do not tick on every frame can then miss
+//! events, or, when a reader is not run on every tick, see them on more ticks than expected.
+//! For event types that are only sent and read in the ticks of one fixed framestep, register
+//! them with `register_tick_event::<E>(framestep)` instead of `add_event`: the events are
+//! then updated at the start of every tick of that fixed framestep, and live for two ticks.
+//! Either way, the events sent by the systems in one sub-stage can be read by the systems
+//! in the later sub-stages of the same tick: the events are never updated during a tick.
+//!
+//! For events that should never outlive the tick they were sent in, use [`TickScopedEvents`]
+//! instead, registered with `register_tick_scoped_event::<E>(framestep)`. It is a plain
+//! buffer, cleared at the end of every tick, so every system that runs later in the tick
+//! sees all of the events, and no system on a later tick sees any of them.
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::event::Event;
+use bevy_ecs::system::SystemParam;
+
+use super::FixedFramesteps;
+
+/// Update the `Events<E>` at the start of a tick (see the [module docs](self))
+pub(super) fn update_tick_events<E: Event>(world: &mut World) {
+    if let Some(mut events) = world.get_resource_mut::<Events<E>>() {
+        events.update();
+    }
+}
+
+/// Resource with the events sent during the current tick, cleared at the end of the tick
+///
+/// Send events to it using `ResMut<TickScopedEvents<E>>`, and read them using
+/// `Res<TickScopedEvents<E>>`, from any later sub-stage (or later system in the same
+/// sub-stage) of the tick. Unlike `EventReader`, reading does not consume the events:
+/// every system sees all of them. See the [module docs](self).
+#[derive(Resource)]
+pub struct TickScopedEvents<E: Event> {
+    events: Vec<E>,
+}
+
+impl<E: Event> Default for TickScopedEvents<E> {
+    fn default() -> Self {
+        Self {
+            events: Vec::new(),
+        }
+    }
+}
+
+impl<E: Event> TickScopedEvents<E> {
+    /// Send an event, readable until the end of the tick
+    pub fn send(&mut self, event: E) {
+        self.events.push(event);
+    }
+
+    /// Send a batch of events, readable until the end of the tick
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = E>) {
+        self.events.extend(events);
+    }
+
+    /// Iterate over the events sent so far on this tick, in the order they were sent
+    pub fn iter(&self) -> std::slice::Iter<'_, E> {
+        self.events.iter()
+    }
+
+    /// The number of events sent so far on this tick
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Were no events sent so far on this tick?
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Drop all the events now, instead of at the end of the tick
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+}
+
+impl<'a, E: Event> IntoIterator for &'a TickScopedEvents<E> {
+    type Item = &'a E;
+    type IntoIter = std::slice::Iter<'a, E>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Clear the [`TickScopedEvents<E>`] at the end of a tick
+pub(super) fn clear_tick_scoped_events<E: Event>(world: &mut World, _tick: u64) {
+    if let Some(mut events) = world.get_resource_mut::<TickScopedEvents<E>>() {
+        events.clear();
+    }
+}
+
+/// An event, together with the number of the fixed framestep tick it was sent in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ticked<E> {
+    /// The tick number (see [`FixedFramestepInfo::tick`](super::FixedFramestepInfo::tick))
+    pub tick: u64,
+    /// The original event
+    pub event: E,
+}
+
+/// System parameter for sending events stamped with the current tick number
+///
+/// Works like `EventWriter<Ticked<E>>`, but fills in the tick for you.
+/// Panics if used outside of a fixed framestep (like [`FixedFramesteps::current`]).
+#[derive(SystemParam)]
+pub struct TickedEventWriter<'w, 's, E: Event> {
+    framesteps: Res<'w, FixedFramesteps>,
+    events: EventWriter<'w, 's, Ticked<E>>,
+}
+
+impl<'w, 's, E: Event> TickedEventWriter<'w, 's, E> {
+    /// Send an event, stamped with the current tick
+    pub fn send(&mut self, event: E) {
+        let tick = self.current_tick();
+        self.events.send(Ticked { tick, event });
+    }
+
+    /// Send a batch of events, all stamped with the current tick
+    pub fn send_batch(&mut self, events: impl IntoIterator<Item = E>) {
+        let tick = self.current_tick();
+        self.events.send_batch(events.into_iter().map(|event| Ticked { tick, event }));
+    }
+
+    fn current_tick(&self) -> u64 {
+        self.framesteps.get_current()
+            .expect("TickedEventWriter can only be used when running inside a fixed framestep.")
+            .tick()
+    }
+}
@@ -0,0 +1,109 @@
+//! Running the sub-stages of a fixed framestep on a dedicated task pool
+//!
+//! Bevy's parallel executor always runs systems on the global `ComputeTaskPool`, which is
+//! shared with everything else that happens in the frame. A fixed framestep that runs huge
+//! ticks (or many catch-up ticks) can hog all of its threads. With
+//! [`FixedFramestepStage::set_task_pool`](super::FixedFramestepStage::set_task_pool), the
+//! `SystemStage` sub-stages of the fixed framestep use the [`TaskPoolExecutor`] instead,
+//! which runs their systems on a task pool of your own (say, one with fewer threads).
+//!
+//! The `TaskPoolExecutor` runs the systems in waves: every wave starts all the systems
+//! whose dependencies have finished and whose data access does not conflict, then waits
+//! for all of them. That is simpler than Bevy's executor, which starts every system as
+//! soon as possible, so stages with long chains of ordered systems may run a bit slower.
+
+use std::sync::Arc;
+
+use bevy_ecs::archetype::ArchetypeComponentId;
+use bevy_ecs::query::Access;
+use bevy_ecs::schedule::{ParallelSystemExecutor, SystemContainer};
+use bevy_ecs::world::World;
+use bevy_tasks::TaskPool;
+
+/// Parallel executor for `SystemStage`s, that runs the systems on the given task pool
+///
+/// See the [module docs](self).
+pub struct TaskPoolExecutor {
+    pool: Arc<TaskPool>,
+    // archetype-component access of every system, updated before every run
+    access: Vec<Access<ArchetypeComponentId>>,
+    finished: Vec<bool>,
+}
+
+impl TaskPoolExecutor {
+    /// Create an executor that runs systems on the given task pool
+    pub fn new(pool: Arc<TaskPool>) -> Self {
+        Self {
+            pool,
+            access: Vec::new(),
+            finished: Vec::new(),
+        }
+    }
+
+    /// The task pool that the systems run on
+    pub fn task_pool(&self) -> &Arc<TaskPool> {
+        &self.pool
+    }
+}
+
+impl ParallelSystemExecutor for TaskPoolExecutor {
+    fn rebuild_cached_data(&mut self, systems: &[SystemContainer]) {
+        self.access.clear();
+        self.access.resize_with(systems.len(), Access::default);
+        self.finished.clear();
+        self.finished.resize(systems.len(), false);
+    }
+
+    fn run_systems(&mut self, systems: &mut [SystemContainer], world: &mut World) {
+        for (container, access) in systems.iter_mut().zip(self.access.iter_mut()) {
+            let system = container.system_mut();
+            system.update_archetype_component_access(world);
+            access.extend(system.archetype_component_access());
+        }
+        // systems that should not run count as finished, so their dependants are not held up
+        for (container, finished) in systems.iter().zip(self.finished.iter_mut()) {
+            *finished = !container.should_run();
+        }
+        let world: &World = world;
+        loop {
+            let mut wave = vec![false; systems.len()];
+            let mut wave_access = Access::<ArchetypeComponentId>::default();
+            let mut non_send = false;
+            for (i, container) in systems.iter().enumerate() {
+                if self.finished[i] || !container.dependencies().iter().all(|&dependency| self.finished[dependency]) {
+                    continue;
+                }
+                let is_send = container.system().is_send();
+                if (!is_send && non_send) || !self.access[i].is_compatible(&wave_access) {
+                    continue;
+                }
+                wave[i] = true;
+                wave_access.extend(&self.access[i]);
+                non_send |= !is_send;
+            }
+            if !wave.contains(&true) {
+                break;
+            }
+            self.pool.scope(|scope| {
+                for (_, container) in systems.iter_mut().enumerate().filter(|(i, _)| wave[*i]) {
+                    let is_send = container.system().is_send();
+                    let system = container.system_mut();
+                    let task = async move {
+                        // SAFETY: the systems of a wave have compatible access, and the
+                        // world is not accessed by anything else while they run.
+                        unsafe { system.run_unsafe((), world) };
+                    };
+                    if is_send {
+                        scope.spawn(task);
+                    } else {
+                        // non-send systems must run on the thread that owns the world
+                        scope.spawn_on_scope(task);
+                    }
+                }
+            });
+            for (finished, ran) in self.finished.iter_mut().zip(wave) {
+                *finished |= ran;
+            }
+        }
+    }
+}
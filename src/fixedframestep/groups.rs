@@ -0,0 +1,113 @@
+//! Named system groups inside a fixed framestep, that can be replaced at runtime
+//!
+//! Bevy's `SystemStage` cannot remove systems once they are added. For hot-reload
+//! workflows (reloading a dylib, or systems generated by a scripting layer), put the
+//! systems that you want to swap out into a *group*: a sub-stage of its own, with a
+//! name. Then, replace the whole group with a new set of systems whenever you like,
+//! using `replace_fixed_framestep_group`, or [`FixedFramestepStage::replace_group`].
+//!
+//! Each group is a separate sub-stage, so its `Commands` are applied before the
+//! next sub-stage runs, as usual. System labels only order systems within the same
+//! group. Replacing a group drops the old systems, including their `Local` state;
+//! the new systems are initialized when they first run.
+
+use bevy_ecs::prelude::*;
+
+use super::{FixedFramestepStage, make_strict};
+
+/// A sub-stage of a fixed framestep, with a name, whose systems can be replaced
+///
+/// See the [module docs](self).
+pub struct SystemGroupStage {
+    name: &'static str,
+    pub(super) stage: SystemStage,
+}
+
+impl SystemGroupStage {
+    /// Create a group with the given name, running the given systems in parallel
+    pub fn new(name: &'static str, systems: SystemSet) -> Self {
+        Self {
+            name,
+            stage: SystemStage::parallel().with_system_set(systems),
+        }
+    }
+
+    /// The name of the group
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Get access to the `SystemStage` with the systems of the group
+    pub fn stage(&self) -> &SystemStage {
+        &self.stage
+    }
+
+    /// Get mut access to the `SystemStage` with the systems of the group
+    pub fn stage_mut(&mut self) -> &mut SystemStage {
+        &mut self.stage
+    }
+
+    /// Replace all the systems of the group
+    ///
+    /// The new systems run in parallel. Prefer [`FixedFramestepStage::replace_group`], which
+    /// also keeps [strict determinism](FixedFramestepStage::set_strict_determinism).
+    pub fn replace(&mut self, systems: SystemSet) {
+        self.stage = SystemStage::parallel().with_system_set(systems);
+    }
+}
+
+impl Stage for SystemGroupStage {
+    fn run(&mut self, world: &mut World) {
+        self.stage.run(world);
+    }
+}
+
+impl FixedFramestepStage {
+    /// Add a named system group as a new child stage, at the end
+    ///
+    /// Panics if a group with the same name exists already.
+    pub fn add_group(&mut self, name: &'static str, systems: SystemSet) {
+        assert!(
+            self.group(name).is_none(),
+            "Fixed framestep {:?} already has a system group named {:?}.", self.label, name,
+        );
+        self.add_stage(SystemGroupStage::new(name, systems));
+    }
+
+    /// Builder method for adding a named system group
+    pub fn with_group(mut self, name: &'static str, systems: SystemSet) -> Self {
+        self.add_group(name, systems);
+        self
+    }
+
+    /// Get the system group with the given name
+    pub fn group(&self, name: &str) -> Option<&SystemGroupStage> {
+        self.stages.iter()
+            .filter_map(|stage| stage.downcast_ref::<SystemGroupStage>())
+            .find(|group| group.name == name)
+    }
+
+    /// Get the system group with the given name, mutably
+    pub fn group_mut(&mut self, name: &str) -> Option<&mut SystemGroupStage> {
+        self.stages.iter_mut()
+            .filter_map(|stage| stage.downcast_mut::<SystemGroupStage>())
+            .find(|group| group.name == name)
+    }
+
+    /// Replace all the systems of the system group with the given name
+    ///
+    /// Takes effect from the next tick. Returns `false` if there is no such group.
+    pub fn replace_group(&mut self, name: &str, systems: SystemSet) -> bool {
+        let strict = self.strict_determinism;
+        match self.group_mut(name) {
+            Some(group) => {
+                group.replace(systems);
+                if strict {
+                    make_strict(group);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+}
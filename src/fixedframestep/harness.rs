@@ -0,0 +1,254 @@
+//! Test harness for fixed framestep logic
+//!
+//! [`SimulationHarness`] drives an `App` with scripted patterns of frames, such as a steady
+//! frame rate, a hitch (many frames elapsing during a single update), or alternating
+//! short and long frames, and records which ticks ran when. Use it in your own tests, to
+//! check that your fixed-tick logic behaves under different frame pacing.
+//!
+//! Frame hitches only make a difference to fixed framesteps that count frames using
+//! [`AccumulationSource::FrameCount`](super::AccumulationSource::FrameCount); with the
+//! default source, every update is exactly one frame.
+
+use bevy_app::App;
+use bevy_core::FrameCount;
+use bevy_ecs::prelude::*;
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName};
+
+/// A scripted pattern of frames for [`SimulationHarness::run`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FramePattern {
+    /// `n` updates, one frame each
+    Steady(u32),
+    /// One update, after `n` frames have elapsed since the previous one
+    Hitch(u32),
+    /// `updates` updates, alternating between `short` and `long` elapsed frames (starting with `short`)
+    Alternating {
+        /// Elapsed frames on the even updates
+        short: u32,
+        /// Elapsed frames on the odd updates
+        long: u32,
+        /// The total number of updates
+        updates: u32,
+    },
+}
+
+/// Log of all the ticks that ran, in order, for the tracked fixed framesteps
+#[derive(Default)]
+#[derive(Resource)]
+struct HarnessLog(Vec<(FramestepName, u64)>);
+
+/// Drives an `App` with scripted frame patterns (see the [module docs](self))
+pub struct SimulationHarness {
+    app: App,
+    tracked: Vec<FramestepName>,
+    updates: Vec<Vec<u64>>,
+}
+
+impl SimulationHarness {
+    /// Wrap an `App` that has its fixed framesteps set up
+    pub fn new(mut app: App) -> Self {
+        app.init_resource::<FrameCount>();
+        app.init_resource::<HarnessLog>();
+        Self {
+            app,
+            tracked: Vec::new(),
+            updates: Vec::new(),
+        }
+    }
+
+    /// Record the ticks of a fixed framestep
+    ///
+    /// Adds a system to the first sub-stage of the fixed framestep, so that the
+    /// order of the ticks of all tracked fixed framesteps can be checked.
+    pub fn track(&mut self, label: impl FramestepLabel) -> &mut Self {
+        use super::app::AppLooplessFixedFramestepExt;
+        let label = label.framestep_name();
+        self.app.add_fixed_framestep_system(label, 0, move |mut log: ResMut<HarnessLog>, framesteps: Res<FixedFramesteps>| {
+            let tick = framesteps.current().tick();
+            log.0.push((label, tick));
+        });
+        self.tracked.push(label);
+        self
+    }
+
+    /// Access the `App`
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    /// Access the `App` mutably
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// Run a single update, after `frames` frames have elapsed since the previous one
+    ///
+    /// `frames` must be at least `1`.
+    pub fn update_with_elapsed(&mut self, frames: u32) -> &mut Self {
+        assert!(frames > 0);
+        let ticks_before: Vec<u64> = self.tracked.iter().map(|label| self.tick(*label)).collect();
+
+        // the frames before the update, that were "lost" in the hitch
+        {
+            let mut frame_count = self.app.world.resource_mut::<FrameCount>();
+            frame_count.0 = frame_count.0.wrapping_add(frames - 1);
+        }
+        let frame_before = self.app.world.resource::<FrameCount>().0;
+        self.app.update();
+        // without Bevy's `CorePlugin`, nothing counts the frames; do it ourselves
+        let mut frame_count = self.app.world.resource_mut::<FrameCount>();
+        if frame_count.0 == frame_before {
+            frame_count.0 = frame_count.0.wrapping_add(1);
+        }
+
+        let ticks = self.tracked.iter().zip(ticks_before)
+            .map(|(label, before)| self.tick(*label) - before)
+            .collect();
+        self.updates.push(ticks);
+        self
+    }
+
+    /// Run a scripted pattern of frames
+    pub fn run(&mut self, pattern: FramePattern) -> &mut Self {
+        match pattern {
+            FramePattern::Steady(n) => {
+                for _ in 0..n {
+                    self.update_with_elapsed(1);
+                }
+            }
+            FramePattern::Hitch(n) => {
+                self.update_with_elapsed(n);
+            }
+            FramePattern::Alternating { short, long, updates } => {
+                for i in 0..updates {
+                    self.update_with_elapsed(if i % 2 == 0 { short } else { long });
+                }
+            }
+        }
+        self
+    }
+
+    /// The current tick number of a fixed framestep
+    pub fn tick(&self, label: impl FramestepLabel) -> u64 {
+        self.app.world.resource::<FixedFramesteps>()
+            .get(label.framestep_name())
+            .expect("Fixed Framestep not found")
+            .tick()
+    }
+
+    /// The number of ticks of a tracked fixed framestep that ran on each update, so far
+    pub fn ticks_per_update(&self, label: impl FramestepLabel) -> Vec<u64> {
+        let i = self.tracked_index(label.framestep_name());
+        self.updates.iter().map(|ticks| ticks[i]).collect()
+    }
+
+    /// All the ticks of the tracked fixed framesteps that ran so far, in order
+    pub fn tick_log(&self) -> &[(FramestepName, u64)] {
+        &self.app.world.resource::<HarnessLog>().0
+    }
+
+    /// Clear the recorded ticks (but keep tracking)
+    pub fn clear_log(&mut self) -> &mut Self {
+        self.updates.clear();
+        self.app.world.resource_mut::<HarnessLog>().0.clear();
+        self
+    }
+
+    /// Panic if the fixed framestep is not at the expected tick number
+    pub fn assert_ticks(&self, label: impl FramestepLabel, expected: u64) -> &Self {
+        let label = label.framestep_name();
+        let tick = self.tick(label);
+        assert_eq!(tick, expected, "Fixed framestep {:?} is at tick {}, expected {}.", label, tick, expected);
+        self
+    }
+
+    /// Panic if the ticks that ran on each update (since the log was last cleared) are not as expected
+    pub fn assert_ticks_per_update(&self, label: impl FramestepLabel, expected: &[u64]) -> &Self {
+        let label = label.framestep_name();
+        let ticks = self.ticks_per_update(label);
+        assert_eq!(ticks, expected, "Unexpected ticks per update for fixed framestep {:?}.", label);
+        self
+    }
+
+    /// Panic if the ticks that ran (since the log was last cleared) are not as expected, in order
+    ///
+    /// Only the tracked fixed framesteps are logged.
+    pub fn assert_tick_order(&self, expected: &[(FramestepName, u64)]) -> &Self {
+        assert_eq!(self.tick_log(), expected, "Unexpected order of fixed framestep ticks.");
+        self
+    }
+
+    fn tracked_index(&self, label: FramestepName) -> usize {
+        self.tracked.iter().position(|tracked| *tracked == label)
+            .expect("Fixed Framestep is not tracked by the harness")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::App;
+
+    use super::{FramePattern, SimulationHarness};
+    use crate::fixedframestep::app::AppLooplessFixedFramestepExt;
+    use crate::fixedframestep::{AccumulationSource, FixedFramesteps, Frames};
+
+    /// A harness with the fixed framestep `"sim"`, ticking every 2 frames, counted from `FrameCount`
+    fn harness(max_steps: u32) -> SimulationHarness {
+        let mut app = App::default();
+        app.add_fixed_framestep(2, "sim");
+        let stage = app.get_fixed_framestep_stage_mut("sim");
+        stage.set_accumulation_source(AccumulationSource::FrameCount);
+        stage.set_max_steps_per_frame(max_steps);
+        let mut harness = SimulationHarness::new(app);
+        harness.track("sim");
+        harness
+    }
+
+    fn remaining(harness: &SimulationHarness) -> Frames {
+        harness.app().world.resource::<FixedFramesteps>()["sim"].remaining()
+    }
+
+    #[test]
+    fn steady() {
+        let mut harness = harness(10);
+        harness.run(FramePattern::Steady(6))
+            .assert_ticks_per_update("sim", &[0, 1, 0, 1, 0, 1])
+            .assert_ticks("sim", 3)
+            .assert_tick_order(&[("sim", 1), ("sim", 2), ("sim", 3)]);
+        assert_eq!(remaining(&harness), Frames(0));
+    }
+
+    #[test]
+    fn hitch() {
+        let mut harness = harness(10);
+        harness.run(FramePattern::Steady(1)).run(FramePattern::Hitch(6))
+            .assert_ticks_per_update("sim", &[0, 3])
+            .assert_ticks("sim", 3);
+        // 1 + 6 frames: 3 whole steps, and half a step left in the accumulator
+        assert_eq!(remaining(&harness), Frames(1));
+    }
+
+    #[test]
+    fn alternating() {
+        let mut harness = harness(10);
+        harness.run(FramePattern::Alternating { short: 1, long: 3, updates: 6 })
+            .assert_ticks_per_update("sim", &[0, 2, 0, 2, 0, 2])
+            .assert_ticks("sim", 6);
+        assert_eq!(remaining(&harness), Frames(0));
+    }
+
+    #[test]
+    fn hitch_over_max_steps_drops_the_excess() {
+        let mut harness = harness(3);
+        harness.run(FramePattern::Steady(2)).run(FramePattern::Hitch(11))
+            .assert_ticks_per_update("sim", &[0, 1, 3])
+            .assert_ticks("sim", 4);
+        // 5 steps were due; the 2 over the limit were dropped, keeping the partial step
+        assert_eq!(remaining(&harness), Frames(1));
+
+        harness.clear_log().run(FramePattern::Steady(1))
+            .assert_ticks_per_update("sim", &[1])
+            .assert_tick_order(&[("sim", 5)]);
+    }
+}
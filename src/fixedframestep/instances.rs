@@ -0,0 +1,248 @@
+//! Independent instances of a fixed framestep, created at runtime
+//!
+//! Useful for servers that host many rooms/matches of the same simulation in one
+//! world. Add the fixed framestep once, with all its sub-stages and systems, as a
+//! *template* (using `add_instanced_fixed_framestep`). Then, spawn and despawn
+//! instances of it at any time, using the [`FramestepInstances`] resource.
+//!
+//! Every frame, the [`InstancedFramestepStage`] drives each instance in turn (in order
+//! of their ids), as if it was its own fixed framestep: with its own accumulator,
+//! tick counter, pause state, speed, history, and [`TickCommands`](super::commands::TickCommands).
+//! Fixed framesteps nested inside the template are also instanced.
+//!
+//! While an instance is running, [`FixedFramesteps`] gives access to the info of
+//! that instance (under the template name), and [`FramestepInstances::current`]
+//! returns its id. Tag the entities of each instance with [`FramestepInstanceId`],
+//! so your systems can tell them apart.
+
+use std::collections::BTreeMap;
+
+use bevy_ecs::prelude::*;
+use bevy_utils::HashMap;
+
+use super::commands::{TickCommandQueue, TickQueue};
+use super::{FixedFramestepInfo, FixedFramestepStage, FixedFramesteps, FramestepId, FramestepLabel, FramestepName};
+
+/// Component for marking which instance of a fixed framestep an entity belongs to
+///
+/// Not used by the crate itself; it is for your systems to filter by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[derive(Component)]
+pub struct FramestepInstanceId(pub u64);
+
+/// The saved state of one instance, while it is not running
+#[derive(Default)]
+struct InstanceState {
+    info: HashMap<FramestepName, FixedFramestepInfo>,
+    commands: HashMap<FramestepName, TickQueue>,
+    spawn_paused: bool,
+}
+
+/// Resource for spawning, despawning, and controlling instances of fixed framesteps
+///
+/// Inserted automatically when adding an instanced fixed framestep to the `App`.
+///
+/// The info ([`FixedFramestepInfo`]) of a new instance is available after the
+/// instance runs for the first time (on the next frame).
+#[derive(Default)]
+#[derive(Resource)]
+pub struct FramestepInstances {
+    instances: HashMap<FramestepName, BTreeMap<u64, InstanceState>>,
+    current: Option<u64>,
+}
+
+impl FramestepInstances {
+    /// Create a new instance of the given template, starting from its initial parameters
+    ///
+    /// Returns `false` (and does nothing) if the instance already exists.
+    pub fn spawn(&mut self, template: impl FramestepLabel, id: u64) -> bool {
+        self.spawn_inner(template.framestep_name(), id, false)
+    }
+
+    /// Create a new instance of the given template, that starts out paused
+    ///
+    /// Returns `false` (and does nothing) if the instance already exists.
+    pub fn spawn_paused(&mut self, template: impl FramestepLabel, id: u64) -> bool {
+        self.spawn_inner(template.framestep_name(), id, true)
+    }
+
+    fn spawn_inner(&mut self, template: FramestepName, id: u64, spawn_paused: bool) -> bool {
+        let instances = self.instances.entry(template).or_default();
+        if instances.contains_key(&id) {
+            return false;
+        }
+        instances.insert(id, InstanceState {
+            spawn_paused,
+            ..Default::default()
+        });
+        true
+    }
+
+    /// Remove an instance of the given template, with all its state
+    ///
+    /// If called from inside the instance itself, it will finish its current tick,
+    /// but not run any more. Returns `false` if the instance does not exist.
+    pub fn despawn(&mut self, template: impl FramestepLabel, id: u64) -> bool {
+        self.instances.get_mut(template.framestep_name())
+            .map(|instances| instances.remove(&id).is_some())
+            .unwrap_or(false)
+    }
+
+    /// Does the given instance exist?
+    pub fn contains(&self, template: impl FramestepLabel, id: u64) -> bool {
+        self.instances.get(template.framestep_name())
+            .map(|instances| instances.contains_key(&id))
+            .unwrap_or(false)
+    }
+
+    /// The ids of all instances of the given template, in ascending order
+    pub fn ids(&self, template: impl FramestepLabel) -> impl Iterator<Item = u64> + '_ {
+        self.instances.get(template.framestep_name())
+            .into_iter()
+            .flat_map(|instances| instances.keys().copied())
+    }
+
+    /// The number of instances of the given template
+    pub fn count(&self, template: impl FramestepLabel) -> usize {
+        self.instances.get(template.framestep_name())
+            .map(BTreeMap::len)
+            .unwrap_or(0)
+    }
+
+    /// Returns a reference to the framestep info of an instance
+    ///
+    /// Returns [`None`] if the instance does not exist, has not run yet, or is
+    /// currently running (use [`FixedFramesteps`] from inside the instance).
+    pub fn get(&self, template: impl FramestepLabel, id: u64) -> Option<&FixedFramestepInfo> {
+        let template = template.framestep_name();
+        self.instances.get(template)?
+            .get(&id)?
+            .info.get(template)
+    }
+
+    /// Returns a mut reference to the framestep info of an instance
+    ///
+    /// Use it to pause/unpause the instance, change its step, etc.
+    /// Returns [`None`] in the same cases as [`get`](Self::get).
+    pub fn get_mut(&mut self, template: impl FramestepLabel, id: u64) -> Option<&mut FixedFramestepInfo> {
+        let template = template.framestep_name();
+        self.instances.get_mut(template)?
+            .get_mut(&id)?
+            .info.get_mut(template)
+    }
+
+    /// The id of the instance that is currently running
+    ///
+    /// Returns [`Some`] only if called inside an instanced fixed framestep.
+    pub fn current(&self) -> Option<u64> {
+        self.current
+    }
+
+    fn state_mut(&mut self, template: FramestepName, id: u64) -> Option<&mut InstanceState> {
+        self.instances.get_mut(template)?.get_mut(&id)
+    }
+}
+
+/// Stage that drives all the instances of a template fixed framestep
+///
+/// See the [module docs](self) for how to use it.
+pub struct InstancedFramestepStage {
+    pub(super) template: FixedFramestepStage,
+}
+
+impl InstancedFramestepStage {
+    /// Create an instanced fixed framestep, from the given template
+    ///
+    /// The template is not run on its own; only its instances are.
+    pub fn new(template: FixedFramestepStage) -> Self {
+        Self { template }
+    }
+
+    /// Get access to the template fixed framestep
+    pub fn template(&self) -> &FixedFramestepStage {
+        &self.template
+    }
+
+    /// Get mut access to the template fixed framestep
+    pub fn template_mut(&mut self) -> &mut FixedFramestepStage {
+        &mut self.template
+    }
+
+    /// Move the saved state of an instance into the places where the fixed framesteps look for it
+    fn swap_in(world: &mut World, labels: &[FramestepName], mut state: InstanceState) {
+        let mut framesteps = world.get_resource_or_insert_with(FixedFramesteps::default);
+        for label in labels {
+            let id = FramestepId::new(label);
+            match state.info.remove(label) {
+                Some(info) => framesteps.info.insert(id, info),
+                None => framesteps.info.remove(&id),
+            };
+        }
+        let mut queue = world.get_resource_or_insert_with(TickCommandQueue::default);
+        for label in labels {
+            queue.put_queue(label, state.commands.remove(label).unwrap_or_default());
+        }
+    }
+
+    /// Take the state of the instance that just ran back out of the world
+    fn swap_out(world: &mut World, labels: &[FramestepName]) -> InstanceState {
+        let mut state = InstanceState::default();
+        let mut framesteps = world.resource_mut::<FixedFramesteps>();
+        for label in labels {
+            if let Some(info) = framesteps.info.remove(&FramestepId::new(label)) {
+                state.info.insert(label, info);
+            }
+        }
+        let mut queue = world.resource_mut::<TickCommandQueue>();
+        for label in labels {
+            state.commands.insert(label, queue.take_queue(label));
+        }
+        state
+    }
+}
+
+impl Stage for InstancedFramestepStage {
+    fn run(&mut self, world: &mut World) {
+        let label = self.template.label;
+        let ids: Vec<u64> = match world.get_resource::<FramestepInstances>() {
+            Some(instances) => instances.ids(label).collect(),
+            None => return,
+        };
+
+        let mut labels = Vec::new();
+        self.template.collect_labels(&mut labels);
+
+        for id in ids {
+            let state = {
+                let mut instances = world.resource_mut::<FramestepInstances>();
+                let state = match instances.state_mut(label, id) {
+                    Some(state) => std::mem::take(state),
+                    None => continue,
+                };
+                instances.current = Some(id);
+                state
+            };
+            let spawn_paused = state.spawn_paused;
+            Self::swap_in(world, &labels, state);
+
+            if spawn_paused {
+                // start from the initial parameters, but paused
+                self.template.load_world_state(world);
+                self.template.pause_held = true;
+                self.template.store_fixedframestepinfo(world)
+                    .info.get_mut(&self.template.id).unwrap()
+                    .pause();
+            }
+
+            self.template.run(world);
+
+            let state = Self::swap_out(world, &labels);
+            let mut instances = world.resource_mut::<FramestepInstances>();
+            instances.current = None;
+            // the instance might have been despawned while it was running
+            if let Some(slot) = instances.state_mut(label, id) {
+                *slot = state;
+            }
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Suspending the ticking of entities that are far from everything interesting
+//!
+//! In open worlds, most entities are too far from the camera or the players to matter.
+//! Add the [`TickInterestPlugin`] for a fixed framestep, put a [`TickInterest`] component
+//! on the anchors (the camera, the players, ...), and an [`InterestManaged`] component
+//! (together with an [`UpdateRate`]) on the entities that may be suspended.
+//!
+//! At the start of every tick, entities that are outside of the radius of every anchor
+//! get [`UpdateRate::Suspended`], so [`DueThisTick`](super::lod::DueThisTick) skips them.
+//! When they come back in range, their previous rate is restored, and they get a
+//! [`Reactivated`] component that tells your systems how many ticks were missed, so
+//! they can catch up.
+//!
+//! Catching up tick by tick would defeat the purpose. Instead, register a *coarse catch-up*
+//! callback with [`TickInterestPlugin::with_catch_up`], which simulates all the missed ticks
+//! at once (say, growing crops by `missed_ticks` steps). It is called for every reactivated
+//! entity, right when it comes back, before any systems of the tick run. Add a
+//! [`LastTicked`](super::lod::LastTicked) component to entities to also know the last tick
+//! they were updated on.
+//!
+//! Distances are measured between the `GlobalTransform`s; entities without one are not
+//! managed. Requires the `"framestep-interest"` cargo feature.
+
+use std::sync::Arc;
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_transform::prelude::GlobalTransform;
+use bevy_utils::HashMap;
+
+use super::lod::{LastTicked, UpdateRate};
+use super::{FixedFramesteps, FramestepLabel, FramestepName, find_framestep_mut};
+
+/// Component for anchors: entities around which other entities keep ticking
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Component)]
+pub struct TickInterest {
+    /// Entities within this distance keep ticking
+    pub radius: f32,
+}
+
+/// Component for entities that are suspended while far from every [`TickInterest`] anchor
+///
+/// The entity also needs an [`UpdateRate`] and a `GlobalTransform`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[derive(Component)]
+pub struct InterestManaged {
+    resume_rate: UpdateRate,
+    suspended_at: Option<u64>,
+}
+
+impl InterestManaged {
+    /// Is the entity suspended right now?
+    pub fn is_suspended(&self) -> bool {
+        self.suspended_at.is_some()
+    }
+
+    /// The tick on which the entity was suspended, if it is suspended
+    pub fn suspended_at(&self) -> Option<u64> {
+        self.suspended_at
+    }
+}
+
+/// Component added to an entity when it comes back in range of a [`TickInterest`] anchor
+///
+/// Added at the start of the first tick after it came back, after the [catch-up
+/// callback](TickInterestPlugin::with_catch_up), if any. Stays until you remove it (or the
+/// entity is reactivated again), so your systems can catch up on the missed ticks, and
+/// then remove it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Component)]
+pub struct Reactivated {
+    /// The tick on which the entity was suspended
+    pub suspended_at: u64,
+    /// The number of ticks that the entity missed
+    pub missed_ticks: u64,
+}
+
+/// A coarse catch-up callback (see [`TickInterestPlugin::with_catch_up`])
+pub type CatchUpCallback = Arc<dyn Fn(&mut World, Entity, Reactivated) + Send + Sync>;
+
+/// Plugin for suspending far-away entities on the ticks of a fixed framestep
+///
+/// See the [module docs](self). The fixed framestep must already be added to the app.
+#[derive(Clone)]
+pub struct TickInterestPlugin {
+    framestep: FramestepName,
+    catch_up: Option<CatchUpCallback>,
+}
+
+impl TickInterestPlugin {
+    /// Create the plugin for the given fixed framestep
+    pub fn new(framestep: impl FramestepLabel) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            catch_up: None,
+        }
+    }
+
+    /// Builder method for setting the coarse catch-up callback
+    ///
+    /// Called with each entity that comes back in range, and how many ticks it missed, so
+    /// that it can simulate them all at once. The [`Reactivated`] component is inserted
+    /// after the callback returns.
+    pub fn with_catch_up(mut self, callback: impl Fn(&mut World, Entity, Reactivated) + Send + Sync + 'static) -> Self {
+        self.catch_up = Some(Arc::new(callback));
+        self
+    }
+}
+
+impl Plugin for TickInterestPlugin {
+    fn build(&self, app: &mut App) {
+        if !app.world.contains_resource::<InterestState>() {
+            let state = InterestState {
+                anchors: app.world.query(),
+                managed: app.world.query(),
+                last_ticked: app.world.query(),
+                catch_up: HashMap::default(),
+            };
+            app.insert_resource(state);
+        }
+        if let Some(callback) = self.catch_up.clone() {
+            app.world.resource_mut::<InterestState>().catch_up.insert(self.framestep, callback);
+        }
+        find_framestep_mut(&mut app.schedule, self.framestep)
+            .expect("Fixed Framestep Stage not found")
+            .add_tick_begin_hook(update_tick_interest);
+    }
+}
+
+type ManagedQuery = (Entity, &'static GlobalTransform, &'static mut InterestManaged, &'static mut UpdateRate);
+
+/// The cached queries and catch-up callbacks
+#[derive(Resource)]
+struct InterestState {
+    anchors: QueryState<(&'static GlobalTransform, &'static TickInterest)>,
+    managed: QueryState<ManagedQuery>,
+    last_ticked: QueryState<(Entity, &'static UpdateRate, &'static mut LastTicked)>,
+    catch_up: HashMap<FramestepName, CatchUpCallback>,
+}
+
+fn update_tick_interest(world: &mut World) {
+    let (label, tick) = {
+        let info = world.resource::<FixedFramesteps>().current();
+        (info.label(), info.tick())
+    };
+    world.resource_scope(|world, mut state: Mut<InterestState>| {
+        let anchors: Vec<_> = state.anchors.iter(world)
+            .map(|(transform, interest)| (transform.translation(), interest.radius))
+            .collect();
+        let mut reactivated = Vec::new();
+        for (entity, transform, mut managed, mut rate) in state.managed.iter_mut(world) {
+            let position = transform.translation();
+            let in_range = anchors.iter()
+                .any(|(anchor, radius)| anchor.distance_squared(position) <= radius * radius);
+            match (in_range, managed.suspended_at) {
+                (false, None) => {
+                    managed.resume_rate = *rate;
+                    managed.suspended_at = Some(tick);
+                    *rate = UpdateRate::Suspended;
+                }
+                (true, Some(suspended_at)) => {
+                    managed.suspended_at = None;
+                    *rate = managed.resume_rate;
+                    reactivated.push((entity, Reactivated {
+                        suspended_at,
+                        missed_ticks: tick - suspended_at,
+                    }));
+                }
+                _ => {}
+            }
+        }
+        for (entity, reactivated) in reactivated {
+            if let Some(callback) = state.catch_up.get(label).cloned() {
+                callback(world, entity, reactivated);
+            }
+            if let Some(mut entity) = world.get_entity_mut(entity) {
+                entity.insert(reactivated);
+            }
+        }
+        for (entity, rate, mut last_ticked) in state.last_ticked.iter_mut(world) {
+            if rate.is_due(entity, tick) {
+                last_ticked.0 = tick;
+            }
+        }
+    });
+}
@@ -0,0 +1,259 @@
+//! A write-ahead journal of the inputs of every tick, for recovering from crashes
+//!
+//! Add the [`TickJournalPlugin`] for a fixed framestep and your [`ReplayInput`] type, to
+//! append the input of every tick to a file on disk at the start of the tick, before any
+//! of its systems run. Every tick is written with one call to the OS, so that the file
+//! survives the game crashing (with [`with_sync`](TickJournalPlugin::with_sync), it also
+//! survives the OS crashing, at the cost of waiting for the disk on every tick).
+//!
+//! After a crash, load the last savegame, [read](read_journal) the journal, and play back
+//! the ticks since that savegame, with the [`ReplayPlayerPlugin`](super::replay::ReplayPlayerPlugin)
+//! (see [`Replay::since`]). If your simulation is deterministic, that brings it back to
+//! the state of the last tick that started before the crash.
+//!
+//! To keep the journal short, [restart](TickJournal::restart) it after every save.
+//!
+//! # Format
+//!
+//! Like the replay format (see the [`replay`](super::replay) module), but with the magic
+//! bytes `IYLJ` and the journal format version ([`JOURNAL_FORMAT_VERSION`]), and without
+//! the number of ticks: the ticks follow the header until the end of the file. An
+//! incomplete tick at the end (from a crash in the middle of writing it) is ignored.
+
+use std::fs::File;
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_utils::tracing::warn;
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName, find_framestep_mut};
+use super::replay::{ChecksumFn, Reader, Replay, ReplayError, ReplayHeader, ReplayInput, ReplayTick};
+
+/// The version of the journal format written by the [`TickJournalPlugin`]
+pub const JOURNAL_FORMAT_VERSION: u16 = 1;
+
+const MAGIC: &[u8; 4] = b"IYLJ";
+
+/// Read a journal written by the [`TickJournalPlugin`], as a [`Replay`]
+///
+/// An incomplete tick at the end of the file is dropped.
+pub fn read_journal(path: impl AsRef<Path>) -> Result<Replay, JournalError> {
+    let bytes = std::fs::read(path).map_err(JournalError::Io)?;
+    journal_from_bytes(&bytes).map_err(JournalError::Format)
+}
+
+fn journal_from_bytes(bytes: &[u8]) -> Result<Replay, ReplayError> {
+    let mut reader = Reader { bytes };
+    if reader.take(4)? != MAGIC {
+        return Err(ReplayError::BadMagic);
+    }
+    let version = reader.u16()?;
+    if version != JOURNAL_FORMAT_VERSION {
+        return Err(ReplayError::Version(version));
+    }
+    let header = reader.header()?;
+    let mut ticks = Vec::new();
+    while !reader.bytes.is_empty() {
+        match reader.tick() {
+            Ok(tick) => ticks.push(tick),
+            Err(ReplayError::Truncated) => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(Replay { header, ticks })
+}
+
+/// Error from [`read_journal`]
+#[derive(Debug)]
+pub enum JournalError {
+    /// The file could not be read
+    Io(std::io::Error),
+    /// The file is not a valid journal
+    Format(ReplayError),
+}
+
+impl std::fmt::Display for JournalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JournalError::Io(e) => write!(f, "could not read the journal: {}", e),
+            JournalError::Format(e) => write!(f, "invalid journal: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for JournalError {}
+
+/// Resource with the journal being written by the [`TickJournalPlugin`]
+#[derive(Resource)]
+pub struct TickJournal<I: ReplayInput> {
+    path: PathBuf,
+    // `None` if the file could not be written
+    file: Option<File>,
+    header: ReplayHeader,
+    // the ticks in the file, for restarting it
+    ticks: Vec<ReplayTick>,
+    checksum: Option<ChecksumFn>,
+    sync: bool,
+    marker: PhantomData<fn() -> I>,
+}
+
+impl<I: ReplayInput> TickJournal<I> {
+    /// The file that the journal is written to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Is the journal being written? (`false` after an error, which was logged)
+    pub fn is_writing(&self) -> bool {
+        self.file.is_some()
+    }
+
+    /// The ticks written to the journal, since it was (re)started
+    pub fn ticks(&self) -> &[ReplayTick] {
+        &self.ticks
+    }
+
+    /// Start the journal over from the given tick, keeping only the later ticks
+    ///
+    /// Call this after saving the game after that tick, so that the journal only holds
+    /// the ticks since the savegame. The file is replaced atomically: it is written next
+    /// to the old one, which is then replaced. Writing resumes if it had stopped.
+    pub fn restart(&mut self, tick: u64) -> std::io::Result<()> {
+        self.header.start_tick = tick;
+        self.ticks.retain(|record| record.tick > tick);
+        let mut bytes = Vec::new();
+        self.write_header(&mut bytes);
+        for record in self.ticks.iter() {
+            record.write(&mut bytes);
+        }
+        let mut temp = self.path.clone().into_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        let mut file = File::create(&temp)?;
+        file.write_all(&bytes)?;
+        file.sync_data()?;
+        std::fs::rename(&temp, &self.path)?;
+        self.file = Some(std::fs::OpenOptions::new().append(true).open(&self.path)?);
+        Ok(())
+    }
+
+    fn write_header(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&JOURNAL_FORMAT_VERSION.to_le_bytes());
+        self.header.write(out);
+    }
+
+    fn append(&mut self, record: ReplayTick) -> std::io::Result<()> {
+        let file = match self.file.as_mut() {
+            Some(file) => file,
+            None => return Ok(()),
+        };
+        let mut bytes = Vec::new();
+        record.write(&mut bytes);
+        file.write_all(&bytes)?;
+        if self.sync {
+            file.sync_data()?;
+        }
+        self.ticks.push(record);
+        Ok(())
+    }
+}
+
+/// Plugin for journaling the input of every tick of a fixed framestep (see the [module docs](self))
+///
+/// The fixed framestep must already be added to the app. The file is created (or
+/// replaced) when the plugin is added; if that fails, the error is logged, and nothing
+/// is journaled. The journal is in the [`TickJournal<I>`] resource.
+pub struct TickJournalPlugin<I: ReplayInput> {
+    framestep: FramestepName,
+    path: PathBuf,
+    seed: u64,
+    checksum: Option<ChecksumFn>,
+    sync: bool,
+    marker: PhantomData<fn() -> I>,
+}
+
+impl<I: ReplayInput> TickJournalPlugin<I> {
+    /// Create the plugin to journal the given fixed framestep into the given file
+    pub fn new(framestep: impl FramestepLabel, path: impl Into<PathBuf>) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            path: path.into(),
+            seed: 0,
+            checksum: None,
+            sync: false,
+            marker: PhantomData,
+        }
+    }
+
+    /// Builder method for storing the seed of your random number generators in the journal
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Builder method for also journaling a checksum of the world at the start of every tick
+    ///
+    /// Pass the same function to the `ReplayPlayerPlugin` on recovery, to check that the
+    /// simulation recovered correctly.
+    pub fn with_checksum(mut self, checksum: ChecksumFn) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Builder method for waiting for every tick to reach the disk (see the [module docs](self))
+    pub fn with_sync(mut self, sync: bool) -> Self {
+        self.sync = sync;
+        self
+    }
+}
+
+impl<I: ReplayInput> Plugin for TickJournalPlugin<I> {
+    fn build(&self, app: &mut App) {
+        let info = app.world.resource::<FixedFramesteps>().get(self.framestep)
+            .expect("Fixed Framestep not found");
+        let mut journal = TickJournal::<I> {
+            path: self.path.clone(),
+            file: None,
+            header: ReplayHeader {
+                framestep: self.framestep.to_string(),
+                step: info.step,
+                seed: self.seed,
+                start_tick: info.tick(),
+            },
+            ticks: Vec::new(),
+            checksum: self.checksum,
+            sync: self.sync,
+            marker: PhantomData,
+        };
+        let start_tick = journal.header.start_tick;
+        if let Err(e) = journal.restart(start_tick) {
+            warn!("Could not create the tick journal {:?}: {}", self.path, e);
+        }
+        app.insert_resource(journal);
+        find_framestep_mut(&mut app.schedule, self.framestep)
+            .expect("Fixed Framestep Stage not found")
+            .add_tick_begin_hook(journal_tick::<I>);
+    }
+}
+
+fn journal_tick<I: ReplayInput>(world: &mut World) {
+    let checksum = match world.get_resource::<TickJournal<I>>() {
+        Some(journal) if journal.is_writing() => journal.checksum,
+        _ => return,
+    };
+    let tick = world.resource::<FixedFramesteps>().current().tick();
+    let mut input = Vec::new();
+    if let Some(res) = world.get_resource::<I>() {
+        res.encode(&mut input);
+    }
+    let checksum = checksum.map(|checksum| checksum(world));
+    let mut journal = world.resource_mut::<TickJournal<I>>();
+    if let Err(e) = journal.append(ReplayTick { tick, input, checksum }) {
+        warn!("Could not write tick {} to the tick journal {:?}, no longer journaling: {}", tick, journal.path, e);
+        journal.file = None;
+    }
+}
@@ -0,0 +1,107 @@
+//! Debug-only linter for non-deterministic inputs of fixed framestep systems
+//!
+//! Lockstep and rollback simulations desync if any tick system reads something that
+//! is different on every machine, like the wall-clock `Time`. Enable the linter on a
+//! fixed framestep with [`FixedFramestepStage::with_determinism_lint`](super::FixedFramestepStage::with_determinism_lint).
+//! After its first tick (when all the systems have been initialized), it inspects the
+//! access of every system in its `SystemStage` sub-stages, and logs a warning for each
+//! system that reads one of the [`NondeterministicResources`].
+//!
+//! The linter only runs in debug builds. It cannot see inside exclusive systems (they
+//! have access to the whole world), custom sub-stages, or systems added after the first tick.
+
+use std::any::TypeId;
+use std::borrow::Cow;
+
+use bevy_ecs::prelude::*;
+
+/// Resource with the list of resources that the determinism linter warns about
+///
+/// By default, this contains Bevy's `Time` and this crate's `FixedTimesteps` (if those
+/// cargo features are enabled). Register your own, like `Instant`-based resources or
+/// wrappers around a thread-local RNG, using [`register`](Self::register). If this
+/// resource is not present in the world, the defaults are used.
+#[derive(Resource)]
+pub struct NondeterministicResources {
+    resources: Vec<(TypeId, &'static str)>,
+}
+
+impl Default for NondeterministicResources {
+    fn default() -> Self {
+        let mut resources = Self::empty();
+        #[cfg(feature = "bevy_time")]
+        resources.register::<bevy_time::Time>();
+        #[cfg(feature = "fixedtimestep")]
+        resources.register::<crate::fixedtimestep::FixedTimesteps>();
+        resources
+    }
+}
+
+impl NondeterministicResources {
+    /// Create an empty list, without the defaults
+    pub fn empty() -> Self {
+        Self {
+            resources: Vec::new(),
+        }
+    }
+
+    /// Add a resource type to the list
+    pub fn register<R: Resource>(&mut self) {
+        let type_id = TypeId::of::<R>();
+        if !self.contains_type(type_id) {
+            self.resources.push((type_id, std::any::type_name::<R>()));
+        }
+    }
+
+    /// Builder-style method for [`register`](Self::register)
+    pub fn with<R: Resource>(mut self) -> Self {
+        self.register::<R>();
+        self
+    }
+
+    /// Is the resource type in the list?
+    pub fn contains<R: Resource>(&self) -> bool {
+        self.contains_type(TypeId::of::<R>())
+    }
+
+    fn contains_type(&self, type_id: TypeId) -> bool {
+        self.resources.iter().any(|(id, _)| *id == type_id)
+    }
+}
+
+/// Find the systems in a `SystemStage` that read any of the [`NondeterministicResources`]
+///
+/// Returns the pairs of (system name, resource type name). The stage must have run at
+/// least once, for its systems to be initialized.
+pub fn nondeterministic_reads(world: &World, stage: &SystemStage) -> Vec<(Cow<'static, str>, &'static str)> {
+    let default;
+    let resources = match world.get_resource::<NondeterministicResources>() {
+        Some(resources) => resources,
+        None => {
+            default = NondeterministicResources::default();
+            &default
+        }
+    };
+    // resources that no system uses may not have an id yet
+    let ids: Vec<_> = resources.resources.iter()
+        .filter_map(|(type_id, name)| world.components().get_resource_id(*type_id).map(|id| (id, *name)))
+        .collect();
+
+    let mut reads = Vec::new();
+    let systems = stage.exclusive_at_start_systems().iter()
+        .chain(stage.parallel_systems())
+        .chain(stage.exclusive_before_commands_systems())
+        .chain(stage.exclusive_at_end_systems());
+    for container in systems {
+        let access = container.system().component_access();
+        if access.has_read_all() {
+            continue;
+        }
+        for (id, name) in ids.iter() {
+            if access.has_read(*id) {
+                reads.push((container.system().name(), *name));
+            }
+        }
+    }
+    reads
+}
@@ -0,0 +1,70 @@
+//! Per-framestep local storage for systems
+//!
+//! A `Local<T>` belongs to the system: it keeps its value across every tick of every
+//! fixed framestep that the system runs in, and across [resets](super::FixedFramestepInfo::reset)
+//! and [rewinds](super::FixedFramestepInfo::rewind). Use [`TickLocal<T>`] instead for
+//! working state that belongs to the simulation: it keeps a separate value for each fixed
+//! framestep (and each [instance](super::instances) of it), and goes back to
+//! `T::default()` whenever that fixed framestep is reset, rewound, or created anew.
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+use bevy_utils::HashMap;
+
+use super::instances::FramestepInstances;
+use super::{FixedFramesteps, FramestepName};
+
+/// The values of a [`TickLocal`], for each fixed framestep (and instance)
+pub struct TickLocalState<T> {
+    values: HashMap<(FramestepName, Option<u64>), (u64, T)>,
+}
+
+impl<T> Default for TickLocalState<T> {
+    fn default() -> Self {
+        Self {
+            values: HashMap::default(),
+        }
+    }
+}
+
+/// System parameter for local state that persists across the ticks of a fixed framestep
+///
+/// Like `Local<T>`, but isolated per fixed framestep, and reset along with it
+/// (see the [module docs](self)).
+/// Panics if used outside of a fixed framestep (like [`FixedFramesteps::current`]).
+///
+/// It reads the [`FixedFramesteps`] resource, so it conflicts with `ResMut<FixedFramesteps>`
+/// in the same system.
+#[derive(SystemParam)]
+pub struct TickLocal<'w, 's, T: Default + Send + Sync + 'static> {
+    framesteps: Res<'w, FixedFramesteps>,
+    instances: Option<Res<'w, FramestepInstances>>,
+    state: Local<'s, TickLocalState<T>>,
+}
+
+impl<'w, 's, T: Default + Send + Sync + 'static> TickLocal<'w, 's, T> {
+    /// Get the value for the current fixed framestep
+    pub fn get(&mut self) -> &T {
+        self.get_mut()
+    }
+
+    /// Get mut access to the value for the current fixed framestep
+    pub fn get_mut(&mut self) -> &mut T {
+        let info = self.framesteps.get_current()
+            .expect("TickLocal can only be used when running inside a fixed framestep.");
+        let key = (info.label(), self.instances.as_ref().and_then(|instances| instances.current()));
+        let generation = info.generation;
+        let (value_generation, value) = self.state.values.entry(key)
+            .or_insert_with(|| (generation, T::default()));
+        if *value_generation != generation {
+            *value_generation = generation;
+            *value = T::default();
+        }
+        value
+    }
+
+    /// Replace the value for the current fixed framestep, returning the old one
+    pub fn replace(&mut self, value: T) -> T {
+        std::mem::replace(self.get_mut(), value)
+    }
+}
@@ -0,0 +1,229 @@
+//! Lockstep networking: holding back the ticks until the inputs of all players are in
+//!
+//! In a lockstep model, every peer runs the same deterministic simulation, and only the
+//! player commands are sent over the network. Each command is tagged with the tick it
+//! must be executed on (usually a few ticks in the future, to hide the latency), and no
+//! peer may run a tick before it has the commands of *every* player for it, even if
+//! that player had nothing to do.
+//!
+//! Add the [`LockstepPlugin`] for a fixed framestep, and feed the [`LockstepInbox`]
+//! resource with the commands of all players, including the local one, as they arrive
+//! (see [`LockstepInbox::receive`]). Before every tick, the fixed framestep checks the
+//! inbox: if the commands of some player are still missing, it stalls (see
+//! [`Suppression::Stalled`]), and runs the tick on a later frame, once they have arrived.
+//! The ticks are not lost while stalling: they stay in the accumulator, and are caught
+//! up on afterwards.
+//!
+//! With a [deadline](LockstepPlugin::with_deadline), a tick only waits for so many
+//! frames; after that, the missing players are assumed to have sent no commands, and
+//! the tick runs anyway (it is *padded*). This keeps a game going when a player has
+//! dropped out.
+//!
+//! Your tick systems read the commands of the current tick using
+//! [`LockstepInbox::current`].
+//!
+//! [`Suppression::Stalled`]: super::Suppression::Stalled
+
+use std::collections::BTreeMap;
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName, find_framestep_mut};
+
+/// Resource with the commands of all players, by tick (see the [module docs](self))
+#[derive(Resource)]
+pub struct LockstepInbox<C: Send + Sync + 'static> {
+    framestep: FramestepName,
+    players: usize,
+    deadline: Option<u32>,
+    // `None` for the players whose commands have not arrived yet
+    ticks: BTreeMap<u64, Vec<Option<Vec<C>>>>,
+    // the tick that is running, or ran last
+    started: u64,
+    waiting: Option<(u64, u32)>,
+    padded_ticks: u64,
+}
+
+impl<C: Send + Sync + 'static> LockstepInbox<C> {
+    /// Create an empty inbox for the given number of players (numbered from `0`)
+    fn new(framestep: FramestepName, players: usize, deadline: Option<u32>) -> Self {
+        Self {
+            framestep,
+            players,
+            deadline,
+            ticks: BTreeMap::new(),
+            started: 0,
+            waiting: None,
+            padded_ticks: 0,
+        }
+    }
+
+    /// The fixed framestep that this inbox holds back
+    pub fn framestep(&self) -> FramestepName {
+        self.framestep
+    }
+
+    /// The number of players
+    pub fn players(&self) -> usize {
+        self.players
+    }
+
+    /// Store all the commands of a player for the given tick
+    ///
+    /// Call this once per player and tick, with an empty list if the player did nothing.
+    /// Returns `false` (and drops the commands) if that tick has already started, or if
+    /// the commands of that player for that tick were already received.
+    ///
+    /// Panics if `player` is out of range.
+    pub fn receive(&mut self, player: usize, tick: u64, commands: Vec<C>) -> bool {
+        assert!(player < self.players, "Lockstep player {} out of range (there are {} players).", player, self.players);
+        if tick <= self.started {
+            return false;
+        }
+        let players = self.players;
+        let slot = &mut self.ticks.entry(tick)
+            .or_insert_with(|| std::iter::repeat_with(|| None).take(players).collect())[player];
+        if slot.is_some() {
+            return false;
+        }
+        *slot = Some(commands);
+        true
+    }
+
+    /// Have the commands of all players for the given tick arrived?
+    pub fn is_ready(&self, tick: u64) -> bool {
+        self.ticks.get(&tick)
+            .map(|players| players.iter().all(Option::is_some))
+            .unwrap_or(false)
+    }
+
+    /// The players whose commands for the given tick have not arrived yet
+    pub fn missing_players(&self, tick: u64) -> Vec<usize> {
+        match self.ticks.get(&tick) {
+            Some(players) => players.iter()
+                .enumerate()
+                .filter(|(_, commands)| commands.is_none())
+                .map(|(player, _)| player)
+                .collect(),
+            None => (0..self.players).collect(),
+        }
+    }
+
+    /// The commands of the given tick, with the player they came from, in player order
+    pub fn commands(&self, tick: u64) -> impl Iterator<Item = (usize, &C)> {
+        self.ticks.get(&tick)
+            .into_iter()
+            .flat_map(|players| players.iter().enumerate())
+            .flat_map(|(player, commands)| commands.iter().flatten().map(move |command| (player, command)))
+    }
+
+    /// The commands of the tick that is running (or ran last), in player order
+    pub fn current(&self) -> impl Iterator<Item = (usize, &C)> {
+        self.commands(self.started)
+    }
+
+    /// For how many frames the next tick has been waiting for commands (`0` if it is not waiting)
+    pub fn stalled_frames(&self) -> u32 {
+        match self.waiting {
+            Some((tick, frames)) if tick == self.started + 1 => frames,
+            _ => 0,
+        }
+    }
+
+    /// How many ticks ran without the commands of some players, after the deadline
+    pub fn padded_ticks(&self) -> u64 {
+        self.padded_ticks
+    }
+
+    /// Can the given tick run? Pads it if the deadline has passed.
+    fn check(&mut self, tick: u64) -> bool {
+        if self.is_ready(tick) {
+            self.waiting = None;
+            return true;
+        }
+        let frames = match self.waiting {
+            Some((waiting, frames)) if waiting == tick => frames + 1,
+            _ => 1,
+        };
+        self.waiting = Some((tick, frames));
+        match self.deadline {
+            Some(deadline) if frames > deadline => {
+                let players = self.players;
+                let slots = self.ticks.entry(tick)
+                    .or_insert_with(|| std::iter::repeat_with(|| None).take(players).collect());
+                for slot in slots.iter_mut().filter(|slot| slot.is_none()) {
+                    *slot = Some(Vec::new());
+                }
+                self.waiting = None;
+                self.padded_ticks += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Start a tick: drop the commands of the earlier ticks
+    fn start(&mut self, tick: u64) {
+        self.started = tick;
+        self.ticks = self.ticks.split_off(&tick);
+    }
+}
+
+/// Plugin for holding back the ticks of a fixed framestep until all player commands are in
+///
+/// See the [module docs](self). The fixed framestep must already be added to the app.
+/// `C` is your command type; add one plugin per fixed framestep, with different types.
+pub struct LockstepPlugin<C> {
+    framestep: FramestepName,
+    players: usize,
+    deadline: Option<u32>,
+    marker: std::marker::PhantomData<fn() -> C>,
+}
+
+impl<C: Send + Sync + 'static> LockstepPlugin<C> {
+    /// Create the plugin for the given fixed framestep and number of players
+    pub fn new(framestep: impl FramestepLabel, players: usize) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            players,
+            deadline: None,
+            marker: std::marker::PhantomData,
+        }
+    }
+
+    /// Builder method for padding the ticks that have waited for more than `frames` frames
+    ///
+    /// Without a deadline, a tick waits for the commands of all players forever.
+    pub fn with_deadline(mut self, frames: u32) -> Self {
+        self.deadline = Some(frames);
+        self
+    }
+}
+
+impl<C: Send + Sync + 'static> Plugin for LockstepPlugin<C> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LockstepInbox::<C>::new(self.framestep, self.players, self.deadline));
+        let stage = find_framestep_mut(&mut app.schedule, self.framestep)
+            .expect("Fixed Framestep Stage not found");
+        stage.add_tick_gate(lockstep_gate::<C>);
+        stage.add_tick_begin_hook(lockstep_begin::<C>);
+    }
+}
+
+fn lockstep_gate<C: Send + Sync + 'static>(world: &mut World, label: FramestepName, tick: u64) -> bool {
+    match world.get_resource_mut::<LockstepInbox<C>>() {
+        Some(mut inbox) if inbox.framestep == label => inbox.check(tick),
+        _ => true,
+    }
+}
+
+fn lockstep_begin<C: Send + Sync + 'static>(world: &mut World) {
+    let info = world.resource::<FixedFramesteps>().current();
+    let (label, tick) = (info.label(), info.tick());
+    if let Some(mut inbox) = world.get_resource_mut::<LockstepInbox<C>>() {
+        if inbox.framestep == label {
+            inbox.start(tick);
+        }
+    }
+}
@@ -0,0 +1,138 @@
+//! Per-entity tick rates (LOD ticking), for huge worlds
+//!
+//! Give entities an [`UpdateRate`] component to have them updated on every tick, on
+//! every 2nd tick, or on every 4th tick of a fixed framestep. Your tick systems then
+//! skip the entities that are not due, using the [`DueThisTick`] system param:
+//!
+//! ```ignore
+//! use bevy::prelude::*;
+//! use iyes_loopless::prelude::*;
+//!
+//! #[derive(Component)]
+//! struct Velocity(Vec3);
+//!
+//! fn move_far_away_things(
+//!     due: DueThisTick,
+//!     mut query: Query<(Entity, &UpdateRate, &Velocity, &mut Transform)>,
+//! ) {
+//!     for (entity, rate, velocity, mut transform) in query.iter_mut() {
+//!         if !due.is_due(entity, rate) {
+//!             continue;
+//!         }
+//!         // cover all the ticks since the last update
+//!         transform.translation += velocity.0 * rate.ticks() as f32;
+//!     }
+//! }
+//! ```
+//!
+//! Each entity is assigned a stable phase, derived from its `Entity` id, so the entities
+//! with the same rate are spread evenly over the ticks, instead of all being updated on
+//! the same one. The phase never changes while the entity is alive, and depends on
+//! nothing but the id: the same entities are due on the same ticks on every run.
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+
+use super::{FixedFramesteps, mix64};
+
+/// Component for how often an entity is updated by the fixed framestep systems
+///
+/// See the [module docs](self). Entities without it should be treated as [`Full`](UpdateRate::Full).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Component)]
+pub enum UpdateRate {
+    /// Updated on every tick
+    #[default]
+    Full,
+    /// Updated on every 2nd tick
+    Half,
+    /// Updated on every 4th tick
+    Quarter,
+    /// Not updated at all (used by the `interest` module)
+    Suspended,
+}
+
+impl UpdateRate {
+    /// The number of ticks between the updates (`1`, `2`, or `4`; `0` if suspended)
+    ///
+    /// Multiply the per-tick changes by this, to make up for the skipped ticks.
+    pub fn ticks(self) -> u64 {
+        match self {
+            UpdateRate::Full => 1,
+            UpdateRate::Half => 2,
+            UpdateRate::Quarter => 4,
+            UpdateRate::Suspended => 0,
+        }
+    }
+
+    /// Is an entity with this rate due on the given tick?
+    pub fn is_due(self, entity: Entity, tick: u64) -> bool {
+        match self.ticks() {
+            0 => false,
+            ticks => (tick + phase(entity) % ticks) % ticks == 0,
+        }
+    }
+}
+
+/// Component with the last tick on which the entity was due
+///
+/// Updated at the start of every tick on which the entity is due (so, during a tick, it
+/// is the current tick if the entity is due). Only for entities managed by the
+/// `TickInterestPlugin` (see the `interest` module), or if you add [`track_last_ticked`]
+/// to your fixed framestep yourself. Start it at `0`, for "never".
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Component)]
+pub struct LastTicked(pub u64);
+
+/// System that updates the [`LastTicked`] of the entities that are due on the current tick
+///
+/// Add it at the start of the first sub-stage of a fixed framestep.
+pub fn track_last_ticked(due: DueThisTick, mut query: Query<(Entity, &UpdateRate, &mut LastTicked)>) {
+    let tick = due.tick();
+    for (entity, rate, mut last_ticked) in query.iter_mut() {
+        if rate.is_due(entity, tick) {
+            last_ticked.0 = tick;
+        }
+    }
+}
+
+/// The stable phase of an entity, which spreads the entities over the ticks
+///
+/// Hashed from the entity index, so that entities spawned together (or in a pattern)
+/// do not clump on the same ticks.
+fn phase(entity: Entity) -> u64 {
+    mix64(entity.index() as u64)
+}
+
+/// System parameter for checking if entities are due on the current tick (see the [module docs](self))
+///
+/// Panics if used outside of a fixed framestep (like [`FixedFramesteps::current`]).
+/// It reads the [`FixedFramesteps`] resource, so it conflicts with `ResMut<FixedFramesteps>`
+/// in the same system.
+#[derive(SystemParam)]
+pub struct DueThisTick<'w, 's> {
+    framesteps: Res<'w, FixedFramesteps>,
+    #[system_param(ignore)]
+    _marker: std::marker::PhantomData<&'s ()>,
+}
+
+impl<'w, 's> DueThisTick<'w, 's> {
+    /// Is the entity due on the current tick, with the given rate?
+    pub fn is_due(&self, entity: Entity, rate: &UpdateRate) -> bool {
+        rate.is_due(entity, self.tick())
+    }
+
+    /// Like [`is_due`](Self::is_due), for entities that may not have an [`UpdateRate`]
+    ///
+    /// Entities without one are always due.
+    pub fn is_due_opt(&self, entity: Entity, rate: Option<&UpdateRate>) -> bool {
+        rate.map(|rate| self.is_due(entity, rate)).unwrap_or(true)
+    }
+
+    /// The number of the current tick
+    pub fn tick(&self) -> u64 {
+        self.framesteps.get_current()
+            .expect("DueThisTick can only be used when running inside a fixed framestep.")
+            .tick()
+    }
+}
@@ -0,0 +1,110 @@
+//! Musical beat sync for fixed framesteps
+//!
+//! For rhythm games and other audio-synced gameplay. A [`Metronome`] converts tick numbers
+//! to beat positions, given the tempo (BPM) and the rate of the fixed framestep (ticks per
+//! second). Everything is calculated from the tick number itself, rather than accumulated
+//! tick by tick, so rounding errors do not build up into drift over long songs.
+//!
+//! Insert a [`Metronome`] resource and add the [`emit_beats`] system to your fixed
+//! framestep, to get a [`MetronomeBeat`] event on every tick where a new beat starts.
+//! Remember to register the event type: `app.add_event::<MetronomeBeat>()`.
+
+use bevy_ecs::prelude::*;
+
+use super::FixedFramesteps;
+
+/// Converts between fixed framestep tick numbers and musical beats
+#[derive(Debug, Clone, PartialEq)]
+#[derive(Resource)]
+pub struct Metronome {
+    /// Tempo, in beats per minute
+    pub bpm: f64,
+    /// The rate of the fixed framestep, in ticks per second
+    pub tick_rate: f64,
+    /// The tick on which beat `0` starts (when the music starts playing)
+    pub start_tick: u64,
+}
+
+impl Metronome {
+    /// Create a metronome with the given tempo and tick rate, with beat `0` on tick `0`
+    pub fn new(bpm: f64, tick_rate: f64) -> Self {
+        Self {
+            bpm,
+            tick_rate,
+            start_tick: 0,
+        }
+    }
+
+    /// Builder method for setting the tick on which beat `0` starts
+    pub fn starting_at(mut self, start_tick: u64) -> Self {
+        self.start_tick = start_tick;
+        self
+    }
+
+    /// The (fractional) number of ticks in each beat
+    pub fn ticks_per_beat(&self) -> f64 {
+        self.tick_rate * 60.0 / self.bpm
+    }
+
+    /// The (fractional) beat position at the given tick
+    ///
+    /// Negative before the start tick.
+    pub fn beat_at(&self, tick: u64) -> f64 {
+        (tick as f64 - self.start_tick as f64) / self.ticks_per_beat()
+    }
+
+    /// The number of the beat that is playing at the given tick
+    ///
+    /// Returns `None` before the start tick.
+    pub fn beat_index(&self, tick: u64) -> Option<u64> {
+        if tick < self.start_tick {
+            return None;
+        }
+        Some(self.beat_at(tick).floor() as u64)
+    }
+
+    /// Does a new beat start on the given tick?
+    ///
+    /// That is, the tick closest to (at or after) the exact start time of each beat.
+    pub fn is_beat_tick(&self, tick: u64) -> bool {
+        match (self.beat_index(tick), tick.checked_sub(1).and_then(|prev| self.beat_index(prev))) {
+            (Some(beat), Some(prev_beat)) => beat != prev_beat,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+
+    /// The first tick of the given beat
+    pub fn tick_of_beat(&self, beat: u64) -> u64 {
+        self.start_tick + (beat as f64 * self.ticks_per_beat()).ceil() as u64
+    }
+}
+
+/// Event sent by [`emit_beats`] on every tick where a new beat starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetronomeBeat {
+    /// The tick number
+    pub tick: u64,
+    /// The number of the beat that starts on this tick
+    pub beat: u64,
+}
+
+/// System that sends a [`MetronomeBeat`] on every tick where a new beat starts
+///
+/// Add it to your fixed framestep. Does nothing if there is no [`Metronome`] resource.
+pub fn emit_beats(
+    metronome: Option<Res<Metronome>>,
+    framesteps: Res<FixedFramesteps>,
+    mut beats: EventWriter<MetronomeBeat>,
+) {
+    let metronome = match metronome {
+        Some(metronome) => metronome,
+        None => return,
+    };
+    let tick = framesteps.current().tick();
+    if metronome.is_beat_tick(tick) {
+        if let Some(beat) = metronome.beat_index(tick) {
+            beats.send(MetronomeBeat { tick, beat });
+        }
+    }
+}
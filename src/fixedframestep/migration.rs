@@ -0,0 +1,99 @@
+//! Rescaling rate-dependent state when the step of a fixed framestep changes
+//!
+//! Gameplay values are often tuned for one tick rate: a velocity in units per tick, or a
+//! cooldown that lasts 20 ticks, only mean the same thing as long as the step stays the
+//! same. When the step is changed at runtime (say, from 3 to 1 frames, 20 to 60 Hz), they
+//! have to be rescaled.
+//!
+//! Register a [`RateMigration`] for each kind of such values, using
+//! [`FixedFramestepStage::add_rate_migration`](super::FixedFramestepStage::add_rate_migration)
+//! (or `add_fixed_framestep_rate_migration` on the `App`). When the fixed framestep sees
+//! that its step was changed, before it runs the next tick, it calls all of them with a
+//! [`RateChanged`], which has the factors to rescale by. The same [`RateChanged`] is then
+//! sent as an event, for anything that would rather react to it in a system.
+//!
+//! The migrations should not change the [`FixedFramesteps`](super::FixedFramesteps) resource.
+//! They are not called on a [reset](super::FixedFramestepInfo::reset), which restores the
+//! initial step along with everything else.
+//!
+//! For components, [`migrate_components`] makes a migration out of a function that rescales
+//! one component. [`TickCooldown`](super::cooldown::TickCooldown) has one already:
+//! `migrate_components(TickCooldown::rescale)`.
+
+use bevy_ecs::prelude::*;
+
+use super::{FramestepName, Frames, Ticks};
+
+/// The step of a fixed framestep was changed (see the [module docs](self))
+///
+/// Passed to the [`RateMigration`]s, and then sent as an event. Only sent if the event
+/// type has been registered; the `App` extension methods for adding fixed framesteps do
+/// that for you.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RateChanged {
+    /// The name of the fixed framestep
+    pub label: FramestepName,
+    /// The step before the change
+    pub old_step: Frames,
+    /// The step after the change
+    pub new_step: Frames,
+    /// The nominal rate before the change, in Hz (see [`FixedFramestepInfo::rate`](super::FixedFramestepInfo::rate))
+    pub old_rate: f64,
+    /// The nominal rate after the change, in Hz
+    pub new_rate: f64,
+}
+
+impl RateChanged {
+    /// The factor for values per tick, like a velocity in units per tick
+    ///
+    /// With a longer step, more happens in each tick.
+    pub fn per_tick_factor(&self) -> f64 {
+        self.new_step.0 as f64 / self.old_step.0 as f64
+    }
+
+    /// The factor for durations counted in ticks, like a cooldown
+    ///
+    /// With a longer step, fewer ticks take the same time.
+    pub fn tick_count_factor(&self) -> f64 {
+        self.old_step.0 as f64 / self.new_step.0 as f64
+    }
+
+    /// Rescale a value per tick (see [`per_tick_factor`](Self::per_tick_factor))
+    pub fn rescale_per_tick(&self, value: f32) -> f32 {
+        (value as f64 * self.per_tick_factor()) as f32
+    }
+
+    /// Rescale a duration counted in ticks, rounded to the nearest tick
+    ///
+    /// A duration that is not zero stays at least one tick long.
+    pub fn rescale_ticks(&self, ticks: Ticks) -> Ticks {
+        if ticks.is_zero() {
+            return Ticks::ZERO;
+        }
+        let rescaled = (ticks.0 as f64 * self.tick_count_factor()).round() as u64;
+        Ticks(rescaled.max(1))
+    }
+}
+
+/// A callback for rescaling rate-dependent state, when the step of a fixed framestep changes
+///
+/// Implemented for all `FnMut(&mut World, &RateChanged)` closures. See the [module docs](self).
+pub trait RateMigration: Send + Sync + 'static {
+    /// Rescale the state of the world, for the new step
+    fn migrate(&mut self, world: &mut World, change: &RateChanged);
+}
+
+impl<F: FnMut(&mut World, &RateChanged) + Send + Sync + 'static> RateMigration for F {
+    fn migrate(&mut self, world: &mut World, change: &RateChanged) {
+        self(world, change)
+    }
+}
+
+/// Make a [`RateMigration`] that calls `rescale` for every `C` component in the world
+pub fn migrate_components<C: Component>(rescale: fn(&mut C, &RateChanged)) -> impl RateMigration {
+    move |world: &mut World, change: &RateChanged| {
+        for mut component in world.query::<&mut C>().iter_mut(world) {
+            rescale(&mut component, change);
+        }
+    }
+}
@@ -0,0 +1,311 @@
+//! Glue between a network transport and lockstep fixed framesteps
+//!
+//! This is a thin adapter on top of the [`lockstep`](super::lockstep) module, for any
+//! transport (`bevy_renet`, `quinn`, plain UDP sockets, ...). It does not do any I/O or
+//! serialization itself: it only deals in [`NetMessage`]s. Your networking code
+//! deserializes the messages it receives and sends them as [`NetReceived`] events, and
+//! serializes and sends out the contents of the [`NetSend`] events. With the `serde`
+//! dependency enabled (say, by the `framestep-save` feature), the messages can be
+//! serialized with serde.
+//!
+//! Add the [`LockstepNetPlugin`] after the [`LockstepPlugin`](super::lockstep::LockstepPlugin)
+//! of the fixed framestep, and push the commands of the local player into the
+//! [`LockstepNet`] resource whenever they happen (see [`LockstepNet::push`]). Then:
+//!
+//!  - At the start of every tick, the commands pushed since the previous tick are stamped
+//!    with the tick they will run on (the current tick plus the
+//!    [input delay](LockstepNetPlugin::with_input_delay)), stored in the local
+//!    [`LockstepInbox`], and sent out as a [`NetMessage::Commands`] packet, even if there
+//!    were no commands. The input delay hides the latency: the packet has that many ticks
+//!    to reach the other peers, before they stall waiting for it.
+//!  - Every frame, in `CoreStage::PreUpdate`, the commands in the [`NetReceived`] events are
+//!    stored in the [`LockstepInbox`], so they are there before the fixed framestep checks it.
+//!  - Every few ticks (see [`with_sync_interval`](LockstepNetPlugin::with_sync_interval)), a
+//!    [`NetMessage::TickSync`] is sent out with the current tick. When a peer finds itself too
+//!    far ahead of the slowest peer (see [`with_max_lead`](LockstepNetPlugin::with_max_lead)),
+//!    it holds back one tick per frame, and drops one frame from the accumulator (using
+//!    [`stretch`](super::FixedFramestepInfo::stretch)), slowing down smoothly instead of
+//!    running ahead and stalling all the time.
+//!
+//! There are no packets for the first ticks (up to the input delay): all the peers start
+//! with no commands for them, for all players.
+//!
+//! The player numbers must be the same on all peers. The transport must deliver every
+//! [`NetMessage::Commands`] packet eventually (possibly out of order), or a lockstep deadline
+//! must be set, or the game stalls forever.
+
+use std::marker::PhantomData;
+
+use bevy_app::{App, CoreStage, Plugin};
+use bevy_ecs::prelude::*;
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName, find_framestep_mut};
+use super::lockstep::LockstepInbox;
+
+/// The commands of a player, stamped with the tick they run on
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TickPacket<C> {
+    /// The player that the commands came from
+    pub player: usize,
+    /// The tick that the commands run on
+    pub tick: u64,
+    /// The commands; empty if the player did nothing
+    pub commands: Vec<C>,
+}
+
+/// A message between the peers of a lockstep game
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NetMessage<C> {
+    /// The commands of a player for a tick
+    Commands(TickPacket<C>),
+    /// The tick that a player is at, for keeping the peers in sync
+    TickSync {
+        /// The player that the message came from
+        player: usize,
+        /// The tick that the player has started last
+        tick: u64,
+    },
+}
+
+/// Event with a message received from the network, to be handed to the fixed framestep
+///
+/// Send these from your networking code, after deserializing the messages.
+#[derive(Debug, Clone)]
+pub struct NetReceived<C>(pub NetMessage<C>);
+
+/// Event with a message to be sent to all the other peers
+///
+/// Read these from your networking code, then serialize and send the messages.
+#[derive(Debug, Clone)]
+pub struct NetSend<C>(pub NetMessage<C>);
+
+/// Resource with the local player's commands, and the state of the other peers
+#[derive(Resource)]
+pub struct LockstepNet<C: Send + Sync + 'static> {
+    framestep: FramestepName,
+    local_player: usize,
+    input_delay: u64,
+    sync_interval: u64,
+    max_lead: Option<u64>,
+    pending: Vec<C>,
+    // the last tick heard of from every player
+    remote_ticks: Vec<Option<u64>>,
+    rejected: u64,
+    slow_down: bool,
+}
+
+impl<C: Send + Sync + 'static> LockstepNet<C> {
+    /// Add a command of the local player, to be sent with the next packet
+    pub fn push(&mut self, command: C) {
+        self.pending.push(command);
+    }
+
+    /// The commands of the local player waiting for the next packet
+    pub fn pending(&self) -> &[C] {
+        &self.pending
+    }
+
+    /// The player number of the local player
+    pub fn local_player(&self) -> usize {
+        self.local_player
+    }
+
+    /// The number of ticks between a local command and the tick it runs on
+    pub fn input_delay(&self) -> u64 {
+        self.input_delay
+    }
+
+    /// The last tick heard of from the given player, if any
+    ///
+    /// This is either the tick from its last [`NetMessage::TickSync`], or (if later) the
+    /// tick that its last commands were sent on.
+    pub fn remote_tick(&self, player: usize) -> Option<u64> {
+        self.remote_ticks.get(player).copied().flatten()
+    }
+
+    /// The number of received commands that were dropped, because they came too late for their
+    /// tick (or twice)
+    pub fn rejected_packets(&self) -> u64 {
+        self.rejected
+    }
+
+    /// How many ticks ahead the local player is of the slowest other player
+    ///
+    /// `None` until every other player has been heard of.
+    pub fn lead(&self, tick: u64) -> Option<u64> {
+        let mut slowest: Option<u64> = None;
+        for (player, remote) in self.remote_ticks.iter().enumerate() {
+            if player == self.local_player {
+                continue;
+            }
+            let remote = (*remote)?;
+            slowest = Some(slowest.map_or(remote, |slowest| slowest.min(remote)));
+        }
+        slowest.map(|slowest| tick.saturating_sub(slowest))
+    }
+
+    fn heard_of(&mut self, player: usize, tick: u64) {
+        if let Some(remote) = self.remote_ticks.get_mut(player) {
+            *remote = Some(remote.map_or(tick, |remote| remote.max(tick)));
+        }
+    }
+}
+
+/// Plugin for sending and receiving the commands of a lockstep fixed framestep
+///
+/// See the [module docs](self). Requires the `LockstepPlugin` for the same fixed framestep
+/// and command type.
+pub struct LockstepNetPlugin<C> {
+    framestep: FramestepName,
+    local_player: usize,
+    input_delay: u64,
+    sync_interval: u64,
+    max_lead: Option<u64>,
+    marker: PhantomData<fn() -> C>,
+}
+
+impl<C: Clone + Send + Sync + 'static> LockstepNetPlugin<C> {
+    /// Create the plugin for the given fixed framestep, and the player number of the local player
+    ///
+    /// The input delay defaults to 2 ticks, and tick syncs are sent every 30 ticks.
+    pub fn new(framestep: impl FramestepLabel, local_player: usize) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            local_player,
+            input_delay: 2,
+            sync_interval: 30,
+            max_lead: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Builder method for setting the number of ticks between a local command and the tick it runs on
+    ///
+    /// Panics if `ticks` is zero: the commands of a tick must be sent before it runs.
+    pub fn with_input_delay(mut self, ticks: u64) -> Self {
+        assert!(ticks > 0, "The input delay must be at least one tick.");
+        self.input_delay = ticks;
+        self
+    }
+
+    /// Builder method for setting how often (in ticks) a [`NetMessage::TickSync`] is sent
+    ///
+    /// `0` disables them; the commands packets still tell the other peers where we are.
+    pub fn with_sync_interval(mut self, ticks: u64) -> Self {
+        self.sync_interval = ticks;
+        self
+    }
+
+    /// Builder method for slowing down when more than `ticks` ticks ahead of the slowest other peer
+    ///
+    /// The lead is measured against the last tick heard of from the other peers, so it includes
+    /// the latency: set `ticks` to a bit more than the usual latency, in ticks.
+    pub fn with_max_lead(mut self, ticks: u64) -> Self {
+        self.max_lead = Some(ticks);
+        self
+    }
+}
+
+impl<C: Clone + Send + Sync + 'static> Plugin for LockstepNetPlugin<C> {
+    fn build(&self, app: &mut App) {
+        let mut inbox = app.world.get_resource_mut::<LockstepInbox<C>>()
+            .expect("Add the LockstepPlugin before the LockstepNetPlugin");
+        assert!(inbox.framestep() == self.framestep, "The LockstepPlugin is for another fixed framestep.");
+        let players = inbox.players();
+        assert!(self.local_player < players, "Lockstep player {} out of range (there are {} players).", self.local_player, players);
+        for tick in 1..=self.input_delay {
+            for player in 0..players {
+                inbox.receive(player, tick, Vec::new());
+            }
+        }
+        app.insert_resource(LockstepNet::<C> {
+            framestep: self.framestep,
+            local_player: self.local_player,
+            input_delay: self.input_delay,
+            sync_interval: self.sync_interval,
+            max_lead: self.max_lead,
+            pending: Vec::new(),
+            remote_ticks: vec![None; players],
+            rejected: 0,
+            slow_down: false,
+        });
+        app.add_event::<NetReceived<C>>();
+        app.add_event::<NetSend<C>>();
+        app.add_system_to_stage(CoreStage::PreUpdate, receive_net_messages::<C>);
+        let stage = find_framestep_mut(&mut app.schedule, self.framestep)
+            .expect("Fixed Framestep Stage not found");
+        stage.add_tick_gate(slow_down_gate::<C>);
+        stage.add_tick_begin_hook(send_local_commands::<C>);
+    }
+}
+
+fn receive_net_messages<C: Send + Sync + 'static>(
+    mut received: ResMut<Events<NetReceived<C>>>,
+    mut inbox: ResMut<LockstepInbox<C>>,
+    mut net: ResMut<LockstepNet<C>>,
+    framesteps: Res<FixedFramesteps>,
+) {
+    for NetReceived(message) in received.drain() {
+        match message {
+            NetMessage::Commands(packet) => {
+                let sent_on = packet.tick.saturating_sub(net.input_delay);
+                net.heard_of(packet.player, sent_on);
+                if packet.player >= inbox.players() || !inbox.receive(packet.player, packet.tick, packet.commands) {
+                    net.rejected += 1;
+                }
+            }
+            NetMessage::TickSync { player, tick } => net.heard_of(player, tick),
+        }
+    }
+    let max_lead = match net.max_lead {
+        Some(max_lead) => max_lead,
+        None => return,
+    };
+    let tick = match framesteps.get(net.framestep) {
+        Some(info) => info.tick(),
+        None => return,
+    };
+    net.slow_down = net.lead(tick).map(|lead| lead > max_lead).unwrap_or(false);
+}
+
+/// Hold back the first due tick of a frame where we are too far ahead, and drop its frame
+fn slow_down_gate<C: Send + Sync + 'static>(world: &mut World, label: FramestepName, _tick: u64) -> bool {
+    let slow_down = match world.get_resource_mut::<LockstepNet<C>>() {
+        Some(mut net) => std::mem::take(&mut net.slow_down),
+        None => return true,
+    };
+    if slow_down {
+        if let Some(info) = world.resource_mut::<FixedFramesteps>().get_mut(label) {
+            info.stretch(-1);
+        }
+    }
+    !slow_down
+}
+
+fn send_local_commands<C: Clone + Send + Sync + 'static>(world: &mut World) {
+    let tick = world.resource::<FixedFramesteps>().current().tick();
+    let (packet, sync) = {
+        let mut net = match world.get_resource_mut::<LockstepNet<C>>() {
+            Some(net) => net,
+            None => return,
+        };
+        let packet = TickPacket {
+            player: net.local_player,
+            tick: tick + net.input_delay,
+            commands: std::mem::take(&mut net.pending),
+        };
+        let sync = net.sync_interval != 0 && tick % net.sync_interval == 0;
+        (packet, sync.then_some(net.local_player))
+    };
+    if let Some(mut inbox) = world.get_resource_mut::<LockstepInbox<C>>() {
+        inbox.receive(packet.player, packet.tick, packet.commands.clone());
+    }
+    if let Some(mut events) = world.get_resource_mut::<Events<NetSend<C>>>() {
+        if let Some(player) = sync {
+            events.send(NetSend(NetMessage::TickSync { player, tick }));
+        }
+        events.send(NetSend(NetMessage::Commands(packet)));
+    }
+}
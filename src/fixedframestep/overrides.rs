@@ -0,0 +1,171 @@
+//! Overriding fixed framestep parameters from environment variables or command line arguments
+//!
+//! Add the [`FramestepOverridesPlugin`] to let QA and benchmark scripts change the
+//! parameters of your fixed framesteps without rebuilding. On startup, it looks for
+//! variables named `{PREFIX}_{NAME}_{PARAM}`, where `PREFIX` is `FFS` by default, `NAME`
+//! is the name of the fixed framestep in uppercase (with any characters other than
+//! letters and digits replaced by `_`), and `PARAM` is one of:
+//!
+//! - `STEP`: the number of frames per tick (see [`FixedFramestepInfo::step`](super::FixedFramestepInfo::step))
+//! - `PAUSED`: `1`/`true` to start paused, `0`/`false` to start running
+//! - `SPEED`: the speed multiplier (see [`FixedFramestepInfo::set_speed`](super::FixedFramestepInfo::set_speed))
+//!
+//! For example, `FFS_SIM_STEP=2 FFS_SIM_PAUSED=1` for a fixed framestep named `"sim"`.
+//! The same can be given as command line arguments, in lowercase, with dashes:
+//! `--ffs-sim-step=2 --ffs-sim-paused=1`. Command line arguments take precedence over
+//! environment variables. Every override that is applied is logged, and invalid values
+//! are ignored with a warning.
+
+use bevy_app::{App, Plugin, StartupStage};
+use bevy_ecs::prelude::*;
+use bevy_utils::tracing::{info, warn};
+
+use super::{FixedFramesteps, FrameCounter, PauseReason};
+
+/// Plugin for overriding fixed framestep parameters on startup (see the [module docs](self))
+///
+/// The overrides are applied in `StartupStage::PreStartup`, to all fixed framesteps that
+/// have been added to the app by then.
+#[derive(Debug, Clone)]
+#[derive(Resource)]
+pub struct FramestepOverridesPlugin {
+    /// The prefix of the variable names
+    pub prefix: String,
+    /// Read the environment variables
+    pub from_env: bool,
+    /// Read the command line arguments
+    pub from_args: bool,
+}
+
+impl Default for FramestepOverridesPlugin {
+    fn default() -> Self {
+        Self {
+            prefix: "FFS".to_owned(),
+            from_env: true,
+            from_args: true,
+        }
+    }
+}
+
+impl FramestepOverridesPlugin {
+    /// Builder method for using a different prefix for the variable names
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Builder method for ignoring the environment variables
+    pub fn without_env(mut self) -> Self {
+        self.from_env = false;
+        self
+    }
+
+    /// Builder method for ignoring the command line arguments
+    pub fn without_args(mut self) -> Self {
+        self.from_args = false;
+        self
+    }
+}
+
+impl Plugin for FramestepOverridesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.clone());
+        app.add_startup_system_to_stage(StartupStage::PreStartup, apply_startup_overrides);
+    }
+}
+
+fn apply_startup_overrides(config: Res<FramestepOverridesPlugin>, framesteps: Option<ResMut<FixedFramesteps>>) {
+    let mut framesteps = match framesteps {
+        Some(framesteps) => framesteps,
+        None => return,
+    };
+    let mut vars = Vec::new();
+    if config.from_env {
+        vars.extend(std::env::vars());
+    }
+    if config.from_args {
+        // later values win, so these take precedence
+        vars.extend(std::env::args().skip(1).filter_map(|arg| arg_to_var(&arg)));
+    }
+    apply_overrides(&mut framesteps, &config.prefix, vars);
+}
+
+/// Turn `--ffs-sim-step=2` into (`FFS_SIM_STEP`, `2`)
+fn arg_to_var(arg: &str) -> Option<(String, String)> {
+    let (key, value) = arg.strip_prefix("--")?.split_once('=')?;
+    Some((key.to_ascii_uppercase().replace('-', "_"), value.to_owned()))
+}
+
+/// Apply the overrides from a list of (variable name, value) pairs
+///
+/// This is what the [`FramestepOverridesPlugin`] does on startup. Call it yourself to
+/// apply overrides from another source, like a config file. Variables with the prefix
+/// that do not match any fixed framestep are ignored with a warning. If a variable
+/// occurs several times, the last value wins.
+pub fn apply_overrides(framesteps: &mut FixedFramesteps, prefix: &str, vars: impl IntoIterator<Item = (String, String)>) {
+    let prefix = format!("{}_", prefix);
+    let vars: Vec<(String, String)> = vars.into_iter()
+        .filter(|(key, _)| key.starts_with(&prefix))
+        .collect();
+    if vars.is_empty() {
+        return;
+    }
+    let mut used = vec![false; vars.len()];
+    for (label, info) in framesteps.info.iter_mut() {
+        let name: String = label.name().chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+            .collect();
+        let var_prefix = format!("{}{}_", prefix, name);
+        for ((key, value), used) in vars.iter().zip(used.iter_mut()) {
+            let param = match key.strip_prefix(&var_prefix) {
+                Some(param) => param,
+                None => continue,
+            };
+            let applied = match param {
+                "STEP" => value.parse::<FrameCounter>().ok()
+                    .and_then(|step| info.try_set_step(step).ok())
+                    .is_some(),
+                "PAUSED" => match parse_bool(value) {
+                    Some(paused) => {
+                        // the stage may already start paused; hold the reason once
+                        if paused && !info.is_paused_by(PauseReason::User) {
+                            info.pause();
+                        } else if !paused {
+                            info.clear_pause(PauseReason::User);
+                        }
+                        true
+                    }
+                    None => false,
+                },
+                "SPEED" => match value.parse() {
+                    Ok(speed) => {
+                        info.set_speed(speed);
+                        true
+                    }
+                    Err(_) => false,
+                },
+                // might be meant for another fixed framestep, whose name starts with ours
+                _ => continue,
+            };
+            *used = true;
+            if applied {
+                info!("Fixed framestep {:?}: {} overridden to {} (from {}).", label, param, value, key);
+            } else {
+                warn!("Invalid value {:?} for {}; ignoring it.", value, key);
+            }
+        }
+    }
+    for ((key, _), used) in vars.iter().zip(used) {
+        if !used {
+            warn!("{} does not match any fixed framestep parameter; ignoring it.", key);
+        }
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
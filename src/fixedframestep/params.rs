@@ -0,0 +1,112 @@
+//! System parameters for the [`FixedFramesteps`] resource, that know which fixed framestep is running
+//!
+//! [`FixedFramesteps::current`] panics when called from a system that does not run inside
+//! a fixed framestep, which is easy to do by accident (say, by adding a system to a regular
+//! stage, or sharing a helper between both kinds of systems). These system parameters
+//! return [`None`] instead, so that the code has to handle both cases:
+//!
+//! - [`Framesteps`], to read the infos of all the fixed framesteps, and of the current one
+//! - [`CurrentFramestepMut`], to change the info of the fixed framestep that is running
+//!
+//! Like `Res<FixedFramesteps>` and `ResMut<FixedFramesteps>`, `CurrentFramestepMut`
+//! conflicts with any other access to the resource in the same system.
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::SystemParam;
+
+use super::{FixedFramestepInfo, FixedFramesteps, FramestepLabel, FramestepName};
+
+/// System parameter for reading the [`FixedFramesteps`] resource (see the [module docs](self))
+#[derive(SystemParam)]
+pub struct Framesteps<'w, 's> {
+    framesteps: Res<'w, FixedFramesteps>,
+    #[system_param(ignore)]
+    _marker: std::marker::PhantomData<&'s ()>,
+}
+
+impl<'w, 's> Framesteps<'w, 's> {
+    /// Is the system running inside a fixed framestep?
+    pub fn in_tick(&self) -> bool {
+        self.framesteps.current_label().is_some()
+    }
+
+    /// The name of the fixed framestep that is running, if any
+    pub fn current_label(&self) -> Option<FramestepName> {
+        self.framesteps.current_label()
+    }
+
+    /// The info of the fixed framestep that is running, if any
+    pub fn current(&self) -> Option<&FixedFramestepInfo> {
+        self.framesteps.get_current()
+    }
+
+    /// The number of the tick that is running, if any
+    pub fn current_tick(&self) -> Option<u64> {
+        self.current().map(FixedFramestepInfo::tick)
+    }
+
+    /// The info of the fixed framestep with the given name
+    pub fn get(&self, label: impl FramestepLabel) -> Option<&FixedFramestepInfo> {
+        self.framesteps.get(label)
+    }
+
+    /// The info of the only fixed framestep, if there is exactly one
+    pub fn single(&self) -> Option<&FixedFramestepInfo> {
+        self.framesteps.get_single()
+    }
+
+    /// Iterate over the infos of all the fixed framesteps, in arbitrary order
+    pub fn iter(&self) -> impl Iterator<Item = &FixedFramestepInfo> {
+        self.framesteps.info.values()
+    }
+
+    /// The whole resource, for anything else
+    pub fn resource(&self) -> &FixedFramesteps {
+        &self.framesteps
+    }
+}
+
+/// System parameter for changing the info of the fixed framestep that is running (see the [module docs](self))
+///
+/// Outside of a fixed framestep, there is nothing to change: [`get_mut`](Self::get_mut)
+/// returns [`None`]. As with `ResMut`, the resource is only marked as changed when it is
+/// actually accessed mutably.
+#[derive(SystemParam)]
+pub struct CurrentFramestepMut<'w, 's> {
+    framesteps: ResMut<'w, FixedFramesteps>,
+    #[system_param(ignore)]
+    _marker: std::marker::PhantomData<&'s ()>,
+}
+
+impl<'w, 's> CurrentFramestepMut<'w, 's> {
+    /// Is the system running inside a fixed framestep?
+    pub fn in_tick(&self) -> bool {
+        self.framesteps.current_label().is_some()
+    }
+
+    /// The name of the fixed framestep that is running, if any
+    pub fn label(&self) -> Option<FramestepName> {
+        self.framesteps.current_label()
+    }
+
+    /// The number of the tick that is running, if any
+    pub fn tick(&self) -> Option<u64> {
+        self.get().map(FixedFramestepInfo::tick)
+    }
+
+    /// The info of the fixed framestep that is running, if any
+    pub fn get(&self) -> Option<&FixedFramestepInfo> {
+        self.framesteps.get_current()
+    }
+
+    /// Mut access to the info of the fixed framestep that is running, if any
+    ///
+    /// Changes are taken into account like changes made using [`FixedFramesteps::current_mut`].
+    pub fn get_mut(&mut self) -> Option<&mut FixedFramestepInfo> {
+        if !self.in_tick() {
+            // do not mark the resource as changed
+            return None;
+        }
+        self.framesteps.get_current_mut()
+    }
+}
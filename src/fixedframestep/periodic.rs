@@ -0,0 +1,80 @@
+//! Running work periodically, every N ticks of a fixed framestep
+//!
+//! There are two levels to this:
+//!  - Whole systems: use the [`every_n_ticks`] run condition, to run a fixed framestep
+//!    system only on some of the ticks.
+//!  - Per-entity work: instead of updating thousands of entities "every 60 ticks" all on the
+//!    same tick, spread them out evenly across the 60 ticks, processing a different slice of
+//!    them every tick. Use [`is_slice_due`] in your system, or give the entities
+//!    a [`PeriodicJob`] component for more control.
+//!
+//! Everything is based on the tick number (and entity index), so it is deterministic.
+
+use bevy_ecs::prelude::*;
+
+use super::FixedFramesteps;
+
+/// Run condition: run only on every `n`-th tick of the current fixed framestep
+///
+/// Use it on systems inside a fixed framestep, like `my_system.run_if(every_n_ticks(10))`.
+/// Runs on the ticks that are a multiple of `n`.
+pub fn every_n_ticks(n: u64) -> impl FnMut(Res<FixedFramesteps>) -> bool {
+    every_n_ticks_offset(n, 0)
+}
+
+/// Run condition: run only on every `n`-th tick of the current fixed framestep, starting from `offset`
+///
+/// Several systems with the same `n` and different offsets will never run on the same tick
+/// (if the offsets are different modulo `n`).
+pub fn every_n_ticks_offset(n: u64, offset: u64) -> impl FnMut(Res<FixedFramesteps>) -> bool {
+    assert!(n > 0);
+    move |framesteps: Res<FixedFramesteps>| {
+        framesteps.current().tick() % n == offset % n
+    }
+}
+
+/// Is this entity's slice of work due on the given tick?
+///
+/// For work that each entity needs done every `period` ticks. The entities are spread out
+/// evenly over the ticks, based on their index, so that each tick only processes about
+/// `1 / period` of them.
+pub fn is_slice_due(entity: Entity, tick: u64, period: u64) -> bool {
+    assert!(period > 0);
+    (tick + entity.index() as u64) % period == 0
+}
+
+/// Component for entities that need work done periodically
+///
+/// Check [`is_due`](Self::is_due) in your fixed framestep system. If you don't set a
+/// phase, entities are spread out over the period based on their index (like [`is_slice_due`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Component)]
+pub struct PeriodicJob {
+    /// The work is due every `period` ticks
+    pub period: u32,
+    /// Offset of the ticks on which the work is due (`None`: based on the entity index)
+    pub phase: Option<u32>,
+}
+
+impl PeriodicJob {
+    /// Work due every `period` ticks, spread out based on the entity index
+    pub fn new(period: u32) -> Self {
+        assert!(period > 0);
+        Self {
+            period,
+            phase: None,
+        }
+    }
+
+    /// Builder method for setting a fixed phase, instead of spreading based on the entity index
+    pub fn with_phase(mut self, phase: u32) -> Self {
+        self.phase = Some(phase);
+        self
+    }
+
+    /// Is the work for the given entity due on the given tick?
+    pub fn is_due(&self, entity: Entity, tick: u64) -> bool {
+        let phase = self.phase.unwrap_or(entity.index()) as u64;
+        (tick + phase) % self.period as u64 == 0
+    }
+}
@@ -0,0 +1,135 @@
+//! Catching tick systems added to per-frame stages by mistake (and vice versa)
+//!
+//! The most common mistake with fixed framesteps is adding a system to the wrong place:
+//! a physics system to `CoreStage::Update` (so it runs at the frame rate), or an input or
+//! rendering system to the fixed framestep (so it misses frames, or runs several times per
+//! frame). Label the systems that must only run in a tick with [`TickOnly`], and those
+//! that must only run once per frame with [`FrameOnly`]. Then, after the app is built,
+//! check the whole schedule with `validate_fixed_framestep_systems` on the `App` (which
+//! panics with a list of the misplaced systems), or with [`misplaced_systems`].
+//!
+//! The check is opt-in, and only looks at the systems that are in the schedule when it
+//! runs: in `SystemStage`s (also inside nested schedules, system groups, and cleanup
+//! sub-stages), and in the sub-stages of fixed framesteps (also dynamic and instanced
+//! ones). It cannot see inside custom stage types.
+
+use std::borrow::Cow;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::GraphNode;
+
+use super::{FixedFramestepStage, FramestepName, as_framestep, cleanup, dynamic, groups};
+
+/// System label for systems that must only run in the ticks of a fixed framestep
+///
+/// See the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(SystemLabel)]
+pub struct TickOnly;
+
+/// System label for systems that must only run once per frame, outside of the fixed framesteps
+///
+/// See the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(SystemLabel)]
+pub struct FrameOnly;
+
+/// A system found in the wrong place by [`misplaced_systems`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MisplacedSystem {
+    /// A [`TickOnly`] system, in a stage that runs every frame
+    TickSystemPerFrame {
+        /// The name of the system
+        system: Cow<'static, str>,
+        /// The path of the stage it is in, from the top of the schedule
+        stage: String,
+    },
+    /// A [`FrameOnly`] system, in a sub-stage of a fixed framestep
+    FrameSystemInTick {
+        /// The name of the system
+        system: Cow<'static, str>,
+        /// The name of the fixed framestep it is in
+        framestep: FramestepName,
+        /// The path of the stage it is in, from the top of the schedule
+        stage: String,
+    },
+}
+
+impl std::fmt::Display for MisplacedSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MisplacedSystem::TickSystemPerFrame { system, stage } => write!(
+                f, "system {} is labeled TickOnly, but was added to the per-frame stage {}; add it to a fixed framestep instead",
+                system, stage,
+            ),
+            MisplacedSystem::FrameSystemInTick { system, framestep, stage } => write!(
+                f, "system {} is labeled FrameOnly, but was added to fixed framestep {:?} (in {}); add it to a per-frame stage instead",
+                system, framestep, stage,
+            ),
+        }
+    }
+}
+
+/// Find all the [`TickOnly`] and [`FrameOnly`] systems that are in the wrong place in the schedule
+///
+/// See the [module docs](self). Returns an empty list if all of them are where they should be.
+pub fn misplaced_systems(schedule: &Schedule) -> Vec<MisplacedSystem> {
+    let mut found = Vec::new();
+    check_schedule(schedule, "", &mut found);
+    found
+}
+
+fn check_schedule(schedule: &Schedule, path: &str, found: &mut Vec<MisplacedSystem>) {
+    for (label, stage) in schedule.iter_stages() {
+        check_stage(stage, &format!("{}{:?}", path, label), None, found);
+    }
+}
+
+fn check_stage(stage: &dyn Stage, path: &str, framestep: Option<FramestepName>, found: &mut Vec<MisplacedSystem>) {
+    if let Some(stage) = stage.downcast_ref::<SystemStage>() {
+        check_systems(stage, path, framestep, found);
+    } else if let Some(group) = stage.downcast_ref::<groups::SystemGroupStage>() {
+        check_systems(&group.stage, &format!("{}/{}", path, group.name()), framestep, found);
+    } else if let Some(cleanup) = stage.downcast_ref::<cleanup::CleanupStage>() {
+        check_systems(&cleanup.stage, &format!("{}/cleanup", path), framestep, found);
+    } else if let Some(schedule) = stage.downcast_ref::<Schedule>() {
+        check_schedule(schedule, &format!("{}/", path), found);
+    } else if let Some(dynamic) = stage.downcast_ref::<dynamic::DynamicFramestepsStage>() {
+        for stage in dynamic.stages.iter() {
+            check_framestep(stage, path, found);
+        }
+    } else if let Some(stage) = as_framestep(stage) {
+        check_framestep(stage, path, found);
+    }
+}
+
+fn check_framestep(stage: &FixedFramestepStage, path: &str, found: &mut Vec<MisplacedSystem>) {
+    for (i, substage) in stage.stages.iter().enumerate() {
+        let path = format!("{}/{:?}[{}]", path, stage.label, i);
+        check_stage(&**substage, &path, Some(stage.label), found);
+    }
+}
+
+fn check_systems(stage: &SystemStage, path: &str, framestep: Option<FramestepName>, found: &mut Vec<MisplacedSystem>) {
+    let systems = stage.exclusive_at_start_systems().iter()
+        .chain(stage.parallel_systems())
+        .chain(stage.exclusive_before_commands_systems())
+        .chain(stage.exclusive_at_end_systems());
+    let tick_only = TickOnly.as_label();
+    let frame_only = FrameOnly.as_label();
+    for container in systems {
+        let labels = container.labels();
+        match framestep {
+            None if labels.contains(&tick_only) => found.push(MisplacedSystem::TickSystemPerFrame {
+                system: container.name(),
+                stage: path.to_owned(),
+            }),
+            Some(framestep) if labels.contains(&frame_only) => found.push(MisplacedSystem::FrameSystemInTick {
+                system: container.name(),
+                framestep,
+                stage: path.to_owned(),
+            }),
+            _ => {}
+        }
+    }
+}
@@ -0,0 +1,472 @@
+//! Client-side prediction, and reconciliation with the server
+//!
+//! With a server-authoritative model, the client runs its fixed framestep ahead of the
+//! server, simulating the local player's inputs right away ("predicting" their results),
+//! instead of waiting for the server. When the server's state for some tick arrives, the
+//! client checks it against what it predicted for that tick. If they differ, it rolls
+//! back to that tick, takes the server's state, and re-simulates all the ticks since.
+//!
+//! Add the [`PredictionPlugin`], with all the predicted component types (see
+//! [`with_predicted`](PredictionPlugin::with_predicted)), and a [`Predicted<T>`] component
+//! next to every predicted component `T`. It keeps the values of `T` at the end of the
+//! recent ticks. Store the states received from the server in the [`ServerStates<T>`]
+//! resource (with the client's entities: mapping the server's entities is up to you).
+//!
+//! Every frame, in `CoreStage::PreUpdate`, the newest server states (for a tick that the
+//! client has already simulated) are checked against the predicted values. On a
+//! misprediction, all the predicted components (of all types) are reverted to their values
+//! on that tick, the server states are applied, the fixed framestep is
+//! [rewound](super::FixedFramesteps::rewind) to that tick, and the ticks since are
+//! [queued](super::FixedFramestepInfo::queue_ticks), to be re-simulated on the same frame. A
+//! [`Reconciled`] event is sent. The fixed framestep must run after `CoreStage::PreUpdate`,
+//! like in its default position.
+//!
+//! The tick bookkeeping of the fixed framestep is rolled back too: the plugin takes a
+//! [snapshot](super::FixedFramesteps::snapshot) of it on every tick, and
+//! [restores](super::FixedFramesteps::restore) the one from the end of the tick it rolls
+//! back to. So the [speed](super::FixedFramestepInfo::speed), the pause reasons, the
+//! history, and the [rng](super::FixedFramestepInfo::rng) are as they were on that tick, if
+//! your tick systems changed them since. The accumulator and the other frame timing are
+//! kept: the frames that elapsed since are still real.
+//!
+//! Your tick systems must use the inputs of the tick they are simulating (not the latest
+//! ones), for the re-simulation to give the same results. Anything that is neither a
+//! predicted component, nor tick bookkeeping, is not rolled back.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use bevy_app::{App, CoreStage, Plugin};
+use bevy_ecs::prelude::*;
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName, FramestepsSnapshot, find_framestep_mut};
+
+/// The default number of ticks that [`Predicted`] components remember
+pub const DEFAULT_PREDICTION_TICKS: u64 = 64;
+
+/// Component with the recent predicted values of the component `T` on the same entity
+///
+/// See the [module docs](self). The values are recorded at the start of every tick (as
+/// the value at the end of the previous one).
+#[derive(Component)]
+pub struct Predicted<T: Component + Clone> {
+    history: VecDeque<(u64, T)>,
+}
+
+impl<T: Component + Clone> Default for Predicted<T> {
+    fn default() -> Self {
+        Self {
+            history: VecDeque::new(),
+        }
+    }
+}
+
+impl<T: Component + Clone> Predicted<T> {
+    /// Create the component with an empty history
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The predicted value at the end of the given tick, if it is still remembered
+    ///
+    /// The value at the end of the current tick is the component itself.
+    pub fn at(&self, tick: u64) -> Option<&T> {
+        self.history.iter()
+            .rev()
+            .find(|(t, _)| *t == tick)
+            .map(|(_, value)| value)
+    }
+
+    /// The number of ticks remembered
+    pub fn len(&self) -> usize {
+        self.history.len()
+    }
+
+    /// Are no ticks remembered?
+    pub fn is_empty(&self) -> bool {
+        self.history.is_empty()
+    }
+}
+
+/// Resource with the authoritative states of the component `T`, as received from the server
+#[derive(Resource)]
+pub struct ServerStates<T: Component> {
+    states: BTreeMap<u64, Vec<(Entity, T)>>,
+    // the states being reconciled
+    due: Vec<(Entity, T)>,
+}
+
+impl<T: Component> Default for ServerStates<T> {
+    fn default() -> Self {
+        Self {
+            states: BTreeMap::new(),
+            due: Vec::new(),
+        }
+    }
+}
+
+impl<T: Component> ServerStates<T> {
+    /// Store the server's value of the component of an entity, at the end of the given tick
+    pub fn receive(&mut self, tick: u64, entity: Entity, value: T) {
+        self.states.entry(tick).or_default().push((entity, value));
+    }
+
+    /// The newest tick with server states, if any
+    pub fn latest_tick(&self) -> Option<u64> {
+        self.states.keys().next_back().copied()
+    }
+
+    /// The number of server states waiting to be reconciled
+    pub fn len(&self) -> usize {
+        self.states.values().map(Vec::len).sum()
+    }
+
+    /// Are there no server states waiting to be reconciled?
+    pub fn is_empty(&self) -> bool {
+        self.states.is_empty()
+    }
+}
+
+/// Event sent when the client rolled back and re-simulated, after a misprediction
+///
+/// Sent by the [`PredictionPlugin`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Reconciled {
+    /// The name of the fixed framestep
+    pub label: FramestepName,
+    /// The tick that it was rolled back to
+    pub tick: u64,
+    /// The number of ticks to be re-simulated
+    pub resimulated: u64,
+}
+
+/// The functions for reconciling one predicted component type
+#[derive(Clone, Copy)]
+struct PredictedType {
+    build: fn(&mut App, FramestepName),
+    latest: fn(&World, u64) -> Option<u64>,
+    mispredicted: fn(&mut World, u64, u64) -> bool,
+    apply: fn(&mut World, u64, bool),
+}
+
+/// Resource with the prediction settings and counters (see the [`PredictionPlugin`])
+#[derive(Resource)]
+pub struct Prediction {
+    framestep: FramestepName,
+    max_ticks: u64,
+    types: Vec<PredictedType>,
+    rollbacks: u64,
+    last_rollback: Option<u64>,
+    // the stepping state of the fixed framestep at the end of the recent ticks, oldest first
+    snapshots: VecDeque<(u64, FramestepsSnapshot)>,
+}
+
+impl Prediction {
+    /// The fixed framestep that is predicted
+    pub fn framestep(&self) -> FramestepName {
+        self.framestep
+    }
+
+    /// The number of ticks that [`Predicted`] components remember
+    pub fn max_ticks(&self) -> u64 {
+        self.max_ticks
+    }
+
+    /// The number of rollbacks so far
+    pub fn rollbacks(&self) -> u64 {
+        self.rollbacks
+    }
+
+    /// The tick that was rolled back to last, if any
+    pub fn last_rollback(&self) -> Option<u64> {
+        self.last_rollback
+    }
+}
+
+/// Plugin for client-side prediction and reconciliation (see the [module docs](self))
+///
+/// The fixed framestep must already be added to the app.
+pub struct PredictionPlugin {
+    framestep: FramestepName,
+    max_ticks: u64,
+    types: Vec<PredictedType>,
+}
+
+impl PredictionPlugin {
+    /// Create the plugin for the given fixed framestep
+    pub fn new(framestep: impl FramestepLabel) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            max_ticks: DEFAULT_PREDICTION_TICKS,
+            types: Vec::new(),
+        }
+    }
+
+    /// Builder method for predicting the component `T`
+    ///
+    /// Add a [`Predicted<T>`] component to the entities to predict.
+    pub fn with_predicted<T: Component + Clone + PartialEq>(mut self) -> Self {
+        self.types.push(PredictedType {
+            build: build_type::<T>,
+            latest: latest_tick::<T>,
+            mispredicted: mispredicted::<T>,
+            apply: apply_states::<T>,
+        });
+        self
+    }
+
+    /// Builder method for setting the number of ticks that [`Predicted`] components remember
+    ///
+    /// Server states for older ticks cannot be rolled back to; they are applied to the
+    /// current tick instead.
+    pub fn with_max_ticks(mut self, ticks: u64) -> Self {
+        self.max_ticks = ticks;
+        self
+    }
+}
+
+impl Plugin for PredictionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Prediction {
+            framestep: self.framestep,
+            max_ticks: self.max_ticks,
+            types: self.types.clone(),
+            rollbacks: 0,
+            last_rollback: None,
+            snapshots: VecDeque::new(),
+        });
+        app.add_event::<Reconciled>();
+        find_framestep_mut(&mut app.schedule, self.framestep)
+            .expect("Fixed Framestep Stage not found")
+            .add_tick_begin_hook(record_framestep);
+        for predicted in self.types.iter() {
+            (predicted.build)(app, self.framestep);
+        }
+        app.add_system_to_stage(CoreStage::PreUpdate, reconcile);
+    }
+}
+
+fn build_type<T: Component + Clone + PartialEq>(app: &mut App, framestep: FramestepName) {
+    app.init_resource::<ServerStates<T>>();
+    let stage = find_framestep_mut(&mut app.schedule, framestep)
+        .expect("Fixed Framestep Stage not found");
+    stage.add_tick_begin_hook(record_predicted::<T>);
+}
+
+/// Remember the values at the end of the previous tick
+fn record_predicted<T: Component + Clone>(world: &mut World) {
+    let tick = world.resource::<FixedFramesteps>().current().tick();
+    let max_ticks = match world.get_resource::<Prediction>() {
+        Some(prediction) => prediction.max_ticks,
+        None => return,
+    };
+    let mut query = world.query::<(&T, &mut Predicted<T>)>();
+    for (value, mut predicted) in query.iter_mut(world) {
+        let previous = tick.saturating_sub(1);
+        while predicted.history.back().map(|(t, _)| *t >= previous).unwrap_or(false) {
+            predicted.history.pop_back();
+        }
+        predicted.history.push_back((previous, value.clone()));
+        while predicted.history.front().map(|(t, _)| *t + max_ticks < tick).unwrap_or(false) {
+            predicted.history.pop_front();
+        }
+    }
+}
+
+/// Remember the stepping state of the fixed framestep at the end of the previous tick
+fn record_framestep(world: &mut World) {
+    let framesteps = world.resource::<FixedFramesteps>();
+    let tick = framesteps.current().tick();
+    let mut snapshot = framesteps.snapshot();
+    let mut prediction = match world.get_resource_mut::<Prediction>() {
+        Some(prediction) => prediction,
+        None => return,
+    };
+    // other fixed framesteps are not rolled back
+    let framestep = prediction.framestep;
+    snapshot.framesteps.retain(|saved| saved.label == framestep);
+    let previous = tick.saturating_sub(1);
+    while prediction.snapshots.back().map(|(t, _)| *t >= previous).unwrap_or(false) {
+        prediction.snapshots.pop_back();
+    }
+    prediction.snapshots.push_back((previous, snapshot));
+    let max_ticks = prediction.max_ticks;
+    while prediction.snapshots.front().map(|(t, _)| *t + max_ticks < tick).unwrap_or(false) {
+        prediction.snapshots.pop_front();
+    }
+}
+
+/// Roll the stepping state of the fixed framestep back to the end of the given tick
+///
+/// Restores the snapshot from that tick, if it is still remembered, but keeps the frame
+/// timing (the accumulator, and so on) and the queued ticks, and queues the ticks to
+/// re-simulate on top.
+fn roll_back_framestep(world: &mut World, framestep: FramestepName, tick: u64, resimulated: u64) {
+    let snapshot = world.resource::<Prediction>().snapshots.iter()
+        .find(|(t, _)| *t == tick)
+        .map(|(_, snapshot)| snapshot.clone());
+    let mut framesteps = world.resource_mut::<FixedFramesteps>();
+    let now = framesteps.snapshot();
+    let now = match now.framesteps.iter().find(|saved| saved.label == framestep) {
+        Some(now) => now,
+        None => return,
+    };
+    if let Some(snapshot) = snapshot {
+        framesteps.restore(&snapshot);
+    }
+    framesteps.rewind(framestep, tick);
+    if let Some(info) = framesteps.get_mut(framestep) {
+        info.accumulator = now.accumulator;
+        info.pending_stretch = now.pending_stretch;
+        info.step_index = now.step_index;
+        info.steps_this_frame = now.steps_this_frame;
+        info.last_frame_count = now.last_frame_count;
+        info.time_carry = now.time_carry;
+        info.scale_carry = now.scale_carry;
+        info.queued_ticks = now.queued_ticks;
+        info.queue_ticks(resimulated);
+    }
+}
+
+/// The newest tick with server states, that has already been simulated
+fn latest_tick<T: Component>(world: &World, current: u64) -> Option<u64> {
+    world.get_resource::<ServerStates<T>>()?
+        .states
+        .range(..=current)
+        .next_back()
+        .map(|(tick, _)| *tick)
+}
+
+/// Take the server states for the given tick, and check them against the predictions
+///
+/// Older states are dropped: the newer ones supersede them.
+fn mispredicted<T: Component + Clone + PartialEq>(world: &mut World, tick: u64, current: u64) -> bool {
+    let due = match world.get_resource_mut::<ServerStates<T>>() {
+        Some(mut states) => {
+            let later = states.states.split_off(&(tick + 1));
+            let mut earlier = std::mem::replace(&mut states.states, later);
+            earlier.remove(&tick).unwrap_or_default()
+        }
+        None => return false,
+    };
+    let mut query = world.query::<(&T, &Predicted<T>)>();
+    let mispredicted = due.iter().any(|(entity, server)| {
+        match query.get(world, *entity) {
+            Ok((value, _)) if tick == current => value != server,
+            // if the tick is too old to tell, assume the worst
+            Ok((_, predicted)) => predicted.at(tick) != Some(server),
+            // not predicted: nothing to reconcile
+            Err(_) => false,
+        }
+    });
+    world.resource_mut::<ServerStates<T>>().due = due;
+    mispredicted
+}
+
+/// Apply the server states taken by `mispredicted`, rolling back to their tick first
+fn apply_states<T: Component + Clone + PartialEq>(world: &mut World, tick: u64, rollback: bool) {
+    let due = std::mem::take(&mut world.resource_mut::<ServerStates<T>>().due);
+    if !rollback {
+        return;
+    }
+    let mut query = world.query::<(&mut T, &mut Predicted<T>)>();
+    for (mut value, mut predicted) in query.iter_mut(world) {
+        if let Some(old) = predicted.at(tick).cloned() {
+            *value = old;
+        }
+        // recorded again on re-simulation
+        while predicted.history.back().map(|(t, _)| *t >= tick).unwrap_or(false) {
+            predicted.history.pop_back();
+        }
+    }
+    for (entity, server) in due {
+        if let Ok((mut value, _)) = query.get_mut(world, entity) {
+            *value = server;
+        }
+    }
+}
+
+fn reconcile(world: &mut World) {
+    let (framestep, max_ticks, types) = {
+        let prediction = world.resource::<Prediction>();
+        (prediction.framestep, prediction.max_ticks, prediction.types.clone())
+    };
+    let current = match world.resource::<FixedFramesteps>().get(framestep) {
+        Some(info) => info.tick(),
+        None => return,
+    };
+    let tick = match types.iter().filter_map(|predicted| (predicted.latest)(world, current)).max() {
+        Some(tick) => tick,
+        None => return,
+    };
+    let mut rollback = false;
+    for predicted in types.iter() {
+        rollback |= (predicted.mispredicted)(world, tick, current);
+    }
+    for predicted in types.iter() {
+        (predicted.apply)(world, tick, rollback);
+    }
+    if !rollback {
+        return;
+    }
+    let mut prediction = world.resource_mut::<Prediction>();
+    prediction.rollbacks += 1;
+    prediction.last_rollback = Some(tick);
+    // too old to roll back to: the server states were applied to the current tick
+    let resimulated = if current - tick <= max_ticks { current - tick } else { 0 };
+    if resimulated > 0 {
+        roll_back_framestep(world, framestep, tick, resimulated);
+    }
+    if let Some(mut events) = world.get_resource_mut::<Events<Reconciled>>() {
+        events.send(Reconciled {
+            label: framestep,
+            tick,
+            resimulated,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::App;
+    use bevy_ecs::prelude::*;
+
+    use super::{Predicted, PredictionPlugin, ServerStates};
+    use crate::fixedframestep::app::AppLooplessFixedFramestepExt;
+    use crate::fixedframestep::FixedFramesteps;
+
+    #[derive(Component, Clone, Debug, PartialEq)]
+    struct Position(i32);
+
+    /// Move, and speed the fixed framestep up once past 3
+    fn step(mut framesteps: ResMut<FixedFramesteps>, mut query: Query<&mut Position>) {
+        for mut position in query.iter_mut() {
+            position.0 += 1;
+            if position.0 >= 3 {
+                framesteps.current_mut().set_speed(2);
+            }
+        }
+    }
+
+    #[test]
+    fn rollback_restores_the_tick_bookkeeping() {
+        let mut app = App::default();
+        app.add_fixed_framestep(1, "sim");
+        app.add_fixed_framestep_system("sim", 0, step);
+        app.add_plugin(PredictionPlugin::new("sim").with_predicted::<Position>());
+        let entity = app.world.spawn((Position(0), Predicted::<Position>::new())).id();
+        for _ in 0..3 {
+            app.update();
+        }
+        let info = &app.world.resource::<FixedFramesteps>()["sim"];
+        assert_eq!((info.tick(), info.speed()), (3, 2));
+        assert_eq!(app.world.get::<Position>(entity), Some(&Position(3)));
+
+        // the server says the entity was far behind after tick 1: it never got fast
+        app.world.resource_mut::<ServerStates<Position>>().receive(1, entity, Position(-10));
+        app.update();
+        let info = &app.world.resource::<FixedFramesteps>()["sim"];
+        // ticks 2 and 3 re-simulated, and tick 4 for this frame, at normal speed
+        assert_eq!((info.tick(), info.speed()), (4, 1));
+        assert_eq!(app.world.get::<Position>(entity), Some(&Position(-7)));
+    }
+}
@@ -0,0 +1,308 @@
+//! Pre-simulating a detached world in the background
+//!
+//! During a loading screen, you may want to run a fresh (say, procedurally generated)
+//! world for a while before the player gets to see it, so that it looks lived-in. A
+//! [`Presimulation`] takes a `World` of its own and a [`FixedFramestepStage`] with the
+//! systems to run on it, and runs a number of ticks on a background task, as fast as it
+//! can. Poll it from the loading screen (it can be stored as a resource), and show its
+//! [`progress`](Presimulation::progress).
+//!
+//! Once it is done, move the results into the app's world, using
+//! [`Presimulated::merge_into`] with a [`WorldMerge`] that lists the component and
+//! resource types to move. Merging must happen between ticks (say, in an exclusive
+//! system outside of the fixed framesteps). The stepping state of the pre-simulated fixed
+//! framestep (the tick counter, and so on) can be moved too: the app should then have a
+//! fixed framestep with the same name, to continue the simulation.
+//!
+//! Bevy's `SystemStage` can only ever run on one world, so the stage must be separate from
+//! the stages of the app, even if it has the same systems.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use bevy_ecs::entity::{EntityMap, MapEntities};
+use bevy_ecs::prelude::*;
+use bevy_tasks::{AsyncComputeTaskPool, Task, TaskPool};
+use bevy_utils::tracing::warn;
+use futures_lite::future;
+
+use super::{FixedFramestepStage, FixedFramesteps, FramestepName};
+
+/// A fixed framestep running on a detached world, on a background task (see the [module docs](self))
+#[derive(Resource)]
+pub struct Presimulation {
+    label: FramestepName,
+    ticks: u64,
+    done: Arc<AtomicU64>,
+    cancelled: Arc<AtomicBool>,
+    task: Option<Task<Presimulated>>,
+}
+
+impl Presimulation {
+    /// Run `ticks` ticks of the stage on the world, on the `AsyncComputeTaskPool`
+    ///
+    /// Panics if the task pool has not been initialized (by Bevy's `CorePlugin`).
+    pub fn spawn(world: World, stage: FixedFramestepStage, ticks: u64) -> Self {
+        Self::spawn_on(AsyncComputeTaskPool::get(), world, stage, ticks)
+    }
+
+    /// Run `ticks` ticks of the stage on the world, on the given task pool
+    pub fn spawn_on(pool: &TaskPool, mut world: World, mut stage: FixedFramestepStage, ticks: u64) -> Self {
+        let done = Arc::new(AtomicU64::new(0));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let label = stage.label;
+        let task = {
+            let done = done.clone();
+            let cancelled = cancelled.clone();
+            pool.spawn(async move {
+                for _ in 0..ticks {
+                    if cancelled.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    stage.run_once(&mut world);
+                    done.fetch_add(1, Ordering::Relaxed);
+                }
+                Presimulated {
+                    ticks: done.load(Ordering::Relaxed),
+                    world,
+                    stage,
+                }
+            })
+        };
+        Self {
+            label,
+            ticks,
+            done,
+            cancelled,
+            task: Some(task),
+        }
+    }
+
+    /// The name of the fixed framestep being pre-simulated
+    pub fn label(&self) -> FramestepName {
+        self.label
+    }
+
+    /// The number of ticks that have run so far
+    pub fn ticks_done(&self) -> u64 {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    /// How far along the pre-simulation is, from `0.0` to `1.0`
+    pub fn progress(&self) -> f32 {
+        if self.ticks == 0 {
+            return 1.0;
+        }
+        self.ticks_done() as f32 / self.ticks as f32
+    }
+
+    /// Stop after the tick that is running
+    ///
+    /// The result still has to be taken using [`try_finish`](Self::try_finish) or
+    /// [`finish`](Self::finish), with the ticks that did run.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Have all the ticks run (or has it been cancelled and stopped)?
+    pub fn is_finished(&self) -> bool {
+        self.task.as_ref().map(Task::is_finished).unwrap_or(true)
+    }
+
+    /// Take the result, if the pre-simulation has finished
+    ///
+    /// Returns `None` if it is still running, or if the result was already taken.
+    pub fn try_finish(&mut self) -> Option<Presimulated> {
+        if !self.task.as_ref()?.is_finished() {
+            return None;
+        }
+        self.task.take().map(future::block_on)
+    }
+
+    /// Wait for the pre-simulation to finish, and take the result
+    ///
+    /// Panics if the result was already taken.
+    pub fn finish(mut self) -> Presimulated {
+        let task = self.task.take().expect("The result of the presimulation was already taken.");
+        future::block_on(task)
+    }
+}
+
+/// The result of a [`Presimulation`]: the world, and the stage that ran on it
+pub struct Presimulated {
+    world: World,
+    stage: FixedFramestepStage,
+    ticks: u64,
+}
+
+impl Presimulated {
+    /// The pre-simulated world
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// Mut access to the pre-simulated world (say, to tidy it up before merging)
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    /// The number of ticks that ran (less than requested, if cancelled)
+    pub fn ticks(&self) -> u64 {
+        self.ticks
+    }
+
+    /// Take the world and the stage, for running some more
+    pub fn into_parts(self) -> (World, FixedFramestepStage) {
+        (self.world, self.stage)
+    }
+
+    /// Move the results into another world; returns the map from old to new entities
+    ///
+    /// See [`WorldMerge`]. Panics if called from inside a tick of the target world.
+    pub fn merge_into(mut self, target: &mut World, merge: &WorldMerge) -> EntityMap {
+        let inside_tick = target.get_resource::<FixedFramesteps>()
+            .map(|framesteps| !framesteps.running().is_empty())
+            .unwrap_or(false);
+        assert!(!inside_tick, "Cannot merge a presimulated world from inside a tick.");
+        let mut entities = EntityMap::default();
+        for move_components in merge.components.iter() {
+            move_components(&mut self.world, target, &mut entities);
+        }
+        for map_components in merge.mapped.iter() {
+            map_components(target, &entities);
+        }
+        for move_resource in merge.resources.iter() {
+            move_resource(&mut self.world, target);
+        }
+        // if no ticks ran, there is no stepping state to move
+        let snapshot = self.world.get_resource::<FixedFramesteps>().map(FixedFramesteps::snapshot);
+        if let (true, Some(snapshot)) = (merge.framestep_state, snapshot) {
+            if let Some(mut framesteps) = target.get_resource_mut::<FixedFramesteps>() {
+                framesteps.restore(&snapshot);
+            }
+        }
+        entities
+    }
+}
+
+type MoveComponents = fn(&mut World, &mut World, &mut EntityMap);
+type MapComponents = fn(&mut World, &EntityMap);
+type MoveResource = fn(&mut World, &mut World);
+
+/// What to move from a pre-simulated world into another one (see [`Presimulated::merge_into`])
+///
+/// Every entity of the pre-simulated world that has any of the listed components gets
+/// a new entity in the target world, with those components. Other components stay behind.
+#[derive(Default)]
+pub struct WorldMerge {
+    components: Vec<MoveComponents>,
+    mapped: Vec<MapComponents>,
+    resources: Vec<MoveResource>,
+    framestep_state: bool,
+}
+
+impl WorldMerge {
+    /// Create an empty merge, that moves nothing
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder method for moving the component `T`
+    ///
+    /// Any `Entity` in the component still refers to the pre-simulated world; use
+    /// [`with_mapped_component`](Self::with_mapped_component) for components with entities.
+    pub fn with_component<T: Component>(mut self) -> Self {
+        self.components.push(move_components::<T>);
+        self
+    }
+
+    /// Builder method for moving the component `T`, and mapping its entities to the new ones
+    pub fn with_mapped_component<T: Component + MapEntities>(mut self) -> Self {
+        self.components.push(move_components::<T>);
+        self.mapped.push(map_components::<T>);
+        self
+    }
+
+    /// Builder method for moving the resource `R` (replacing the one in the target world)
+    pub fn with_resource<R: Resource>(mut self) -> Self {
+        self.resources.push(move_resource::<R>);
+        self
+    }
+
+    /// Builder method for moving the stepping state of the fixed framesteps
+    ///
+    /// The state of the pre-simulated fixed framestep replaces the state of the fixed
+    /// framestep with the same name in the target world (see [`FixedFramesteps::restore`]).
+    /// That fixed framestep must have run at least once in the target world, to have a
+    /// state to replace.
+    pub fn with_framestep_state(mut self) -> Self {
+        self.framestep_state = true;
+        self
+    }
+}
+
+fn move_components<T: Component>(source: &mut World, target: &mut World, entities: &mut EntityMap) {
+    let mut query = source.query_filtered::<Entity, With<T>>();
+    let moved: Vec<Entity> = query.iter(source).collect();
+    for old in moved {
+        let value = match source.entity_mut(old).remove::<T>() {
+            Some(value) => value,
+            None => continue,
+        };
+        let new = match entities.get(old) {
+            Ok(new) => new,
+            Err(_) => {
+                let new = target.spawn_empty().id();
+                entities.insert(old, new);
+                new
+            }
+        };
+        target.entity_mut(new).insert(value);
+    }
+}
+
+fn map_components<T: Component + MapEntities>(target: &mut World, entities: &EntityMap) {
+    for new in entities.values() {
+        if let Some(mut value) = target.get_mut::<T>(new) {
+            if let Err(e) = value.map_entities(entities) {
+                warn!("Could not map the entities of a merged {}: {}", std::any::type_name::<T>(), e);
+            }
+        }
+    }
+}
+
+fn move_resource<R: Resource>(source: &mut World, target: &mut World) {
+    if let Some(resource) = source.remove_resource::<R>() {
+        target.insert_resource(resource);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::prelude::*;
+    use bevy_tasks::TaskPool;
+
+    use super::{Presimulation, WorldMerge};
+    use crate::fixedframestep::{FixedFramestepStage, FixedFramesteps};
+
+    #[derive(Component)]
+    struct Tree;
+
+    #[test]
+    fn merge_without_ticks() {
+        let mut world = World::new();
+        world.spawn(Tree);
+        let stage = FixedFramestepStage::new(1, "presim").with_stage(SystemStage::parallel());
+        let presimulated = Presimulation::spawn_on(&TaskPool::new(), world, stage, 0).finish();
+        assert_eq!(presimulated.ticks(), 0);
+        assert!(presimulated.world().get_resource::<FixedFramesteps>().is_none());
+
+        let mut target = World::new();
+        target.init_resource::<FixedFramesteps>();
+        let merge = WorldMerge::new().with_component::<Tree>().with_framestep_state();
+        let entities = presimulated.merge_into(&mut target, &merge);
+        assert_eq!(entities.len(), 1);
+        assert_eq!(target.query::<&Tree>().iter(&target).count(), 1);
+        assert_eq!(target.resource::<FixedFramesteps>().iter().count(), 0);
+    }
+}
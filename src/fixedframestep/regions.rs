@@ -0,0 +1,136 @@
+//! Partial ticks for streaming worlds: processing only some regions on each tick
+//!
+//! Open worlds are split into regions (chunks, cells, ...), and simulating all of them on
+//! every tick is too much. Spawn an entity for each region, with a [`RegionTickState`]
+//! component for the fixed framestep that simulates it, and mark the sub-stages that work
+//! region by region with
+//! [`FixedFramestepStage::with_region_substage`](super::FixedFramestepStage::with_region_substage).
+//! Limit how many regions are processed on each tick with
+//! [`FixedFramestepStage::with_regions_per_tick`](super::FixedFramestepStage::with_regions_per_tick).
+//!
+//! On every tick, right before the first per-region sub-stage runs, the fixed framestep
+//! picks the regions to process, and marks them as [due](RegionTickState::is_due). The
+//! regions take turns (round-robin): the ones that have waited the longest go first, and
+//! the ones that were never processed before all others. Your systems in the per-region
+//! sub-stages skip the regions that are not due, and scale their work by
+//! [`ticks_elapsed`](RegionTickState::ticks_elapsed), to make up for the skipped ticks:
+//!
+//! ```ignore
+//! use bevy::prelude::*;
+//! use iyes_loopless::prelude::*;
+//!
+//! #[derive(Component)]
+//! struct Weather { rain: f32 }
+//!
+//! fn update_weather(mut regions: Query<(&RegionTickState, &mut Weather)>) {
+//!     for (state, mut weather) in regions.iter_mut() {
+//!         if !state.is_due() {
+//!             continue;
+//!         }
+//!         weather.rain *= 0.99f32.powi(state.ticks_elapsed() as i32);
+//!     }
+//! }
+//! ```
+//!
+//! Streamed-out regions can be [deactivated](RegionTickState::set_active), so that they
+//! are never due; once active again, their first `ticks_elapsed` covers all the missed
+//! ticks. Ties are broken by `Entity`, so the regions are processed in the same order on
+//! every run, as long as they are spawned in the same order.
+
+use bevy_ecs::prelude::*;
+use bevy_utils::HashSet;
+
+use super::{FramestepLabel, FramestepName};
+
+/// Component for the entities of the regions of a streaming world (see the [module docs](self))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Component)]
+pub struct RegionTickState {
+    framestep: FramestepName,
+    active: bool,
+    due: bool,
+    last_tick: Option<u64>,
+    elapsed: u64,
+}
+
+impl RegionTickState {
+    /// A region simulated by the given fixed framestep, active and never processed yet
+    pub fn new(framestep: impl FramestepLabel) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            active: true,
+            due: false,
+            last_tick: None,
+            elapsed: 0,
+        }
+    }
+
+    /// The fixed framestep that simulates the region
+    pub fn framestep(&self) -> FramestepName {
+        self.framestep
+    }
+
+    /// Is the region processed on the current tick?
+    ///
+    /// Stays as it was on the last tick that ran a per-region sub-stage, until the next one.
+    pub fn is_due(&self) -> bool {
+        self.due
+    }
+
+    /// The number of ticks since the region was processed before (`1` on its first time)
+    ///
+    /// Multiply the per-tick changes by this, to make up for the skipped ticks.
+    pub fn ticks_elapsed(&self) -> u64 {
+        self.elapsed
+    }
+
+    /// The last tick on which the region was due, if any
+    pub fn last_tick(&self) -> Option<u64> {
+        self.last_tick
+    }
+
+    /// Is the region active? Inactive regions are never due.
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Activate or deactivate the region (say, when it is streamed in or out)
+    ///
+    /// A deactivated region stops being due right away.
+    pub fn set_active(&mut self, active: bool) {
+        self.active = active;
+        if !active {
+            self.due = false;
+        }
+    }
+}
+
+/// Mark the regions of a fixed framestep that are due on the given tick
+///
+/// `per_tick` is the maximum number of regions to process (`None` for all of them).
+pub(super) fn schedule_regions(world: &mut World, label: FramestepName, tick: u64, per_tick: Option<usize>) {
+    let mut query = world.query::<(Entity, &mut RegionTickState)>();
+
+    // the longest waiting first; `None` (never processed) sorts before every tick
+    let mut waiting: Vec<(Option<u64>, Entity)> = query.iter(world)
+        .filter(|(_, state)| state.framestep == label && state.active)
+        .map(|(entity, state)| (state.last_tick, entity))
+        .collect();
+    waiting.sort_unstable();
+    waiting.truncate(per_tick.unwrap_or(usize::MAX));
+    let due: HashSet<Entity> = waiting.into_iter().map(|(_, entity)| entity).collect();
+
+    for (entity, mut state) in query.iter_mut(world) {
+        if state.framestep != label {
+            continue;
+        }
+        if due.contains(&entity) {
+            // after a rewind, the last tick can be in the future
+            state.elapsed = state.last_tick.map_or(1, |last| tick.saturating_sub(last).max(1));
+            state.last_tick = Some(tick);
+            state.due = true;
+        } else if state.due {
+            state.due = false;
+        }
+    }
+}
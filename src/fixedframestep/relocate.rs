@@ -0,0 +1,179 @@
+//! Moving a fixed framestep to another position in the schedule
+//!
+//! Bevy schedules cannot remove or reorder stages, so a fixed framestep that is moved
+//! (using `move_fixed_framestep` on the `App` or `Schedule`) is taken out of its stage, and
+//! put into a [`FramestepSlotStage`] at the new position, like the [`DynamicFramestepsStage`](super::dynamic::DynamicFramestepsStage)
+//! hosts the fixed framesteps created at runtime. The [`FixedFramestepStage`] keeps all its
+//! sub-stages and systems, and its stepping state (tick counter, accumulator, ...) is in the
+//! [`FixedFramesteps`](super::FixedFramesteps) resource, so it carries on from its new position.
+//!
+//! The stage at the old position stays in the schedule, with its `FixedFramestepStageLabel`,
+//! but it is empty and does nothing. The slot stage at the new position is labeled with the
+//! name of the fixed framestep and the position, like `"sim (after CoreStage::PostUpdate)"`.
+//! Moving the fixed framestep again leaves the slot empty, and the slot is reused if the
+//! fixed framestep is moved back to the same position, so moving it back and forth does
+//! not grow the schedule.
+//!
+//! Only top-level fixed framesteps can be moved (not nested, instanced, or dynamic ones).
+//! Stages and systems that were ordered relative to the old stage label are not moved along.
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::StageLabelId;
+
+use super::{FixedFramestepStage, FixedFramestepStageLabel, FramestepName, FramestepPosition, SchedulePosition};
+use super::{resolve_position, Insertion};
+
+/// Type used as the Bevy Stage Label of a [`FramestepSlotStage`]
+#[derive(Debug, Clone)]
+pub struct FramestepSlotLabel(pub &'static str);
+
+impl StageLabel for FramestepSlotLabel {
+    fn as_str(&self) -> &'static str {
+        self.0
+    }
+}
+
+/// Stage that hosts a fixed framestep that was moved (see the [module docs](self))
+#[derive(Default)]
+pub struct FramestepSlotStage {
+    pub(super) stage: Option<FixedFramestepStage>,
+}
+
+impl FramestepSlotStage {
+    /// The fixed framestep in the slot, if it was not moved away again
+    pub fn framestep(&self) -> Option<&FixedFramestepStage> {
+        self.stage.as_ref()
+    }
+}
+
+impl Stage for FramestepSlotStage {
+    fn run(&mut self, world: &mut World) {
+        if let Some(stage) = self.stage.as_mut() {
+            stage.run(world);
+        }
+    }
+}
+
+/// Move a top-level fixed framestep stage to another position in the schedule
+///
+/// Panics if the fixed framestep is not at the top level of the schedule, or if a stage
+/// that the position refers to is not in the schedule.
+pub(super) fn move_framestep(schedule: &mut Schedule, label: FramestepName, position: FramestepPosition) {
+    let mut stage = take_framestep(schedule, label)
+        .unwrap_or_else(|| panic!("Fixed framestep {:?} not found at the top level of the schedule.", label));
+    let target = resolve_position(schedule, &position);
+    let slot_name = match target {
+        Insertion::Before(target) => format!("{} (before {})", label, target.as_str()),
+        Insertion::After(target) => format!("{} (after {})", label, target.as_str()),
+        Insertion::End => format!("{} (at the end)", label),
+    };
+    stage.position = match target {
+        Insertion::Before(target) => SchedulePosition::Before(target.as_str()),
+        Insertion::After(target) => SchedulePosition::After(target.as_str()),
+        Insertion::End => SchedulePosition::Unknown,
+    };
+    stage.registered_position = Some(position);
+    stage.layout_changed = true;
+
+    let existing = schedule.iter_stages()
+        .find(|(slot, host)| slot.as_str() == slot_name && host.is::<FramestepSlotStage>())
+        .map(|(slot, _)| slot);
+    if let Some(slot) = existing {
+        schedule.get_stage_mut::<FramestepSlotStage>(slot).unwrap().stage = Some(stage);
+        return;
+    }
+    // labels must be `'static`; there is at most one slot per fixed framestep and position
+    let slot_label = FramestepSlotLabel(Box::leak(slot_name.into_boxed_str()));
+    let slot = FramestepSlotStage { stage: Some(stage) };
+    match target {
+        Insertion::Before(target) => {
+            schedule.add_stage_before(target, slot_label, slot);
+        }
+        Insertion::After(target) => {
+            schedule.add_stage_after(target, slot_label, slot);
+        }
+        Insertion::End => {
+            schedule.add_stage(slot_label, slot);
+        }
+    }
+}
+
+/// Take a top-level fixed framestep out of its stage or slot
+fn take_framestep(schedule: &mut Schedule, label: FramestepName) -> Option<FixedFramestepStage> {
+    if let Some(stage) = schedule.get_stage_mut::<FixedFramestepStage>(FixedFramestepStageLabel(label)) {
+        if !stage.moved {
+            let mut empty = FixedFramestepStage::new(stage.step, label);
+            empty.moved = true;
+            return Some(std::mem::replace(stage, empty));
+        }
+    }
+    let slot: StageLabelId = schedule.iter_stages()
+        .find(|(_, host)| {
+            host.downcast_ref::<FramestepSlotStage>()
+                .and_then(FramestepSlotStage::framestep)
+                .map(|stage| stage.label == label)
+                .unwrap_or(false)
+        })
+        .map(|(slot, _)| slot)?;
+    schedule.get_stage_mut::<FramestepSlotStage>(slot)?.stage.take()
+}
+
+#[cfg(all(test, feature = "app"))]
+mod tests {
+    use bevy_app::{App, CoreStage};
+    use bevy_ecs::prelude::*;
+
+    use crate::fixedframestep::app::AppLooplessFixedFramestepExt;
+    use crate::fixedframestep::{FixedFramesteps, FramestepLayouts, FramestepPosition, Frames, SchedulePosition};
+
+    #[derive(Default, Resource)]
+    struct Order(Vec<&'static str>);
+
+    fn app() -> App {
+        let mut app = App::default();
+        app.init_resource::<Order>();
+        app.add_fixed_framestep(2, "sim");
+        app.add_fixed_framestep_system("sim", 0, |mut order: ResMut<Order>| order.0.push("sim"));
+        app.add_system(|mut order: ResMut<Order>| order.0.push("update"));
+        app
+    }
+
+    fn take_order(app: &mut App) -> Vec<&'static str> {
+        std::mem::take(&mut app.world.resource_mut::<Order>().0)
+    }
+
+    #[test]
+    fn moved_framestep_keeps_its_state() {
+        let mut app = app();
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(take_order(&mut app), ["update", "sim", "update", "update"]);
+
+        app.move_fixed_framestep("sim", FramestepPosition::after(CoreStage::PostUpdate));
+        let info = &app.world.resource::<FixedFramesteps>()["sim"];
+        assert_eq!((info.tick(), info.remaining()), (1, Frames(1)));
+        let layout = app.world.resource::<FramestepLayouts>().get("sim").unwrap();
+        assert_eq!(layout.schedule_position(), SchedulePosition::After("CoreStage::PostUpdate"));
+
+        // the accumulator carried over: the next frame completes the tick, now after `Update`
+        app.update();
+        assert_eq!(take_order(&mut app), ["update", "sim"]);
+        assert_eq!(app.world.resource::<FixedFramesteps>()["sim"].tick(), 2);
+    }
+
+    #[test]
+    fn moving_back_and_forth_reuses_the_slots() {
+        let mut app = app();
+        app.move_fixed_framestep("sim", FramestepPosition::after(CoreStage::PostUpdate));
+        app.move_fixed_framestep("sim", FramestepPosition::Default);
+        let n_stages = app.schedule.iter_stages().count();
+        app.move_fixed_framestep("sim", FramestepPosition::after(CoreStage::PostUpdate));
+        app.move_fixed_framestep("sim", FramestepPosition::Default);
+        assert_eq!(app.schedule.iter_stages().count(), n_stages);
+
+        app.update();
+        app.update();
+        assert_eq!(take_order(&mut app), ["update", "sim", "update"]);
+    }
+}
@@ -0,0 +1,556 @@
+//! Deterministic replays of a fixed framestep
+//!
+//! A replay records the input of every tick of a fixed framestep, so that the
+//! simulation can be run again later, tick by tick, with exactly the same input.
+//! If your simulation is deterministic, it will produce exactly the same result.
+//!
+//! Your input type must implement [`ReplayInput`], and be available as a resource
+//! when the ticks run. Add the [`ReplayRecorderPlugin`] to record the input of each
+//! tick, and the [`ReplayPlayerPlugin`] to play a [`Replay`] back: it overwrites the
+//! input resource at the start of each tick, with the recorded one.
+//!
+//! Optionally, the recorder can also store a checksum of the world at the start of
+//! every tick. The player compares it against the checksum of its own world, and
+//! sends a [`ReplayDesync`] event if they are different.
+//!
+//! Only the input resource is recorded. Events are not: if your tick systems read events
+//! sent from outside the fixed framestep (say, by per-frame input handling), a replay does
+//! not have them. Collect what the tick needs from such events into the input resource
+//! before the tick, and have the tick systems read that instead.
+//!
+//! # Format
+//!
+//! [`Replay::to_bytes`] writes a versioned binary format (all integers little-endian):
+//!
+//! - magic bytes `IYLR`, and the format version ([`REPLAY_FORMAT_VERSION`]), as `u16`
+//! - header: the fixed framestep name (`u16` length + UTF-8), its step (`u64`),
+//!   the seed (`u64`), and the tick it started after (`u64`)
+//! - the number of ticks (`u64`), and then for each tick: the tick number (`u64`), the
+//!   input (`u32` length + bytes), and the checksum (`u8` flag + `u64` if present)
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName, Frames, PauseReason, find_framestep_mut};
+
+/// The version of the replay format written by [`Replay::to_bytes`]
+pub const REPLAY_FORMAT_VERSION: u16 = 1;
+
+const MAGIC: &[u8; 4] = b"IYLR";
+
+/// The reason the fixed framestep is paused for, once a replay has been played back
+///
+/// Pop it (with [`pop_pause`](super::FixedFramestepInfo::pop_pause)) to let the
+/// simulation carry on past the end of the replay. Other pause reasons are left alone.
+pub const REPLAY_FINISHED: PauseReason = PauseReason::custom("replay finished");
+
+/// Types that can be recorded as the per-tick input of a replay
+///
+/// The type is used as a resource, which your fixed framestep systems read.
+pub trait ReplayInput: Resource + Sized {
+    /// Write the input as bytes
+    fn encode(&self, out: &mut Vec<u8>);
+    /// Read the input from the bytes written by `encode`
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+/// The header of a [`Replay`]: how to set up the simulation to play it back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayHeader {
+    /// The name of the fixed framestep that was recorded
+    pub framestep: String,
+    /// The step of the fixed framestep, in frames
+    pub step: Frames,
+    /// The seed for any random number generators of the simulation
+    pub seed: u64,
+    /// The tick number before the first recorded tick
+    pub start_tick: u64,
+}
+
+/// The recorded data of one tick of a [`Replay`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayTick {
+    /// The tick number
+    pub tick: u64,
+    /// The encoded input (see [`ReplayInput`])
+    pub input: Vec<u8>,
+    /// The checksum of the world at the start of the tick, if recorded
+    pub checksum: Option<u64>,
+}
+
+/// A recording of the input of a fixed framestep, tick by tick (see the [module docs](self))
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay {
+    /// How to set up the simulation
+    pub header: ReplayHeader,
+    /// The recorded ticks, in order
+    pub ticks: Vec<ReplayTick>,
+}
+
+/// Error from [`Replay::from_bytes`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    /// The data does not start with the magic bytes of a replay
+    BadMagic,
+    /// The replay was written by an unsupported version of the format
+    Version(u16),
+    /// The data ended too early
+    Truncated,
+    /// The fixed framestep name is not valid UTF-8
+    InvalidName,
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::BadMagic => write!(f, "not a replay file"),
+            ReplayError::Version(v) => write!(f, "unsupported replay format version {} (expected {})", v, REPLAY_FORMAT_VERSION),
+            ReplayError::Truncated => write!(f, "the replay data is truncated"),
+            ReplayError::InvalidName => write!(f, "the fixed framestep name is not valid UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+impl Replay {
+    /// Write the replay in the binary format (see the [module docs](self#format))
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&REPLAY_FORMAT_VERSION.to_le_bytes());
+        self.header.write(&mut out);
+        out.extend_from_slice(&(self.ticks.len() as u64).to_le_bytes());
+        for tick in self.ticks.iter() {
+            tick.write(&mut out);
+        }
+        out
+    }
+
+    /// Read a replay written by [`to_bytes`](Self::to_bytes)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Replay, ReplayError> {
+        let mut reader = Reader { bytes };
+        if reader.take(4)? != MAGIC {
+            return Err(ReplayError::BadMagic);
+        }
+        let version = reader.u16()?;
+        if version != REPLAY_FORMAT_VERSION {
+            return Err(ReplayError::Version(version));
+        }
+        let header = reader.header()?;
+        let n_ticks = reader.u64()?;
+        let mut ticks = Vec::new();
+        for _ in 0..n_ticks {
+            ticks.push(reader.tick()?);
+        }
+        Ok(Replay { header, ticks })
+    }
+
+    /// The part of the replay after the given tick, to play back from a savegame of that tick
+    pub fn since(&self, tick: u64) -> Replay {
+        Replay {
+            header: ReplayHeader {
+                start_tick: tick.max(self.header.start_tick),
+                ..self.header.clone()
+            },
+            ticks: self.ticks.iter().filter(|record| record.tick > tick).cloned().collect(),
+        }
+    }
+}
+
+impl ReplayHeader {
+    pub(super) fn write(&self, out: &mut Vec<u8>) {
+        let name = self.framestep.as_bytes();
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        out.extend_from_slice(name);
+        out.extend_from_slice(&self.step.0.to_le_bytes());
+        out.extend_from_slice(&self.seed.to_le_bytes());
+        out.extend_from_slice(&self.start_tick.to_le_bytes());
+    }
+}
+
+impl ReplayTick {
+    pub(super) fn write(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(&self.tick.to_le_bytes());
+        out.extend_from_slice(&(self.input.len() as u32).to_le_bytes());
+        out.extend_from_slice(&self.input);
+        match self.checksum {
+            Some(checksum) => {
+                out.push(1);
+                out.extend_from_slice(&checksum.to_le_bytes());
+            }
+            None => out.push(0),
+        }
+    }
+}
+
+pub(super) struct Reader<'a> {
+    pub(super) bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(super) fn header(&mut self) -> Result<ReplayHeader, ReplayError> {
+        let name_len = self.u16()? as usize;
+        let framestep = std::str::from_utf8(self.take(name_len)?)
+            .map_err(|_| ReplayError::InvalidName)?
+            .to_string();
+        Ok(ReplayHeader {
+            framestep,
+            step: Frames(self.u64()?),
+            seed: self.u64()?,
+            start_tick: self.u64()?,
+        })
+    }
+
+    pub(super) fn tick(&mut self) -> Result<ReplayTick, ReplayError> {
+        let tick = self.u64()?;
+        let input_len = self.u32()? as usize;
+        let input = self.take(input_len)?.to_vec();
+        let checksum = match self.take(1)?[0] {
+            0 => None,
+            _ => Some(self.u64()?),
+        };
+        Ok(ReplayTick { tick, input, checksum })
+    }
+
+    pub(super) fn take(&mut self, n: usize) -> Result<&'a [u8], ReplayError> {
+        if self.bytes.len() < n {
+            return Err(ReplayError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    pub(super) fn u16(&mut self) -> Result<u16, ReplayError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, ReplayError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, ReplayError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+}
+
+/// Function for computing a checksum of the simulation state
+pub type ChecksumFn = fn(&mut World) -> u64;
+
+/// Resource with the replay being recorded by the [`ReplayRecorderPlugin`]
+#[derive(Resource)]
+pub struct ReplayRecorder<I: ReplayInput> {
+    replay: Replay,
+    checksum: Option<ChecksumFn>,
+    /// Is the recorder recording? Set to `false` to stop recording ticks.
+    pub recording: bool,
+    marker: PhantomData<fn() -> I>,
+}
+
+impl<I: ReplayInput> ReplayRecorder<I> {
+    /// The replay recorded so far
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Discard the recorded ticks after the given tick, e.g. to re-record from a savestate
+    pub fn truncate_after(&mut self, tick: u64) {
+        self.replay.ticks.retain(|record| record.tick <= tick);
+    }
+
+    /// Take the replay recorded so far, and continue with an empty one
+    pub fn take_replay(&mut self) -> Replay {
+        let header = self.replay.header.clone();
+        let ticks = std::mem::take(&mut self.replay.ticks);
+        Replay { header, ticks }
+    }
+}
+
+/// Plugin for recording the input of every tick of a fixed framestep (see the [module docs](self))
+///
+/// The fixed framestep must already be added to the app. The recording is stored in
+/// the [`ReplayRecorder<I>`] resource. Ticks where the input resource is missing are
+/// recorded with empty input.
+pub struct ReplayRecorderPlugin<I: ReplayInput> {
+    framestep: FramestepName,
+    seed: u64,
+    checksum: Option<ChecksumFn>,
+    marker: PhantomData<fn() -> I>,
+}
+
+impl<I: ReplayInput> ReplayRecorderPlugin<I> {
+    /// Create the plugin to record the given fixed framestep
+    pub fn new(framestep: impl FramestepLabel) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            seed: 0,
+            checksum: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Builder method for storing the seed of your random number generators in the replay
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Builder method for recording a checksum of the world at the start of every tick
+    pub fn with_checksum(mut self, checksum: ChecksumFn) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+}
+
+impl<I: ReplayInput> Plugin for ReplayRecorderPlugin<I> {
+    fn build(&self, app: &mut App) {
+        let info = app.world.resource::<FixedFramesteps>().get(self.framestep)
+            .expect("Fixed Framestep not found");
+        let header = ReplayHeader {
+            framestep: self.framestep.to_string(),
+            step: info.step,
+            seed: self.seed,
+            start_tick: info.tick(),
+        };
+        app.insert_resource(ReplayRecorder::<I> {
+            replay: Replay { header, ticks: Vec::new() },
+            checksum: self.checksum,
+            recording: true,
+            marker: PhantomData,
+        });
+        find_framestep_mut(&mut app.schedule, self.framestep)
+            .expect("Fixed Framestep Stage not found")
+            .add_tick_begin_hook(record_tick::<I>);
+    }
+}
+
+fn record_tick<I: ReplayInput>(world: &mut World) {
+    let (recording, checksum) = match world.get_resource::<ReplayRecorder<I>>() {
+        Some(recorder) => (recorder.recording, recorder.checksum),
+        None => return,
+    };
+    if !recording {
+        return;
+    }
+    let tick = world.resource::<FixedFramesteps>().current().tick();
+    let mut input = Vec::new();
+    if let Some(res) = world.get_resource::<I>() {
+        res.encode(&mut input);
+    }
+    let checksum = checksum.map(|checksum| checksum(world));
+    world.resource_mut::<ReplayRecorder<I>>().replay.ticks.push(ReplayTick { tick, input, checksum });
+}
+
+/// Resource with the seed from the header of the replay being played back
+///
+/// Inserted by the [`ReplayPlayerPlugin`]. Use it to seed your random number generators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Resource)]
+pub struct ReplaySeed(pub u64);
+
+/// Event sent when the checksum of a tick does not match the recorded one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayDesync {
+    /// The tick number
+    pub tick: u64,
+    /// The recorded checksum
+    pub expected: u64,
+    /// The checksum of the world that is playing the replay
+    pub actual: u64,
+}
+
+/// Event sent when the [`ReplayPlayerPlugin`] has played back all the ticks of the replay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayFinished {
+    /// The number of the last tick that was played back
+    pub tick: u64,
+}
+
+/// Resource with the replay being played back by the [`ReplayPlayerPlugin`]
+#[derive(Resource)]
+pub struct ReplayPlayer<I: ReplayInput> {
+    replay: Replay,
+    next: usize,
+    checksum: Option<ChecksumFn>,
+    marker: PhantomData<fn() -> I>,
+}
+
+impl<I: ReplayInput> ReplayPlayer<I> {
+    /// The replay being played back
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    /// Have all the ticks been played back?
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.replay.ticks.len()
+    }
+}
+
+/// Plugin for playing back a [`Replay`] (see the [module docs](self))
+///
+/// The fixed framestep must already be added to the app. The plugin sets its step,
+/// tick counter, and [seed](super::FixedFramestepStage::set_seed) as recorded in the
+/// header, and inserts the [`ReplaySeed`] resource.
+/// When all the ticks have been played back, the fixed framestep is paused, with the
+/// [`REPLAY_FINISHED`] reason, and a [`ReplayFinished`] event is sent.
+pub struct ReplayPlayerPlugin<I: ReplayInput> {
+    framestep: FramestepName,
+    replay: Replay,
+    checksum: Option<ChecksumFn>,
+    marker: PhantomData<fn() -> I>,
+}
+
+impl<I: ReplayInput> ReplayPlayerPlugin<I> {
+    /// Create the plugin to play back a replay on the given fixed framestep
+    pub fn new(framestep: impl FramestepLabel, replay: Replay) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            replay,
+            checksum: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Builder method for verifying the recorded checksums, using the same function as the recorder
+    pub fn with_checksum(mut self, checksum: ChecksumFn) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+}
+
+impl<I: ReplayInput> Plugin for ReplayPlayerPlugin<I> {
+    fn build(&self, app: &mut App) {
+        let header = &self.replay.header;
+        let mut framesteps = app.world.resource_mut::<FixedFramesteps>();
+        let info = framesteps.get_mut(self.framestep)
+            .expect("Fixed Framestep not found");
+        if info.try_set_step(header.step).is_err() {
+            panic!("Invalid step in the replay header: {}", header.step);
+        }
+        info.tick = header.start_tick;
+        info.seed = header.seed;
+        app.insert_resource(ReplaySeed(header.seed));
+        app.insert_resource(ReplayPlayer::<I> {
+            replay: self.replay.clone(),
+            next: 0,
+            checksum: self.checksum,
+            marker: PhantomData,
+        });
+        app.add_event::<ReplayDesync>();
+        app.add_event::<ReplayFinished>();
+        let stage = find_framestep_mut(&mut app.schedule, self.framestep)
+            .expect("Fixed Framestep Stage not found");
+        stage.set_seed(header.seed);
+        stage.add_tick_begin_hook(play_tick::<I>);
+    }
+}
+
+fn play_tick<I: ReplayInput>(world: &mut World) {
+    let tick = world.resource::<FixedFramesteps>().current().tick();
+    let (record, checksum) = {
+        let mut player = match world.get_resource_mut::<ReplayPlayer<I>>() {
+            Some(player) => player,
+            None => return,
+        };
+        // skip any ticks that did not happen here
+        while player.replay.ticks.get(player.next).map(|record| record.tick < tick).unwrap_or(false) {
+            player.next += 1;
+        }
+        let record = match player.replay.ticks.get(player.next) {
+            Some(record) if record.tick == tick => record.clone(),
+            _ => return,
+        };
+        player.next += 1;
+        (record, player.checksum)
+    };
+
+    if let Some(input) = I::decode(&record.input) {
+        world.insert_resource(input);
+    }
+    if let (Some(checksum), Some(expected)) = (checksum, record.checksum) {
+        let actual = checksum(world);
+        if actual != expected {
+            if let Some(mut events) = world.get_resource_mut::<Events<ReplayDesync>>() {
+                events.send(ReplayDesync { tick, expected, actual });
+            }
+        }
+    }
+
+    if world.resource::<ReplayPlayer<I>>().is_finished() {
+        world.resource_mut::<FixedFramesteps>().current_mut().push_pause(REPLAY_FINISHED);
+        if let Some(mut events) = world.get_resource_mut::<Events<ReplayFinished>>() {
+            events.send(ReplayFinished { tick });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_app::App;
+    use bevy_ecs::prelude::*;
+
+    use super::{Replay, ReplayHeader, ReplayInput, ReplayPlayerPlugin, ReplayTick, REPLAY_FINISHED};
+    use crate::fixedframestep::app::AppLooplessFixedFramestepExt;
+    use crate::fixedframestep::{FixedFramesteps, Frames, PauseReason};
+
+    #[derive(Resource)]
+    struct Input(u8);
+
+    impl ReplayInput for Input {
+        fn encode(&self, out: &mut Vec<u8>) {
+            out.push(self.0);
+        }
+
+        fn decode(bytes: &[u8]) -> Option<Self> {
+            bytes.first().copied().map(Input)
+        }
+    }
+
+    #[derive(Default, Resource)]
+    struct Played(Vec<u8>);
+
+    fn replay() -> Replay {
+        Replay {
+            header: ReplayHeader {
+                framestep: "sim".into(),
+                step: Frames(1),
+                seed: 7,
+                start_tick: 0,
+            },
+            ticks: vec![
+                ReplayTick { tick: 1, input: vec![10], checksum: None },
+                ReplayTick { tick: 2, input: vec![20], checksum: None },
+            ],
+        }
+    }
+
+    #[test]
+    fn finished_replay_pauses_with_its_own_reason() {
+        let mut app = App::default();
+        app.init_resource::<Played>();
+        app.add_fixed_framestep(1, "sim");
+        app.add_fixed_framestep_system("sim", 0, |input: Res<Input>, mut played: ResMut<Played>| played.0.push(input.0));
+        app.add_plugin(ReplayPlayerPlugin::<Input>::new("sim", replay()));
+        for _ in 0..4 {
+            app.update();
+        }
+        assert_eq!(app.world.resource::<Played>().0, [10, 20]);
+
+        let mut framesteps = app.world.resource_mut::<FixedFramesteps>();
+        let info = framesteps.get_mut("sim").unwrap();
+        assert!(info.is_paused_by(REPLAY_FINISHED));
+        assert_eq!(info.pause_count(PauseReason::User), 0);
+        // the user pausing and unpausing does not resume the simulation past the replay
+        info.pause();
+        info.unpause();
+        assert!(info.is_paused());
+        assert!(info.pop_pause(REPLAY_FINISHED));
+        app.update();
+        assert_eq!(app.world.resource::<Played>().0, [10, 20, 20]);
+    }
+}
@@ -0,0 +1,121 @@
+//! Human-readable report of all the fixed framesteps in a schedule
+//!
+//! Use [`schedule_report`] (or `print_fixed_framestep_report` on the `App`, which logs it)
+//! to get an overview of a big project: every fixed framestep, where it is in the
+//! schedule, how often it ticks, and what sub-stages it has, with how many systems.
+//! For the full list of systems, see the [`debugdump`](super::debugdump) module.
+//!
+//! The report shows the parameters of the stages as of their last run; for the live
+//! values, use the [`FixedFramesteps`](super::FixedFramesteps) resource.
+
+use std::fmt::Write;
+use std::time::Duration;
+
+use bevy_ecs::prelude::*;
+
+use super::{FixedFramestepStage, SchedulePosition};
+use super::cleanup::CleanupStage;
+use super::dynamic::DynamicFramestepsStage;
+use super::relocate::FramestepSlotStage;
+use super::groups::SystemGroupStage;
+use super::instances::InstancedFramestepStage;
+
+/// Produce a report of all the fixed framesteps in a schedule (see the [module docs](self))
+///
+/// Nested schedules and nested fixed framesteps are included too.
+pub fn schedule_report(schedule: &Schedule) -> String {
+    let mut lines = Vec::new();
+    report_schedule(&mut lines, schedule);
+    let mut report = String::new();
+    match lines.len() {
+        0 => writeln!(report, "No fixed framesteps.").unwrap(),
+        _ => {
+            writeln!(report, "Fixed framesteps:").unwrap();
+            for line in lines {
+                writeln!(report, "{}", line).unwrap();
+            }
+        }
+    }
+    report
+}
+
+fn report_schedule(lines: &mut Vec<String>, schedule: &Schedule) {
+    for (_, stage) in schedule.iter_stages() {
+        if let Some(ffstage) = stage.downcast_ref::<FixedFramestepStage>() {
+            // skip the empty stages left behind by `move_fixed_framestep`
+            if !ffstage.moved {
+                report_framestep(lines, ffstage, "", None);
+            }
+        } else if let Some(slot) = stage.downcast_ref::<FramestepSlotStage>() {
+            if let Some(ffstage) = slot.framestep() {
+                report_framestep(lines, ffstage, " (moved)", None);
+            }
+        } else if let Some(instanced) = stage.downcast_ref::<InstancedFramestepStage>() {
+            report_framestep(lines, instanced.template(), " (instanced)", None);
+        } else if let Some(dynamic) = stage.downcast_ref::<DynamicFramestepsStage>() {
+            for ffstage in dynamic.framesteps() {
+                report_framestep(lines, ffstage, " (dynamic)", None);
+            }
+        } else if let Some(nested) = stage.downcast_ref::<Schedule>() {
+            report_schedule(lines, nested);
+        }
+    }
+}
+
+/// Report one fixed framestep; `outer_tick` is the duration of a tick of the fixed framestep it is nested in
+fn report_framestep(lines: &mut Vec<String>, stage: &FixedFramestepStage, kind: &str, outer_tick: Option<Duration>) {
+    let step = stage.framestep();
+    let (unit, tick) = match outer_tick {
+        Some(outer) => ("ticks of the outer fixed framestep", outer.saturating_mul(step.0.min(u32::MAX as u64) as u32)),
+        None => ("frames", stage.frame_duration().saturating_mul(step.0.min(u32::MAX as u64) as u32)),
+    };
+    let hz = 1.0 / tick.as_secs_f64();
+    lines.push(format!(
+        "  {:?}{}: every {} {} ({:.2} Hz), {}, counting {:?}",
+        stage.label(), kind, step, unit, hz, describe_position(stage.schedule_position()), stage.accumulation_source(),
+    ));
+
+    for (i, substage) in stage.substages().enumerate() {
+        let group = substage.downcast_ref::<SystemGroupStage>();
+        let cleanup = substage.downcast_ref::<CleanupStage>();
+        let system_stage = substage.downcast_ref::<SystemStage>()
+            .or_else(|| group.map(SystemGroupStage::stage))
+            .or_else(|| cleanup.map(CleanupStage::stage));
+        let nested = substage.downcast_ref::<FixedFramestepStage>();
+
+        let name = if let Some(group) = group {
+            format!("sub-stage {} (group {:?})", i, group.name())
+        } else if cleanup.is_some() {
+            format!("sub-stage {} (cleanup)", i)
+        } else {
+            format!("sub-stage {}", i)
+        };
+        if let Some(system_stage) = system_stage {
+            let n_systems = system_stage.exclusive_at_start_systems().iter()
+                .chain(system_stage.parallel_systems())
+                .chain(system_stage.exclusive_before_commands_systems())
+                .chain(system_stage.exclusive_at_end_systems())
+                .count();
+            let plural = if n_systems == 1 { "" } else { "s" };
+            lines.push(format!("    {}: {} system{}", name, n_systems, plural));
+        } else if let Some(nested) = nested {
+            lines.push(format!("    {}: nested fixed framestep {:?}", name, nested.label()));
+        } else {
+            lines.push(format!("    {}: custom stage", name));
+        }
+    }
+
+    // list the nested fixed framesteps after their parent, so the parent stays readable
+    for nested in stage.substages().filter_map(|substage| substage.downcast_ref::<FixedFramestepStage>()) {
+        report_framestep(lines, nested, " (nested)", Some(tick));
+    }
+}
+
+fn describe_position(position: SchedulePosition) -> String {
+    match position {
+        SchedulePosition::Before(stage) => format!("before {}", stage),
+        SchedulePosition::After(stage) => format!("after {}", stage),
+        SchedulePosition::Nested(framestep) => format!("nested in {:?}", framestep),
+        SchedulePosition::Unknown => "added to the schedule directly".to_owned(),
+    }
+}
@@ -0,0 +1,358 @@
+//! Savegames that include the state of the fixed framesteps
+//!
+//! [`save_world`] serializes the registered components of all entities marked
+//! [`Persistent`], together with the stepping state of every fixed framestep (tick
+//! counter, accumulator, step, pause reasons, and speed, and its
+//! [simulation clock](super::simtime), if it has one), into one blob. [`load_world`]
+//! restores all of it at once, so the simulation resumes on exactly the same frame
+//! of the tick stream where it was saved, without drifting.
+//!
+//! Register the component types to save in the [`SaveRegistry`] resource. Each type
+//! is stored under a name you choose, which should stay the same when you rename
+//! or move the type, so old savegames can still be loaded. The same goes for the
+//! [config](super::config) types of the fixed framesteps, which are saved with them.
+//!
+//! Not saved: components that are not registered, entity references inside components
+//! (they are not remapped), queued [`TickCommands`](super::commands::TickCommands) (they
+//! are dropped on load), and instanced fixed framesteps.
+//!
+//! Requires the `"framestep-save"` cargo feature. The blob is in the RON format.
+
+use std::fmt;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::world::EntityMut;
+use bevy_utils::Duration;
+use bevy_utils::tracing::warn;
+use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
+
+use super::{FixedFramesteps, FramestepName, Frames, PauseReason};
+use super::commands::TickCommandQueue;
+use super::config::FramestepConfigs;
+use super::simtime::SimulationTime;
+
+/// The version of the save format written by [`save_world`]
+pub const SAVE_FORMAT_VERSION: u32 = 1;
+
+/// Marker component for entities that are saved by [`save_world`]
+///
+/// On [`load_world`], all entities with this component are despawned, and replaced
+/// with the ones from the savegame.
+#[derive(Debug, Default, Clone, Copy)]
+#[derive(Component)]
+pub struct Persistent;
+
+type LoadFn = Box<dyn FnOnce(&mut EntityMut) + Send>;
+
+struct SavedType {
+    name: &'static str,
+    save: fn(&World, Entity) -> Option<Result<String, ron::Error>>,
+    load: fn(&str) -> Result<LoadFn, ron::error::SpannedError>,
+}
+
+type ConfigLoadFn = Box<dyn FnOnce(&mut World, FramestepName) + Send>;
+
+struct SavedConfigType {
+    name: &'static str,
+    save: fn(&World, FramestepName) -> Option<Result<String, ron::Error>>,
+    load: fn(&str) -> Result<ConfigLoadFn, ron::error::SpannedError>,
+}
+
+/// Resource with the component and config types that are saved by [`save_world`]
+#[derive(Default)]
+#[derive(Resource)]
+pub struct SaveRegistry {
+    types: Vec<SavedType>,
+    configs: Vec<SavedConfigType>,
+}
+
+impl SaveRegistry {
+    /// Register a component type to be saved, under the given name
+    ///
+    /// Panics if the name is already used by another type.
+    pub fn register<C: Component + Serialize + DeserializeOwned>(&mut self, name: &'static str) {
+        assert!(
+            self.types.iter().all(|ty| ty.name != name),
+            "A component type is already registered for saving under the name {:?}.", name,
+        );
+        self.types.push(SavedType {
+            name,
+            save: save_component::<C>,
+            load: load_component::<C>,
+        });
+    }
+
+    /// Builder-style method for [`register`](Self::register)
+    pub fn with<C: Component + Serialize + DeserializeOwned>(mut self, name: &'static str) -> Self {
+        self.register::<C>(name);
+        self
+    }
+
+    /// Register a fixed framestep [config](super::config) type to be saved, under the given name
+    ///
+    /// The configs of this type are saved with the fixed framesteps they belong to. Panics
+    /// if the name is already used by another config type.
+    pub fn register_config<T: Serialize + DeserializeOwned + Send + Sync + 'static>(&mut self, name: &'static str) {
+        assert!(
+            self.configs.iter().all(|ty| ty.name != name),
+            "A config type is already registered for saving under the name {:?}.", name,
+        );
+        self.configs.push(SavedConfigType {
+            name,
+            save: save_config::<T>,
+            load: load_config::<T>,
+        });
+    }
+
+    /// Builder-style method for [`register_config`](Self::register_config)
+    pub fn with_config<T: Serialize + DeserializeOwned + Send + Sync + 'static>(mut self, name: &'static str) -> Self {
+        self.register_config::<T>(name);
+        self
+    }
+}
+
+fn save_component<C: Component + Serialize>(world: &World, entity: Entity) -> Option<Result<String, ron::Error>> {
+    world.get::<C>(entity).map(ron::to_string)
+}
+
+fn load_component<C: Component + DeserializeOwned>(data: &str) -> Result<LoadFn, ron::error::SpannedError> {
+    let component: C = ron::from_str(data)?;
+    Ok(Box::new(move |entity: &mut EntityMut| {
+        entity.insert(component);
+    }))
+}
+
+fn save_config<T: Serialize + Send + Sync + 'static>(world: &World, label: FramestepName) -> Option<Result<String, ron::Error>> {
+    world.get_resource::<FramestepConfigs<T>>()?
+        .get(label)
+        .map(ron::to_string)
+}
+
+fn load_config<T: DeserializeOwned + Send + Sync + 'static>(data: &str) -> Result<ConfigLoadFn, ron::error::SpannedError> {
+    let config: T = ron::from_str(data)?;
+    Ok(Box::new(move |world: &mut World, label: FramestepName| {
+        world.get_resource_or_insert_with(FramestepConfigs::<T>::default)
+            .insert(label, config);
+    }))
+}
+
+/// Error from [`save_world`] or [`load_world`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveError {
+    /// The data could not be serialized
+    Serialize(String),
+    /// The data could not be deserialized
+    Deserialize(String),
+    /// The savegame was written by an unsupported version of the save format
+    Version(u32),
+    /// The savegame contains a component type that is not registered
+    UnknownComponent(String),
+    /// The savegame contains a fixed framestep config type that is not registered
+    UnknownConfig(String),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Serialize(e) => write!(f, "failed to serialize: {}", e),
+            SaveError::Deserialize(e) => write!(f, "failed to deserialize: {}", e),
+            SaveError::Version(v) => write!(f, "unsupported save format version {} (expected {})", v, SAVE_FORMAT_VERSION),
+            SaveError::UnknownComponent(name) => write!(f, "component type {:?} is not registered", name),
+            SaveError::UnknownConfig(name) => write!(f, "config type {:?} is not registered", name),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+#[derive(Serialize, Deserialize)]
+struct SaveFile {
+    version: u32,
+    framesteps: Vec<SavedFramestep>,
+    entities: Vec<Vec<(String, String)>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedFramestep {
+    label: String,
+    step: Frames,
+    accumulator: Frames,
+    // paused by the user; only used for older savegames, without the pause reasons
+    paused: bool,
+    // missing in older savegames
+    #[serde(default)]
+    pause_reasons: Option<Vec<(PauseReason, u32)>>,
+    speed: u32,
+    tick: u64,
+    // missing in older savegames
+    #[serde(default)]
+    simulation_time: Option<Duration>,
+    // missing in older savegames
+    #[serde(default)]
+    configs: Vec<(String, String)>,
+}
+
+/// Serialize all [`Persistent`] entities and the state of all fixed framesteps
+///
+/// Should be called outside of the fixed framesteps (not from inside a tick),
+/// so that the saved state is between two ticks.
+pub fn save_world(world: &mut World) -> Result<Vec<u8>, SaveError> {
+    let time = world.get_resource::<SimulationTime>();
+    let config_types = world.get_resource::<SaveRegistry>().map_or(&[][..], |registry| &registry.configs[..]);
+    let mut framesteps = Vec::new();
+    if let Some(infos) = world.get_resource::<FixedFramesteps>() {
+        for (label, info) in infos.info.iter() {
+            let mut configs = Vec::new();
+            for ty in config_types {
+                if let Some(data) = (ty.save)(world, label.name()) {
+                    let data = data.map_err(|e| SaveError::Serialize(e.to_string()))?;
+                    configs.push((ty.name.to_string(), data));
+                }
+            }
+            framesteps.push(SavedFramestep {
+                label: label.to_string(),
+                step: info.step,
+                accumulator: info.accumulator,
+                paused: info.is_paused_by(PauseReason::User),
+                pause_reasons: Some(info.pause_reasons.clone()),
+                speed: info.speed,
+                tick: info.tick,
+                simulation_time: time.and_then(|time| time.get(*label)).map(|clock| clock.elapsed()),
+                configs,
+            });
+        }
+    }
+    // make the output independent of the hashmap order
+    framesteps.sort_by(|a, b| a.label.cmp(&b.label));
+
+    let mut persistent: Vec<Entity> = world.query_filtered::<Entity, With<Persistent>>()
+        .iter(world)
+        .collect();
+    persistent.sort();
+
+    let mut entities = Vec::with_capacity(persistent.len());
+    if let Some(registry) = world.get_resource::<SaveRegistry>() {
+        for entity in persistent {
+            let mut components = Vec::new();
+            for ty in registry.types.iter() {
+                if let Some(data) = (ty.save)(world, entity) {
+                    let data = data.map_err(|e| SaveError::Serialize(e.to_string()))?;
+                    components.push((ty.name.to_string(), data));
+                }
+            }
+            entities.push(components);
+        }
+    } else {
+        entities.resize_with(persistent.len(), Vec::new);
+    }
+
+    let file = SaveFile {
+        version: SAVE_FORMAT_VERSION,
+        framesteps,
+        entities,
+    };
+    ron::to_string(&file)
+        .map(String::into_bytes)
+        .map_err(|e| SaveError::Serialize(e.to_string()))
+}
+
+/// Restore a savegame written by [`save_world`]
+///
+/// The whole savegame is parsed before anything in the world is changed, so on
+/// error, the world is left as it was. Fixed framesteps in the savegame that do not
+/// exist in the world are ignored with a warning.
+pub fn load_world(world: &mut World, data: &[u8]) -> Result<(), SaveError> {
+    let text = std::str::from_utf8(data).map_err(|e| SaveError::Deserialize(e.to_string()))?;
+    let file: SaveFile = ron::from_str(text).map_err(|e| SaveError::Deserialize(e.to_string()))?;
+    if file.version != SAVE_FORMAT_VERSION {
+        return Err(SaveError::Version(file.version));
+    }
+
+    let mut entities = Vec::with_capacity(file.entities.len());
+    {
+        let registry = world.get_resource::<SaveRegistry>();
+        for components in file.entities.iter() {
+            let mut loads = Vec::with_capacity(components.len());
+            for (name, data) in components.iter() {
+                let ty = registry
+                    .and_then(|registry| registry.types.iter().find(|ty| ty.name == name))
+                    .ok_or_else(|| SaveError::UnknownComponent(name.clone()))?;
+                loads.push((ty.load)(data).map_err(|e| SaveError::Deserialize(e.to_string()))?);
+            }
+            entities.push(loads);
+        }
+    }
+    let mut configs = Vec::new();
+    {
+        let registry = world.get_resource::<SaveRegistry>();
+        for saved in file.framesteps.iter() {
+            for (name, data) in saved.configs.iter() {
+                let ty = registry
+                    .and_then(|registry| registry.configs.iter().find(|ty| ty.name == name))
+                    .ok_or_else(|| SaveError::UnknownConfig(name.clone()))?;
+                let load = (ty.load)(data).map_err(|e| SaveError::Deserialize(e.to_string()))?;
+                configs.push((saved.label.as_str(), load));
+            }
+        }
+    }
+
+    // everything parsed; now apply it all
+    let old: Vec<Entity> = world.query_filtered::<Entity, With<Persistent>>()
+        .iter(world)
+        .collect();
+    for entity in old {
+        world.despawn(entity);
+    }
+    for loads in entities {
+        let mut entity = world.spawn(Persistent);
+        for load in loads {
+            load(&mut entity);
+        }
+    }
+
+    if let Some(mut time) = world.get_resource_mut::<SimulationTime>() {
+        for saved in file.framesteps.iter() {
+            let clock = time.clocks.iter_mut().find(|(label, _)| **label == saved.label);
+            if let (Some((_, clock)), Some(elapsed)) = (clock, saved.simulation_time) {
+                clock.restore(elapsed, saved.tick);
+            }
+        }
+    }
+
+    let mut restored = Vec::new();
+    if let Some(mut framesteps) = world.get_resource_mut::<FixedFramesteps>() {
+        for saved in file.framesteps.iter() {
+            match framesteps.info.iter_mut().find(|(label, _)| label.name() == saved.label) {
+                Some((label, info)) => {
+                    info.step = saved.step;
+                    info.accumulator = saved.accumulator;
+                    info.pause_reasons = match &saved.pause_reasons {
+                        Some(reasons) => reasons.clone(),
+                        None if saved.paused => vec![(PauseReason::User, 1)],
+                        None => Vec::new(),
+                    };
+                    info.speed = saved.speed;
+                    info.tick = saved.tick;
+                    info.queued_ticks = 0;
+                    info.pending_stretch = 0;
+                    info.pending_rewind = None;
+                    info.history.clear();
+                    restored.push(label.name());
+                }
+                None => warn!("Fixed framestep {:?} from the savegame does not exist; ignoring it.", saved.label),
+            }
+        }
+    }
+    for (saved_label, load) in configs {
+        if let Some(label) = restored.iter().find(|label| **label == saved_label) {
+            load(world, label);
+        }
+    }
+    if let Some(mut queue) = world.get_resource_mut::<TickCommandQueue>() {
+        for label in restored {
+            queue.clear(label);
+        }
+    }
+    Ok(())
+}
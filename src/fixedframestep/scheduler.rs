@@ -0,0 +1,161 @@
+//! Callbacks and one-shot systems scheduled for an absolute tick number
+//!
+//! The [`TickScheduler`] resource holds work that should happen when a fixed framestep
+//! reaches some tick: scripted events ("the boss spawns on tick 3600"), or actions that
+//! a remote peer asked for in advance. Unlike [`TickCommands`](super::commands::TickCommands),
+//! every scheduled task returns a [`TickTaskHandle`], which can be used to cancel it
+//! before it runs, and tasks can also be systems, with their own system params.
+//!
+//! The due tasks are run at the start of the tick, right after the due `TickCommands`,
+//! and before any of the systems of the tick, in the order they were scheduled. Tasks
+//! scheduled for a tick that has already started (or passed) run at the start of the
+//! next tick. [Resetting](super::FixedFramestepInfo::reset) a fixed framestep drops its
+//! scheduled tasks.
+
+use std::collections::BTreeMap;
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::system::BoxedSystem;
+use bevy_utils::HashMap;
+use bevy_utils::synccell::SyncCell;
+
+use super::{FramestepLabel, FramestepName};
+
+/// Handle to a task scheduled with the [`TickScheduler`], for cancelling it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TickTaskHandle {
+    label: FramestepName,
+    tick: u64,
+    id: u64,
+}
+
+impl TickTaskHandle {
+    /// The fixed framestep that the task is scheduled on
+    pub fn label(&self) -> FramestepName {
+        self.label
+    }
+
+    /// The tick that the task is scheduled for
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+}
+
+type BoxedCallback = SyncCell<Box<dyn FnOnce(&mut World) + Send>>;
+
+enum TickTask {
+    Callback(BoxedCallback),
+    System(BoxedSystem),
+}
+
+/// Resource with the tasks scheduled for future ticks (see the [module docs](self))
+///
+/// Inserted automatically by the fixed framestep stages.
+#[derive(Default)]
+#[derive(Resource)]
+pub struct TickScheduler {
+    tasks: HashMap<FramestepName, BTreeMap<u64, Vec<(u64, TickTask)>>>,
+    next_id: u64,
+}
+
+impl TickScheduler {
+    /// Schedule a callback to run at the start of the given tick of the given fixed framestep
+    pub fn schedule(&mut self, label: impl FramestepLabel, tick: u64, callback: impl FnOnce(&mut World) + Send + 'static) -> TickTaskHandle {
+        let callback: Box<dyn FnOnce(&mut World) + Send> = Box::new(callback);
+        self.push(label.framestep_name(), tick, TickTask::Callback(SyncCell::new(callback)))
+    }
+
+    /// Schedule a system to run once, at the start of the given tick of the given fixed framestep
+    ///
+    /// Its `Commands` are applied right after it runs.
+    pub fn schedule_system<Params>(&mut self, label: impl FramestepLabel, tick: u64, system: impl IntoSystem<(), (), Params>) -> TickTaskHandle {
+        let system: BoxedSystem = Box::new(IntoSystem::into_system(system));
+        self.push(label.framestep_name(), tick, TickTask::System(system))
+    }
+
+    /// Cancel a scheduled task
+    ///
+    /// Returns `false` if the task has already run, or was cancelled already.
+    pub fn cancel(&mut self, handle: TickTaskHandle) -> bool {
+        let queue = match self.tasks.get_mut(handle.label) {
+            Some(queue) => queue,
+            None => return false,
+        };
+        let tasks = match queue.get_mut(&handle.tick) {
+            Some(tasks) => tasks,
+            None => return false,
+        };
+        let len = tasks.len();
+        tasks.retain(|(id, _)| *id != handle.id);
+        let cancelled = tasks.len() != len;
+        if tasks.is_empty() {
+            queue.remove(&handle.tick);
+        }
+        cancelled
+    }
+
+    /// Is the task still waiting to run?
+    pub fn is_scheduled(&self, handle: TickTaskHandle) -> bool {
+        self.tasks.get(handle.label)
+            .and_then(|queue| queue.get(&handle.tick))
+            .map(|tasks| tasks.iter().any(|(id, _)| *id == handle.id))
+            .unwrap_or(false)
+    }
+
+    /// The number of tasks waiting to run on the given fixed framestep
+    pub fn len(&self, label: impl FramestepLabel) -> usize {
+        self.tasks.get(label.framestep_name())
+            .map(|queue| queue.values().map(Vec::len).sum())
+            .unwrap_or(0)
+    }
+
+    /// Are there no tasks waiting to run on the given fixed framestep?
+    pub fn is_empty(&self, label: impl FramestepLabel) -> bool {
+        self.len(label) == 0
+    }
+
+    /// Cancel all the tasks scheduled on the given fixed framestep
+    pub fn clear(&mut self, label: impl FramestepLabel) {
+        self.tasks.remove(label.framestep_name());
+    }
+
+    fn push(&mut self, label: FramestepName, tick: u64, task: TickTask) -> TickTaskHandle {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.tasks.entry(label)
+            .or_default()
+            .entry(tick)
+            .or_default()
+            .push((id, task));
+        TickTaskHandle { label, tick, id }
+    }
+
+    /// Remove all tasks that are due at (or before) the given tick, in order
+    fn take_due(&mut self, label: FramestepName, tick: u64) -> Vec<TickTask> {
+        let queue = match self.tasks.get_mut(label) {
+            Some(queue) => queue,
+            None => return Vec::new(),
+        };
+        let later = queue.split_off(&(tick + 1));
+        let due = std::mem::replace(queue, later);
+        due.into_values().flatten().map(|(_, task)| task).collect()
+    }
+}
+
+/// Run all the scheduled tasks that are due at the given tick of a fixed framestep
+pub(super) fn run_due_tasks(world: &mut World, label: FramestepName, tick: u64) {
+    let due = match world.get_resource_mut::<TickScheduler>() {
+        Some(mut scheduler) => scheduler.take_due(label, tick),
+        None => return,
+    };
+    for task in due {
+        match task {
+            TickTask::Callback(callback) => (SyncCell::to_inner(callback))(world),
+            TickTask::System(mut system) => {
+                system.initialize(world);
+                system.run((), world);
+                system.apply_buffers(world);
+            }
+        }
+    }
+}
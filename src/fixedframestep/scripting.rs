@@ -0,0 +1,210 @@
+//! Hooks for running script callbacks on the ticks of a fixed framestep
+//!
+//! This is a runtime-agnostic API for scripting integrations (like `bevy_mod_scripting`,
+//! or your own Lua/Rhai/WASM host). Add the [`FramestepScriptingPlugin`] for the fixed
+//! framesteps that scripts may hook into. Your script host can then register callbacks
+//! in the [`ScriptTickCallbacks`] resource, that are invoked at the start of every tick of
+//! a fixed framestep (after any due [`TickCommands`](super::commands::TickCommands), and
+//! before any of its systems), with a [`ScriptTick`] describing the tick.
+//!
+//! The free functions in this module ([`pause`], [`set_step`], etc.) are a small, flat
+//! API for controlling fixed framesteps from scripts, which is easy to bind for any
+//! scripting runtime.
+//!
+//! Requires the `"framestep-scripting"` cargo feature.
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_utils::HashMap;
+
+use super::{FixedFramesteps, FramestepError, FramestepLabel, FramestepName, Frames, find_framestep_mut};
+
+/// The description of a tick, passed to the script callbacks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptTick {
+    /// The name of the fixed framestep
+    pub label: FramestepName,
+    /// The number of the tick that is starting
+    pub tick: u64,
+    /// The number of frames per tick (the "delta" of the tick)
+    pub step: Frames,
+    /// The index of the tick among the ticks that run on this frame
+    /// (see [`FixedFramestepInfo::step_index`](super::FixedFramestepInfo::step_index))
+    pub step_index: u32,
+}
+
+/// A callback invoked on every tick of a fixed framestep
+pub type ScriptTickCallback = Box<dyn FnMut(&mut World, &ScriptTick) + Send + Sync>;
+
+/// Identifies a registered callback, for unregistering it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ScriptCallbackId(u64);
+
+/// Resource with the script callbacks for each fixed framestep
+///
+/// Inserted by the [`FramestepScriptingPlugin`].
+#[derive(Default)]
+#[derive(Resource)]
+pub struct ScriptTickCallbacks {
+    callbacks: HashMap<FramestepName, Vec<(ScriptCallbackId, ScriptTickCallback)>>,
+    next_id: u64,
+    // the callbacks that are running right now, and those of them that were unregistered
+    running: Vec<ScriptCallbackId>,
+    removed: Vec<ScriptCallbackId>,
+}
+
+impl ScriptTickCallbacks {
+    /// Register a callback to be invoked on every tick of the given fixed framestep
+    ///
+    /// Callbacks run in the order they were registered. The fixed framestep must have been
+    /// set up with the [`FramestepScriptingPlugin`], or the callback never runs.
+    pub fn register(
+        &mut self,
+        label: impl FramestepLabel,
+        callback: impl FnMut(&mut World, &ScriptTick) + Send + Sync + 'static,
+    ) -> ScriptCallbackId {
+        let id = ScriptCallbackId(self.next_id);
+        self.next_id += 1;
+        self.callbacks.entry(label.framestep_name())
+            .or_default()
+            .push((id, Box::new(callback)));
+        id
+    }
+
+    /// Remove a callback
+    ///
+    /// Can also be called from inside a callback. Returns `false` if there is no such callback.
+    pub fn unregister(&mut self, id: ScriptCallbackId) -> bool {
+        for callbacks in self.callbacks.values_mut() {
+            if let Some(i) = callbacks.iter().position(|(other, _)| *other == id) {
+                drop(callbacks.remove(i));
+                return true;
+            }
+        }
+        if self.running.contains(&id) && !self.removed.contains(&id) {
+            // drop it when it is put back
+            self.removed.push(id);
+            return true;
+        }
+        false
+    }
+
+    /// Remove all the callbacks of the given fixed framestep
+    pub fn clear(&mut self, label: impl FramestepLabel) {
+        self.callbacks.remove(label.framestep_name());
+    }
+
+    /// The number of callbacks registered for the given fixed framestep
+    pub fn count(&self, label: impl FramestepLabel) -> usize {
+        self.callbacks.get(label.framestep_name()).map(Vec::len).unwrap_or(0)
+    }
+
+    /// Put back the callbacks taken out to run them, before any that were registered meanwhile
+    fn put_back(&mut self, label: FramestepName, mut taken: Vec<(ScriptCallbackId, ScriptTickCallback)>) {
+        self.running.clear();
+        let removed = std::mem::take(&mut self.removed);
+        taken.retain(|(id, _)| !removed.contains(id));
+        let callbacks = self.callbacks.entry(label).or_default();
+        taken.append(callbacks);
+        *callbacks = taken;
+    }
+}
+
+/// Plugin for running [script callbacks](ScriptTickCallbacks) on the ticks of fixed framesteps
+///
+/// The fixed framesteps must already be added to the app.
+#[derive(Debug, Clone)]
+pub struct FramestepScriptingPlugin {
+    framesteps: Vec<FramestepName>,
+}
+
+impl FramestepScriptingPlugin {
+    /// Create the plugin to run script callbacks on the ticks of the given fixed framestep
+    pub fn new(framestep: impl FramestepLabel) -> Self {
+        Self {
+            framesteps: vec![framestep.framestep_name()],
+        }
+    }
+
+    /// Builder method for running script callbacks on another fixed framestep too
+    pub fn with_framestep(mut self, framestep: impl FramestepLabel) -> Self {
+        self.framesteps.push(framestep.framestep_name());
+        self
+    }
+}
+
+impl Plugin for FramestepScriptingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScriptTickCallbacks>();
+        for label in self.framesteps.iter() {
+            find_framestep_mut(&mut app.schedule, label)
+                .expect("Fixed Framestep Stage not found")
+                .add_tick_begin_hook(run_script_callbacks);
+        }
+    }
+}
+
+fn run_script_callbacks(world: &mut World) {
+    let tick = {
+        let info = world.resource::<FixedFramesteps>().current();
+        ScriptTick {
+            label: info.label(),
+            tick: info.tick(),
+            step: info.framestep(),
+            step_index: info.step_index(),
+        }
+    };
+    let mut taken = match world.get_resource_mut::<ScriptTickCallbacks>() {
+        Some(mut callbacks) => match callbacks.callbacks.remove(tick.label) {
+            Some(taken) => {
+                callbacks.running = taken.iter().map(|(id, _)| *id).collect();
+                taken
+            }
+            None => return,
+        },
+        None => return,
+    };
+    for (_, callback) in taken.iter_mut() {
+        callback(world, &tick);
+    }
+    if let Some(mut callbacks) = world.get_resource_mut::<ScriptTickCallbacks>() {
+        callbacks.put_back(tick.label, taken);
+    }
+}
+
+/// Pause the given fixed framestep; returns `false` if it does not exist
+pub fn pause(world: &mut World, label: impl FramestepLabel) -> bool {
+    with_info(world, label, |info| info.pause()).is_some()
+}
+
+/// Un-pause (resume) the given fixed framestep; returns `false` if it does not exist
+pub fn unpause(world: &mut World, label: impl FramestepLabel) -> bool {
+    with_info(world, label, |info| info.unpause()).is_some()
+}
+
+/// Is the given fixed framestep paused (by the user, or for any reason)? [`None`] if it does not exist
+pub fn is_paused(world: &World, label: impl FramestepLabel) -> Option<bool> {
+    world.get_resource::<FixedFramesteps>()?.get(label).map(|info| info.is_paused())
+}
+
+/// Change the number of frames per tick of the given fixed framestep
+///
+/// Returns [`None`] if it does not exist.
+pub fn set_step(world: &mut World, label: impl FramestepLabel, step: impl Into<Frames>) -> Option<Result<(), FramestepError>> {
+    with_info(world, label, |info| info.try_set_step(step))
+}
+
+/// Change the speed multiplier of the given fixed framestep; returns `false` if it does not exist
+pub fn set_speed(world: &mut World, label: impl FramestepLabel, multiplier: u32) -> bool {
+    with_info(world, label, |info| info.set_speed(multiplier)).is_some()
+}
+
+/// The number of the current (or last) tick of the given fixed framestep
+pub fn tick(world: &World, label: impl FramestepLabel) -> Option<u64> {
+    world.get_resource::<FixedFramesteps>()?.get(label).map(|info| info.tick())
+}
+
+fn with_info<R>(world: &mut World, label: impl FramestepLabel, f: impl FnOnce(&mut super::FixedFramestepInfo) -> R) -> Option<R> {
+    let mut framesteps = world.get_resource_mut::<FixedFramesteps>()?;
+    framesteps.get_mut(label).map(f)
+}
@@ -0,0 +1,180 @@
+//! A virtual clock driven by the ticks of a fixed framestep
+//!
+//! Many simulation games derive their whole game clock from the tick: every tick is a
+//! fixed amount of in-game time, no matter how long it took to run, or how many ticks
+//! ran on a frame. Add a [`SimulationClock`] to a fixed framestep (using
+//! `add_fixed_framestep_simulation_time`), and read it from the [`SimulationTime`]
+//! resource.
+//!
+//! The clock is only advanced at the start of every tick, by the nominal duration of
+//! the tick (the number of frames in the step, times the nominal
+//! [frame duration](SimulationClock::new)), so it stands still between ticks, and while
+//! the fixed framestep is paused. If the tick counter jumps (on a reset, a rewind, or
+//! when loading a savegame), the clock is recomputed from the tick counter. The clocks
+//! are included in savegames (see the `save` module).
+//!
+//! For in-game calendars, [`day`](SimulationClock::day), [`hour`](SimulationClock::hour),
+//! and [`minute`](SimulationClock::minute) split the elapsed time into days of the
+//! given [length](SimulationClock::with_day_length): with a day length of 24 minutes,
+//! an in-game hour lasts one (virtual) minute.
+
+use bevy_ecs::prelude::*;
+use bevy_utils::{Duration, HashMap};
+
+use super::{FramestepLabel, FramestepName};
+#[cfg(feature = "app")]
+use super::{FixedFramesteps, Frames, Ticks};
+
+/// The virtual clock of a fixed framestep (see the [module docs](self))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationClock {
+    elapsed: Duration,
+    frame_duration: Duration,
+    day_length: Duration,
+    // the tick that the clock was last advanced on
+    tick: u64,
+}
+
+impl SimulationClock {
+    /// Create a clock at zero, where every frame of the step lasts `frame_duration`
+    ///
+    /// For a fixed framestep with a step of 2 frames, and a frame duration of 1/60th of
+    /// a second, every tick advances the clock by 1/30th of a second. Days last 24 hours.
+    pub fn new(frame_duration: Duration) -> Self {
+        Self {
+            elapsed: Duration::ZERO,
+            frame_duration,
+            day_length: Duration::from_secs(24 * 60 * 60),
+            tick: 0,
+        }
+    }
+
+    /// Builder method for setting the length of an in-game day, in virtual time
+    ///
+    /// Panics if `day_length` is zero.
+    pub fn with_day_length(mut self, day_length: Duration) -> Self {
+        assert!(!day_length.is_zero(), "The length of a day cannot be zero.");
+        self.day_length = day_length;
+        self
+    }
+
+    /// The virtual time elapsed since the first tick
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    /// The virtual time elapsed since the first tick, in seconds
+    pub fn elapsed_secs_f64(&self) -> f64 {
+        self.elapsed.as_secs_f64()
+    }
+
+    /// Change the virtual time (say, to skip to the next morning)
+    ///
+    /// The clock carries on from there on the next tick.
+    pub fn set_elapsed(&mut self, elapsed: Duration) {
+        self.elapsed = elapsed;
+    }
+
+    /// The nominal duration of one frame of the step
+    pub fn frame_duration(&self) -> Duration {
+        self.frame_duration
+    }
+
+    /// The length of an in-game day, in virtual time
+    pub fn day_length(&self) -> Duration {
+        self.day_length
+    }
+
+    /// The number of whole in-game days elapsed (`0` on the first day)
+    pub fn day(&self) -> u64 {
+        (self.elapsed.as_nanos() / self.day_length.as_nanos()) as u64
+    }
+
+    /// The virtual time elapsed since the start of the current in-game day
+    pub fn time_of_day(&self) -> Duration {
+        let nanos = self.elapsed.as_nanos() % self.day_length.as_nanos();
+        Duration::from_nanos(nanos as u64)
+    }
+
+    /// How far along the current in-game day is, from `0.0` to `1.0`
+    pub fn day_fraction(&self) -> f64 {
+        self.time_of_day().as_secs_f64() / self.day_length.as_secs_f64()
+    }
+
+    /// The hour of the current in-game day (`0` to `23`)
+    pub fn hour(&self) -> u32 {
+        self.minute_of_day() / 60
+    }
+
+    /// The minute of the current in-game hour (`0` to `59`)
+    pub fn minute(&self) -> u32 {
+        self.minute_of_day() % 60
+    }
+
+    /// The number of whole in-game minutes since the start of the current day
+    fn minute_of_day(&self) -> u32 {
+        (self.time_of_day().as_nanos() * 24 * 60 / self.day_length.as_nanos()) as u32
+    }
+
+    /// The nominal duration of the given number of frames
+    #[cfg(feature = "app")]
+    fn frames_duration(&self, frames: Frames) -> Duration {
+        Duration::from_nanos((self.frame_duration.as_nanos() * frames.0 as u128) as u64)
+    }
+
+    /// Set the clock to the state loaded from a savegame
+    #[cfg(feature = "framestep-save")]
+    pub(super) fn restore(&mut self, elapsed: Duration, tick: u64) {
+        self.elapsed = elapsed;
+        self.tick = tick;
+    }
+}
+
+/// Resource with the [`SimulationClock`]s of the fixed framesteps
+#[derive(Debug, Default)]
+#[derive(Resource)]
+pub struct SimulationTime {
+    pub(super) clocks: HashMap<FramestepName, SimulationClock>,
+}
+
+impl SimulationTime {
+    /// The clock of the given fixed framestep, if it has one
+    pub fn get(&self, label: impl FramestepLabel) -> Option<&SimulationClock> {
+        self.clocks.get(label.framestep_name())
+    }
+
+    /// Mut access to the clock of the given fixed framestep, if it has one
+    pub fn get_mut(&mut self, label: impl FramestepLabel) -> Option<&mut SimulationClock> {
+        self.clocks.get_mut(label.framestep_name())
+    }
+
+    /// The clock, if there is exactly one
+    ///
+    /// Panics if there are no clocks, or more than one.
+    pub fn single(&self) -> &SimulationClock {
+        assert!(self.clocks.len() == 1, "Expected exactly one simulation clock, found {}.", self.clocks.len());
+        self.clocks.values().next().unwrap()
+    }
+}
+
+/// Advance the clock of the current fixed framestep, at the start of the tick
+#[cfg(feature = "app")]
+pub(super) fn advance_simulation_time(world: &mut World) {
+    let (label, tick, step) = {
+        let info = world.resource::<FixedFramesteps>().current();
+        (info.label(), info.tick(), info.step)
+    };
+    let mut time = match world.get_resource_mut::<SimulationTime>() {
+        Some(time) => time,
+        None => return,
+    };
+    if let Some(clock) = time.clocks.get_mut(label) {
+        if tick == clock.tick + 1 {
+            clock.elapsed += clock.frames_duration(step);
+        } else {
+            // the tick counter jumped: assume that the step never changed
+            clock.elapsed = clock.frames_duration(Ticks(tick) * step);
+        }
+        clock.tick = tick;
+    }
+}
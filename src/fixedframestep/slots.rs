@@ -0,0 +1,208 @@
+//! Single tick systems that can be removed or replaced, by their label
+//!
+//! Bevy's `SystemStage` cannot remove systems once they are added. [Groups](super::groups)
+//! swap out whole sub-stages; for tests and hot-reload flows that need to swap out one
+//! system at a time, add it using `add_fixed_framestep_replaceable_system` on the `App`,
+//! with a system label. The system is put in a *slot*, and the slot is what is added to
+//! the sub-stage. Later, `remove_fixed_framestep_system` empties the slot, and
+//! `replace_fixed_framestep_system` puts another system in it. From systems, or anywhere
+//! else with access to the `World`, use the [`TickSystemSlots`] resource for the same.
+//!
+//! Only systems in slots can be removed or replaced. Systems added any other way (like
+//! using `add_fixed_framestep_system`, even with a label) are part of their `SystemStage`
+//! for good; for them, `remove_fixed_framestep_system` and `replace_fixed_framestep_system`
+//! return a [`SlotNotFound`] error.
+//!
+//! The slot is an exclusive system: it runs at the start of its sub-stage, before the
+//! parallel systems, and its `Commands` are applied right after the system runs. The label
+//! is given to the slot, so other exclusive systems can be ordered relative to it. A
+//! replaced system loses its `Local` state; the new one is initialized when it first runs.
+//! Changes take effect the next time the slot runs (even if it is in the same tick).
+
+use bevy_ecs::prelude::*;
+use bevy_ecs::schedule::SystemLabelId;
+use bevy_ecs::system::BoxedSystem;
+use bevy_utils::HashMap;
+
+use super::{FramestepLabel, FramestepName};
+
+/// Error for a system slot that does not exist (see the [module docs](self))
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotNotFound {
+    /// The name of the fixed framestep
+    pub framestep: FramestepName,
+    /// The index of the sub-stage
+    pub substage: usize,
+    /// The label of the system
+    pub label: SystemLabelId,
+}
+
+impl std::fmt::Display for SlotNotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f, "fixed framestep {:?} has no replaceable system {:?} in sub-stage {}",
+            self.framestep, self.label.as_str(), self.substage,
+        )
+    }
+}
+
+impl std::error::Error for SlotNotFound {}
+
+/// Identifies a slot: the fixed framestep, the index of the sub-stage, and the label
+type SlotKey = (FramestepName, usize, SystemLabelId);
+
+/// The system currently in a slot
+struct Slot {
+    system: Option<BoxedSystem>,
+    initialized: bool,
+    /// Incremented on every change, to notice changes made while the system runs
+    version: u64,
+}
+
+/// Resource with the systems in the slots of all the fixed framesteps (see the [module docs](self))
+#[derive(Default)]
+#[derive(Resource)]
+pub struct TickSystemSlots {
+    slots: HashMap<SlotKey, Slot>,
+}
+
+impl TickSystemSlots {
+    /// Is there a slot with the given label, in the given sub-stage of the given fixed framestep?
+    ///
+    /// The slot exists even if the system in it was [removed](Self::remove).
+    pub fn contains(&self, framestep: impl FramestepLabel, substage_i: usize, label: impl SystemLabel) -> bool {
+        self.slots.contains_key(&(framestep.framestep_name(), substage_i, label.as_label()))
+    }
+
+    /// Does the slot have a system in it? (`false` if there is no such slot)
+    pub fn is_occupied(&self, framestep: impl FramestepLabel, substage_i: usize, label: impl SystemLabel) -> bool {
+        self.slots.get(&(framestep.framestep_name(), substage_i, label.as_label()))
+            .map(|slot| slot.system.is_some())
+            .unwrap_or(false)
+    }
+
+    /// Remove the system from a slot, so that nothing runs in its place
+    ///
+    /// Returns `false` if there is no such slot.
+    pub fn remove(&mut self, framestep: impl FramestepLabel, substage_i: usize, label: impl SystemLabel) -> bool {
+        self.set(framestep.framestep_name(), substage_i, label.as_label(), None)
+    }
+
+    /// Put another system in a slot, instead of the one that was in it (if any)
+    ///
+    /// Returns `false` if there is no such slot; the system is not added then.
+    pub fn replace<Params>(
+        &mut self,
+        framestep: impl FramestepLabel,
+        substage_i: usize,
+        label: impl SystemLabel,
+        system: impl IntoSystem<(), (), Params>,
+    ) -> bool {
+        let system: BoxedSystem = Box::new(IntoSystem::into_system(system));
+        self.set(framestep.framestep_name(), substage_i, label.as_label(), Some(system))
+    }
+
+    fn set(&mut self, framestep: FramestepName, substage_i: usize, label: SystemLabelId, system: Option<BoxedSystem>) -> bool {
+        match self.slots.get_mut(&(framestep, substage_i, label)) {
+            Some(slot) => {
+                slot.system = system;
+                slot.initialized = false;
+                slot.version += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Create a slot with the given system in it
+    ///
+    /// Returns `false` if the slot exists already; the system is not added then.
+    #[cfg(feature = "app")]
+    pub(super) fn insert(&mut self, key: SlotKey, system: BoxedSystem) -> bool {
+        if self.slots.contains_key(&key) {
+            return false;
+        }
+        self.slots.insert(key, Slot {
+            system: Some(system),
+            initialized: false,
+            version: 0,
+        });
+        true
+    }
+}
+
+/// Make the exclusive system that runs whatever system is in the given slot
+#[cfg(feature = "app")]
+pub(super) fn slot_system(framestep: FramestepName, substage_i: usize, label: SystemLabelId) -> impl FnMut(&mut World) {
+    let key = (framestep, substage_i, label);
+    move |world: &mut World| {
+        // take the system out, so it can have access to the whole world
+        let taken = world.get_resource_mut::<TickSystemSlots>()
+            .and_then(|mut slots| {
+                let slot = slots.slots.get_mut(&key)?;
+                let system = slot.system.take()?;
+                Some((system, slot.initialized, slot.version))
+            });
+        let (mut system, initialized, version) = match taken {
+            Some(taken) => taken,
+            None => return,
+        };
+        if !initialized {
+            system.initialize(world);
+        }
+        system.run((), world);
+        system.apply_buffers(world);
+        // put it back, unless the slot was changed while it ran
+        if let Some(mut slots) = world.get_resource_mut::<TickSystemSlots>() {
+            if let Some(slot) = slots.slots.get_mut(&key) {
+                if slot.version == version {
+                    slot.system = Some(system);
+                    slot.initialized = true;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "app"))]
+mod tests {
+    use bevy_app::App;
+    use bevy_ecs::prelude::*;
+
+    use super::SlotNotFound;
+    use crate::fixedframestep::app::AppLooplessFixedFramestepExt;
+
+    #[derive(Default, Resource)]
+    struct Ran(Vec<&'static str>);
+
+    #[test]
+    fn replace_and_remove_a_slot() {
+        let mut app = App::default();
+        app.init_resource::<Ran>();
+        app.add_fixed_framestep(1, "sim");
+        app.add_fixed_framestep_replaceable_system("sim", 0, "move", |mut ran: ResMut<Ran>| ran.0.push("old"));
+        app.update();
+        app.replace_fixed_framestep_system("sim", 0, "move", |mut ran: ResMut<Ran>| ran.0.push("new")).unwrap();
+        app.update();
+        app.remove_fixed_framestep_system("sim", 0, "move").unwrap();
+        app.update();
+        assert_eq!(app.world.resource::<Ran>().0, ["old", "new"]);
+    }
+
+    #[test]
+    fn labeled_systems_outside_slots_are_not_found() {
+        let mut app = App::default();
+        app.init_resource::<Ran>();
+        app.add_fixed_framestep(1, "sim");
+        app.add_fixed_framestep_system("sim", 0, (|mut ran: ResMut<Ran>| ran.0.push("labeled")).label("move"));
+        let expected = SlotNotFound {
+            framestep: "sim",
+            substage: 0,
+            label: SystemLabel::as_label(&"move"),
+        };
+        assert_eq!(app.remove_fixed_framestep_system("sim", 0, "move").err(), Some(expected));
+        assert_eq!(app.replace_fixed_framestep_system("sim", 0, "move", || {}).err(), Some(expected));
+        app.update();
+        assert_eq!(app.world.resource::<Ran>().0, ["labeled"]);
+    }
+}
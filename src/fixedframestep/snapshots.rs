@@ -0,0 +1,197 @@
+//! Buffering server snapshots for interpolation on the client
+//!
+//! With a server-authoritative model, the server sends snapshots of its state, tagged with
+//! its tick number, and the client renders a point somewhat in the past, between the two
+//! snapshots around it. That way, there is (usually) always a newer snapshot to
+//! interpolate towards, even if some of them arrive late.
+//!
+//! Insert a [`SnapshotBuffer<S>`] resource for your snapshot type `S`, and store the
+//! snapshots in it as they come in (see [`SnapshotBuffer::insert`]). Then, ask it for the
+//! [`InterpolationWindow`]: the two snapshots to interpolate between, and how far along
+//! from one to the other to render.
+//!
+//! The server tick to render is derived from the local fixed framestep, so that the
+//! interpolation advances at the same rate as the local ticks, including the
+//! [overstep](super::FixedFramestepInfo::overstep) between ticks: it is the local tick,
+//! shifted by the offset to the server tick (from the snapshots received so far), minus
+//! the [delay](SnapshotBuffer::new). The local fixed framestep should run at the same
+//! rate as the server.
+
+use std::collections::BTreeMap;
+
+use bevy_ecs::prelude::*;
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName};
+
+/// The default number of snapshots kept by a [`SnapshotBuffer`]
+pub const DEFAULT_SNAPSHOT_CAPACITY: usize = 32;
+
+/// The two snapshots to render between, and how far along (see [`SnapshotBuffer::window`])
+#[derive(Debug)]
+pub struct InterpolationWindow<'a, S> {
+    /// The server tick of the older snapshot
+    pub from_tick: u64,
+    /// The older snapshot
+    pub from: &'a S,
+    /// The server tick of the newer snapshot
+    ///
+    /// The same as `from_tick`, if there is no newer snapshot yet.
+    pub to_tick: u64,
+    /// The newer snapshot
+    pub to: &'a S,
+    /// How far along from `from` to `to`, from `0.0` to `1.0`
+    pub blend: f32,
+}
+
+/// Resource with the server snapshots, by server tick (see the [module docs](self))
+#[derive(Debug)]
+#[derive(Resource)]
+pub struct SnapshotBuffer<S: Send + Sync + 'static> {
+    framestep: FramestepName,
+    delay: u64,
+    capacity: usize,
+    snapshots: BTreeMap<u64, S>,
+    // server tick minus local tick, once known
+    offset: Option<i64>,
+}
+
+impl<S: Send + Sync + 'static> SnapshotBuffer<S> {
+    /// Create an empty buffer, rendering `delay` ticks behind the server, driven by the given fixed framestep
+    ///
+    /// The delay should be enough to cover the time between two snapshots, plus some jitter.
+    pub fn new(framestep: impl FramestepLabel, delay: u64) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            delay,
+            capacity: DEFAULT_SNAPSHOT_CAPACITY,
+            snapshots: BTreeMap::new(),
+            offset: None,
+        }
+    }
+
+    /// Builder method for setting the maximum number of snapshots kept
+    ///
+    /// Panics if `capacity` is less than 2.
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        assert!(capacity >= 2, "A snapshot buffer needs room for at least 2 snapshots.");
+        self.capacity = capacity;
+        self
+    }
+
+    /// The fixed framestep that drives the interpolation
+    pub fn framestep(&self) -> FramestepName {
+        self.framestep
+    }
+
+    /// The number of ticks behind the server to render at
+    pub fn delay(&self) -> u64 {
+        self.delay
+    }
+
+    /// Change the number of ticks behind the server to render at
+    pub fn set_delay(&mut self, delay: u64) {
+        self.delay = delay;
+    }
+
+    /// Store a snapshot received from the server
+    ///
+    /// The current tick of the local fixed framestep is used to sync it with the server
+    /// tick. When the buffer is full, the oldest snapshot is dropped. Returns `false` (and
+    /// drops the snapshot) if it is older than all the buffered ones, in a full buffer, or
+    /// if there already is a snapshot for that tick.
+    pub fn insert(&mut self, framesteps: &FixedFramesteps, server_tick: u64, snapshot: S) -> bool {
+        if self.snapshots.contains_key(&server_tick) {
+            return false;
+        }
+        if self.snapshots.len() >= self.capacity {
+            match self.snapshots.keys().next() {
+                Some(&oldest) if oldest < server_tick => {
+                    self.snapshots.remove(&oldest);
+                }
+                _ => return false,
+            }
+        }
+        self.snapshots.insert(server_tick, snapshot);
+        let local_tick = match framesteps.get(self.framestep) {
+            Some(info) => info.tick(),
+            None => return true,
+        };
+        // the newest snapshot has had the least time to get here: never move backwards
+        let offset = server_tick as i64 - local_tick as i64;
+        self.offset = Some(self.offset.map_or(offset, |old| old.max(offset)));
+        true
+    }
+
+    /// Forget the offset between the server and local ticks
+    ///
+    /// It is measured again on the next [`insert`](Self::insert). Use this if the local
+    /// fixed framestep was reset, or has fallen behind the server.
+    pub fn resync(&mut self) {
+        self.offset = None;
+    }
+
+    /// Drop all the snapshots, and the offset between the server and local ticks
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+        self.offset = None;
+    }
+
+    /// The snapshot for the given server tick, if it is buffered
+    pub fn get(&self, server_tick: u64) -> Option<&S> {
+        self.snapshots.get(&server_tick)
+    }
+
+    /// The server tick of the newest snapshot
+    pub fn latest_tick(&self) -> Option<u64> {
+        self.snapshots.keys().next_back().copied()
+    }
+
+    /// The number of buffered snapshots
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Are there no snapshots?
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// The (fractional) server tick to render at now, if any snapshots have arrived
+    ///
+    /// The local tick plus its overstep, shifted to the server tick, minus the delay.
+    pub fn render_tick(&self, framesteps: &FixedFramesteps) -> Option<f64> {
+        let offset = self.offset?;
+        let info = framesteps.get(self.framestep)?;
+        let tick = info.tick() as f64 + info.overstep() + offset as f64 - self.delay as f64;
+        Some(tick.max(0.0))
+    }
+
+    /// The snapshots to render between now, and the blend factor between them
+    ///
+    /// If the render tick is past the newest snapshot (they are arriving late), the newest
+    /// one is held. If it is before the oldest snapshot, the oldest one is held.
+    pub fn window(&self, framesteps: &FixedFramesteps) -> Option<InterpolationWindow<'_, S>> {
+        let render = self.render_tick(framesteps)?;
+        let floor = render.floor() as u64;
+        let from = self.snapshots.range(..=floor).next_back();
+        let to = self.snapshots.range(floor + 1..).next();
+        let ((from_tick, from), (to_tick, to)) = match (from, to) {
+            (Some(from), Some(to)) => (from, to),
+            (Some(from), None) => (from, from),
+            (None, Some(to)) => (to, to),
+            (None, None) => return None,
+        };
+        let blend = if to_tick > from_tick {
+            ((render - *from_tick as f64) / (to_tick - from_tick) as f64).clamp(0.0, 1.0) as f32
+        } else {
+            0.0
+        };
+        Some(InterpolationWindow {
+            from_tick: *from_tick,
+            from,
+            to_tick: *to_tick,
+            to,
+            blend,
+        })
+    }
+}
@@ -0,0 +1,190 @@
+//! A resource for each fixed framestep, so systems for different ones do not conflict
+//!
+//! All the fixed framesteps share the [`FixedFramesteps`](super::FixedFramesteps) resource,
+//! so two systems that change the info of two different fixed framesteps cannot run in
+//! parallel, and a change to one marks all of them as changed. Name your fixed framestep
+//! with a type (a unit struct that derives [`FramestepLabel`](super::FramestepLabel) and
+//! `Default`), and register a [`FramestepOf`] resource for it, using
+//! `register_framestep_resource` on the `App` (or
+//! [`FixedFramestepStage::add_framestep_resource`](super::FixedFramestepStage::add_framestep_resource)).
+//! Systems that use `Res<FramestepOf<A>>` and `ResMut<FramestepOf<B>>` do not conflict.
+//!
+//! The resource has a copy of the most used parameters, and is kept in sync by the fixed
+//! framestep stage: it is updated at the start of every tick and at the end of every run
+//! of the stage, and only marked as changed when one of them is different. The changes
+//! made to it are applied the next time the stage loads its info (before the next tick,
+//! or on its next run), like the changes made to the [`FixedFramesteps`](super::FixedFramesteps)
+//! resource. If both are changed, the changes made to this resource win.
+
+use std::marker::PhantomData;
+
+use bevy_ecs::prelude::*;
+
+use super::{validate_framestep, FixedFramesteps, FramestepError, FramestepId, FramestepName, Frames};
+
+/// Resource with the parameters of the fixed framestep named by `F` (see the [module docs](self))
+#[derive(Resource)]
+pub struct FramestepOf<F> {
+    step: Frames,
+    accumulator: Frames,
+    tick: u64,
+    paused: bool,
+    speed: u32,
+    rate: f64,
+    pending: Pending,
+    marker: PhantomData<fn() -> F>,
+}
+
+/// The changes made to a [`FramestepOf`] resource, that are not applied yet
+#[derive(Debug, Default, Clone)]
+struct Pending {
+    step: Option<Frames>,
+    // the calls to `pause` (`true`) and `unpause` (`false`), in order
+    pauses: Vec<bool>,
+    speed: Option<u32>,
+}
+
+impl<F> FramestepOf<F> {
+    /// The number of frames in each framestep
+    pub fn framestep(&self) -> Frames {
+        self.step
+    }
+
+    /// Change the number of frames in each framestep, if it is valid
+    ///
+    /// On error, the step is left unchanged.
+    pub fn try_set_step(&mut self, step: impl Into<Frames>) -> Result<(), FramestepError> {
+        self.step = validate_framestep(step.into())?;
+        self.pending.step = Some(self.step);
+        Ok(())
+    }
+
+    /// The number of frames left over from the last framestep
+    pub fn remaining(&self) -> Frames {
+        self.accumulator
+    }
+
+    /// The number of the current tick (or of the last tick that ran, outside of the ticks)
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Is the fixed framestep paused, by the user or for any reason?
+    ///
+    /// See [`FixedFramestepInfo::is_paused`](super::FixedFramestepInfo::is_paused).
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Pause the fixed framestep (see [`FixedFramestepInfo::pause`](super::FixedFramestepInfo::pause))
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.pending.pauses.push(true);
+    }
+
+    /// Un-pause (resume) the fixed framestep, undoing one [`pause`](Self::pause)
+    ///
+    /// Like [`FixedFramestepInfo::unpause`](super::FixedFramestepInfo::unpause), this only
+    /// pops the [`PauseReason::User`](super::PauseReason::User) reason, so the fixed framestep
+    /// may stay paused. [`is_paused`](Self::is_paused) is only updated on the next sync.
+    pub fn unpause(&mut self) {
+        self.pending.pauses.push(false);
+    }
+
+    /// The speed multiplier (see [`FixedFramestepInfo::set_speed`](super::FixedFramestepInfo::set_speed))
+    pub fn speed(&self) -> u32 {
+        self.speed
+    }
+
+    /// Run the fixed framestep `multiplier` times faster (`0` is treated as `1`)
+    pub fn set_speed(&mut self, multiplier: u32) {
+        self.speed = multiplier.max(1);
+        self.pending.speed = Some(self.speed);
+    }
+
+    /// The nominal number of steps per second (Hz)
+    ///
+    /// See [`FixedFramestepInfo::rate`](super::FixedFramestepInfo::rate).
+    pub fn rate(&self) -> f64 {
+        self.rate
+    }
+}
+
+impl<F> std::fmt::Debug for FramestepOf<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FramestepOf")
+            .field("step", &self.step)
+            .field("accumulator", &self.accumulator)
+            .field("tick", &self.tick)
+            .field("paused", &self.paused)
+            .field("speed", &self.speed)
+            .finish()
+    }
+}
+
+/// The functions for keeping a [`FramestepOf`] resource in sync with a fixed framestep
+pub(super) type SyncFn = fn(&mut World, FramestepName);
+
+/// Apply the changes made to the [`FramestepOf<F>`] resource to the [`FixedFramesteps`] resource
+pub(super) fn pull_framestep_of<F: Send + Sync + 'static>(world: &mut World, label: FramestepName) {
+    let pending = match world.get_resource_mut::<FramestepOf<F>>() {
+        Some(mut res) => std::mem::take(&mut res.bypass_change_detection().pending),
+        None => return,
+    };
+    let mut framesteps = match world.get_resource_mut::<FixedFramesteps>() {
+        Some(framesteps) => framesteps,
+        None => return,
+    };
+    let info = match framesteps.info.get_mut(&FramestepId::new(label)) {
+        Some(info) => info,
+        None => return,
+    };
+    if let Some(step) = pending.step {
+        info.step = step;
+    }
+    for pause in pending.pauses {
+        match pause {
+            true => info.pause(),
+            false => info.unpause(),
+        }
+    }
+    if let Some(speed) = pending.speed {
+        info.speed = speed;
+    }
+}
+
+/// Copy the parameters from the [`FixedFramesteps`] resource to the [`FramestepOf<F>`] resource
+///
+/// Only marks the resource as changed if they are different. Inserts the resource if needed.
+pub(super) fn push_framestep_of<F: Send + Sync + 'static>(world: &mut World, label: FramestepName) {
+    let info = match world.get_resource::<FixedFramesteps>().and_then(|framesteps| framesteps.get(label)) {
+        Some(info) => info,
+        None => return,
+    };
+    let new = FramestepOf::<F> {
+        step: info.framestep(),
+        accumulator: info.remaining(),
+        tick: info.tick(),
+        paused: info.is_paused(),
+        speed: info.speed(),
+        rate: info.rate(),
+        pending: Pending::default(),
+        marker: PhantomData,
+    };
+    match world.get_resource_mut::<FramestepOf<F>>() {
+        Some(mut res) => {
+            let same = res.step == new.step
+                && res.accumulator == new.accumulator
+                && res.tick == new.tick
+                && res.paused == new.paused
+                && res.speed == new.speed
+                && res.rate == new.rate;
+            if !same {
+                // keep the changes that were made since the last pull
+                let pending = std::mem::take(&mut res.pending);
+                *res = FramestepOf { pending, ..new };
+            }
+        }
+        None => world.insert_resource(new),
+    }
+}
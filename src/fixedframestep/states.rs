@@ -0,0 +1,143 @@
+//! Ordering of fixed framestep ticks and state transitions
+//!
+//! # Ordering guarantees
+//!
+//! A state transition never happens in the middle of a tick:
+//!
+//! - A `StateTransitionStage` in the schedule (as added by `add_loopless_state`) runs
+//!   between whole stages, so before or after the fixed framestep stage, never between
+//!   its sub-stages. If a tick system asks for a transition (by inserting a
+//!   [`NextState`]), the resource just stays in the world until that stage runs: after
+//!   *all* the ticks that run on this frame. All of them see the same [`CurrentState`].
+//!   Nothing is queued per tick: a `NextState` inserted by a later tick replaces the one
+//!   from an earlier tick, and only the last one is applied. If the simulation depends on
+//!   the transition happening on a specific tick, make it a tick state instead.
+//! - A tick state (see below) is driven by the fixed framestep itself. Its transition
+//!   stage runs at the end of every tick, after the last sub-stage (including the
+//!   [cleanup sub-stage](super::cleanup)). A `NextState` inserted during a tick is
+//!   applied right after that tick, so the next tick (even on the same frame) runs in
+//!   the new state, and the exit/enter systems run between the two ticks.
+//!
+//! Either way, use `NextState` to change the state. Mutating `CurrentState` directly
+//! changes it immediately, even in the middle of a tick, without running any exit/enter
+//! systems.
+//!
+//! # Tick states
+//!
+//! For states that belong to the simulation (game phases, rounds, ...), let the fixed
+//! framestep drive the transitions, using `add_fixed_framestep_state`, or
+//! [`FixedFramestepStage::add_state_transitions`]. The transitions then happen on tick
+//! boundaries, which keeps them deterministic: they do not depend on how many ticks
+//! happen to run on each frame.
+//!
+//! Requires the `"states"` cargo feature.
+//!
+//! [`NextState`]: crate::state::NextState
+//! [`CurrentState`]: crate::state::CurrentState
+
+use bevy_ecs::schedule::StateData;
+
+use super::FixedFramestepStage;
+use crate::state::StateTransitionStage;
+
+impl FixedFramestepStage {
+    /// Drive the transitions of a state type from this fixed framestep (see the [`states`](self) module)
+    ///
+    /// The stage runs at the end of every tick, after all the child stages. Panics if this
+    /// fixed framestep already drives the transitions of `T`. Do not also add a
+    /// `StateTransitionStage<T>` to the schedule.
+    pub fn add_state_transitions<T: StateData>(&mut self, stage: StateTransitionStage<T>) {
+        assert!(
+            self.state_transitions::<T>().is_none(),
+            "Fixed framestep {:?} already drives the transitions of {}.", self.label, std::any::type_name::<T>(),
+        );
+        self.state_transitions.push(Box::new(stage));
+    }
+
+    /// Builder method for driving the transitions of a state type from this fixed framestep
+    pub fn with_state_transitions<T: StateData>(mut self, stage: StateTransitionStage<T>) -> Self {
+        self.add_state_transitions(stage);
+        self
+    }
+
+    /// Get access to the transition stage of a state type driven by this fixed framestep
+    pub fn state_transitions<T: StateData>(&self) -> Option<&StateTransitionStage<T>> {
+        self.state_transitions.iter()
+            .find_map(|stage| stage.downcast_ref::<StateTransitionStage<T>>())
+    }
+
+    /// Get mut access to the transition stage of a state type driven by this fixed framestep
+    ///
+    /// Use it to add enter/exit systems.
+    pub fn state_transitions_mut<T: StateData>(&mut self) -> Option<&mut StateTransitionStage<T>> {
+        self.state_transitions.iter_mut()
+            .find_map(|stage| stage.downcast_mut::<StateTransitionStage<T>>())
+    }
+}
+
+#[cfg(all(test, feature = "app"))]
+mod tests {
+    use bevy_app::App;
+    use bevy_ecs::prelude::*;
+
+    use crate::fixedframestep::app::AppLooplessFixedFramestepExt;
+    use crate::fixedframestep::FixedFramesteps;
+    use crate::state::{CurrentState, NextState};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum Phase {
+        A,
+        B,
+    }
+
+    #[derive(Default, Resource)]
+    struct Log(Vec<(&'static str, Phase)>);
+
+    fn log(name: &'static str) -> impl FnMut(ResMut<Log>, Res<CurrentState<Phase>>) {
+        move |mut log: ResMut<Log>, state: Res<CurrentState<Phase>>| log.0.push((name, state.0))
+    }
+
+    /// Runs two ticks in one frame; the first one asks for `Phase::B` in sub-stage 0
+    fn run_two_ticks() -> Vec<(&'static str, Phase)> {
+        let mut app = App::default();
+        app.init_resource::<Log>();
+        app.insert_resource(CurrentState(Phase::A));
+        app.add_fixed_framestep(1, "sim");
+        app.add_fixed_framestep_child_stage("sim");
+        app.add_fixed_framestep_state("sim", Phase::A);
+        app.add_fixed_framestep_system("sim", 0, log("sub-stage 0"));
+        app.add_fixed_framestep_system("sim", 0, |mut commands: Commands, mut done: Local<bool>| {
+            if !*done {
+                *done = true;
+                commands.insert_resource(NextState(Phase::B));
+            }
+        });
+        app.add_fixed_framestep_system("sim", 1, log("sub-stage 1"));
+        app.add_fixed_framestep_cleanup_system("sim", log("cleanup"));
+        app.add_fixed_framestep_enter_system("sim", Phase::B, log("enter"));
+        app.world.resource_mut::<FixedFramesteps>().get_mut("sim").unwrap().queue_ticks(1);
+        app.update();
+        std::mem::take(&mut app.world.resource_mut::<Log>().0)
+    }
+
+    #[test]
+    fn next_state_is_applied_after_the_last_substage_and_cleanup() {
+        let log = run_two_ticks();
+        assert_eq!(log[..4], [
+            ("sub-stage 0", Phase::A),
+            ("sub-stage 1", Phase::A),
+            ("cleanup", Phase::A),
+            ("enter", Phase::B),
+        ]);
+    }
+
+    #[test]
+    fn next_tick_on_the_same_frame_sees_the_new_state() {
+        let log = run_two_ticks();
+        assert_eq!(log[4..], [
+            ("sub-stage 0", Phase::B),
+            ("sub-stage 1", Phase::B),
+            ("cleanup", Phase::B),
+        ]);
+    }
+}
@@ -0,0 +1,224 @@
+//! Tool-assisted (TAS-style) stepping, savestates, and input re-recording
+//!
+//! Add the [`TasPlugin`] to control a fixed framestep like in a TAS tool: save the
+//! state of the simulation into one of several slots, load it back, and advance one
+//! tick per frame while a key is held. Use it together with the [`FramestepDebuggerPlugin`](super::debugger::FramestepDebuggerPlugin),
+//! for pausing and single-stepping.
+//!
+//! Savestates use the [`save`](super::save) module, so they contain the registered
+//! components of the [`Persistent`](super::save::Persistent) entities and the fixed
+//! framestep state. If a [`ReplayRecorder`] for the input type is present, loading a
+//! savestate also rewinds the recording to the tick of the savestate, so that recording
+//! continues from there ("re-recording"). The number of re-records is counted.
+//!
+//! Requires the `"framestep-tas"` cargo feature.
+
+use std::marker::PhantomData;
+
+use bevy_app::{App, CoreStage, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_input::{Input, InputSystem};
+use bevy_input::keyboard::KeyCode;
+use bevy_utils::tracing::{info, warn};
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName};
+use super::replay::{ReplayInput, ReplayRecorder};
+use super::save::{load_world, save_world};
+
+/// The hotkeys used by the [`TasPlugin`]
+#[derive(Debug, Clone)]
+pub struct TasKeys {
+    /// Save the state into the selected slot
+    pub save_state: KeyCode,
+    /// Load the state from the selected slot
+    pub load_state: KeyCode,
+    /// Select the next slot (wraps around)
+    pub next_slot: KeyCode,
+    /// Hold to advance one tick on every frame
+    pub advance: KeyCode,
+}
+
+impl Default for TasKeys {
+    fn default() -> Self {
+        Self {
+            save_state: KeyCode::F2,
+            load_state: KeyCode::F3,
+            next_slot: KeyCode::F4,
+            advance: KeyCode::F9,
+        }
+    }
+}
+
+/// A savestate stored in a slot of the [`TasState`]
+#[derive(Debug, Clone)]
+pub struct Savestate {
+    /// The tick the state was saved after
+    pub tick: u64,
+    /// The data written by [`save_world`]
+    pub data: Vec<u8>,
+}
+
+/// Resource with the savestate slots of the [`TasPlugin`]
+#[derive(Debug, Clone)]
+#[derive(Resource)]
+pub struct TasState {
+    slots: Vec<Option<Savestate>>,
+    slot: usize,
+    rerecords: u32,
+}
+
+impl TasState {
+    /// The index of the selected slot
+    pub fn slot(&self) -> usize {
+        self.slot
+    }
+
+    /// Select a slot; out of range values wrap around
+    pub fn select_slot(&mut self, slot: usize) {
+        self.slot = slot % self.slots.len();
+    }
+
+    /// Get the savestate in a slot, if any
+    pub fn savestate(&self, slot: usize) -> Option<&Savestate> {
+        self.slots.get(slot).and_then(Option::as_ref)
+    }
+
+    /// How many times a savestate was loaded
+    pub fn rerecords(&self) -> u32 {
+        self.rerecords
+    }
+}
+
+/// Plugin for TAS-style control of a fixed framestep (see the [module docs](self))
+///
+/// `I` is the input type that is recorded with the [`ReplayRecorderPlugin`](super::replay::ReplayRecorderPlugin),
+/// if you use one.
+#[derive(Resource)]
+pub struct TasPlugin<I: ReplayInput> {
+    /// The name of the fixed framestep to control
+    pub framestep: FramestepName,
+    /// The hotkeys to use
+    pub keys: TasKeys,
+    /// The number of savestate slots
+    pub slots: usize,
+    marker: PhantomData<fn() -> I>,
+}
+
+// not derived, as that would require `I: Clone`
+impl<I: ReplayInput> Clone for TasPlugin<I> {
+    fn clone(&self) -> Self {
+        Self {
+            framestep: self.framestep,
+            keys: self.keys.clone(),
+            slots: self.slots,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<I: ReplayInput> TasPlugin<I> {
+    /// Create the plugin to control the given fixed framestep, with the default hotkeys and 4 slots
+    pub fn new(framestep: impl FramestepLabel) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            keys: TasKeys::default(),
+            slots: 4,
+            marker: PhantomData,
+        }
+    }
+
+    /// Builder method for using different hotkeys
+    pub fn with_keys(mut self, keys: TasKeys) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Builder method for a different number of savestate slots
+    ///
+    /// Panics if `slots` is `0`.
+    pub fn with_slots(mut self, slots: usize) -> Self {
+        assert!(slots > 0);
+        self.slots = slots;
+        self
+    }
+}
+
+impl<I: ReplayInput> Plugin for TasPlugin<I> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.clone());
+        app.insert_resource(TasState {
+            slots: vec![None; self.slots],
+            slot: 0,
+            rerecords: 0,
+        });
+        app.add_system_to_stage(CoreStage::PreUpdate, tas_hotkeys::<I>.after(InputSystem));
+    }
+}
+
+fn tas_hotkeys<I: ReplayInput>(world: &mut World) {
+    let config = world.resource::<TasPlugin<I>>().clone();
+    let (save, load, next, advance) = match world.get_resource::<Input<KeyCode>>() {
+        Some(kbd) => (
+            kbd.just_pressed(config.keys.save_state),
+            kbd.just_pressed(config.keys.load_state),
+            kbd.just_pressed(config.keys.next_slot),
+            kbd.pressed(config.keys.advance),
+        ),
+        None => return,
+    };
+
+    if next {
+        let mut state = world.resource_mut::<TasState>();
+        let slot = state.slot + 1;
+        state.select_slot(slot);
+        info!("TAS: selected slot {}", state.slot);
+    }
+    if save {
+        save_state(world, config.framestep);
+    }
+    if load {
+        load_state::<I>(world, config.framestep);
+    }
+    if advance {
+        if let Some(info) = world.resource_mut::<FixedFramesteps>().get_mut(config.framestep) {
+            info.step_while_paused();
+        }
+    }
+}
+
+fn save_state(world: &mut World, framestep: FramestepName) {
+    let tick = match world.resource::<FixedFramesteps>().get(framestep) {
+        Some(info) => info.tick(),
+        None => return,
+    };
+    match save_world(world) {
+        Ok(data) => {
+            let mut state = world.resource_mut::<TasState>();
+            let slot = state.slot;
+            state.slots[slot] = Some(Savestate { tick, data });
+            info!("TAS: saved tick {} into slot {}", tick, slot);
+        }
+        Err(e) => warn!("TAS: could not save the state: {}", e),
+    }
+}
+
+fn load_state<I: ReplayInput>(world: &mut World, framestep: FramestepName) {
+    let state = world.resource::<TasState>();
+    let slot = state.slot;
+    let savestate = match state.savestate(slot) {
+        Some(savestate) => savestate.clone(),
+        None => {
+            warn!("TAS: slot {} is empty", slot);
+            return;
+        }
+    };
+    if let Err(e) = load_world(world, &savestate.data) {
+        warn!("TAS: could not load the state from slot {}: {}", slot, e);
+        return;
+    }
+    if let Some(mut recorder) = world.get_resource_mut::<ReplayRecorder<I>>() {
+        recorder.truncate_after(savestate.tick);
+    }
+    world.resource_mut::<TasState>().rerecords += 1;
+    info!("TAS: loaded tick {} of {:?} from slot {}", savestate.tick, framestep, slot);
+}
@@ -0,0 +1,200 @@
+//! Async tasks whose results only show up at tick boundaries
+//!
+//! Polling an `AsyncComputeTaskPool` task from a tick system makes its result show up in
+//! the middle of some tick: whichever one happens to run after the task finished, in
+//! whichever system polls it first. A [`TickTask<T>`] component (or [`TickResourceTask<T>`]
+//! resource) holds the task instead, and its result is delivered at the start of a later
+//! tick, before any of its systems run: the `TickTask<T>` component is replaced by the
+//! resulting `T` component (or the `T` resource is inserted).
+//!
+//! By default, the result is delivered at the start of the first tick after the task has
+//! finished. That tick still depends on how long the task took. For determinism, use
+//! [`deliver_at`](TickTask::deliver_at): the result is delivered at the start of exactly
+//! that tick, waiting for the task to finish if needed (or, if the tick has already
+//! passed, at the start of the next one).
+//!
+//! Add the [`TickTaskPlugin<T>`] (or [`TickResourceTaskPlugin<T>`]) for your result type,
+//! and the fixed framestep that delivers the results.
+
+use std::marker::PhantomData;
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_tasks::{AsyncComputeTaskPool, Task};
+use futures_lite::future;
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName, find_framestep_mut};
+
+struct PendingTask<T> {
+    task: Task<T>,
+    deliver_at: Option<u64>,
+}
+
+impl<T> PendingTask<T> {
+    /// The result, if it can be delivered at the start of the given tick
+    fn poll(&mut self, tick: u64) -> Option<T> {
+        let ready = match self.deliver_at {
+            // wait for it, if needed
+            Some(deliver_at) => tick >= deliver_at,
+            None => self.task.is_finished(),
+        };
+        ready.then(|| future::block_on(&mut self.task))
+    }
+}
+
+/// Component with an async task, whose result is inserted as a component on a later tick
+///
+/// See the [module docs](self).
+#[derive(Component)]
+pub struct TickTask<T: Component> {
+    pending: PendingTask<T>,
+}
+
+impl<T: Component> TickTask<T> {
+    /// Wrap a task that was already spawned
+    pub fn new(task: Task<T>) -> Self {
+        Self {
+            pending: PendingTask {
+                task,
+                deliver_at: None,
+            },
+        }
+    }
+
+    /// Spawn a future on the `AsyncComputeTaskPool`
+    ///
+    /// Panics if the task pool has not been initialized (by Bevy's `CorePlugin`).
+    pub fn spawn(future: impl std::future::Future<Output = T> + Send + 'static) -> Self {
+        Self::new(AsyncComputeTaskPool::get().spawn(future))
+    }
+
+    /// Builder method for delivering the result at the start of exactly the given tick
+    ///
+    /// If the task has not finished by then, the tick waits for it.
+    pub fn deliver_at(mut self, tick: u64) -> Self {
+        self.pending.deliver_at = Some(tick);
+        self
+    }
+
+    /// Has the task finished? (its result may still have to wait for its tick)
+    pub fn is_finished(&self) -> bool {
+        self.pending.task.is_finished()
+    }
+}
+
+/// Resource with an async task, whose result is inserted as a resource on a later tick
+///
+/// See the [module docs](self).
+#[derive(Resource)]
+pub struct TickResourceTask<T: Resource> {
+    pending: PendingTask<T>,
+}
+
+impl<T: Resource> TickResourceTask<T> {
+    /// Wrap a task that was already spawned
+    pub fn new(task: Task<T>) -> Self {
+        Self {
+            pending: PendingTask {
+                task,
+                deliver_at: None,
+            },
+        }
+    }
+
+    /// Spawn a future on the `AsyncComputeTaskPool`
+    ///
+    /// Panics if the task pool has not been initialized (by Bevy's `CorePlugin`).
+    pub fn spawn(future: impl std::future::Future<Output = T> + Send + 'static) -> Self {
+        Self::new(AsyncComputeTaskPool::get().spawn(future))
+    }
+
+    /// Builder method for delivering the result at the start of exactly the given tick
+    ///
+    /// If the task has not finished by then, the tick waits for it.
+    pub fn deliver_at(mut self, tick: u64) -> Self {
+        self.pending.deliver_at = Some(tick);
+        self
+    }
+
+    /// Has the task finished? (its result may still have to wait for its tick)
+    pub fn is_finished(&self) -> bool {
+        self.pending.task.is_finished()
+    }
+}
+
+/// Plugin for delivering the results of [`TickTask<T>`] components on the ticks of a fixed framestep
+///
+/// The fixed framestep must already be added to the app.
+pub struct TickTaskPlugin<T> {
+    framestep: FramestepName,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Component> TickTaskPlugin<T> {
+    /// Create the plugin for the given fixed framestep
+    pub fn new(framestep: impl FramestepLabel) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Component> Plugin for TickTaskPlugin<T> {
+    fn build(&self, app: &mut App) {
+        let stage = find_framestep_mut(&mut app.schedule, self.framestep)
+            .expect("Fixed Framestep Stage not found");
+        stage.add_tick_begin_hook(deliver_tick_tasks::<T>);
+    }
+}
+
+/// Plugin for delivering the result of a [`TickResourceTask<T>`] on the ticks of a fixed framestep
+///
+/// The fixed framestep must already be added to the app.
+pub struct TickResourceTaskPlugin<T> {
+    framestep: FramestepName,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Resource> TickResourceTaskPlugin<T> {
+    /// Create the plugin for the given fixed framestep
+    pub fn new(framestep: impl FramestepLabel) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Resource> Plugin for TickResourceTaskPlugin<T> {
+    fn build(&self, app: &mut App) {
+        let stage = find_framestep_mut(&mut app.schedule, self.framestep)
+            .expect("Fixed Framestep Stage not found");
+        stage.add_tick_begin_hook(deliver_tick_resource_task::<T>);
+    }
+}
+
+fn deliver_tick_tasks<T: Component>(world: &mut World) {
+    let tick = world.resource::<FixedFramesteps>().current().tick();
+    let mut query = world.query::<(Entity, &mut TickTask<T>)>();
+    let delivered: Vec<_> = query.iter_mut(world)
+        .filter_map(|(entity, mut task)| task.pending.poll(tick).map(|result| (entity, result)))
+        .collect();
+    for (entity, result) in delivered {
+        let mut entity = world.entity_mut(entity);
+        entity.remove::<TickTask<T>>();
+        entity.insert(result);
+    }
+}
+
+fn deliver_tick_resource_task<T: Resource>(world: &mut World) {
+    let tick = world.resource::<FixedFramesteps>().current().tick();
+    let result = match world.get_resource_mut::<TickResourceTask<T>>() {
+        Some(mut task) => task.pending.poll(tick),
+        None => return,
+    };
+    if let Some(result) = result {
+        world.remove_resource::<TickResourceTask<T>>();
+        world.insert_resource(result);
+    }
+}
@@ -0,0 +1,67 @@
+//! A lightweight resource with just the current tick number of one fixed framestep
+//!
+//! Many systems only need the number of the current tick (say, to stamp a network message
+//! or a log entry), not the whole [`FixedFramesteps`](super::FixedFramesteps) map. Name
+//! your fixed framestep with a type (a unit struct that derives
+//! [`FramestepLabel`](super::FramestepLabel) and `Default`), and register a [`TickOf`]
+//! resource for it, using `register_tick_resource` on the `App` (or
+//! [`FixedFramestepStage::add_tick_resource`](super::FixedFramestepStage::add_tick_resource)).
+//!
+//! The resource is updated at the start of every tick, before the child stages, and is
+//! only marked as changed when the number is different, so `Res<TickOf<F>>::is_changed`
+//! tells if a new tick has started since the system last ran. Outside of the ticks it
+//! has the number of the last tick that ran; it does not follow a
+//! [rewind](super::FixedFramestepInfo::rewind) or [reset](super::FixedFramestepInfo::reset)
+//! until the next tick.
+
+use std::marker::PhantomData;
+
+use bevy_ecs::prelude::*;
+
+use super::FramestepLabel;
+
+/// Resource with the number of the current tick of the fixed framestep named by `F` (see the [module docs](self))
+#[derive(Resource)]
+pub struct TickOf<F> {
+    tick: u64,
+    marker: PhantomData<fn() -> F>,
+}
+
+impl<F> Default for TickOf<F> {
+    fn default() -> Self {
+        Self {
+            tick: 0,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<F> TickOf<F> {
+    /// The number of the current tick (or of the last tick that ran, outside of the ticks)
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+}
+
+impl<F: FramestepLabel + Default> TickOf<F> {
+    /// The name of the fixed framestep
+    pub fn label(&self) -> super::FramestepName {
+        F::default().framestep_name()
+    }
+}
+
+impl<F> std::fmt::Debug for TickOf<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TickOf")
+            .field("tick", &self.tick)
+            .finish()
+    }
+}
+
+/// Update the [`TickOf<F>`] resource, at the start of a tick
+pub(super) fn sync_tick_of<F: Send + Sync + 'static>(world: &mut World, tick: u64) {
+    let mut res = world.get_resource_or_insert_with(TickOf::<F>::default);
+    if res.tick != tick {
+        res.tick = tick;
+    }
+}
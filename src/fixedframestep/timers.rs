@@ -0,0 +1,162 @@
+//! Pausing Bevy timers and stopwatches along with a fixed framestep
+//!
+//! Time-based gameplay helpers (a `Timer` for a cooldown, a `Stopwatch` for a lap time)
+//! are ticked with the frame time, so they keep running while the simulation is paused.
+//! To freeze them too, implement [`PausableTimer`] for the component (or resource) that
+//! holds them, by forwarding to the `Timer` or `Stopwatch` inside (both implement it), and
+//! add a [`PauseTimersPlugin`] for the component type (or a [`PauseTimerResourcePlugin`]
+//! for the resource type).
+//!
+//! Give each entity whose timers should follow a fixed framestep the
+//! [`PausesWithFramestep`] component, with the name of the fixed framestep. Every frame,
+//! in `CoreStage::First`, the timers of those entities are paused when the fixed framestep
+//! is [paused](super::FixedFramestepInfo::is_paused), and unpaused when it is not anymore.
+//! Timers that were already paused by someone else are left alone, and stay paused.
+
+use std::marker::PhantomData;
+
+use bevy_app::{App, CoreStage, Plugin};
+use bevy_ecs::prelude::*;
+use bevy_time::{Stopwatch, Timer};
+
+use super::{FixedFramesteps, FramestepLabel, FramestepName};
+
+/// Timers that can be paused along with a fixed framestep (see the [module docs](self))
+pub trait PausableTimer: Send + Sync + 'static {
+    /// Pause the timer
+    fn pause(&mut self);
+    /// Unpause the timer
+    fn unpause(&mut self);
+    /// Is the timer paused?
+    fn paused(&self) -> bool;
+}
+
+impl PausableTimer for Timer {
+    fn pause(&mut self) {
+        Timer::pause(self);
+    }
+    fn unpause(&mut self) {
+        Timer::unpause(self);
+    }
+    fn paused(&self) -> bool {
+        Timer::paused(self)
+    }
+}
+
+impl PausableTimer for Stopwatch {
+    fn pause(&mut self) {
+        Stopwatch::pause(self);
+    }
+    fn unpause(&mut self) {
+        Stopwatch::unpause(self);
+    }
+    fn paused(&self) -> bool {
+        Stopwatch::paused(self)
+    }
+}
+
+/// Component for entities whose timers are paused along with the given fixed framestep
+///
+/// Only the timer components registered with a [`PauseTimersPlugin`] are paused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Component)]
+pub struct PausesWithFramestep(pub FramestepName);
+
+impl PausesWithFramestep {
+    /// Follow the given fixed framestep
+    pub fn new(framestep: impl FramestepLabel) -> Self {
+        Self(framestep.framestep_name())
+    }
+}
+
+/// Marks the `T` timer of an entity as paused by its fixed framestep, to be unpaused again
+#[derive(Component)]
+struct HeldPaused<T> {
+    marker: PhantomData<fn() -> T>,
+}
+
+/// Plugin for pausing the `T` timer components of the entities with [`PausesWithFramestep`]
+///
+/// See the [module docs](self). Add one plugin per timer component type.
+pub struct PauseTimersPlugin<T> {
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Default for PauseTimersPlugin<T> {
+    fn default() -> Self {
+        Self {
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Component + PausableTimer> Plugin for PauseTimersPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(CoreStage::First, pause_timer_components::<T>);
+    }
+}
+
+/// Plugin for pausing the `T` timer resource along with the given fixed framestep
+///
+/// See the [module docs](self). Add one plugin per timer resource type.
+pub struct PauseTimerResourcePlugin<T> {
+    framestep: FramestepName,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> PauseTimerResourcePlugin<T> {
+    /// Create the plugin for the given fixed framestep
+    pub fn new(framestep: impl FramestepLabel) -> Self {
+        Self {
+            framestep: framestep.framestep_name(),
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Resource + PausableTimer> Plugin for PauseTimerResourcePlugin<T> {
+    fn build(&self, app: &mut App) {
+        let framestep = self.framestep;
+        let mut held = false;
+        app.add_system_to_stage(
+            CoreStage::First,
+            move |framesteps: Option<Res<FixedFramesteps>>, timer: Option<ResMut<T>>| {
+                let mut timer = match timer {
+                    Some(timer) => timer,
+                    None => return,
+                };
+                let paused = framestep_paused(framesteps.as_deref(), framestep);
+                if paused && !held && !timer.paused() {
+                    timer.pause();
+                    held = true;
+                } else if !paused && held {
+                    timer.unpause();
+                    held = false;
+                }
+            },
+        );
+    }
+}
+
+fn framestep_paused(framesteps: Option<&FixedFramesteps>, framestep: FramestepName) -> bool {
+    framesteps.and_then(|framesteps| framesteps.get(framestep))
+        .map(|info| info.is_paused())
+        .unwrap_or(false)
+}
+
+fn pause_timer_components<T: Component + PausableTimer>(
+    mut commands: Commands,
+    framesteps: Option<Res<FixedFramesteps>>,
+    mut query: Query<(Entity, &PausesWithFramestep, &mut T, Option<&HeldPaused<T>>)>,
+) {
+    for (entity, follows, mut timer, held) in query.iter_mut() {
+        let paused = framestep_paused(framesteps.as_deref(), follows.0);
+        if paused && held.is_none() && !timer.paused() {
+            timer.pause();
+            commands.entity(entity).insert(HeldPaused::<T> { marker: PhantomData });
+        } else if !paused && held.is_some() {
+            timer.unpause();
+            commands.entity(entity).remove::<HeldPaused<T>>();
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! One time scale for the whole simulation, leaving the other fixed framesteps alone
+//!
+//! Insert a [`GlobalTickScale`] resource, and opt fixed framesteps into it using
+//! [`FixedFramestepStage::with_global_scale`](super::FixedFramestepStage::with_global_scale).
+//! The elapsed frames of those fixed framesteps are multiplied by the scale, before they
+//! are added to the accumulator, so one slider can slow down (or speed up) the whole
+//! simulation, while the UI ticks keep their rate. The fractions of frames are carried
+//! over to the next frame, so a scale of `0.5` runs a tick every other time it would
+//! have, and not never.
+//!
+//! The scale is on top of the [speed](super::FixedFramestepInfo::set_speed) of each fixed
+//! framestep: the elapsed frames are multiplied by both. Unlike the speed, the scale does
+//! not raise the [max steps per frame](super::FixedFramestepStage::set_max_steps_per_frame).
+//! Fixed framesteps that did not opt in, and all of them if there is no resource, are not
+//! affected.
+
+use bevy_ecs::prelude::*;
+use bevy_utils::tracing::warn;
+
+/// Resource with the time scale of the fixed framesteps that opted into it (see the [module docs](self))
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Resource)]
+pub struct GlobalTickScale {
+    scale: f64,
+}
+
+impl Default for GlobalTickScale {
+    fn default() -> Self {
+        Self { scale: 1.0 }
+    }
+}
+
+impl GlobalTickScale {
+    /// Create a time scale (see [`set`](Self::set))
+    pub fn new(scale: f64) -> Self {
+        let mut this = Self::default();
+        this.set(scale);
+        this
+    }
+
+    /// The time scale: `1.0` is the normal rate
+    pub fn get(&self) -> f64 {
+        self.scale
+    }
+
+    /// Change the time scale
+    ///
+    /// Negative values (and NaN) are treated as `0.0`, which stops the fixed framesteps
+    /// without pausing them. Infinity is rejected: the scale stays as it was.
+    pub fn set(&mut self, scale: f64) {
+        if scale == f64::INFINITY {
+            warn!("Ignoring an infinite global tick scale; keeping {}.", self.scale);
+            return;
+        }
+        self.scale = if scale > 0.0 { scale } else { 0.0 };
+    }
+}
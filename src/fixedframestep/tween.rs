@@ -0,0 +1,143 @@
+//! Tweening driven by fixed framestep ticks
+//!
+//! A [`TickTween<C>`] component animates a component `C` on the same entity over a
+//! fixed number of ticks, with an [`Easing`] curve. Add the [`tick_tweens::<C>`](tick_tweens)
+//! system to the fixed framestep that should drive them. Because the tweens advance by
+//! whole ticks, not by elapsed time, they are deterministic and replay-safe.
+//!
+//! What the tween actually changes is defined by a [`TickLens`]: usually a closure that
+//! sets fields of the component from a ratio between `0.0` and `1.0`. When the tween
+//! finishes, the `TickTween<C>` component is removed.
+
+use bevy_ecs::prelude::*;
+
+/// Easing curves for [`TickTween`]s
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// Constant speed
+    #[default]
+    Linear,
+    /// Accelerate from zero (quadratic)
+    QuadIn,
+    /// Decelerate to zero (quadratic)
+    QuadOut,
+    /// Accelerate, then decelerate (quadratic)
+    QuadInOut,
+    /// Accelerate from zero (cubic)
+    CubicIn,
+    /// Decelerate to zero (cubic)
+    CubicOut,
+    /// Accelerate, then decelerate (cubic)
+    CubicInOut,
+    /// Smoothstep (`3t² - 2t³`)
+    SmoothStep,
+}
+
+impl Easing {
+    /// Map a linear ratio in `0.0..=1.0` onto the curve
+    pub fn apply(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::QuadIn => t * t,
+            Easing::QuadOut => t * (2.0 - t),
+            Easing::QuadInOut => if t < 0.5 {
+                2.0 * t * t
+            } else {
+                -1.0 + (4.0 - 2.0 * t) * t
+            },
+            Easing::CubicIn => t * t * t,
+            Easing::CubicOut => {
+                let u = t - 1.0;
+                u * u * u + 1.0
+            }
+            Easing::CubicInOut => if t < 0.5 {
+                4.0 * t * t * t
+            } else {
+                let u = 2.0 * t - 2.0;
+                0.5 * u * u * u + 1.0
+            },
+            Easing::SmoothStep => t * t * (3.0 - 2.0 * t),
+        }
+    }
+}
+
+/// Applies the progress of a [`TickTween`] to a component
+///
+/// Implemented for closures `Fn(&mut C, f32)`.
+pub trait TickLens<C>: Send + Sync + 'static {
+    /// Update the component, given the eased ratio (`0.0` at the start, `1.0` at the end)
+    fn lerp(&mut self, target: &mut C, ratio: f32);
+}
+
+impl<C, F: Fn(&mut C, f32) + Send + Sync + 'static> TickLens<C> for F {
+    fn lerp(&mut self, target: &mut C, ratio: f32) {
+        self(target, ratio)
+    }
+}
+
+/// Component that animates the component `C` on the same entity (see the [module docs](self))
+#[derive(Component)]
+pub struct TickTween<C: Component> {
+    lens: Box<dyn TickLens<C>>,
+    duration: u32,
+    elapsed: u32,
+    easing: Easing,
+}
+
+impl<C: Component> TickTween<C> {
+    /// Create a tween that lasts `duration` ticks
+    ///
+    /// A `duration` of `0` finishes on the next tick.
+    pub fn new(duration: u32, easing: Easing, lens: impl TickLens<C>) -> Self {
+        Self {
+            lens: Box::new(lens),
+            duration,
+            elapsed: 0,
+            easing,
+        }
+    }
+
+    /// The total number of ticks
+    pub fn duration(&self) -> u32 {
+        self.duration
+    }
+
+    /// The number of ticks that have elapsed
+    pub fn elapsed(&self) -> u32 {
+        self.elapsed
+    }
+
+    /// The linear (not eased) progress, from `0.0` to `1.0`
+    pub fn progress(&self) -> f32 {
+        if self.duration == 0 {
+            1.0
+        } else {
+            self.elapsed as f32 / self.duration as f32
+        }
+    }
+
+    /// Has the tween reached its end?
+    pub fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+/// System that advances all [`TickTween<C>`]s by one tick
+///
+/// Add it to the fixed framestep that the tweens should follow. Finished tweens
+/// are removed from their entity.
+pub fn tick_tweens<C: Component>(
+    mut commands: Commands,
+    mut q: Query<(Entity, &mut TickTween<C>, &mut C)>,
+) {
+    for (e, mut tween, mut target) in q.iter_mut() {
+        let tween = &mut *tween;
+        tween.elapsed = (tween.elapsed + 1).min(tween.duration);
+        let ratio = tween.easing.apply(tween.progress());
+        tween.lens.lerp(&mut target, ratio);
+        if tween.is_finished() {
+            commands.entity(e).remove::<TickTween<C>>();
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Units for the values of the fixed framesteps: [`Frames`] and [`Ticks`]
+//!
+//! The length of a framestep and the accumulator count frames, while the catch-up and
+//! queued ticks count ticks. Both used to be plain integers, which made it easy to
+//! mix them up. The arithmetic only allows what makes sense:
+//!
+//! - frames and frames add up to frames, and ticks and ticks to ticks
+//! - frames divided by the length of a framestep are [`Ticks`] (the whole ticks that fit),
+//!   with the rest as frames
+//! - ticks times the length of a framestep are [`Frames`]
+//! - either can be scaled by a plain number
+//!
+//! The arithmetic saturates: it stops at zero and at the largest value, instead of
+//! overflowing, so a huge elapsed time or speed cannot wrap the accumulator around.
+//! The number is in the public field, for anything else.
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Div, Mul, Rem, RemAssign, Sub, SubAssign};
+
+use super::FrameCounter;
+
+/// A number of frames, like the length of a framestep, or the frames in the accumulator
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct Frames(pub FrameCounter);
+
+/// A number of ticks of a fixed framestep
+///
+/// Only for counts, like the queued or skipped ticks. Tick numbers, like
+/// [`FixedFramestepInfo::tick`](super::FixedFramestepInfo::tick), are plain `u64`s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct Ticks(pub u64);
+
+impl Frames {
+    /// No frames
+    pub const ZERO: Frames = Frames(0);
+    /// The largest number of frames
+    pub const MAX: Frames = Frames(FrameCounter::MAX);
+
+    /// How many whole ticks of the given length fit in these frames
+    ///
+    /// Panics if `step` is zero.
+    pub fn ticks(self, step: Frames) -> Ticks {
+        self / step
+    }
+
+    /// Subtract, stopping at zero
+    pub fn saturating_sub(self, rhs: Frames) -> Frames {
+        Frames(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Add, stopping at [`Frames::MAX`]
+    pub fn saturating_add(self, rhs: Frames) -> Frames {
+        Frames(self.0.saturating_add(rhs.0))
+    }
+
+    /// Is it zero frames?
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl Ticks {
+    /// No ticks
+    pub const ZERO: Ticks = Ticks(0);
+
+    /// How many frames these ticks take, with the given framestep length
+    pub fn frames(self, step: Frames) -> Frames {
+        self * step
+    }
+
+    /// Subtract, stopping at zero
+    pub fn saturating_sub(self, rhs: Ticks) -> Ticks {
+        Ticks(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Is it zero ticks?
+    pub fn is_zero(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl From<FrameCounter> for Frames {
+    fn from(frames: FrameCounter) -> Frames {
+        Frames(frames)
+    }
+}
+
+impl From<Frames> for FrameCounter {
+    fn from(frames: Frames) -> FrameCounter {
+        frames.0
+    }
+}
+
+impl From<u64> for Ticks {
+    fn from(ticks: u64) -> Ticks {
+        Ticks(ticks)
+    }
+}
+
+impl From<Ticks> for u64 {
+    fn from(ticks: Ticks) -> u64 {
+        ticks.0
+    }
+}
+
+impl fmt::Display for Frames {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl fmt::Display for Ticks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Add for Frames {
+    type Output = Frames;
+    fn add(self, rhs: Frames) -> Frames {
+        Frames(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl AddAssign for Frames {
+    fn add_assign(&mut self, rhs: Frames) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Frames {
+    type Output = Frames;
+    fn sub(self, rhs: Frames) -> Frames {
+        Frames(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl SubAssign for Frames {
+    fn sub_assign(&mut self, rhs: Frames) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<FrameCounter> for Frames {
+    type Output = Frames;
+    fn mul(self, rhs: FrameCounter) -> Frames {
+        Frames(self.0.saturating_mul(rhs))
+    }
+}
+
+impl Div<Frames> for Frames {
+    type Output = Ticks;
+    fn div(self, step: Frames) -> Ticks {
+        Ticks(self.0 / step.0)
+    }
+}
+
+impl Rem<Frames> for Frames {
+    type Output = Frames;
+    fn rem(self, step: Frames) -> Frames {
+        Frames(self.0 % step.0)
+    }
+}
+
+impl RemAssign<Frames> for Frames {
+    fn rem_assign(&mut self, step: Frames) {
+        self.0 %= step.0;
+    }
+}
+
+impl Add for Ticks {
+    type Output = Ticks;
+    fn add(self, rhs: Ticks) -> Ticks {
+        Ticks(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl AddAssign for Ticks {
+    fn add_assign(&mut self, rhs: Ticks) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Ticks {
+    type Output = Ticks;
+    fn sub(self, rhs: Ticks) -> Ticks {
+        Ticks(self.0.saturating_sub(rhs.0))
+    }
+}
+
+impl SubAssign for Ticks {
+    fn sub_assign(&mut self, rhs: Ticks) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<u64> for Ticks {
+    type Output = Ticks;
+    fn mul(self, rhs: u64) -> Ticks {
+        Ticks(self.0.saturating_mul(rhs))
+    }
+}
+
+impl Mul<Frames> for Ticks {
+    type Output = Frames;
+    fn mul(self, step: Frames) -> Frames {
+        Frames(self.0.saturating_mul(step.0))
+    }
+}
+
+impl std::iter::Sum for Frames {
+    fn sum<I: Iterator<Item = Frames>>(iter: I) -> Frames {
+        iter.fold(Frames::ZERO, Add::add)
+    }
+}
+
+impl std::iter::Sum for Ticks {
+    fn sum<I: Iterator<Item = Ticks>>(iter: I) -> Ticks {
+        iter.fold(Ticks::ZERO, Add::add)
+    }
+}
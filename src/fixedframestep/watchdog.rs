@@ -0,0 +1,203 @@
+//! Detecting runaway ticks
+//!
+//! A [`TickWatchdog`] watches the ticks of the fixed framesteps it is given to (see
+//! [`FixedFramestepStage::set_watchdog`](super::FixedFramestepStage::set_watchdog)), from
+//! a thread of its own. When a tick has been running for longer than the timeout, it
+//! logs an error naming the fixed framestep, the tick, and the sub-stage that is running,
+//! while the tick is still stuck. If the tick finishes after all, the fixed framestep
+//! sends a [`TickStalled`] event.
+//!
+//! Unlike the [tick budget](super::FixedFramestepStage::set_tick_budget), which is about
+//! ticks that are a bit too slow, this is about ticks that are stuck (say, in an endless
+//! loop), so make the timeout generous: seconds, not milliseconds. A dedicated server can
+//! report the stall (or abort) from a [handler](TickWatchdog::with_on_stall), which runs
+//! on the watchdog thread, since the main thread is busy with the stuck tick.
+//!
+//! If the thread cannot be started, the watchdog still checks every tick when it ends.
+//!
+//! One watchdog can watch several fixed framesteps, including nested ones: clone it.
+
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use bevy_utils::Instant;
+use bevy_utils::tracing::{error, warn};
+
+use super::FramestepName;
+
+/// Event sent when a tick of a fixed framestep ran for longer than the [`TickWatchdog`] timeout
+///
+/// Sent right after the tick (so never for a tick that stays stuck). Only sent if the
+/// event type has been registered; the `App` extension methods for adding fixed
+/// framesteps do that for you.
+#[derive(Debug, Clone)]
+pub struct TickStalled {
+    /// The name of the fixed framestep
+    pub label: FramestepName,
+    /// The number of the tick
+    pub tick: u64,
+    /// The sub-stage that was running when the timeout ran out
+    ///
+    /// `None` outside of the sub-stages: in the tick hooks and callbacks, or the state transitions.
+    pub substage: Option<usize>,
+    /// How long the tick ran: in total, for the event; until it was noticed, for the handler
+    pub duration: Duration,
+}
+
+type StallHandler = Box<dyn Fn(&TickStalled) + Send + Sync>;
+
+/// Watches the ticks of fixed framesteps for running longer than a timeout (see the [module docs](self))
+///
+/// Cheap to clone: the clones share the thread, and the handler.
+#[derive(Clone)]
+pub struct TickWatchdog {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    timeout: Duration,
+    running: Mutex<Vec<RunningTick>>,
+    handler: Mutex<Option<StallHandler>>,
+}
+
+struct RunningTick {
+    label: FramestepName,
+    tick: u64,
+    substage: Option<usize>,
+    start: Instant,
+    reported: bool,
+}
+
+impl TickWatchdog {
+    /// Create a watchdog with the given timeout, and start its thread
+    ///
+    /// The thread stops when the watchdog and all of its clones are dropped.
+    pub fn new(timeout: Duration) -> Self {
+        let shared = Arc::new(Shared {
+            timeout,
+            running: Mutex::new(Vec::new()),
+            handler: Mutex::new(None),
+        });
+        let weak = Arc::downgrade(&shared);
+        let poll = (timeout / 4).max(Duration::from_millis(1));
+        let spawned = std::thread::Builder::new()
+            .name("tick watchdog".into())
+            .spawn(move || watch(weak, poll));
+        if let Err(e) = spawned {
+            warn!("Could not start the tick watchdog thread, only checking the ticks when they end: {}", e);
+        }
+        Self { shared }
+    }
+
+    /// Builder method for calling the given function when a tick has run for too long
+    ///
+    /// It is called once for every stalled tick, right after logging it: on the watchdog
+    /// thread while the tick is still running, or when it ends, if the thread did not
+    /// notice in time.
+    pub fn with_on_stall(self, handler: impl Fn(&TickStalled) + Send + Sync + 'static) -> Self {
+        *lock(&self.shared.handler) = Some(Box::new(handler));
+        self
+    }
+
+    /// How long a tick may run
+    pub fn timeout(&self) -> Duration {
+        self.shared.timeout
+    }
+
+    /// A tick of the given fixed framestep starts
+    pub(super) fn begin_tick(&self, label: FramestepName, tick: u64, start: Instant) {
+        lock(&self.shared.running).push(RunningTick {
+            label,
+            tick,
+            substage: None,
+            start,
+            reported: false,
+        });
+    }
+
+    /// The tick of the given fixed framestep moves on to another sub-stage (or out of them)
+    pub(super) fn enter_substage(&self, label: FramestepName, substage: Option<usize>) {
+        let mut running = lock(&self.shared.running);
+        if let Some(tick) = running.iter_mut().rev().find(|tick| tick.label == label) {
+            // keep the sub-stage that ran out of time, even if the thread has not noticed yet
+            if !tick.reported && tick.start.elapsed() <= self.shared.timeout {
+                tick.substage = substage;
+            }
+        }
+    }
+
+    /// The tick of the given fixed framestep ended; returns the event to send, if it stalled
+    pub(super) fn end_tick(&self, label: FramestepName) -> Option<TickStalled> {
+        let tick = {
+            let mut running = lock(&self.shared.running);
+            let i = running.iter().rposition(|tick| tick.label == label)?;
+            running.remove(i)
+        };
+        let duration = tick.start.elapsed();
+        if !tick.reported && duration <= self.shared.timeout {
+            return None;
+        }
+        let stalled = TickStalled {
+            label: tick.label,
+            tick: tick.tick,
+            substage: tick.substage,
+            duration,
+        };
+        if !tick.reported {
+            self.shared.report(&stalled);
+        }
+        Some(stalled)
+    }
+}
+
+impl Shared {
+    /// Report the ticks that have just run out of time
+    fn check(&self) {
+        let stalled: Vec<TickStalled> = lock(&self.running).iter_mut()
+            .filter(|tick| !tick.reported && tick.start.elapsed() > self.timeout)
+            .map(|tick| {
+                tick.reported = true;
+                TickStalled {
+                    label: tick.label,
+                    tick: tick.tick,
+                    substage: tick.substage,
+                    duration: tick.start.elapsed(),
+                }
+            })
+            .collect();
+        for stalled in stalled.iter() {
+            self.report(stalled);
+        }
+    }
+
+    fn report(&self, stalled: &TickStalled) {
+        match stalled.substage {
+            Some(i) => error!(
+                "Tick {} of fixed framestep {:?} has been running for {:?} (timeout {:?}), in sub-stage {}",
+                stalled.tick, stalled.label, stalled.duration, self.timeout, i,
+            ),
+            None => error!(
+                "Tick {} of fixed framestep {:?} has been running for {:?} (timeout {:?}), outside of the sub-stages",
+                stalled.tick, stalled.label, stalled.duration, self.timeout,
+            ),
+        }
+        if let Some(handler) = lock(&self.handler).as_ref() {
+            handler(stalled);
+        }
+    }
+}
+
+fn watch(shared: Weak<Shared>, poll: Duration) {
+    loop {
+        std::thread::sleep(poll);
+        match shared.upgrade() {
+            Some(shared) => shared.check(),
+            None => return,
+        }
+    }
+}
+
+/// Lock a mutex, ignoring poisoning: the data stays consistent if a handler panics
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
@@ -159,6 +159,21 @@ impl FixedTimestepInfo {
     }
 }
 
+/// Event sent when a huge time delta was clamped (see [`FixedTimestepStage::set_max_frame_delta`])
+///
+/// The `dropped` time was never accumulated, so the simulation is behind the real time
+/// by that much. Only sent if the event type is registered (the `App` extension methods
+/// do this).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedTimestepDeltaClamped {
+    /// The name of the fixed timestep
+    pub label: TimestepName,
+    /// The time delta of the frame, before clamping
+    pub delta: Duration,
+    /// How much of it was dropped
+    pub dropped: Duration,
+}
+
 /// A Stage that runs a number of child stages with a fixed timestep
 ///
 /// You can set the timestep duration. Every frame update, the time delta
@@ -179,6 +194,7 @@ pub struct FixedTimestepStage {
     stages: Vec<Box<dyn Stage>>,
     rate_lock: (u32, f32),
     lock_accum: u32,
+    max_frame_delta: Option<Duration>,
 }
 
 impl FixedTimestepStage {
@@ -197,6 +213,7 @@ impl FixedTimestepStage {
             stages: Vec::new(),
             rate_lock: (u32::MAX, 0.0),
             lock_accum: 0,
+            max_frame_delta: None,
         }
     }
 
@@ -249,6 +266,22 @@ impl FixedTimestepStage {
         self
     }
 
+    /// Clamp the time delta of every frame to at most `max` (`None` to disable)
+    ///
+    /// After a long stall (hitting a breakpoint, the laptop going to sleep, ...), the time
+    /// delta of the next frame is huge, and the fixed timestep would run a burst of ticks
+    /// to catch up. With a maximum, the excess time is dropped instead, and a
+    /// [`FixedTimestepDeltaClamped`] event is sent.
+    pub fn set_max_frame_delta(&mut self, max: Option<Duration>) {
+        self.max_frame_delta = max;
+    }
+
+    /// Builder-style method for [`set_max_frame_delta`]
+    pub fn with_max_frame_delta(mut self, max: Duration) -> Self {
+        self.set_max_frame_delta(Some(max));
+        self
+    }
+
     /// ensure the FixedTimesteps resource exists and contains the latest data
     fn store_fixedtimestepinfo(&self, world: &mut World) {
         if let Some(mut timesteps) = world.get_resource_mut::<FixedTimesteps>() {
@@ -291,7 +324,7 @@ impl Stage for FixedTimestepStage {
             return;
         }
 
-        self.accumulator += {
+        let delta = {
             let time = world.get_resource::<Time>();
             if let Some(time) = time {
                 time.delta()
@@ -299,6 +332,19 @@ impl Stage for FixedTimestepStage {
                 return;
             }
         };
+        self.accumulator += match self.max_frame_delta {
+            Some(max) if delta > max => {
+                if let Some(mut events) = world.get_resource_mut::<Events<FixedTimestepDeltaClamped>>() {
+                    events.send(FixedTimestepDeltaClamped {
+                        label: self.label,
+                        delta,
+                        dropped: delta - max,
+                    });
+                }
+                max
+            }
+            _ => delta,
+        };
 
         if self.lock_accum >= self.rate_lock.0 {
             let overstep = self.accumulator.as_secs_f32() / self.step.as_secs_f32();
@@ -373,7 +419,7 @@ pub mod app {
     use bevy_ecs::schedule::IntoSystemDescriptor;
     use bevy_app::{App, CoreStage};
 
-    use super::{FixedTimestepStage, FixedTimestepStageLabel, TimestepName};
+    use super::{FixedTimestepStage, FixedTimestepStageLabel, FixedTimestepDeltaClamped, TimestepName};
 
     /// Extension trait with the methods to add to Bevy's `App`
     pub trait AppLooplessFixedTimestepExt {
@@ -433,6 +479,7 @@ pub mod app {
         fn add_fixed_timestep_before_stage(&mut self, stage: impl StageLabel, timestep: Duration, label: TimestepName) -> &mut App {
             let ftstage = FixedTimestepStage::from_stage(timestep, label, SystemStage::parallel());
             ftstage.store_fixedtimestepinfo(&mut self.world);
+            self.add_event::<FixedTimestepDeltaClamped>();
             self.add_stage_before(
                 stage,
                 FixedTimestepStageLabel(label),
@@ -443,6 +490,7 @@ pub mod app {
         fn add_fixed_timestep_after_stage(&mut self, stage: impl StageLabel, timestep: Duration, label: TimestepName) -> &mut App {
             let ftstage = FixedTimestepStage::from_stage(timestep, label, SystemStage::parallel());
             ftstage.store_fixedtimestepinfo(&mut self.world);
+            self.add_event::<FixedTimestepDeltaClamped>();
             self.add_stage_after(
                 stage,
                 FixedTimestepStageLabel(label),
@@ -12,6 +12,10 @@
 pub mod condition;
 #[cfg(feature = "fixedtimestep")]
 pub mod fixedtimestep;
+#[cfg(feature = "fixedframestep")]
+pub mod fixedframestep;
+#[cfg(feature = "framestep-samples")]
+pub mod samples;
 #[cfg(feature = "states")]
 pub mod state;
 
@@ -20,12 +24,87 @@ pub mod prelude {
     pub use crate::condition::{ConditionHelpers, IntoConditionalSystem, ConditionSet, AddConditionalToSet};
 
     #[cfg(feature = "fixedtimestep")]
-    pub use crate::fixedtimestep::{FixedTimesteps, FixedTimestepStage};
+    pub use crate::fixedtimestep::{FixedTimesteps, FixedTimestepStage, FixedTimestepDeltaClamped};
     #[cfg(feature = "fixedtimestep")]
     pub use crate::fixedtimestep::schedule::ScheduleLooplessFixedTimestepExt;
     #[cfg(all(feature = "fixedtimestep", feature = "app"))]
     pub use crate::fixedtimestep::app::AppLooplessFixedTimestepExt;
 
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::{FixedFramesteps, FixedFramestepInfo, FixedFramestepStage, FramestepLabel, FramestepId, AccumulationSource, PauseMode, PauseReason, Suppression, TickRng};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::{Frames, Ticks};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::{FixedFramestepStageLabel, FixedFramestepAnchorLabel, FixedFramestepSkipped, FixedFramestepStretchClamped, TicksRewound, RunCriteriaStage};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::{FramestepConfigChanged, FramestepConfigField, SchedulePosition, FramestepPosition, FramestepLayout, FramestepLayouts, TickOverBudget, WarmupFinished};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::commands::TickCommands;
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::scheduler::{TickScheduler, TickTaskHandle};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::events::{Ticked, TickedEventWriter, TickScopedEvents};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::tick::TickOf;
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::split::FramestepOf;
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::slots::TickSystemSlots;
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::placement::{TickOnly, FrameOnly};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::config::{FramestepConfigs, TickConfig};
+    #[cfg(all(feature = "fixedframestep", feature = "app"))]
+    pub use crate::fixedframestep::timers::{PausableTimer, PausesWithFramestep, PauseTimersPlugin, PauseTimerResourcePlugin};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::instances::{FramestepInstances, FramestepInstanceId};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::local::TickLocal;
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::lod::{UpdateRate, DueThisTick, LastTicked};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::regions::RegionTickState;
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::cleanup::{DespawnAtTickEnd, DespawnAfterTicks};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::simtime::{SimulationClock, SimulationTime};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::snapshots::{SnapshotBuffer, InterpolationWindow};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::presim::{Presimulation, Presimulated, WorldMerge};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::watchdog::{TickWatchdog, TickStalled};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::budget::FrameBudget;
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::migration::{RateChanged, RateMigration};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::timescale::GlobalTickScale;
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::params::{Framesteps, CurrentFramestepMut};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::dynamic::DynamicFramestepsExt;
+    #[cfg(feature = "framestep-debugger")]
+    pub use crate::fixedframestep::debugger::FramestepDebuggerPlugin;
+    #[cfg(feature = "framestep-big-brain")]
+    pub use crate::fixedframestep::bigbrain::BigBrainFramestepPlugin;
+    #[cfg(feature = "framestep-interest")]
+    pub use crate::fixedframestep::interest::{TickInterest, InterestManaged, Reactivated, TickInterestPlugin};
+    #[cfg(feature = "fixedframestep")]
+    pub use crate::fixedframestep::schedule::ScheduleLooplessFixedFramestepExt;
+    #[cfg(all(feature = "fixedframestep", feature = "app"))]
+    pub use crate::fixedframestep::lockstep::{LockstepInbox, LockstepPlugin};
+    #[cfg(all(feature = "fixedframestep", feature = "app"))]
+    pub use crate::fixedframestep::prediction::{Predicted, ServerStates, Prediction, PredictionPlugin, Reconciled};
+    #[cfg(all(feature = "fixedframestep", feature = "app"))]
+    pub use crate::fixedframestep::tasks::{TickTask, TickResourceTask, TickTaskPlugin, TickResourceTaskPlugin};
+    #[cfg(feature = "framestep-net")]
+    pub use crate::fixedframestep::net::{LockstepNet, LockstepNetPlugin, NetMessage, NetReceived, NetSend, TickPacket};
+    #[cfg(all(feature = "framestep-save", feature = "app"))]
+    pub use crate::fixedframestep::autosave::{Autosave, Autosaves, AutosaveCompleted, AutosavePlugin};
+    #[cfg(all(feature = "fixedframestep", feature = "app"))]
+    pub use crate::fixedframestep::app::AppLooplessFixedFramestepExt;
+
     #[cfg(feature = "states")]
     pub use crate::state::{CurrentState, NextState, StateTransitionStage};
     #[cfg(feature = "states")]
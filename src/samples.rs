@@ -0,0 +1,9 @@
+//! Complete, minimal games built on the public API of this crate
+//!
+//! Unlike the examples, these are building blocks that you can embed in your own app, or
+//! use as the starting point for your own: take the plugins as they are, or copy the
+//! module and change it.
+//!
+//! They only use the public API of this crate, like your code would.
+
+pub mod lockstep;
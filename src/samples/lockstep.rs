@@ -0,0 +1,399 @@
+//! A minimal deterministic lockstep match: lobby, countdown, and game
+//!
+//! The match goes through three [phases](MatchPhase), each driven by a fixed framestep of
+//! its own, that is paused during the other phases:
+//!
+//!  - the lobby ([`LOBBY_FRAMESTEP`], 10 Hz): the players [join](Lobby::join) and get
+//!    [ready](Lobby::set_ready). Once all of them are ready, the countdown starts.
+//!  - the countdown ([`COUNTDOWN_FRAMESTEP`], 1 Hz): counts down to the start of the game,
+//!    or goes back to the lobby if some player is no longer ready.
+//!  - the game ([`GAME_FRAMESTEP`]): a lockstep simulation (see the
+//!    [`lockstep`](crate::fixedframestep::lockstep) module), where every player moves a
+//!    point around. It is a stand-in for your game: replace the [`SampleGame`] resource
+//!    and the [`SampleCommand`]s with your own.
+//!
+//! Set the player number of the local player (from [`Lobby::join`]) in the [`LocalCommands`]
+//! resource, and queue their commands in it (commands queued before the game starts are
+//! dropped). At the end of every game tick, they are stamped with the tick they run on (a
+//! few ticks in the future, see [`LockstepMatchPlugin::with_input_delay`]), put into the
+//! [`LockstepInbox`], and sent as an [`OutgoingCommands`] event. Send these to the other
+//! peers with your transport, and [receive](LockstepInbox::receive) theirs into the inbox.
+//! To tell if the peers are still in sync, compare their [`SampleGame::checksum`]s for the
+//! same tick.
+//!
+//! The lobby itself is not networked: every peer must see the same lobby, so that the
+//! match starts on the same frame everywhere (for a real game, the host would decide).
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::prelude::*;
+
+use crate::fixedframestep::Frames;
+use crate::fixedframestep::lockstep::{LockstepInbox, LockstepPlugin};
+use crate::prelude::*;
+
+/// The name of the fixed framestep of the lobby
+pub const LOBBY_FRAMESTEP: &str = "lockstep_sample_lobby";
+/// The name of the fixed framestep of the countdown
+pub const COUNTDOWN_FRAMESTEP: &str = "lockstep_sample_countdown";
+/// The name of the fixed framestep of the game
+pub const GAME_FRAMESTEP: &str = "lockstep_sample_game";
+
+/// Resource with the phase of the match
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[derive(Resource)]
+pub enum MatchPhase {
+    /// Waiting for the players to join and get ready
+    #[default]
+    Lobby,
+    /// Counting down to the start of the game
+    Countdown {
+        /// The number of countdown ticks (seconds) left
+        remaining: u32,
+    },
+    /// The game is running
+    Playing,
+}
+
+/// Event sent when the match moves to another phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MatchPhaseChanged {
+    /// The phase before
+    pub from: MatchPhase,
+    /// The phase after
+    pub to: MatchPhase,
+}
+
+/// Resource with the players in the lobby
+#[derive(Debug)]
+#[derive(Resource)]
+pub struct Lobby {
+    // `Some(ready)` for the players that have joined
+    slots: Vec<Option<bool>>,
+}
+
+impl Lobby {
+    fn new(players: usize) -> Self {
+        Self {
+            slots: vec![None; players],
+        }
+    }
+
+    /// Join the lobby; returns the player number, or `None` if the lobby is full
+    pub fn join(&mut self) -> Option<usize> {
+        let player = self.slots.iter().position(Option::is_none)?;
+        self.slots[player] = Some(false);
+        Some(player)
+    }
+
+    /// Leave the lobby, freeing the player number
+    pub fn leave(&mut self, player: usize) {
+        if let Some(slot) = self.slots.get_mut(player) {
+            *slot = None;
+        }
+    }
+
+    /// Mark a player as ready (or not); returns `false` if they have not joined
+    pub fn set_ready(&mut self, player: usize, ready: bool) -> bool {
+        match self.slots.get_mut(player) {
+            Some(Some(slot)) => {
+                *slot = ready;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Has the given player joined?
+    pub fn has_joined(&self, player: usize) -> bool {
+        matches!(self.slots.get(player), Some(Some(_)))
+    }
+
+    /// Is the given player ready?
+    pub fn is_ready(&self, player: usize) -> bool {
+        matches!(self.slots.get(player), Some(Some(true)))
+    }
+
+    /// The number of players that have joined
+    pub fn joined(&self) -> usize {
+        self.slots.iter().filter(|slot| slot.is_some()).count()
+    }
+
+    /// The number of players in a match
+    pub fn players(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Have all the players joined, and are they ready?
+    pub fn all_ready(&self) -> bool {
+        self.slots.iter().all(|slot| *slot == Some(true))
+    }
+}
+
+/// A command of a player in the sample game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SampleCommand {
+    /// Move the point of the player
+    Move {
+        /// Horizontal distance
+        dx: i32,
+        /// Vertical distance
+        dy: i32,
+    },
+}
+
+/// Resource with the state of the sample game
+///
+/// Only integers, so that it is deterministic on any platform.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Resource)]
+pub struct SampleGame {
+    /// The point of every player
+    pub positions: Vec<(i64, i64)>,
+    /// The game tick that was simulated last
+    pub tick: u64,
+    /// The checksum of the state after the last tick, for desync detection
+    pub checksum: u64,
+}
+
+impl SampleGame {
+    fn new(players: usize) -> Self {
+        Self {
+            positions: vec![(0, 0); players],
+            tick: 0,
+            checksum: 0,
+        }
+    }
+
+    /// Compute the checksum of the current state (FNV-1a)
+    pub fn compute_checksum(&self) -> u64 {
+        let mut hash = 0xcbf2_9ce4_8422_2325u64;
+        let mut add = |value: i64| {
+            for byte in value.to_le_bytes() {
+                hash = (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+            }
+        };
+        add(self.tick as i64);
+        for (x, y) in self.positions.iter() {
+            add(*x);
+            add(*y);
+        }
+        hash
+    }
+}
+
+/// Resource with the commands of the local player, for the next game tick
+#[derive(Debug, Default)]
+#[derive(Resource)]
+pub struct LocalCommands {
+    /// The player number of the local player, once they have joined
+    pub player: Option<usize>,
+    commands: Vec<SampleCommand>,
+}
+
+impl LocalCommands {
+    /// Queue a command of the local player
+    pub fn push(&mut self, command: SampleCommand) {
+        self.commands.push(command);
+    }
+
+    /// The commands queued so far
+    pub fn pending(&self) -> &[SampleCommand] {
+        &self.commands
+    }
+}
+
+/// Event with the commands of the local player for a tick, to send to the other peers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutgoingCommands {
+    /// The player number of the local player
+    pub player: usize,
+    /// The game tick that the commands run on
+    pub tick: u64,
+    /// The commands (empty if the player did nothing)
+    pub commands: Vec<SampleCommand>,
+}
+
+/// The settings of the match (see the [`LockstepMatchPlugin`])
+#[derive(Debug, Clone, Copy)]
+#[derive(Resource)]
+pub struct MatchSettings {
+    /// The number of players
+    pub players: usize,
+    /// The number of countdown ticks (seconds)
+    pub countdown: u32,
+    /// How many ticks in the future the local commands run
+    pub input_delay: u64,
+}
+
+/// Plugin for the sample lockstep match (see the [module docs](self))
+pub struct LockstepMatchPlugin {
+    settings: MatchSettings,
+    game_step: Frames,
+    deadline: Option<u32>,
+}
+
+impl LockstepMatchPlugin {
+    /// Create the plugin for a match with the given number of players
+    pub fn new(players: usize) -> Self {
+        Self {
+            settings: MatchSettings {
+                players,
+                countdown: 3,
+                input_delay: 2,
+            },
+            game_step: Frames(2),
+            deadline: None,
+        }
+    }
+
+    /// Builder method for setting the number of countdown ticks (seconds); the default is 3
+    pub fn with_countdown(mut self, seconds: u32) -> Self {
+        self.settings.countdown = seconds;
+        self
+    }
+
+    /// Builder method for setting how many ticks in the future the local commands run
+    ///
+    /// The default is 2. It should cover the time for the commands to reach the other peers.
+    pub fn with_input_delay(mut self, ticks: u64) -> Self {
+        self.settings.input_delay = ticks;
+        self
+    }
+
+    /// Builder method for setting the number of frames per game tick; the default is 2
+    pub fn with_game_step(mut self, frames: impl Into<Frames>) -> Self {
+        self.game_step = frames.into();
+        self
+    }
+
+    /// Builder method for padding the game ticks that waited for more than `frames` frames
+    ///
+    /// See [`LockstepPlugin::with_deadline`].
+    pub fn with_deadline(mut self, frames: u32) -> Self {
+        self.deadline = Some(frames);
+        self
+    }
+}
+
+impl Plugin for LockstepMatchPlugin {
+    fn build(&self, app: &mut App) {
+        let players = self.settings.players;
+        app.insert_resource(self.settings);
+        app.init_resource::<MatchPhase>();
+        app.insert_resource(Lobby::new(players));
+        app.insert_resource(SampleGame::new(players));
+        app.init_resource::<LocalCommands>();
+        app.add_event::<MatchPhaseChanged>();
+        app.add_event::<OutgoingCommands>();
+
+        app.add_fixed_framestep(6, LOBBY_FRAMESTEP);
+        app.pause_fixed_framestep_when(LOBBY_FRAMESTEP, |world| {
+            world.resource::<MatchPhase>() != &MatchPhase::Lobby
+        });
+        app.add_fixed_framestep_system(LOBBY_FRAMESTEP, 0, lobby_tick);
+
+        app.add_fixed_framestep(60, COUNTDOWN_FRAMESTEP);
+        app.pause_fixed_framestep_when(COUNTDOWN_FRAMESTEP, |world| {
+            !matches!(world.resource::<MatchPhase>(), MatchPhase::Countdown { .. })
+        });
+        app.add_fixed_framestep_system(COUNTDOWN_FRAMESTEP, 0, countdown_tick);
+
+        app.add_fixed_framestep(self.game_step, GAME_FRAMESTEP);
+        app.pause_fixed_framestep_when(GAME_FRAMESTEP, |world| {
+            world.resource::<MatchPhase>() != &MatchPhase::Playing
+        });
+        let lockstep = LockstepPlugin::<SampleCommand>::new(GAME_FRAMESTEP, players);
+        app.add_plugin(match self.deadline {
+            Some(frames) => lockstep.with_deadline(frames),
+            None => lockstep,
+        });
+        app.add_fixed_framestep_system(GAME_FRAMESTEP, 0, game_tick);
+        app.on_fixed_framestep_tick_end(GAME_FRAMESTEP, |world, tick| {
+            let input_delay = world.resource::<MatchSettings>().input_delay;
+            send_local_commands(world, tick + input_delay);
+        });
+    }
+}
+
+fn set_phase(phase: &mut MatchPhase, events: &mut EventWriter<MatchPhaseChanged>, to: MatchPhase) {
+    events.send(MatchPhaseChanged { from: *phase, to });
+    *phase = to;
+}
+
+fn lobby_tick(
+    mut phase: ResMut<MatchPhase>,
+    mut events: EventWriter<MatchPhaseChanged>,
+    lobby: Res<Lobby>,
+    settings: Res<MatchSettings>,
+) {
+    if lobby.all_ready() {
+        set_phase(&mut phase, &mut events, MatchPhase::Countdown { remaining: settings.countdown });
+    }
+}
+
+fn countdown_tick(
+    mut commands: Commands,
+    mut phase: ResMut<MatchPhase>,
+    mut events: EventWriter<MatchPhaseChanged>,
+    lobby: Res<Lobby>,
+    local: Res<LocalCommands>,
+) {
+    if !lobby.all_ready() {
+        set_phase(&mut phase, &mut events, MatchPhase::Lobby);
+        return;
+    }
+    let remaining = match *phase {
+        MatchPhase::Countdown { remaining } => remaining.saturating_sub(1),
+        _ => return,
+    };
+    if remaining > 0 {
+        *phase = MatchPhase::Countdown { remaining };
+        return;
+    }
+    set_phase(&mut phase, &mut events, MatchPhase::Playing);
+    // the first ticks run before any local commands can reach them
+    if let Some(player) = local.player {
+        commands.add(move |world: &mut World| {
+            // queued before the game started
+            world.resource_mut::<LocalCommands>().commands.clear();
+            let input_delay = world.resource::<MatchSettings>().input_delay;
+            for tick in 1..=input_delay {
+                send_local_commands_for(world, player, tick, Vec::new());
+            }
+        });
+    }
+}
+
+fn game_tick(mut game: ResMut<SampleGame>, inbox: Res<LockstepInbox<SampleCommand>>, framesteps: Res<FixedFramesteps>) {
+    for (player, command) in inbox.current() {
+        match *command {
+            SampleCommand::Move { dx, dy } => {
+                let position = &mut game.positions[player];
+                position.0 += dx as i64;
+                position.1 += dy as i64;
+            }
+        }
+    }
+    game.tick = framesteps.get_current().map(|info| info.tick()).unwrap_or(game.tick);
+    game.checksum = game.compute_checksum();
+}
+
+/// Stamp the queued local commands with the given tick, and send them
+fn send_local_commands(world: &mut World, tick: u64) {
+    let mut local = world.resource_mut::<LocalCommands>();
+    let player = match local.player {
+        Some(player) => player,
+        None => return,
+    };
+    let commands = std::mem::take(&mut local.commands);
+    send_local_commands_for(world, player, tick, commands);
+}
+
+fn send_local_commands_for(world: &mut World, player: usize, tick: u64, commands: Vec<SampleCommand>) {
+    world.resource_mut::<LockstepInbox<SampleCommand>>().receive(player, tick, commands.clone());
+    world.resource_mut::<Events<OutgoingCommands>>().send(OutgoingCommands {
+        player,
+        tick,
+        commands,
+    });
+}